@@ -5,10 +5,10 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
-use qauth::proof::Proof;
+use qauth::proof::ProofOfPossession;
 
 fuzz_target!(|data: &[u8]| {
-    // Attempt to parse arbitrary bytes as a Proof
+    // Attempt to parse arbitrary bytes as a ProofOfPossession
     // This should never panic
-    let _ = Proof::from_bytes(data);
+    let _ = ProofOfPossession::from_bytes(data);
 });
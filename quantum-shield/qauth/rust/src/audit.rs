@@ -0,0 +1,144 @@
+//! Tamper-evident audit log
+//!
+//! Each appended entry extends a SHA3-256 hash chain:
+//!
+//! ```text
+//! head_0 = 0^32
+//! head_n = SHA3-256(head_{n-1} || entry_n)
+//! ```
+//!
+//! The current head can be sealed with the issuer's dual signature. Anyone
+//! holding the entries, the seal, and the issuer's verifying keys can then
+//! detect an inserted, deleted, reordered, or modified entry.
+
+use sha3::{Digest, Sha3_256};
+
+use crate::crypto::{DualSignature, IssuerSigningKeys, IssuerVerifyingKeys, SignaturePurpose};
+use crate::error::Result;
+
+/// Size of a chain hash in bytes
+pub const AUDIT_HASH_SIZE: usize = 32;
+
+/// Append-only, hash-chained audit log
+#[derive(Clone, Debug, Default)]
+pub struct AuditChain {
+    entries: Vec<Vec<u8>>,
+    head: [u8; AUDIT_HASH_SIZE],
+}
+
+impl AuditChain {
+    /// Create an empty chain
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an entry and return the new head hash
+    pub fn append(&mut self, entry: &[u8]) -> [u8; AUDIT_HASH_SIZE] {
+        self.head = chain_hash(&self.head, entry);
+        self.entries.push(entry.to_vec());
+        self.head
+    }
+
+    /// Current head hash (all zeros for an empty chain)
+    pub fn head(&self) -> [u8; AUDIT_HASH_SIZE] {
+        self.head
+    }
+
+    /// Entries in append order
+    pub fn entries(&self) -> &[Vec<u8>] {
+        &self.entries
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the chain has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sign the current head hash with the issuer's dual signature
+    pub fn seal(&self, signing_keys: &IssuerSigningKeys) -> DualSignature {
+        signing_keys.sign_for(SignaturePurpose::AuditChain, &self.head)
+    }
+
+    /// Recompute the head over `entries` and verify it against a seal
+    ///
+    /// Fails if any entry was modified, inserted, removed, or reordered since
+    /// the seal was produced.
+    pub fn verify_chain<T: AsRef<[u8]>>(
+        entries: &[T],
+        head_signature: &DualSignature,
+        verifying_keys: &IssuerVerifyingKeys,
+    ) -> Result<()> {
+        let head = entries
+            .iter()
+            .fold([0u8; AUDIT_HASH_SIZE], |prev, entry| chain_hash(&prev, entry.as_ref()));
+
+        verifying_keys.verify_for(SignaturePurpose::AuditChain, &head, head_signature)
+    }
+}
+
+fn chain_hash(prev: &[u8; AUDIT_HASH_SIZE], entry: &[u8]) -> [u8; AUDIT_HASH_SIZE] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(prev);
+    hasher.update(entry);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_keys() -> (IssuerSigningKeys, IssuerVerifyingKeys) {
+        let signing_keys = IssuerSigningKeys::generate();
+        let verifying_keys = IssuerVerifyingKeys::from_bytes(
+            &signing_keys.ed25519.public_key_bytes(),
+            &signing_keys.mldsa.public_key_bytes(),
+        )
+        .unwrap();
+        (signing_keys, verifying_keys)
+    }
+
+    fn sample_chain() -> AuditChain {
+        let mut chain = AuditChain::new();
+        chain.append(b"user-123 logged in");
+        chain.append(b"user-123 read /api/records/7");
+        chain.append(b"user-123 logged out");
+        chain
+    }
+
+    #[test]
+    fn test_chain_verifies() {
+        let (signing_keys, verifying_keys) = setup_keys();
+        let chain = sample_chain();
+        let seal = chain.seal(&signing_keys);
+
+        assert_eq!(chain.len(), 3);
+        assert!(AuditChain::verify_chain(chain.entries(), &seal, &verifying_keys).is_ok());
+    }
+
+    #[test]
+    fn test_modified_entry_detected() {
+        let (signing_keys, verifying_keys) = setup_keys();
+        let chain = sample_chain();
+        let seal = chain.seal(&signing_keys);
+
+        let mut entries = chain.entries().to_vec();
+        entries[1] = b"user-123 read /api/records/8".to_vec();
+        assert!(AuditChain::verify_chain(&entries, &seal, &verifying_keys).is_err());
+    }
+
+    #[test]
+    fn test_deleted_entry_detected() {
+        let (signing_keys, verifying_keys) = setup_keys();
+        let chain = sample_chain();
+        let seal = chain.seal(&signing_keys);
+
+        let mut entries = chain.entries().to_vec();
+        entries.remove(1);
+        assert!(AuditChain::verify_chain(&entries, &seal, &verifying_keys).is_err());
+    }
+}
@@ -784,5 +784,5 @@ fn load_encryption_key(key_file: &KeyFile) -> Result<EncryptionKey, String> {
 
     let mut key_array = [0u8; 32];
     key_array.copy_from_slice(&key_bytes);
-    Ok(EncryptionKey::from_bytes(key_array))
+    Ok(EncryptionKey::from_bytes(&key_array))
 }
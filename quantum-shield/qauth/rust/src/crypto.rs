@@ -17,7 +17,7 @@ use pqcrypto_dilithium::dilithium3::{
 };
 use pqcrypto_traits::sign::{DetachedSignature, PublicKey, SecretKey};
 use sha2::{Digest, Sha256};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 /// Size constants
 pub const ED25519_SIGNATURE_SIZE: usize = 64;
@@ -27,14 +27,66 @@ pub const XCHACHA20_NONCE_SIZE: usize = 24;
 pub const XCHACHA20_TAG_SIZE: usize = 16;
 pub const KEY_SIZE: usize = 32;
 pub const KEY_ID_SIZE: usize = 32;
+/// Ed25519 public key size
+pub const ED25519_PUBLIC_KEY_SIZE: usize = 32;
+/// ML-DSA-65 (Dilithium3) public key size
+pub const MLDSA_PUBLIC_KEY_SIZE: usize = 1952;
+
+/// Default upper bound on ciphertext length accepted by `EncryptedData::from_bytes`.
+///
+/// QToken encodes the encrypted payload length as a `u16`, so anything larger
+/// cannot have come from a well-formed token.
+pub const MAX_CIPHERTEXT_SIZE: usize = u16::MAX as usize;
+
+/// What an issuer signature is for.
+///
+/// Each purpose prefixes the signed bytes with its own domain tag, so a
+/// signature produced for one purpose never verifies as another even when
+/// the underlying messages collide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignaturePurpose {
+    /// QToken header and encrypted payload
+    QToken,
+    /// Audit chain head seal
+    AuditChain,
+}
+
+impl SignaturePurpose {
+    /// Domain tag prepended to the message before signing
+    pub fn domain(&self) -> &'static [u8] {
+        match self {
+            Self::QToken => b"qtoken",
+            Self::AuditChain => b"auditchain",
+        }
+    }
+
+    /// Domain-separated message: [domain_len: u8][domain][message]
+    fn signed_message(&self, message: &[u8]) -> Vec<u8> {
+        let domain = self.domain();
+        let mut signed = Vec::with_capacity(1 + domain.len() + message.len());
+        signed.push(domain.len() as u8);
+        signed.extend_from_slice(domain);
+        signed.extend_from_slice(message);
+        signed
+    }
+}
 
 /// Ed25519 signing key pair
-#[derive(ZeroizeOnDrop)]
+///
+/// The dalek signing key wipes its own secret scalar on drop.
 pub struct Ed25519KeyPair {
-    #[zeroize(skip)]
     signing_key: Ed25519SigningKey,
 }
 
+impl Zeroize for Ed25519KeyPair {
+    fn zeroize(&mut self) {
+        // Dropping the replaced key wipes its secret scalar
+        self.signing_key = Ed25519SigningKey::from_bytes(&[0u8; 32]);
+    }
+}
+
+impl ZeroizeOnDrop for Ed25519KeyPair {}
+
 impl Ed25519KeyPair {
     /// Generate a new random keypair
     pub fn generate() -> Self {
@@ -58,21 +110,40 @@ impl Ed25519KeyPair {
         self.signing_key.sign(message).to_bytes()
     }
 
-    /// Get the private key bytes
-    pub fn private_key_bytes(&self) -> [u8; 32] {
-        self.signing_key.to_bytes()
+    /// Get the private key bytes, wiped when the returned buffer is dropped
+    pub fn private_key_bytes(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.signing_key.to_bytes())
     }
 }
 
 /// ML-DSA-65 (Dilithium3) signing key pair
-#[derive(ZeroizeOnDrop)]
+///
+/// `pqcrypto` keys do not implement `Zeroize`, so the secret key is
+/// overwritten in place on drop.
 pub struct MlDsaKeyPair {
-    #[zeroize(skip)]
     public_key: MlDsaPublicKey,
-    #[zeroize(skip)]
     secret_key: MlDsaSecretKey,
 }
 
+impl Zeroize for MlDsaKeyPair {
+    fn zeroize(&mut self) {
+        let zeroed = MlDsaSecretKey::from_bytes(&[0u8; dilithium3::secret_key_bytes()])
+            .expect("zeroed buffer has the secret key length");
+        // SAFETY: `secret_key` is a plain byte array owned by `self`, so
+        // overwriting it is sound; the volatile write cannot be elided.
+        unsafe { std::ptr::write_volatile(&mut self.secret_key, zeroed) };
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl Drop for MlDsaKeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for MlDsaKeyPair {}
+
 impl MlDsaKeyPair {
     /// Generate a new random keypair
     pub fn generate() -> Self {
@@ -94,9 +165,9 @@ impl MlDsaKeyPair {
         sig.as_bytes().to_vec()
     }
 
-    /// Get the private key bytes
-    pub fn private_key_bytes(&self) -> Vec<u8> {
-        self.secret_key.as_bytes().to_vec()
+    /// Get the private key bytes, wiped when the returned buffer is dropped
+    pub fn private_key_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.secret_key.as_bytes().to_vec())
     }
 
     /// Create from raw bytes
@@ -113,6 +184,9 @@ impl MlDsaKeyPair {
 }
 
 /// Combined issuer signing keys for dual signatures
+///
+/// Each key pair zeroizes itself on drop.
+#[derive(Zeroize, ZeroizeOnDrop)]
 pub struct IssuerSigningKeys {
     pub ed25519: Ed25519KeyPair,
     pub mldsa: MlDsaKeyPair,
@@ -137,7 +211,7 @@ impl IssuerSigningKeys {
         if ed25519_private.len() != 32 {
             return Err(QAuthError::CryptoError);
         }
-        let mut ed25519_private_arr = [0u8; 32];
+        let mut ed25519_private_arr = Zeroizing::new([0u8; 32]);
         ed25519_private_arr.copy_from_slice(ed25519_private);
 
         let ed25519 = Ed25519KeyPair::from_bytes(&ed25519_private_arr)?;
@@ -161,8 +235,10 @@ impl IssuerSigningKeys {
         hasher.finalize().into()
     }
 
-    /// Create dual signature over a message
-    pub fn sign(&self, message: &[u8]) -> DualSignature {
+    /// Create a dual signature over raw `message` bytes
+    ///
+    /// Untagged, so only reachable through `sign_for`.
+    fn sign(&self, message: &[u8]) -> DualSignature {
         let ed25519_sig = self.ed25519.sign(message);
         let mldsa_sig = self.mldsa.sign(message);
         DualSignature {
@@ -170,6 +246,13 @@ impl IssuerSigningKeys {
             mldsa: mldsa_sig,
         }
     }
+
+    /// Create a dual signature over `message` bound to `purpose`
+    ///
+    /// Must be verified with `IssuerVerifyingKeys::verify_for` and the same purpose.
+    pub fn sign_for(&self, purpose: SignaturePurpose, message: &[u8]) -> DualSignature {
+        self.sign(&purpose.signed_message(message))
+    }
 }
 
 /// Combined public keys for verification
@@ -180,15 +263,40 @@ pub struct IssuerVerifyingKeys {
 
 impl IssuerVerifyingKeys {
     /// Create from raw bytes
-    pub fn from_bytes(ed25519_bytes: &[u8; 32], mldsa_bytes: &[u8]) -> Result<Self> {
-        let ed25519 = Ed25519VerifyingKey::from_bytes(ed25519_bytes)
-            .map_err(|_| QAuthError::CryptoError)?;
+    ///
+    /// Each key is length-checked on its own, so swapped or truncated
+    /// arguments fail with a message naming the offending key rather than
+    /// producing a wrong key ID.
+    pub fn from_bytes(ed25519_bytes: &[u8], mldsa_bytes: &[u8]) -> Result<Self> {
+        let ed25519_bytes: &[u8; ED25519_PUBLIC_KEY_SIZE] =
+            ed25519_bytes.try_into().map_err(|_| {
+                QAuthError::InvalidInput(format!(
+                    "Ed25519 public key must be {} bytes, got {}",
+                    ED25519_PUBLIC_KEY_SIZE,
+                    ed25519_bytes.len()
+                ))
+            })?;
+        if mldsa_bytes.len() != MLDSA_PUBLIC_KEY_SIZE {
+            return Err(QAuthError::InvalidInput(format!(
+                "ML-DSA public key must be {} bytes, got {}",
+                MLDSA_PUBLIC_KEY_SIZE,
+                mldsa_bytes.len()
+            )));
+        }
+
+        let ed25519 = Ed25519VerifyingKey::from_bytes(ed25519_bytes).map_err(|_| {
+            QAuthError::InvalidInput("Ed25519 public key is not a valid point".into())
+        })?;
         let mldsa = MlDsaPublicKey::from_bytes(mldsa_bytes)
             .map_err(|_| QAuthError::CryptoError)?;
         Ok(Self { ed25519, mldsa })
     }
 
     /// Compute the Key ID
+    ///
+    /// `SHA-256(0x51 || 0x41 || ed25519_pk || mldsa_pk)` as in QTOKEN-FORMAT
+    /// §2.3. It depends only on the public key bytes, so it matches
+    /// `IssuerSigningKeys::key_id` and is stable across processes and releases.
     pub fn key_id(&self) -> [u8; KEY_ID_SIZE] {
         let mut hasher = Sha256::new();
         hasher.update(&[0x51, 0x41]); // "QA" magic bytes
@@ -197,22 +305,36 @@ impl IssuerVerifyingKeys {
         hasher.finalize().into()
     }
 
-    /// Verify a dual signature
-    pub fn verify(&self, message: &[u8], signature: &DualSignature) -> Result<()> {
-        // Verify Ed25519 signature
-        let ed25519_sig = Ed25519Signature::from_bytes(&signature.ed25519);
-        self.ed25519
-            .verify(message, &ed25519_sig)
-            .map_err(|_| QAuthError::CryptoError)?;
-
+    /// Verify a dual signature over raw `message` bytes
+    ///
+    /// Untagged, so only reachable through `verify_for`. ML-DSA is checked
+    /// first: a forgery has to defeat it regardless, so invalid signatures
+    /// are rejected without also running the classical check.
+    fn verify(&self, message: &[u8], signature: &DualSignature) -> Result<()> {
         // Verify ML-DSA signature
         let mldsa_sig = MlDsaSignature::from_bytes(&signature.mldsa)
             .map_err(|_| QAuthError::CryptoError)?;
         dilithium3::verify_detached_signature(&mldsa_sig, message, &self.mldsa)
             .map_err(|_| QAuthError::CryptoError)?;
 
+        // Verify Ed25519 signature
+        let ed25519_sig = Ed25519Signature::from_bytes(&signature.ed25519);
+        self.ed25519
+            .verify(message, &ed25519_sig)
+            .map_err(|_| QAuthError::CryptoError)?;
+
         Ok(())
     }
+
+    /// Verify a dual signature produced by `IssuerSigningKeys::sign_for`
+    pub fn verify_for(
+        &self,
+        purpose: SignaturePurpose,
+        message: &[u8],
+        signature: &DualSignature,
+    ) -> Result<()> {
+        self.verify(&purpose.signed_message(message), signature)
+    }
 }
 
 /// Dual signature (Ed25519 + ML-DSA-65)
@@ -249,7 +371,7 @@ impl DualSignature {
 }
 
 /// Encryption key for payload encryption
-#[derive(ZeroizeOnDrop)]
+#[derive(Zeroize, ZeroizeOnDrop)]
 pub struct EncryptionKey {
     pub(crate) key: [u8; KEY_SIZE],
 }
@@ -262,13 +384,17 @@ impl EncryptionKey {
     }
 
     /// Create from bytes
-    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
-        Self { key: bytes }
+    ///
+    /// Takes a reference so the only copy made is the one wiped on drop.
+    pub fn from_bytes(bytes: &[u8; KEY_SIZE]) -> Self {
+        Self { key: *bytes }
     }
 
     /// Get the key bytes (for creating validators)
-    pub fn to_bytes(&self) -> [u8; KEY_SIZE] {
-        self.key
+    ///
+    /// The copy is wiped when the returned buffer is dropped.
+    pub fn to_bytes(&self) -> Zeroizing<[u8; KEY_SIZE]> {
+        Zeroizing::new(self.key)
     }
 
     /// Encrypt data with XChaCha20-Poly1305
@@ -313,18 +439,32 @@ impl EncryptedData {
     }
 
     /// Deserialize from bytes
+    ///
+    /// Rejects inputs shorter than nonce + tag and ciphertexts longer than
+    /// [`MAX_CIPHERTEXT_SIZE`].
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        if bytes.len() < XCHACHA20_NONCE_SIZE {
+        Self::from_bytes_with_limit(bytes, MAX_CIPHERTEXT_SIZE)
+    }
+
+    /// Deserialize from bytes with a custom maximum ciphertext length
+    pub fn from_bytes_with_limit(bytes: &[u8], max_ciphertext_len: usize) -> Result<Self> {
+        if bytes.len() < XCHACHA20_NONCE_SIZE + XCHACHA20_TAG_SIZE {
             return Err(QAuthError::InvalidInput("Encrypted data too short".into()));
         }
 
-        let nonce: [u8; XCHACHA20_NONCE_SIZE] = bytes[..XCHACHA20_NONCE_SIZE]
+        let (nonce_bytes, ciphertext) = bytes.split_at(XCHACHA20_NONCE_SIZE);
+        if ciphertext.len() > max_ciphertext_len {
+            return Err(QAuthError::InvalidInput("Ciphertext too large".into()));
+        }
+
+        let nonce: [u8; XCHACHA20_NONCE_SIZE] = nonce_bytes
             .try_into()
             .map_err(|_| QAuthError::InvalidInput("Invalid nonce".into()))?;
 
-        let ciphertext = bytes[XCHACHA20_NONCE_SIZE..].to_vec();
-
-        Ok(Self { nonce, ciphertext })
+        Ok(Self {
+            nonce,
+            ciphertext: ciphertext.to_vec(),
+        })
     }
 }
 
@@ -391,6 +531,25 @@ mod tests {
         assert!(verifying_keys.verify(message, &signature).is_ok());
     }
 
+    #[test]
+    fn test_signature_purpose_not_replayable() {
+        let issuer_keys = IssuerSigningKeys::generate();
+        let verifying_keys = IssuerVerifyingKeys::from_bytes(
+            &issuer_keys.ed25519.public_key_bytes(),
+            &issuer_keys.mldsa.public_key_bytes(),
+        )
+        .unwrap();
+        let message = b"same bytes, different meaning";
+
+        let signature = issuer_keys.sign_for(SignaturePurpose::QToken, message);
+        let verify = |purpose| verifying_keys.verify_for(purpose, message, &signature);
+        assert!(verify(SignaturePurpose::QToken).is_ok());
+
+        // A token signature must not be accepted for any other purpose
+        assert!(verify(SignaturePurpose::AuditChain).is_err());
+        assert!(verifying_keys.verify(message, &signature).is_err());
+    }
+
     #[test]
     fn test_encryption_decryption() {
         let key = EncryptionKey::generate();
@@ -415,6 +574,29 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_encrypted_data_too_short() {
+        // Nonce present but no room for the authentication tag
+        let bytes = [0u8; XCHACHA20_NONCE_SIZE + XCHACHA20_TAG_SIZE - 1];
+        assert!(matches!(
+            EncryptedData::from_bytes(&bytes),
+            Err(QAuthError::InvalidInput(_))
+        ));
+        assert!(EncryptedData::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_data_ciphertext_too_large() {
+        let bytes = vec![0u8; XCHACHA20_NONCE_SIZE + MAX_CIPHERTEXT_SIZE + 1];
+        assert!(EncryptedData::from_bytes(&bytes).is_err());
+
+        // A caller-supplied limit is enforced as well
+        let key = EncryptionKey::generate();
+        let encrypted = key.encrypt(&[0u8; 64], b"").unwrap().to_bytes();
+        assert!(EncryptedData::from_bytes_with_limit(&encrypted, 32).is_err());
+        assert!(EncryptedData::from_bytes_with_limit(&encrypted, 64 + XCHACHA20_TAG_SIZE).is_ok());
+    }
+
     #[test]
     fn test_key_id_computation() {
         let issuer_keys = IssuerSigningKeys::generate();
@@ -429,4 +611,90 @@ mod tests {
 
         assert_eq!(key_id_1, key_id_2);
     }
+
+    fn invalid_input(result: Result<IssuerVerifyingKeys>) -> String {
+        match result {
+            Err(QAuthError::InvalidInput(msg)) => msg,
+            Err(e) => panic!("expected InvalidInput, got {:?}", e),
+            Ok(_) => panic!("expected InvalidInput, got Ok"),
+        }
+    }
+
+    #[test]
+    fn test_verifying_keys_swapped_arguments() {
+        let issuer_keys = IssuerSigningKeys::generate();
+        let ed25519 = issuer_keys.ed25519.public_key_bytes();
+        let mldsa = issuer_keys.mldsa.public_key_bytes();
+
+        assert_eq!(
+            invalid_input(IssuerVerifyingKeys::from_bytes(&mldsa, &ed25519)),
+            "Ed25519 public key must be 32 bytes, got 1952"
+        );
+    }
+
+    #[test]
+    fn test_verifying_keys_wrong_lengths() {
+        let issuer_keys = IssuerSigningKeys::generate();
+        let ed25519 = issuer_keys.ed25519.public_key_bytes();
+        let mldsa = issuer_keys.mldsa.public_key_bytes();
+
+        assert_eq!(
+            invalid_input(IssuerVerifyingKeys::from_bytes(&ed25519[..31], &mldsa)),
+            "Ed25519 public key must be 32 bytes, got 31"
+        );
+        assert_eq!(
+            invalid_input(IssuerVerifyingKeys::from_bytes(&ed25519, &mldsa[..1951])),
+            "ML-DSA public key must be 1952 bytes, got 1951"
+        );
+        assert_eq!(
+            invalid_input(IssuerVerifyingKeys::from_bytes(&ed25519, &[0u8; 0])),
+            "ML-DSA public key must be 1952 bytes, got 0"
+        );
+    }
+
+    #[test]
+    fn test_key_id_stable() {
+        let issuer_keys = IssuerSigningKeys::generate();
+        let ed25519 = issuer_keys.ed25519.public_key_bytes();
+        let mldsa = issuer_keys.mldsa.public_key_bytes();
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"QA");
+        hasher.update(ed25519);
+        hasher.update(&mldsa);
+        let expected: [u8; KEY_ID_SIZE] = hasher.finalize().into();
+
+        let verifying_keys = IssuerVerifyingKeys::from_bytes(&ed25519, &mldsa).unwrap();
+        assert_eq!(verifying_keys.key_id(), expected);
+        assert_eq!(
+            IssuerVerifyingKeys::from_bytes(&ed25519, &mldsa).unwrap().key_id(),
+            verifying_keys.key_id()
+        );
+    }
+
+    #[test]
+    fn test_secret_keys_zeroize() {
+        let issuer_keys = IssuerSigningKeys::generate();
+        let ed25519_private = issuer_keys.ed25519.private_key_bytes();
+        let restored = IssuerSigningKeys::from_bytes(
+            &issuer_keys.ed25519.public_key_bytes(),
+            &*ed25519_private,
+            &issuer_keys.mldsa.public_key_bytes(),
+            &issuer_keys.mldsa.private_key_bytes(),
+        )
+        .unwrap();
+        assert_eq!(restored.key_id(), issuer_keys.key_id());
+
+        // The same wipe each key type runs on drop
+        let mut issuer_keys = issuer_keys;
+        issuer_keys.zeroize();
+        assert_eq!(*issuer_keys.ed25519.private_key_bytes(), [0u8; 32]);
+        assert!(issuer_keys.mldsa.private_key_bytes().iter().all(|&b| b == 0));
+        assert_ne!(*restored.ed25519.private_key_bytes(), [0u8; 32]);
+
+        let mut key = EncryptionKey::generate();
+        assert_eq!(EncryptionKey::from_bytes(&key.to_bytes()).key, key.key);
+        key.zeroize();
+        assert_eq!(key.key, [0u8; KEY_SIZE]);
+    }
 }
@@ -27,6 +27,10 @@ pub enum ErrorCode {
     BindingMismatch,
     /// E010: Token has been revoked
     TokenRevoked,
+    /// E011: Header timestamp outside allowed skew
+    InvalidTimestamp,
+    /// E012: Policy reference unknown or not currently valid
+    UnknownPolicy,
 }
 
 impl ErrorCode {
@@ -43,6 +47,8 @@ impl ErrorCode {
             Self::InvalidIssuer => "E008",
             Self::BindingMismatch => "E009",
             Self::TokenRevoked => "E010",
+            Self::InvalidTimestamp => "E011",
+            Self::UnknownPolicy => "E012",
         }
     }
 }
@@ -78,6 +84,10 @@ pub enum QAuthError {
     #[error("Proof of possession invalid")]
     InvalidProof,
 
+    /// Validator requires a proof of possession but none was supplied
+    #[error("Proof of possession required")]
+    ProofRequired,
+
     /// Key not found
     #[error("Key not found: {0}")]
     KeyNotFound(String),
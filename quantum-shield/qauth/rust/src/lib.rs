@@ -75,6 +75,7 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
 
+pub mod audit;
 pub mod crypto;
 pub mod error;
 pub mod policy;
@@ -83,19 +84,28 @@ pub mod revocation;
 pub mod token;
 
 // Re-export commonly used types
+pub use audit::AuditChain;
 pub use crypto::{
-    DualSignature, EncryptionKey, IssuerSigningKeys, IssuerVerifyingKeys,
+    DualSignature, EncryptionKey, IssuerSigningKeys, IssuerVerifyingKeys, SignaturePurpose,
 };
 pub use error::{ErrorCode, QAuthError, Result};
-pub use policy::{Effect, EvaluationContext, EvaluationResult, Policy, PolicyEngine, Rule};
+pub use policy::{
+    Effect, EvaluationContext, EvaluationResult, HolidayCalendar, Policy, PolicyEngine,
+    PolicyValidationError, RelationshipResolver, Rule,
+};
 pub use proof::{ProofGenerator, ProofOfPossession, ProofValidator};
 pub use revocation::{
     InMemoryRevocationStore, RevocationCache, RevocationChecker, RevocationEntry,
-    RevocationReason, RevocationStatus, RevocationStore,
+    RevocationMetricsSnapshot, RevocationReason, RevocationStatus, RevocationStore,
 };
+#[cfg(feature = "sqlite")]
+pub use revocation::SqliteRevocationStore;
+#[cfg(feature = "tokio")]
+pub use revocation::{AsyncRevocationChecker, AsyncRevocationStore, InMemoryAsyncRevocationStore};
 pub use token::{
-    ProofBinding, QToken, QTokenBuilder, QTokenHeader, QTokenPayload, QTokenValidator,
-    TokenType, ValidatedToken,
+    context_hash, jwk_thumbprint, CheckResult, Confirmation, ProofBinding, QToken, QTokenBuilder,
+    QTokenHeader, QTokenPayload, QTokenValidator, TokenType, TokenVerificationReport,
+    ValidatedToken, ANY_AUDIENCE,
 };
 
 /// QAuth protocol version
@@ -3,12 +3,15 @@
 //! Implements the QAuth Policy Language (QPL) as specified in QAUTH-POLICY.md
 
 use crate::error::{QAuthError, Result};
-use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
 use glob_match::glob_match;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
 
 /// Policy effect
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -84,6 +87,138 @@ impl Default for PolicyDefaults {
     }
 }
 
+/// Problem found by `Policy::validate`
+///
+/// `rule` is the rule id, or `#<index>` for rules without one.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PolicyValidationError {
+    /// Rule matches no resources
+    #[error("rule {rule}: resources must not be empty")]
+    EmptyResources {
+        /// Offending rule
+        rule: String,
+    },
+    /// Two or more rules share an id
+    #[error("duplicate rule id: {0}")]
+    DuplicateRuleId(String),
+    /// Time condition value is not HH:MM
+    #[error("rule {rule}: invalid time {value:?}, expected HH:MM")]
+    InvalidTime {
+        /// Offending rule
+        rule: String,
+        /// Rejected time
+        value: String,
+    },
+    /// Time condition timezone is not a known IANA name
+    #[error("rule {rule}: unknown timezone {value:?}")]
+    InvalidTimezone {
+        /// Offending rule
+        rule: String,
+        /// Rejected timezone
+        value: String,
+    },
+    /// IP condition value is not an address or CIDR range
+    #[error("rule {rule}: invalid IP range {value:?}")]
+    InvalidIpRange {
+        /// Offending rule
+        rule: String,
+        /// Rejected range
+        value: String,
+    },
+}
+
+impl Policy {
+    /// Check the policy for problems that deserialization does not catch
+    ///
+    /// Returns every problem found, not just the first.
+    pub fn validate(&self) -> std::result::Result<(), Vec<PolicyValidationError>> {
+        let mut errors = Vec::new();
+        let mut seen_ids = HashSet::new();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            let label = rule.id.clone().unwrap_or_else(|| format!("#{}", index));
+
+            if let Some(ref id) = rule.id {
+                if !seen_ids.insert(id.as_str()) {
+                    errors.push(PolicyValidationError::DuplicateRuleId(id.clone()));
+                }
+            }
+
+            if rule.resources.is_empty() {
+                errors.push(PolicyValidationError::EmptyResources { rule: label.clone() });
+            }
+
+            if let Some(ref time) = rule.conditions.time {
+                for value in time.after.iter().chain(time.before.iter()) {
+                    if NaiveTime::parse_from_str(value, "%H:%M").is_err() {
+                        errors.push(PolicyValidationError::InvalidTime {
+                            rule: label.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                }
+                if let Some(ref tz) = time.timezone {
+                    if tz.parse::<Tz>().is_err() {
+                        errors.push(PolicyValidationError::InvalidTimezone {
+                            rule: label.clone(),
+                            value: tz.clone(),
+                        });
+                    }
+                }
+            }
+
+            if let Some(ref ip) = rule.conditions.ip {
+                for value in ip.allow_ranges.iter().chain(ip.deny_ranges.iter()) {
+                    if !is_valid_ip_range(value) {
+                        errors.push(PolicyValidationError::InvalidIpRange {
+                            rule: label.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// An address, or an address with a prefix length valid for its family
+fn is_valid_ip_range(range: &str) -> bool {
+    parse_ip_range(range).is_some()
+}
+
+/// Parse `addr` or `addr/prefix` into a network address and prefix length
+///
+/// A bare address is a single-host range. IPv4-mapped IPv6 networks are
+/// converted to IPv4, as request addresses are.
+fn parse_ip_range(range: &str) -> Option<(IpAddr, u8)> {
+    let (network, prefix) = match range.split_once('/') {
+        Some((network, prefix)) => {
+            let network = IpAddr::from_str(network).ok()?;
+            let prefix = prefix.parse::<u8>().ok()?;
+            (network, prefix)
+        }
+        None => {
+            let network = IpAddr::from_str(range).ok()?;
+            (network, if network.is_ipv4() { 32 } else { 128 })
+        }
+    };
+
+    match network {
+        IpAddr::V4(_) if prefix <= 32 => Some((network, prefix)),
+        IpAddr::V6(v6) if prefix <= 128 => match v6.to_ipv4_mapped() {
+            Some(v4) if prefix >= 96 => Some((IpAddr::V4(v4), prefix - 96)),
+            _ => Some((network, prefix)),
+        },
+        _ => None,
+    }
+}
+
 /// Authorization rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rule {
@@ -102,6 +237,10 @@ pub struct Rule {
     /// Rule priority (higher = evaluated first)
     #[serde(default)]
     pub priority: i32,
+    /// Guard rule: evaluated before all other rules; a matching guard deny
+    /// denies regardless of priority
+    #[serde(default)]
+    pub guard: bool,
     /// Audit configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audit: Option<AuditConfig>,
@@ -142,7 +281,7 @@ pub struct TimeCondition {
     /// Allowed days
     #[serde(skip_serializing_if = "Option::is_none")]
     pub days: Option<Vec<String>>,
-    /// Timezone
+    /// IANA timezone the times and days are in (UTC if unset)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
     /// Exclude holidays
@@ -382,6 +521,8 @@ pub struct EvaluationResult {
     pub reason: String,
     /// Audit requirements
     pub audit: Option<AuditConfig>,
+    /// Policy that produced the decision
+    pub policy: Option<String>,
 }
 
 impl EvaluationResult {
@@ -391,6 +532,7 @@ impl EvaluationResult {
             matched_rule: rule_id,
             reason: "Allowed by policy rule".to_string(),
             audit: None,
+            policy: None,
         }
     }
 
@@ -400,6 +542,7 @@ impl EvaluationResult {
             matched_rule: rule_id,
             reason: reason.to_string(),
             audit: None,
+            policy: None,
         }
     }
 
@@ -409,14 +552,37 @@ impl EvaluationResult {
             matched_rule: None,
             reason: "No matching rule, default deny".to_string(),
             audit: None,
+            policy: None,
         }
     }
 }
 
+/// Answers relationship (ReBAC) questions for the policy engine
+///
+/// Typically backed by an application's ownership or membership tables.
+pub trait RelationshipResolver: Send + Sync {
+    /// Whether `subject_id` has `relationship` (e.g. `"owner"`) to the
+    /// resource at `resource_path`
+    ///
+    /// `resource_path` is empty for conditions with `of_resource: false`,
+    /// which ask about a relationship not tied to a resource.
+    fn has_relationship(&self, subject_id: &str, relationship: &str, resource_path: &str) -> bool;
+}
+
+/// Holiday dates for time conditions with `not_holidays`
+pub trait HolidayCalendar: Send + Sync {
+    /// Whether `date` (in the time condition's timezone) is a holiday
+    fn is_holiday(&self, date: NaiveDate) -> bool;
+}
+
 /// Policy engine
 pub struct PolicyEngine {
     /// Loaded policies
     policies: HashMap<String, Policy>,
+    /// Resolver for relationship conditions
+    relationship_resolver: Option<Arc<dyn RelationshipResolver>>,
+    /// Calendar for `not_holidays` time conditions
+    holiday_calendar: Option<Arc<dyn HolidayCalendar>>,
 }
 
 impl PolicyEngine {
@@ -424,20 +590,46 @@ impl PolicyEngine {
     pub fn new() -> Self {
         Self {
             policies: HashMap::new(),
+            relationship_resolver: None,
+            holiday_calendar: None,
         }
     }
 
+    /// Deny rules with `not_holidays` on dates `calendar` marks as holidays
+    ///
+    /// Without a calendar the flag is not enforced; a decision made by such
+    /// a rule says so in its reason.
+    pub fn with_holiday_calendar(mut self, calendar: Arc<dyn HolidayCalendar>) -> Self {
+        self.holiday_calendar = Some(calendar);
+        self
+    }
+
+    /// Evaluate relationship conditions with `resolver`
+    ///
+    /// Without a resolver, a request reaching a rule with a relationship
+    /// condition is denied.
+    pub fn with_relationship_resolver(mut self, resolver: Arc<dyn RelationshipResolver>) -> Self {
+        self.relationship_resolver = Some(resolver);
+        self
+    }
+
     /// Load a policy
-    pub fn load_policy(&mut self, policy: Policy) {
+    ///
+    /// The policy is validated first; all problems are reported in one error.
+    pub fn load_policy(&mut self, policy: Policy) -> Result<()> {
+        policy.validate().map_err(|errors| {
+            let problems: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            QAuthError::PolicyError(format!("Invalid policy: {}", problems.join("; ")))
+        })?;
         self.policies.insert(policy.id.clone(), policy);
+        Ok(())
     }
 
     /// Load a policy from JSON
     pub fn load_policy_json(&mut self, json: &str) -> Result<()> {
         let policy: Policy =
             serde_json::from_str(json).map_err(|e| QAuthError::PolicyError(e.to_string()))?;
-        self.load_policy(policy);
-        Ok(())
+        self.load_policy(policy)
     }
 
     /// Get a policy by ID
@@ -451,11 +643,74 @@ impl PolicyEngine {
         policy_id: &str,
         context: &EvaluationContext,
     ) -> Result<EvaluationResult> {
-        let policy = self
-            .policies
+        let policy = self.loaded_policy(policy_id)?;
+        let mut result = self.evaluate_policy(policy, context)?;
+        result.policy = Some(policy.id.clone());
+        Ok(result)
+    }
+
+    /// Evaluate several policies, allowing only if every one of them allows
+    ///
+    /// Policies are evaluated in order and the first deny is returned, so its
+    /// `policy` field identifies the denying policy. All ids must be loaded,
+    /// and at least one must be given.
+    pub fn evaluate_all(
+        &self,
+        policy_ids: &[&str],
+        context: &EvaluationContext,
+    ) -> Result<EvaluationResult> {
+        let results = self.evaluate_each(policy_ids, context)?;
+        let decisive = results
+            .iter()
+            .position(|r| r.effect == Effect::Deny)
+            .unwrap_or(results.len() - 1);
+        Ok(results.into_iter().nth(decisive).unwrap())
+    }
+
+    /// Evaluate several policies, allowing if any one of them allows
+    ///
+    /// Returns the first allow, or the first deny if none allows. All ids must
+    /// be loaded, and at least one must be given.
+    pub fn evaluate_any(
+        &self,
+        policy_ids: &[&str],
+        context: &EvaluationContext,
+    ) -> Result<EvaluationResult> {
+        let results = self.evaluate_each(policy_ids, context)?;
+        let decisive = results
+            .iter()
+            .position(|r| r.effect == Effect::Allow)
+            .unwrap_or(0);
+        Ok(results.into_iter().nth(decisive).unwrap())
+    }
+
+    /// Evaluate every listed policy, failing if any id is unknown
+    fn evaluate_each(
+        &self,
+        policy_ids: &[&str],
+        context: &EvaluationContext,
+    ) -> Result<Vec<EvaluationResult>> {
+        if policy_ids.is_empty() {
+            return Err(QAuthError::PolicyError("No policies to evaluate".to_string()));
+        }
+        // Resolve all ids first so a missing policy errors regardless of order
+        for id in policy_ids {
+            self.loaded_policy(id)?;
+        }
+        policy_ids.iter().map(|id| self.evaluate(id, context)).collect()
+    }
+
+    fn loaded_policy(&self, policy_id: &str) -> Result<&Policy> {
+        self.policies
             .get(policy_id)
-            .ok_or_else(|| QAuthError::PolicyError(format!("Policy not found: {}", policy_id)))?;
+            .ok_or_else(|| QAuthError::PolicyError(format!("Policy not found: {}", policy_id)))
+    }
 
+    fn evaluate_policy(
+        &self,
+        policy: &Policy,
+        context: &EvaluationContext,
+    ) -> Result<EvaluationResult> {
         // Check policy validity period
         if let Some(valid_from) = policy.valid_from {
             if context.request.timestamp < valid_from {
@@ -468,12 +723,36 @@ impl PolicyEngine {
             }
         }
 
-        // Sort rules by priority (descending)
-        let mut rules: Vec<&Rule> = policy.rules.iter().collect();
-        rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+        // The policy's rules come first, then each ancestor's in turn
+        let levels: Vec<Vec<&Rule>> = self
+            .policy_chain(policy)?
+            .into_iter()
+            .map(|level| {
+                // Sort rules by priority (descending)
+                let mut rules: Vec<&Rule> = level.rules.iter().collect();
+                rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
+                rules
+            })
+            .collect();
+
+        // Guard denies act as a kill switch ahead of normal ordering, at
+        // every level, so a child cannot override an ancestor's guard
+        for rule in levels.iter().flatten().filter(|r| r.guard && r.effect == Effect::Deny) {
+            if let Some(result) = self.unresolvable_relationship(rule, context) {
+                return Ok(result);
+            }
+            if self.matches_rule(rule, context)? {
+                let mut result = EvaluationResult::deny("Denied by guard rule", rule.id.clone());
+                result.audit = rule.audit.clone();
+                return Ok(result);
+            }
+        }
 
         // Evaluate rules
-        for rule in rules {
+        for rule in levels.into_iter().flatten() {
+            if let Some(result) = self.unresolvable_relationship(rule, context) {
+                return Ok(result);
+            }
             if self.matches_rule(rule, context)? {
                 let mut result = match rule.effect {
                     Effect::Allow => EvaluationResult::allow(rule.id.clone()),
@@ -482,6 +761,10 @@ impl PolicyEngine {
                     }
                 };
                 result.audit = rule.audit.clone();
+                let skips_holidays = matches!(rule.conditions.time, Some(ref t) if t.not_holidays);
+                if skips_holidays && self.holiday_calendar.is_none() {
+                    result.reason.push_str(" (not_holidays not enforced: no HolidayCalendar)");
+                }
                 return Ok(result);
             }
         }
@@ -490,6 +773,50 @@ impl PolicyEngine {
         Ok(EvaluationResult::default_deny())
     }
 
+    /// `policy` followed by the policies it extends, nearest first
+    ///
+    /// Fails if a parent is not loaded or the chain loops back on itself.
+    fn policy_chain<'a>(&'a self, policy: &'a Policy) -> Result<Vec<&'a Policy>> {
+        let mut chain = vec![policy];
+        let mut current = policy;
+        while let Some(ref parent_id) = current.extends {
+            if chain.iter().any(|p| &p.id == parent_id) {
+                let ids: Vec<&str> = chain.iter().map(|p| p.id.as_str()).collect();
+                return Err(QAuthError::PolicyError(format!(
+                    "Policy inheritance cycle: {} -> {}",
+                    ids.join(" -> "),
+                    parent_id
+                )));
+            }
+            current = self.policies.get(parent_id).ok_or_else(|| {
+                QAuthError::PolicyError(format!(
+                    "Parent policy not found: {} (extended by {})",
+                    parent_id, current.id
+                ))
+            })?;
+            chain.push(current);
+        }
+        Ok(chain)
+    }
+
+    /// A deny for a rule that applies to the request but needs a relationship
+    /// resolver the engine does not have
+    fn unresolvable_relationship(
+        &self,
+        rule: &Rule,
+        context: &EvaluationContext,
+    ) -> Option<EvaluationResult> {
+        let applies = rule.conditions.relationship.is_some()
+            && self.matches_resources(&rule.resources, &context.resource.path)
+            && self.matches_actions(&rule.actions, &context.request.action);
+        (applies && self.relationship_resolver.is_none()).then(|| {
+            EvaluationResult::deny(
+                "Relationship condition requires a RelationshipResolver, none configured",
+                rule.id.clone(),
+            )
+        })
+    }
+
     /// Check if a rule matches the context
     fn matches_rule(&self, rule: &Rule, context: &EvaluationContext) -> Result<bool> {
         // Check resource matches
@@ -563,6 +890,13 @@ impl PolicyEngine {
             }
         }
 
+        // Relationship condition (unresolvable ones are denied before matching)
+        if let Some(ref rel_cond) = conditions.relationship {
+            if !self.matches_relationship_condition(rel_cond, context) {
+                return Ok(false);
+            }
+        }
+
         // Custom conditions
         for (key, cond) in &conditions.custom {
             if !self.matches_custom_condition(key, cond, context)? {
@@ -573,36 +907,73 @@ impl PolicyEngine {
         Ok(true)
     }
 
+    /// Check relationship condition
+    fn matches_relationship_condition(
+        &self,
+        cond: &RelationshipCondition,
+        context: &EvaluationContext,
+    ) -> bool {
+        let Some(ref resolver) = self.relationship_resolver else {
+            return false;
+        };
+        let resource_path = if cond.of_resource { context.resource.path.as_str() } else { "" };
+        resolver.has_relationship(&context.subject.id, &cond.subject_is, resource_path)
+    }
+
     /// Check time condition
     fn matches_time_condition(
         &self,
         cond: &TimeCondition,
         timestamp: &DateTime<Utc>,
     ) -> Result<bool> {
-        let time = timestamp.time();
+        // Compare wall-clock time and weekday in the condition's timezone
+        let local = match cond.timezone {
+            Some(ref tz) => {
+                let tz: Tz = tz
+                    .parse()
+                    .map_err(|_| QAuthError::PolicyError(format!("Unknown timezone: {}", tz)))?;
+                timestamp.with_timezone(&tz).naive_local()
+            }
+            None => timestamp.naive_utc(),
+        };
+        let time = local.time();
+        let parse_time = |value: &str| {
+            NaiveTime::parse_from_str(value, "%H:%M")
+                .map_err(|_| QAuthError::PolicyError("Invalid time format".into()))
+        };
+        let after = cond.after.as_deref().map(parse_time).transpose()?;
+        let before = cond.before.as_deref().map(parse_time).transpose()?;
 
-        // Check after time
-        if let Some(ref after) = cond.after {
-            let after_time = NaiveTime::parse_from_str(after, "%H:%M")
-                .map_err(|_| QAuthError::PolicyError("Invalid time format".into()))?;
-            if time < after_time {
-                return Ok(false);
+        // The date the current window opened on
+        let mut date = local.date();
+
+        match (after, before) {
+            // Overnight window, e.g. 22:00-06:00
+            (Some(after), Some(before)) if before < after => {
+                if time > before && time < after {
+                    return Ok(false);
+                }
+                // After midnight, the window belongs to the previous day
+                if time <= before {
+                    date = date.pred_opt().unwrap_or(date);
+                }
             }
-        }
+            _ => {
+                // Check after time
+                if matches!(after, Some(after) if time < after) {
+                    return Ok(false);
+                }
 
-        // Check before time
-        if let Some(ref before) = cond.before {
-            let before_time = NaiveTime::parse_from_str(before, "%H:%M")
-                .map_err(|_| QAuthError::PolicyError("Invalid time format".into()))?;
-            if time > before_time {
-                return Ok(false);
+                // Check before time
+                if matches!(before, Some(before) if time > before) {
+                    return Ok(false);
+                }
             }
         }
 
         // Check days
         if let Some(ref days) = cond.days {
-            let day = timestamp.weekday();
-            let day_str = match day {
+            let day_str = match date.weekday() {
                 Weekday::Mon => "monday",
                 Weekday::Tue => "tuesday",
                 Weekday::Wed => "wednesday",
@@ -616,6 +987,15 @@ impl PolicyEngine {
             }
         }
 
+        // Check holidays (not enforced without a calendar)
+        if cond.not_holidays {
+            if let Some(ref calendar) = self.holiday_calendar {
+                if calendar.is_holiday(date) {
+                    return Ok(false);
+                }
+            }
+        }
+
         Ok(true)
     }
 
@@ -645,19 +1025,25 @@ impl PolicyEngine {
             }
         }
 
-        // Check IP ranges (simplified - full CIDR matching would need ip_network crate)
+        // Check IP ranges
         if let Some(ref ip_str) = context.request.ip {
             if let Ok(ip) = IpAddr::from_str(ip_str) {
+                // Dual-stack clients may present IPv4 as ::ffff:a.b.c.d
+                let ip = normalize_ip(ip);
+
                 // Check deny ranges first
                 for range in &cond.deny_ranges {
-                    if self.ip_in_range(&ip, range) {
+                    if ip_in_range(&ip, range)? {
                         return Ok(false);
                     }
                 }
 
                 // Check allow ranges if specified
                 if !cond.allow_ranges.is_empty() {
-                    let allowed = cond.allow_ranges.iter().any(|r| self.ip_in_range(&ip, r));
+                    let mut allowed = false;
+                    for range in &cond.allow_ranges {
+                        allowed |= ip_in_range(&ip, range)?;
+                    }
                     if !allowed {
                         return Ok(false);
                     }
@@ -668,31 +1054,6 @@ impl PolicyEngine {
         Ok(true)
     }
 
-    /// Simple IP range check (simplified version)
-    fn ip_in_range(&self, ip: &IpAddr, range: &str) -> bool {
-        // This is a simplified check - full CIDR matching would need a proper library
-        if let Some((network, _prefix)) = range.split_once('/') {
-            if let Ok(network_ip) = IpAddr::from_str(network) {
-                // For simplicity, just check if the first octet matches for /8
-                match (ip, network_ip) {
-                    (IpAddr::V4(ip), IpAddr::V4(net)) => {
-                        ip.octets()[0] == net.octets()[0]
-                    }
-                    _ => false,
-                }
-            } else {
-                false
-            }
-        } else {
-            // Exact match
-            if let Ok(range_ip) = IpAddr::from_str(range) {
-                ip == &range_ip
-            } else {
-                false
-            }
-        }
-    }
-
     /// Check device condition
     fn matches_device_condition(&self, cond: &DeviceCondition, context: &EvaluationContext) -> bool {
         // Check device type
@@ -851,6 +1212,35 @@ impl Default for PolicyEngine {
     }
 }
 
+/// Whether `ip` lies in the CIDR range (or equals the address) `range`
+///
+/// Addresses never match a range of the other family. An unparseable range
+/// is a `PolicyError`.
+fn ip_in_range(ip: &IpAddr, range: &str) -> Result<bool> {
+    let (network, prefix) = parse_ip_range(range)
+        .ok_or_else(|| QAuthError::PolicyError(format!("Invalid IP range: {}", range)))?;
+
+    Ok(match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(*ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(*ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    })
+}
+
+/// Map IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) to their IPv4 form
+fn normalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -898,7 +1288,7 @@ mod tests {
     fn test_policy_loading() {
         let mut engine = PolicyEngine::new();
         let policy = create_test_policy();
-        engine.load_policy(policy);
+        engine.load_policy(policy).unwrap();
 
         assert!(engine.get_policy("urn:qauth:policy:test").is_some());
     }
@@ -906,7 +1296,7 @@ mod tests {
     #[test]
     fn test_allow_read_projects() {
         let mut engine = PolicyEngine::new();
-        engine.load_policy(create_test_policy());
+        engine.load_policy(create_test_policy()).unwrap();
 
         let context = EvaluationContext {
             resource: ResourceContext {
@@ -925,10 +1315,92 @@ mod tests {
         assert_eq!(result.effect, Effect::Allow);
     }
 
+    /// Engine with the test policy plus a team policy that only allows reads
+    fn engine_with_team_policy() -> PolicyEngine {
+        let mut engine = PolicyEngine::new();
+        engine.load_policy(create_test_policy()).unwrap();
+        engine
+            .load_policy_json(
+                r#"{
+                    "id": "urn:qauth:policy:team",
+                    "version": "2026-01-30",
+                    "issuer": "https://auth.example.com",
+                    "rules": [
+                        {
+                            "id": "team-read",
+                            "effect": "allow",
+                            "resources": ["projects/**"],
+                            "actions": ["read"]
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        engine
+    }
+
+    fn project_context(path: &str, action: &str) -> EvaluationContext {
+        EvaluationContext {
+            resource: ResourceContext {
+                path: path.to_string(),
+                ..Default::default()
+            },
+            request: RequestContext {
+                action: action.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_all_requires_every_policy() {
+        let engine = engine_with_team_policy();
+        let policies = ["urn:qauth:policy:test", "urn:qauth:policy:team"];
+
+        let result = engine
+            .evaluate_all(&policies, &project_context("projects/123", "read"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+
+        // The org policy allows the write, but the team policy does not
+        let result = engine
+            .evaluate_all(&policies, &project_context("projects/123", "write"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.policy.as_deref(), Some("urn:qauth:policy:team"));
+
+        let result = engine
+            .evaluate_any(&policies, &project_context("projects/123", "write"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+        assert_eq!(result.policy.as_deref(), Some("urn:qauth:policy:test"));
+
+        let result = engine
+            .evaluate_any(&policies, &project_context("projects/123", "admin"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+    }
+
+    #[test]
+    fn test_evaluate_all_missing_policy_errors() {
+        let engine = engine_with_team_policy();
+        let context = project_context("projects/123", "read");
+
+        // An unknown id is an error even when listed after a loaded policy
+        let ids = ["urn:qauth:policy:team", "urn:qauth:policy:missing"];
+        assert!(matches!(
+            engine.evaluate_all(&ids, &context),
+            Err(QAuthError::PolicyError(_))
+        ));
+        assert!(engine.evaluate_any(&ids, &context).is_err());
+        assert!(engine.evaluate_all(&[], &context).is_err());
+    }
+
     #[test]
     fn test_allow_write_specific_project() {
         let mut engine = PolicyEngine::new();
-        engine.load_policy(create_test_policy());
+        engine.load_policy(create_test_policy()).unwrap();
 
         let context = EvaluationContext {
             resource: ResourceContext {
@@ -951,7 +1423,7 @@ mod tests {
     #[test]
     fn test_deny_admin_access() {
         let mut engine = PolicyEngine::new();
-        engine.load_policy(create_test_policy());
+        engine.load_policy(create_test_policy()).unwrap();
 
         let context = EvaluationContext {
             resource: ResourceContext {
@@ -973,7 +1445,7 @@ mod tests {
     #[test]
     fn test_deny_unmatched() {
         let mut engine = PolicyEngine::new();
-        engine.load_policy(create_test_policy());
+        engine.load_policy(create_test_policy()).unwrap();
 
         let context = EvaluationContext {
             resource: ResourceContext {
@@ -1020,9 +1492,228 @@ mod tests {
         .unwrap();
 
         let mut engine = PolicyEngine::new();
-        engine.load_policy(policy);
+        engine.load_policy(policy).unwrap();
+
+        let evaluate = |timestamp: &str| {
+            let context = time_context(timestamp);
+            engine.evaluate("urn:qauth:policy:time-test", &context).unwrap().effect
+        };
+
+        assert_eq!(evaluate("2026-01-30T10:00:00Z"), Effect::Allow);
+        assert_eq!(evaluate("2026-01-30T20:00:00Z"), Effect::Deny);
+        assert_eq!(evaluate("2026-01-30T08:59:00Z"), Effect::Deny);
+    }
+
+    fn time_context(timestamp: &str) -> EvaluationContext {
+        EvaluationContext {
+            resource: ResourceContext {
+                path: "reports/daily".to_string(),
+                ..Default::default()
+            },
+            request: RequestContext {
+                action: "read".to_string(),
+                timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_time_condition_weekend_days() {
+        let mut engine = PolicyEngine::new();
+        engine
+            .load_policy(policy_with_rules(
+                r#"[{
+                    "effect": "allow",
+                    "resources": ["*"],
+                    "actions": ["*"],
+                    "conditions": { "time": { "days": ["Saturday", "sunday"] } }
+                }]"#,
+            ))
+            .unwrap();
+
+        let evaluate = |timestamp: &str| {
+            let context = time_context(timestamp);
+            engine.evaluate("urn:qauth:policy:validation-test", &context).unwrap().effect
+        };
+
+        // 2026-01-30 is a Friday
+        assert_eq!(evaluate("2026-01-30T12:00:00Z"), Effect::Deny);
+        assert_eq!(evaluate("2026-01-31T12:00:00Z"), Effect::Allow);
+        assert_eq!(evaluate("2026-02-01T23:59:00Z"), Effect::Allow);
+        assert_eq!(evaluate("2026-02-02T00:00:00Z"), Effect::Deny);
+    }
+
+    #[test]
+    fn test_time_condition_uses_timezone() {
+        let mut engine = PolicyEngine::new();
+        engine
+            .load_policy(policy_with_rules(
+                r#"[{
+                    "effect": "allow",
+                    "resources": ["*"],
+                    "actions": ["*"],
+                    "conditions": {
+                        "time": {
+                            "after": "09:00",
+                            "before": "17:00",
+                            "days": ["friday"],
+                            "timezone": "America/New_York"
+                        }
+                    }
+                }]"#,
+            ))
+            .unwrap();
 
-        // Test would depend on current time - in a real scenario, you'd mock the time
+        let evaluate = |timestamp: &str| {
+            let context = time_context(timestamp);
+            engine.evaluate("urn:qauth:policy:validation-test", &context).unwrap().effect
+        };
+
+        // 15:00Z is 10:00 in New York (EST, UTC-5)
+        assert_eq!(evaluate("2026-01-30T15:00:00Z"), Effect::Allow);
+        // 10:00Z is 05:00 in New York, inside the window only in UTC
+        assert_eq!(evaluate("2026-01-30T10:00:00Z"), Effect::Deny);
+        // Saturday 02:00Z is still Friday 21:00 in New York: right day, outside hours
+        assert_eq!(evaluate("2026-01-31T02:00:00Z"), Effect::Deny);
+    }
+
+    #[test]
+    fn test_time_condition_overnight_window() {
+        let mut engine = PolicyEngine::new();
+        engine
+            .load_policy(policy_with_rules(
+                r#"[{
+                    "effect": "allow",
+                    "resources": ["*"],
+                    "actions": ["*"],
+                    "conditions": {
+                        "time": {
+                            "after": "22:00",
+                            "before": "06:00",
+                            "days": ["friday"],
+                            "timezone": "Asia/Tokyo"
+                        }
+                    }
+                }]"#,
+            ))
+            .unwrap();
+
+        let evaluate = |timestamp: &str| {
+            let context = time_context(timestamp);
+            engine.evaluate("urn:qauth:policy:validation-test", &context).unwrap().effect
+        };
+
+        // Tokyo is UTC+9; 2026-01-30 is a Friday
+        // Friday 23:00 local
+        assert_eq!(evaluate("2026-01-30T14:00:00Z"), Effect::Allow);
+        // Saturday 05:59 local, still Friday night's window
+        assert_eq!(evaluate("2026-01-30T20:59:00Z"), Effect::Allow);
+        // Saturday 06:01 local, window closed
+        assert_eq!(evaluate("2026-01-30T21:01:00Z"), Effect::Deny);
+        // Friday 12:00 local, between windows
+        assert_eq!(evaluate("2026-01-30T03:00:00Z"), Effect::Deny);
+        // Friday 02:00 local belongs to Thursday night's window
+        assert_eq!(evaluate("2026-01-29T17:00:00Z"), Effect::Deny);
+        // Saturday 23:00 local, wrong day
+        assert_eq!(evaluate("2026-01-31T14:00:00Z"), Effect::Deny);
+    }
+
+    /// Calendar with a single holiday
+    struct StubCalendar(NaiveDate);
+
+    impl HolidayCalendar for StubCalendar {
+        fn is_holiday(&self, date: NaiveDate) -> bool {
+            date == self.0
+        }
+    }
+
+    fn holiday_policy() -> Policy {
+        policy_with_rules(
+            r#"[{
+                "id": "workdays",
+                "effect": "allow",
+                "resources": ["*"],
+                "actions": ["*"],
+                "conditions": {
+                    "time": { "not_holidays": true, "timezone": "America/New_York" }
+                }
+            }]"#,
+        )
+    }
+
+    #[test]
+    fn test_time_condition_not_holidays() {
+        let holiday = NaiveDate::from_ymd_opt(2026, 7, 3).unwrap();
+        let mut engine =
+            PolicyEngine::new().with_holiday_calendar(Arc::new(StubCalendar(holiday)));
+        engine.load_policy(holiday_policy()).unwrap();
+
+        let evaluate = |timestamp: &str| {
+            let context = time_context(timestamp);
+            engine.evaluate("urn:qauth:policy:validation-test", &context).unwrap()
+        };
+
+        assert_eq!(evaluate("2026-07-03T15:00:00Z").effect, Effect::Deny);
+        assert_eq!(evaluate("2026-07-02T15:00:00Z").effect, Effect::Allow);
+        // 02:00Z on the 4th is still the 3rd in New York
+        assert_eq!(evaluate("2026-07-04T02:00:00Z").effect, Effect::Deny);
+        assert_eq!(evaluate("2026-07-02T15:00:00Z").reason, "Allowed by policy rule");
+    }
+
+    #[test]
+    fn test_time_condition_not_holidays_without_calendar() {
+        let mut engine = PolicyEngine::new();
+        engine.load_policy(holiday_policy()).unwrap();
+
+        let context = time_context("2026-07-03T15:00:00Z");
+        let result = engine.evaluate("urn:qauth:policy:validation-test", &context).unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+        assert!(result.reason.contains("not_holidays not enforced"));
+    }
+
+    #[test]
+    fn test_time_condition_unknown_timezone_errors() {
+        let mut policy = policy_with_rules(
+            r#"[{
+                "effect": "allow",
+                "resources": ["*"],
+                "actions": ["*"],
+                "conditions": { "time": { "after": "09:00" } }
+            }]"#,
+        );
+        let time = policy.rules[0].conditions.time.as_mut().unwrap();
+        time.timezone = Some("Mars/Olympus_Mons".into());
+
+        // Bypass load-time validation, as with a policy edited after loading
+        let mut engine = PolicyEngine::new();
+        engine.policies.insert(policy.id.clone(), policy.clone());
+        assert!(matches!(
+            engine.evaluate(&policy.id, &time_context("2026-01-30T10:00:00Z")),
+            Err(QAuthError::PolicyError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_unknown_timezone() {
+        let policy = policy_with_rules(
+            r#"[{
+                "id": "office-hours",
+                "effect": "allow",
+                "resources": ["*"],
+                "actions": ["read"],
+                "conditions": { "time": { "after": "09:00", "timezone": "Mars/Olympus_Mons" } }
+            }]"#,
+        );
+
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            vec![PolicyValidationError::InvalidTimezone {
+                rule: "office-hours".into(),
+                value: "Mars/Olympus_Mons".into(),
+            }]
+        );
     }
 
     #[test]
@@ -1052,7 +1743,7 @@ mod tests {
         .unwrap();
 
         let mut engine = PolicyEngine::new();
-        engine.load_policy(policy);
+        engine.load_policy(policy).unwrap();
 
         // Without MFA
         let context_no_mfa = EvaluationContext {
@@ -1119,7 +1810,7 @@ mod tests {
         .unwrap();
 
         let mut engine = PolicyEngine::new();
-        engine.load_policy(policy);
+        engine.load_policy(policy).unwrap();
 
         // With matching attributes
         let mut attributes = HashMap::new();
@@ -1146,4 +1837,470 @@ mod tests {
         let result = engine.evaluate("urn:qauth:policy:custom-test", &context).unwrap();
         assert_eq!(result.effect, Effect::Allow);
     }
+
+    #[test]
+    fn test_guard_deny_beats_higher_priority_allow() {
+        let policy: Policy = serde_json::from_str(
+            r#"
+            {
+                "id": "urn:qauth:policy:guard-test",
+                "version": "2026-01-30",
+                "issuer": "https://auth.example.com",
+                "rules": [
+                    {
+                        "id": "allow-all",
+                        "effect": "allow",
+                        "resources": ["*"],
+                        "actions": ["*"],
+                        "priority": 1000
+                    },
+                    {
+                        "id": "suspended-kill-switch",
+                        "effect": "deny",
+                        "resources": ["*"],
+                        "actions": ["*"],
+                        "conditions": {
+                            "custom": {
+                                "status": {"eq": "suspended"}
+                            }
+                        },
+                        "priority": 1,
+                        "guard": true
+                    },
+                    {
+                        "id": "deny-reports",
+                        "effect": "deny",
+                        "resources": ["reports/*"],
+                        "actions": ["*"],
+                        "priority": 10
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut engine = PolicyEngine::new();
+        engine.load_policy(policy).unwrap();
+
+        let context = |status: &str, path: &str| EvaluationContext {
+            subject: SubjectContext {
+                attributes: HashMap::from([("status".to_string(), serde_json::json!(status))]),
+                ..Default::default()
+            },
+            resource: ResourceContext {
+                path: path.to_string(),
+                ..Default::default()
+            },
+            request: RequestContext {
+                action: "read".to_string(),
+                timestamp: Utc::now(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Matching guard deny wins over the priority-1000 allow
+        let result = engine
+            .evaluate("urn:qauth:policy:guard-test", &context("suspended", "projects/1"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.matched_rule, Some("suspended-kill-switch".to_string()));
+
+        // Non-guard rules keep priority order: the allow outranks the lower deny
+        let result = engine
+            .evaluate("urn:qauth:policy:guard-test", &context("active", "reports/q1"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+        assert_eq!(result.matched_rule, Some("allow-all".to_string()));
+    }
+
+    #[test]
+    fn test_ipv4_mapped_ipv6_matches_ipv4_range() {
+        let policy: Policy = serde_json::from_str(
+            r#"
+            {
+                "id": "urn:qauth:policy:ip-test",
+                "version": "2026-01-30",
+                "issuer": "https://auth.example.com",
+                "rules": [
+                    {
+                        "effect": "allow",
+                        "resources": ["*"],
+                        "actions": ["*"],
+                        "conditions": {
+                            "ip": {
+                                "allow_ranges": ["192.0.2.0/24"]
+                            }
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut engine = PolicyEngine::new();
+        engine.load_policy(policy).unwrap();
+
+        let context = |ip: &str| EvaluationContext {
+            resource: ResourceContext {
+                path: "anything".to_string(),
+                ..Default::default()
+            },
+            request: RequestContext {
+                action: "read".to_string(),
+                ip: Some(ip.to_string()),
+                timestamp: Utc::now(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // IPv4-mapped IPv6 is treated as the IPv4 address
+        let result = engine
+            .evaluate("urn:qauth:policy:ip-test", &context("::ffff:192.0.2.1"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+
+        let result = engine
+            .evaluate("urn:qauth:policy:ip-test", &context("192.0.2.1"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+
+        // A genuine IPv6 address never matches an IPv4 range
+        let result = engine
+            .evaluate("urn:qauth:policy:ip-test", &context("2001:db8::c000:201"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+    }
+
+    /// Resolver granting each listed (subject, relationship, resource) triple
+    struct MockResolver(HashSet<(String, String, String)>);
+
+    impl RelationshipResolver for MockResolver {
+        fn has_relationship(
+            &self,
+            subject_id: &str,
+            relationship: &str,
+            resource_path: &str,
+        ) -> bool {
+            self.0.contains(&(
+                subject_id.to_string(),
+                relationship.to_string(),
+                resource_path.to_string(),
+            ))
+        }
+    }
+
+    fn rebac_policy() -> Policy {
+        policy_with_rules(
+            r#"[{
+                "id": "owner-write",
+                "effect": "allow",
+                "resources": ["projects/*"],
+                "actions": ["write"],
+                "conditions": { "relationship": { "subject_is": "owner", "of_resource": true } }
+            }]"#,
+        )
+    }
+
+    fn rebac_context(subject: &str, path: &str) -> EvaluationContext {
+        EvaluationContext {
+            subject: SubjectContext {
+                id: subject.to_string(),
+                ..Default::default()
+            },
+            resource: ResourceContext {
+                path: path.to_string(),
+                ..Default::default()
+            },
+            request: RequestContext {
+                action: "write".to_string(),
+                timestamp: Utc::now(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_relationship_condition_with_resolver() {
+        let resolver = MockResolver(HashSet::from([(
+            "alice".to_string(),
+            "owner".to_string(),
+            "projects/1".to_string(),
+        )]));
+        let mut engine = PolicyEngine::new().with_relationship_resolver(Arc::new(resolver));
+        let policy = rebac_policy();
+        engine.load_policy(policy.clone()).unwrap();
+
+        let result = engine.evaluate(&policy.id, &rebac_context("alice", "projects/1")).unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+        assert_eq!(result.matched_rule, Some("owner-write".to_string()));
+
+        // Not the owner of this project, and not the owner at all
+        let result = engine.evaluate(&policy.id, &rebac_context("alice", "projects/2")).unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.matched_rule, None);
+        let result = engine.evaluate(&policy.id, &rebac_context("bob", "projects/1")).unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+    }
+
+    #[test]
+    fn test_relationship_condition_without_resolver_denies() {
+        let mut engine = PolicyEngine::new();
+        let policy = rebac_policy();
+        engine.load_policy(policy.clone()).unwrap();
+
+        let result = engine.evaluate(&policy.id, &rebac_context("alice", "projects/1")).unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.matched_rule, Some("owner-write".to_string()));
+        assert!(result.reason.contains("RelationshipResolver"));
+
+        // Requests the rule does not apply to are unaffected
+        let result = engine.evaluate(&policy.id, &rebac_context("alice", "billing/1")).unwrap();
+        assert_eq!(result.reason, "No matching rule, default deny");
+    }
+
+    fn inheritance_engine(child_extends: &str, child_rules: &str) -> PolicyEngine {
+        let mut engine = PolicyEngine::new();
+        let mut parent = policy_with_rules(
+            r#"[{
+                "id": "parent-read",
+                "effect": "allow",
+                "resources": ["projects/**"],
+                "actions": ["read", "delete"]
+            }]"#,
+        );
+        parent.id = "urn:qauth:policy:parent".into();
+        engine.load_policy(parent).unwrap();
+
+        let mut child = policy_with_rules(child_rules);
+        child.id = "urn:qauth:policy:child".into();
+        child.extends = Some(child_extends.into());
+        engine.load_policy(child).unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_extends_child_overrides_parent() {
+        let engine = inheritance_engine(
+            "urn:qauth:policy:parent",
+            r#"[{
+                "id": "child-no-delete",
+                "effect": "deny",
+                "resources": ["projects/**"],
+                "actions": ["delete"]
+            }]"#,
+        );
+
+        let result = engine
+            .evaluate("urn:qauth:policy:child", &project_context("projects/1", "delete"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.matched_rule, Some("child-no-delete".to_string()));
+
+        // The parent on its own still allows it
+        let result = engine
+            .evaluate("urn:qauth:policy:parent", &project_context("projects/1", "delete"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+    }
+
+    #[test]
+    fn test_extends_falls_through_to_parent() {
+        let engine = inheritance_engine(
+            "urn:qauth:policy:parent",
+            r#"[{
+                "id": "child-analytics",
+                "effect": "allow",
+                "resources": ["analytics/**"],
+                "actions": ["read"]
+            }]"#,
+        );
+
+        let context = project_context("projects/1", "read");
+        let result = engine.evaluate("urn:qauth:policy:child", &context).unwrap();
+        assert_eq!(result.effect, Effect::Allow);
+        assert_eq!(result.matched_rule, Some("parent-read".to_string()));
+        assert_eq!(result.policy, Some("urn:qauth:policy:child".to_string()));
+
+        // Nothing in the chain matches: default deny
+        let result = engine
+            .evaluate("urn:qauth:policy:child", &project_context("billing/1", "read"))
+            .unwrap();
+        assert_eq!(result.effect, Effect::Deny);
+        assert_eq!(result.matched_rule, None);
+    }
+
+    #[test]
+    fn test_extends_cycle_and_missing_parent() {
+        let context = project_context("projects/1", "read");
+        let rules = r#"[{ "effect": "allow", "resources": ["other/**"], "actions": ["read"] }]"#;
+
+        let engine = inheritance_engine("urn:qauth:policy:child", rules);
+        let err = engine.evaluate("urn:qauth:policy:child", &context).unwrap_err();
+        assert!(matches!(err, QAuthError::PolicyError(ref msg) if msg.contains("cycle")));
+
+        let engine = inheritance_engine("urn:qauth:policy:missing", rules);
+        let err = engine.evaluate("urn:qauth:policy:child", &context).unwrap_err();
+        assert!(matches!(
+            err,
+            QAuthError::PolicyError(ref msg) if msg.contains("urn:qauth:policy:missing")
+        ));
+    }
+
+    #[test]
+    fn test_ip_in_range_cidr() {
+        let ip = |s: &str| normalize_ip(IpAddr::from_str(s).unwrap());
+
+        // /16 honours the second octet
+        assert!(ip_in_range(&ip("10.0.255.1"), "10.0.0.0/16").unwrap());
+        assert!(!ip_in_range(&ip("10.1.0.0"), "10.0.0.0/16").unwrap());
+
+        // /24, with the first address past the end
+        assert!(ip_in_range(&ip("192.0.2.0"), "192.0.2.0/24").unwrap());
+        assert!(ip_in_range(&ip("192.0.2.255"), "192.0.2.0/24").unwrap());
+        assert!(!ip_in_range(&ip("192.0.3.0"), "192.0.2.0/24").unwrap());
+        assert!(!ip_in_range(&ip("192.0.1.255"), "192.0.2.0/24").unwrap());
+
+        // /32 and a bare address are single hosts; /0 matches everything
+        assert!(ip_in_range(&ip("203.0.113.7"), "203.0.113.7/32").unwrap());
+        assert!(!ip_in_range(&ip("203.0.113.8"), "203.0.113.7/32").unwrap());
+        assert!(ip_in_range(&ip("203.0.113.7"), "203.0.113.7").unwrap());
+        assert!(ip_in_range(&ip("198.51.100.1"), "0.0.0.0/0").unwrap());
+
+        // IPv6 /64
+        assert!(ip_in_range(&ip("2001:db8:1:2:ffff::1"), "2001:db8:1:2::/64").unwrap());
+        assert!(!ip_in_range(&ip("2001:db8:1:3::"), "2001:db8:1:2::/64").unwrap());
+
+        // Families never match each other; mapped networks act as IPv4
+        assert!(!ip_in_range(&ip("::1"), "0.0.0.0/0").unwrap());
+        assert!(ip_in_range(&ip("10.0.0.1"), "::ffff:10.0.0.0/104").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_ip_range_is_policy_error() {
+        let ip = IpAddr::from_str("10.0.0.1").unwrap();
+        for range in ["10.0.0.0/33", "10.0.0.0/", "not-an-ip", "::/129"] {
+            assert!(matches!(ip_in_range(&ip, range), Err(QAuthError::PolicyError(_))));
+        }
+
+        // Evaluation surfaces it instead of failing closed
+        let mut policy = policy_with_rules(
+            r#"[{
+                "effect": "allow",
+                "resources": ["*"],
+                "actions": ["*"],
+                "conditions": { "ip": { "allow_ranges": ["10.0.0.0/8"] } }
+            }]"#,
+        );
+        let mut engine = PolicyEngine::new();
+        engine.load_policy(policy.clone()).unwrap();
+        let context = EvaluationContext {
+            request: RequestContext {
+                action: "read".to_string(),
+                ip: Some("10.0.0.1".to_string()),
+                timestamp: Utc::now(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(engine.evaluate(&policy.id, &context).unwrap().effect, Effect::Allow);
+
+        policy.rules[0].conditions.ip.as_mut().unwrap().allow_ranges = vec!["10.0.0.0/40".into()];
+        engine.policies.insert(policy.id.clone(), policy.clone());
+        assert!(matches!(
+            engine.evaluate(&policy.id, &context),
+            Err(QAuthError::PolicyError(_))
+        ));
+    }
+
+    fn policy_with_rules(rules: &str) -> Policy {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "urn:qauth:policy:validation-test",
+                "version": "2026-01-30",
+                "issuer": "https://auth.example.com",
+                "rules": {}
+            }}"#,
+            rules
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_accepts_test_policy() {
+        assert!(create_test_policy().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid_time() {
+        let policy = policy_with_rules(
+            r#"[{
+                "id": "office-hours",
+                "effect": "allow",
+                "resources": ["*"],
+                "actions": ["read"],
+                "conditions": { "time": { "after": "9am", "before": "17:00" } }
+            }]"#,
+        );
+
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            vec![PolicyValidationError::InvalidTime {
+                rule: "office-hours".into(),
+                value: "9am".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_empty_resources() {
+        let policy =
+            policy_with_rules(r#"[{ "effect": "allow", "resources": [], "actions": ["read"] }]"#);
+
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            vec![PolicyValidationError::EmptyResources { rule: "#0".into() }]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let policy = policy_with_rules(
+            r#"[
+                { "id": "dup", "effect": "allow", "resources": ["a"], "actions": ["read"] },
+                {
+                    "id": "dup",
+                    "effect": "deny",
+                    "resources": ["b"],
+                    "actions": ["read"],
+                    "conditions": { "ip": { "deny_ranges": ["10.0.0.0/33", "not-an-ip"] } }
+                }
+            ]"#,
+        );
+
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            vec![
+                PolicyValidationError::DuplicateRuleId("dup".into()),
+                PolicyValidationError::InvalidIpRange {
+                    rule: "dup".into(),
+                    value: "10.0.0.0/33".into(),
+                },
+                PolicyValidationError::InvalidIpRange {
+                    rule: "dup".into(),
+                    value: "not-an-ip".into(),
+                },
+            ]
+        );
+
+        // The engine refuses to load it
+        let mut engine = PolicyEngine::new();
+        let result = engine.load_policy(policy);
+        assert!(matches!(result, Err(QAuthError::PolicyError(_))));
+        assert!(engine.policies.is_empty());
+    }
 }
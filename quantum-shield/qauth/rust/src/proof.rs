@@ -8,15 +8,28 @@ use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::Utc;
 use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::Mutex;
+use zeroize::Zeroizing;
 
 /// Maximum age of a proof in seconds
 pub const PROOF_MAX_AGE_SECONDS: i64 = 60;
 
+/// How far in the future a proof's timestamp may be, in seconds
+pub const PROOF_MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
 /// Size of the nonce in bytes
 pub const NONCE_SIZE: usize = 16;
 
+/// Proof wire format version
+pub const PROOF_VERSION: u8 = 0x01;
+
+/// Size of the fixed-length fields in an encoded proof
+///
+/// version (1) + timestamp (8) + nonce (16) + method length (2) + URI length (2)
+/// + body hash (32) + token hash (32) + signature (64)
+pub const PROOF_FIXED_SIZE: usize = 1 + 8 + NONCE_SIZE + 2 + 2 + 32 + 32 + 64;
+
 /// Proof of possession for API requests
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofOfPossession {
@@ -61,6 +74,15 @@ mod hex_serde {
 }
 
 impl ProofOfPossession {
+    /// Digest a proof is bound to: SHA-256 of the encoded QToken
+    ///
+    /// The signature covers only this fixed-size digest, never the token
+    /// itself. Callers handling many proofs for one token can compute it
+    /// once and use the `*_token_hash` variants.
+    pub fn hash_token(token_bytes: &[u8]) -> [u8; 32] {
+        sha256(token_bytes)
+    }
+
     /// Create a new proof of possession
     pub fn create(
         method: &str,
@@ -68,11 +90,33 @@ impl ProofOfPossession {
         body: Option<&[u8]>,
         token_bytes: &[u8],
         signing_key: &Ed25519KeyPair,
+    ) -> Self {
+        Self::create_for_token_hash(method, uri, body, Self::hash_token(token_bytes), signing_key)
+    }
+
+    /// Create a proof bound to a precomputed `hash_token` digest
+    pub fn create_for_token_hash(
+        method: &str,
+        uri: &str,
+        body: Option<&[u8]>,
+        token_hash: [u8; 32],
+        signing_key: &Ed25519KeyPair,
     ) -> Self {
         let timestamp = Utc::now().timestamp_millis() as u64;
+        Self::create_at(timestamp, method, uri, body, token_hash, signing_key)
+    }
+
+    /// Create a proof with an explicit timestamp (Unix milliseconds)
+    fn create_at(
+        timestamp: u64,
+        method: &str,
+        uri: &str,
+        body: Option<&[u8]>,
+        token_hash: [u8; 32],
+        signing_key: &Ed25519KeyPair,
+    ) -> Self {
         let nonce: [u8; NONCE_SIZE] = rand::random();
         let body_hash = body.map(sha256).unwrap_or([0u8; 32]);
-        let token_hash = sha256(token_bytes);
 
         // Create message to sign
         let message = Self::create_signing_message(
@@ -127,13 +171,85 @@ impl ProofOfPossession {
         serde_json::from_str(json).map_err(|e| QAuthError::SerializationError(e.to_string()))
     }
 
-    /// Encode to base64url for HTTP header
+    /// Serialize to the compact binary form
+    ///
+    /// For storage and internal transport; the `X-QAuth-Proof` header keeps
+    /// the JSON form (see `encode`). Layout (all integers big-endian):
+    ///
+    /// ```text
+    /// version (1) || timestamp (8) || nonce (16)
+    ///   || method_len (2) || method || uri_len (2) || uri
+    ///   || body_hash (32) || token_hash (32) || signature (64)
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let method_len = u16::try_from(self.method.len())
+            .map_err(|_| QAuthError::InvalidInput("Proof method too long".into()))?;
+        let uri_len = u16::try_from(self.uri.len())
+            .map_err(|_| QAuthError::InvalidInput("Proof URI too long".into()))?;
+
+        let mut bytes = Vec::with_capacity(PROOF_FIXED_SIZE + self.method.len() + self.uri.len());
+        bytes.push(PROOF_VERSION);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&method_len.to_be_bytes());
+        bytes.extend_from_slice(self.method.as_bytes());
+        bytes.extend_from_slice(&uri_len.to_be_bytes());
+        bytes.extend_from_slice(self.uri.as_bytes());
+        bytes.extend_from_slice(&self.body_hash);
+        bytes.extend_from_slice(&self.token_hash);
+        bytes.extend_from_slice(&self.signature);
+        Ok(bytes)
+    }
+
+    /// Deserialize from bytes
+    ///
+    /// Parsing is strict: the version must match, every length must fit the
+    /// input, and trailing bytes are rejected. No cryptographic checks are
+    /// performed here.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < PROOF_FIXED_SIZE {
+            return Err(QAuthError::InvalidInput("Proof too short".into()));
+        }
+
+        if bytes[0] != PROOF_VERSION {
+            return Err(QAuthError::InvalidInput(format!(
+                "Unsupported proof version: {}",
+                bytes[0]
+            )));
+        }
+
+        let mut reader = ProofReader::new(&bytes[1..]);
+
+        let timestamp = u64::from_be_bytes(reader.take_array("timestamp")?);
+        let nonce: [u8; NONCE_SIZE] = reader.take_array("nonce")?;
+        let method = reader.take_string("method")?;
+        let uri = reader.take_string("URI")?;
+        let body_hash: [u8; 32] = reader.take_array("body hash")?;
+        let token_hash: [u8; 32] = reader.take_array("token hash")?;
+        let signature: [u8; 64] = reader.take_array("signature")?;
+
+        if !reader.is_empty() {
+            return Err(QAuthError::InvalidInput("Trailing bytes after proof".into()));
+        }
+
+        Ok(Self {
+            timestamp,
+            nonce,
+            method,
+            uri,
+            body_hash,
+            token_hash,
+            signature,
+        })
+    }
+
+    /// Encode to base64url(JSON) for the `X-QAuth-Proof` HTTP header
     pub fn encode(&self) -> Result<String> {
         let json = self.to_json()?;
         Ok(URL_SAFE_NO_PAD.encode(json.as_bytes()))
     }
 
-    /// Decode from base64url HTTP header
+    /// Decode from the base64url(JSON) `X-QAuth-Proof` HTTP header
     pub fn decode(s: &str) -> Result<Self> {
         let json_bytes = URL_SAFE_NO_PAD
             .decode(s)
@@ -144,44 +260,90 @@ impl ProofOfPossession {
     }
 }
 
+/// Bounds-checked cursor over an encoded proof
+struct ProofReader<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ProofReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { remaining: bytes }
+    }
+
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8]> {
+        if self.remaining.len() < len {
+            return Err(QAuthError::InvalidInput(format!("Proof truncated in {}", field)));
+        }
+        let (head, tail) = self.remaining.split_at(len);
+        self.remaining = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self, field: &str) -> Result<[u8; N]> {
+        let bytes = self.take(N, field)?;
+        // Length was checked by `take`
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn take_string(&mut self, field: &str) -> Result<String> {
+        let len = u16::from_be_bytes(self.take_array(field)?) as usize;
+        let bytes = self.take(len, field)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| QAuthError::InvalidInput(format!("Proof {} is not valid UTF-8", field)))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
 /// Proof validator with replay protection
 pub struct ProofValidator {
     /// Client's Ed25519 public key
     client_public_key: VerifyingKey,
+    /// Max allowed proof age in seconds
+    max_age_seconds: i64,
     /// Max allowed clock skew in seconds
     max_clock_skew_seconds: i64,
     /// Used nonces for replay protection
     used_nonces: Mutex<NonceCache>,
 }
 
-/// Simple time-windowed nonce cache
+/// Nonces of accepted proofs, kept until their proofs would be too old
+#[derive(Default)]
 struct NonceCache {
-    nonces: HashSet<[u8; NONCE_SIZE]>,
-    window_start: i64,
-    window_size_seconds: i64,
+    /// Nonce -> time (Unix milliseconds) after which it can be forgotten
+    nonces: HashMap<[u8; NONCE_SIZE], u64>,
+    /// Time (Unix milliseconds) of the next sweep for expired nonces
+    next_sweep_ms: u64,
 }
 
 impl NonceCache {
-    fn new(window_size_seconds: i64) -> Self {
-        Self {
-            nonces: HashSet::new(),
-            window_start: Utc::now().timestamp(),
-            window_size_seconds,
+    /// Mark a nonce as used until `expires_ms`, returning false if it
+    /// already was
+    ///
+    /// Expired nonces are swept at most once per `sweep_interval_ms`, so a
+    /// check is O(1) amortized; an expired nonce not yet swept is treated as
+    /// unused.
+    fn check_and_mark(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        now_ms: u64,
+        expires_ms: u64,
+        sweep_interval_ms: u64,
+    ) -> bool {
+        if now_ms >= self.next_sweep_ms {
+            self.nonces.retain(|_, expiry| *expiry >= now_ms);
+            self.next_sweep_ms = now_ms.saturating_add(sweep_interval_ms.max(1000));
         }
-    }
-
-    /// Check if nonce was already used, and mark it as used
-    fn check_and_mark(&mut self, nonce: &[u8; NONCE_SIZE]) -> bool {
-        let now = Utc::now().timestamp();
 
-        // Rotate window if needed
-        if now - self.window_start > self.window_size_seconds {
-            self.nonces.clear();
-            self.window_start = now;
+        match self.nonces.get(nonce) {
+            Some(&expiry) if expiry >= now_ms => false,
+            _ => {
+                self.nonces.insert(*nonce, expires_ms);
+                true
+            }
         }
-
-        // Check and insert
-        self.nonces.insert(*nonce)
     }
 }
 
@@ -193,12 +355,24 @@ impl ProofValidator {
 
         Ok(Self {
             client_public_key,
-            max_clock_skew_seconds: PROOF_MAX_AGE_SECONDS,
-            used_nonces: Mutex::new(NonceCache::new(PROOF_MAX_AGE_SECONDS * 2)),
+            max_age_seconds: PROOF_MAX_AGE_SECONDS,
+            max_clock_skew_seconds: PROOF_MAX_CLOCK_SKEW_SECONDS,
+            used_nonces: Mutex::new(NonceCache::default()),
         })
     }
 
-    /// Set maximum allowed clock skew
+    /// Client public key proofs are verified against
+    pub fn client_public_key(&self) -> [u8; 32] {
+        self.client_public_key.to_bytes()
+    }
+
+    /// Set maximum allowed proof age
+    pub fn with_max_age(mut self, seconds: i64) -> Self {
+        self.max_age_seconds = seconds;
+        self
+    }
+
+    /// Set how far in the future a proof may be dated
     pub fn with_max_clock_skew(mut self, seconds: i64) -> Self {
         self.max_clock_skew_seconds = seconds;
         self
@@ -213,51 +387,107 @@ impl ProofValidator {
         body: Option<&[u8]>,
         token_bytes: &[u8],
     ) -> Result<()> {
-        // 1. Check timestamp (within allowed window)
+        self.validate_token_hash(
+            proof,
+            expected_method,
+            expected_uri,
+            body,
+            &ProofOfPossession::hash_token(token_bytes),
+        )
+    }
+
+    /// Validate a proof with freshness bounds other than the validator's own
+    ///
+    /// Rejects proofs older than `max_age_seconds` or dated more than
+    /// `clock_skew_seconds` in the future. Nonces are shared with `validate`,
+    /// so a proof is accepted at most once either way.
+    #[allow(clippy::too_many_arguments)]
+    pub fn validate_with_age(
+        &self,
+        max_age_seconds: i64,
+        clock_skew_seconds: i64,
+        proof: &ProofOfPossession,
+        expected_method: &str,
+        expected_uri: &str,
+        body: Option<&[u8]>,
+        token_bytes: &[u8],
+    ) -> Result<()> {
+        self.check_proof(
+            proof,
+            expected_method,
+            expected_uri,
+            body,
+            &ProofOfPossession::hash_token(token_bytes),
+            max_age_seconds,
+            clock_skew_seconds,
+        )
+    }
+
+    /// Validate a proof against a precomputed `ProofOfPossession::hash_token` digest
+    pub fn validate_token_hash(
+        &self,
+        proof: &ProofOfPossession,
+        expected_method: &str,
+        expected_uri: &str,
+        body: Option<&[u8]>,
+        expected_token_hash: &[u8; 32],
+    ) -> Result<()> {
+        self.check_proof(
+            proof,
+            expected_method,
+            expected_uri,
+            body,
+            expected_token_hash,
+            self.max_age_seconds,
+            self.max_clock_skew_seconds,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_proof(
+        &self,
+        proof: &ProofOfPossession,
+        expected_method: &str,
+        expected_uri: &str,
+        body: Option<&[u8]>,
+        expected_token_hash: &[u8; 32],
+        max_age_seconds: i64,
+        clock_skew_seconds: i64,
+    ) -> Result<()> {
+        // 1. Check timestamp (not too old, not too far in the future)
         let now_ms = Utc::now().timestamp_millis() as u64;
-        let proof_age_ms = now_ms.saturating_sub(proof.timestamp);
-        let max_age_ms = (self.max_clock_skew_seconds * 1000) as u64;
+        let max_age_ms = max_age_seconds.max(0) as u64 * 1000;
+        let max_skew_ms = clock_skew_seconds.max(0) as u64 * 1000;
 
-        if proof_age_ms > max_age_ms {
+        if now_ms.saturating_sub(proof.timestamp) > max_age_ms {
             return Err(QAuthError::InvalidProof);
         }
-
-        // Also check for future timestamps (clock skew)
-        if proof.timestamp > now_ms + max_age_ms {
+        if proof.timestamp > now_ms + max_skew_ms {
             return Err(QAuthError::InvalidProof);
         }
 
-        // 2. Check nonce for replay protection
-        {
-            let mut cache = self.used_nonces.lock().unwrap();
-            if !cache.check_and_mark(&proof.nonce) {
-                return Err(QAuthError::InvalidProof); // Nonce reuse
-            }
-        }
-
-        // 3. Verify method matches
+        // 2. Verify method matches
         if proof.method != expected_method {
             return Err(QAuthError::InvalidProof);
         }
 
-        // 4. Verify URI matches
+        // 3. Verify URI matches
         if proof.uri != expected_uri {
             return Err(QAuthError::InvalidProof);
         }
 
-        // 5. Verify body hash
+        // 4. Verify body hash
         let expected_body_hash = body.map(sha256).unwrap_or([0u8; 32]);
         if proof.body_hash != expected_body_hash {
             return Err(QAuthError::InvalidProof);
         }
 
-        // 6. Verify token hash
-        let expected_token_hash = sha256(token_bytes);
-        if proof.token_hash != expected_token_hash {
+        // 5. Verify token hash
+        if proof.token_hash != *expected_token_hash {
             return Err(QAuthError::InvalidProof);
         }
 
-        // 7. Verify signature
+        // 6. Verify signature
         let message = ProofOfPossession::create_signing_message(
             proof.timestamp,
             &proof.nonce,
@@ -272,6 +502,23 @@ impl ProofValidator {
             .verify(&message, &signature)
             .map_err(|_| QAuthError::InvalidProof)?;
 
+        // 7. Check nonce for replay protection, once the proof is known to be
+        // genuine. It is remembered for as long as the proof stays fresh under
+        // this call's limit or the validator's own, whichever is longer, so a
+        // proof first accepted with a shorter max age cannot be replayed later
+        // through `validate`.
+        let validator_max_age_ms = self.max_age_seconds.max(0) as u64 * 1000;
+        let retain_ms = max_age_ms.max(validator_max_age_ms);
+        let mut cache = self.used_nonces.lock().unwrap();
+        if !cache.check_and_mark(
+            &proof.nonce,
+            now_ms,
+            proof.timestamp.saturating_add(retain_ms),
+            validator_max_age_ms,
+        ) {
+            return Err(QAuthError::InvalidProof); // Nonce reuse
+        }
+
         Ok(())
     }
 }
@@ -295,6 +542,36 @@ impl ProofGenerator {
         (Self { signing_key }, public_key)
     }
 
+    /// Restore a generator from bytes produced by `export_secret`
+    ///
+    /// Returns the generator and its public key. The secret must be 32
+    /// bytes and not all zeros (as left by uninitialized storage).
+    pub fn from_secret(secret_bytes: &[u8]) -> Result<(Self, [u8; 32])> {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        if secret_bytes.len() != secret.len() {
+            return Err(QAuthError::InvalidInput(format!(
+                "Proof key secret must be 32 bytes, got {}",
+                secret_bytes.len()
+            )));
+        }
+        secret.copy_from_slice(secret_bytes);
+        if secret.iter().all(|&b| b == 0) {
+            return Err(QAuthError::InvalidInput("Proof key secret is all zeros".into()));
+        }
+
+        let generator = Self::new(&secret)?;
+        let public_key = generator.public_key();
+        Ok((generator, public_key))
+    }
+
+    /// Export the private key, to persist the generator across restarts
+    ///
+    /// The bytes are wiped when the returned buffer is dropped; store them
+    /// encrypted.
+    pub fn export_secret(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.signing_key.private_key_bytes().to_vec())
+    }
+
     /// Get the public key
     pub fn public_key(&self) -> [u8; 32] {
         self.signing_key.public_key_bytes()
@@ -310,6 +587,17 @@ impl ProofGenerator {
     ) -> ProofOfPossession {
         ProofOfPossession::create(method, uri, body, token_bytes, &self.signing_key)
     }
+
+    /// Create a proof bound to a precomputed `ProofOfPossession::hash_token` digest
+    pub fn create_proof_for_token_hash(
+        &self,
+        method: &str,
+        uri: &str,
+        body: Option<&[u8]>,
+        token_hash: [u8; 32],
+    ) -> ProofOfPossession {
+        ProofOfPossession::create_for_token_hash(method, uri, body, token_hash, &self.signing_key)
+    }
 }
 
 /// Token request proof for the token endpoint
@@ -428,6 +716,30 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_proof_generator_restored_from_secret() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let secret = generator.export_secret();
+        drop(generator);
+
+        let (restored, restored_public_key) = ProofGenerator::from_secret(&secret).unwrap();
+        assert_eq!(restored_public_key, public_key);
+
+        let token = b"sample-qtoken-bytes";
+        let proof = restored.create_proof("GET", "/api/resource", None, token);
+        let validator = ProofValidator::new(&public_key).unwrap();
+        assert!(validator.validate(&proof, "GET", "/api/resource", None, token).is_ok());
+
+        assert!(matches!(
+            ProofGenerator::from_secret(&secret[..31]),
+            Err(QAuthError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            ProofGenerator::from_secret(&[0u8; 32]),
+            Err(QAuthError::InvalidInput(_))
+        ));
+    }
+
     #[test]
     fn test_proof_wrong_method_fails() {
         let (generator, public_key) = ProofGenerator::generate();
@@ -467,6 +779,45 @@ mod tests {
         assert!(matches!(result, Err(QAuthError::InvalidProof)));
     }
 
+    #[test]
+    fn test_proof_bound_to_token() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token_a = vec![0xaa; 2048];
+        let token_b = vec![0xbb; 2048];
+
+        let proof = generator.create_proof("GET", "/api/resource", None, &token_a);
+        assert_eq!(proof.token_hash, ProofOfPossession::hash_token(&token_a));
+
+        // Each validator has its own nonce cache, so the proof is fresh for both
+        let validator = ProofValidator::new(&public_key).unwrap();
+        let result = validator.validate(&proof, "GET", "/api/resource", None, &token_b);
+        assert!(matches!(result, Err(QAuthError::InvalidProof)));
+
+        let validator = ProofValidator::new(&public_key).unwrap();
+        assert!(validator.validate(&proof, "GET", "/api/resource", None, &token_a).is_ok());
+
+        // Swapping in B's digest breaks the signature
+        let mut transplanted = generator.create_proof("GET", "/api/resource", None, &token_a);
+        transplanted.token_hash = ProofOfPossession::hash_token(&token_b);
+        let validator = ProofValidator::new(&public_key).unwrap();
+        let result = validator.validate(&transplanted, "GET", "/api/resource", None, &token_b);
+        assert!(matches!(result, Err(QAuthError::InvalidProof)));
+    }
+
+    #[test]
+    fn test_proof_with_precomputed_token_hash() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token = b"sample-qtoken-bytes";
+        let token_hash = ProofOfPossession::hash_token(token);
+
+        let proof = generator.create_proof_for_token_hash("GET", "/api/resource", None, token_hash);
+
+        let validator = ProofValidator::new(&public_key).unwrap();
+        assert!(validator
+            .validate_token_hash(&proof, "GET", "/api/resource", None, &token_hash)
+            .is_ok());
+    }
+
     #[test]
     fn test_replay_protection() {
         let (generator, public_key) = ProofGenerator::generate();
@@ -483,6 +834,108 @@ mod tests {
         assert!(validator.validate(&proof, "GET", "/api/resource", None, token).is_err());
     }
 
+    /// Proof for `GET /api` dated `offset_seconds` from now
+    fn proof_at(
+        generator: &ProofGenerator,
+        offset_seconds: i64,
+        token: &[u8],
+    ) -> ProofOfPossession {
+        let timestamp = (Utc::now().timestamp_millis() + offset_seconds * 1000) as u64;
+        let token_hash = ProofOfPossession::hash_token(token);
+        ProofOfPossession::create_at(
+            timestamp,
+            "GET",
+            "/api",
+            None,
+            token_hash,
+            &generator.signing_key,
+        )
+    }
+
+    #[test]
+    fn test_proof_max_age() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token = b"sample-qtoken-bytes";
+        let validator = ProofValidator::new(&public_key).unwrap();
+
+        // Two minutes old: past the default, within a looser explicit limit
+        let proof = proof_at(&generator, -120, token);
+        assert!(matches!(
+            validator.validate(&proof, "GET", "/api", None, token),
+            Err(QAuthError::InvalidProof)
+        ));
+        assert!(validator.validate_with_age(300, 5, &proof, "GET", "/api", None, token).is_ok());
+
+        // Thirty seconds old, but the caller only accepts ten
+        let proof = proof_at(&generator, -30, token);
+        assert!(validator.validate_with_age(10, 5, &proof, "GET", "/api", None, token).is_err());
+    }
+
+    #[test]
+    fn test_proof_future_dated() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token = b"sample-qtoken-bytes";
+        let validator = ProofValidator::new(&public_key).unwrap();
+
+        let proof = proof_at(&generator, 30, token);
+        assert!(validator.validate_with_age(60, 5, &proof, "GET", "/api", None, token).is_err());
+        assert!(validator.validate_with_age(60, 60, &proof, "GET", "/api", None, token).is_ok());
+
+        let proof = proof_at(&generator, 120, token);
+        assert!(matches!(
+            validator.validate(&proof, "GET", "/api", None, token),
+            Err(QAuthError::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_replayed_nonce_rejected() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token = b"sample-qtoken-bytes";
+        let validator = ProofValidator::new(&public_key).unwrap();
+
+        let proof = proof_at(&generator, -5, token);
+        assert!(validator.validate_with_age(60, 5, &proof, "GET", "/api", None, token).is_ok());
+        assert!(validator.validate_with_age(60, 5, &proof, "GET", "/api", None, token).is_err());
+        assert!(validator.validate(&proof, "GET", "/api", None, token).is_err());
+
+        // A rejected proof does not use up its nonce
+        let proof = proof_at(&generator, -5, token);
+        assert!(validator.validate(&proof, "POST", "/api", None, token).is_err());
+        assert!(validator.validate(&proof, "GET", "/api", None, token).is_ok());
+    }
+
+    #[test]
+    fn test_short_max_age_keeps_nonce_for_validator_max_age() {
+        let (generator, public_key) = ProofGenerator::generate();
+        let token = b"sample-qtoken-bytes";
+        let validator = ProofValidator::new(&public_key).unwrap();
+
+        // Accepted under a 10 second limit, but remembered for as long as
+        // `validate` would still accept it
+        let proof = proof_at(&generator, -5, token);
+        assert!(validator.validate_with_age(10, 5, &proof, "GET", "/api", None, token).is_ok());
+        let expiry = validator.used_nonces.lock().unwrap().nonces[&proof.nonce];
+        assert_eq!(expiry, proof.timestamp + PROOF_MAX_AGE_SECONDS as u64 * 1000);
+    }
+
+    #[test]
+    fn test_nonce_cache_sweeps_on_interval() {
+        let mut cache = NonceCache::default();
+        assert!(cache.check_and_mark(&[1; NONCE_SIZE], 1_000, 2_000, 10_000));
+        assert!(cache.check_and_mark(&[2; NONCE_SIZE], 1_500, 50_000, 10_000));
+        assert!(!cache.check_and_mark(&[1; NONCE_SIZE], 1_900, 2_900, 10_000));
+
+        // Expired but not yet swept: usable again, and not swept until the interval passes
+        assert!(cache.check_and_mark(&[1; NONCE_SIZE], 3_000, 4_000, 10_000));
+        assert!(cache.check_and_mark(&[3; NONCE_SIZE], 5_000, 6_000, 10_000));
+        assert_eq!(cache.nonces.len(), 3);
+
+        assert!(cache.check_and_mark(&[4; NONCE_SIZE], 11_000, 20_000, 10_000));
+        assert_eq!(cache.nonces.len(), 2);
+        assert!(!cache.check_and_mark(&[2; NONCE_SIZE], 11_000, 60_000, 10_000));
+    }
+
     #[test]
     fn test_proof_serialization() {
         let (generator, _) = ProofGenerator::generate();
@@ -497,6 +950,62 @@ mod tests {
         assert_eq!(proof.nonce, decoded.nonce);
         assert_eq!(proof.method, decoded.method);
         assert_eq!(proof.uri, decoded.uri);
+        assert_eq!(proof.body_hash, decoded.body_hash);
+        assert_eq!(proof.token_hash, decoded.token_hash);
+        assert_eq!(proof.signature, decoded.signature);
+        assert_eq!(proof.to_bytes().unwrap(), decoded.to_bytes().unwrap());
+
+        // The header stays base64url(JSON), which the SDKs parse
+        let json = String::from_utf8(URL_SAFE_NO_PAD.decode(&encoded).unwrap()).unwrap();
+        assert_eq!(json, proof.to_json().unwrap());
+
+        let from_binary = ProofOfPossession::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert_eq!(from_binary.to_json().unwrap(), proof.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_proof_decode_rejects_truncated() {
+        let (generator, _) = ProofGenerator::generate();
+        let proof = generator.create_proof("POST", "/api/resource", Some(b"body"), b"token");
+        let bytes = proof.to_bytes().unwrap();
+
+        for len in [0, 1, PROOF_FIXED_SIZE - 1, bytes.len() - 1] {
+            assert!(matches!(
+                ProofOfPossession::from_bytes(&bytes[..len]),
+                Err(QAuthError::InvalidInput(_))
+            ));
+        }
+
+        // Method length pointing past the end of the input
+        let mut bytes = proof.to_bytes().unwrap();
+        bytes[1 + 8 + NONCE_SIZE..1 + 8 + NONCE_SIZE + 2].copy_from_slice(&u16::MAX.to_be_bytes());
+        assert!(ProofOfPossession::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_proof_decode_rejects_trailing_bytes() {
+        let (generator, _) = ProofGenerator::generate();
+        let proof = generator.create_proof("GET", "/api/resource", None, b"token");
+
+        let mut bytes = proof.to_bytes().unwrap();
+        bytes.push(0);
+        assert!(matches!(
+            ProofOfPossession::from_bytes(&bytes),
+            Err(QAuthError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_decode_rejects_wrong_version() {
+        let (generator, _) = ProofGenerator::generate();
+        let proof = generator.create_proof("GET", "/api/resource", None, b"token");
+
+        let mut bytes = proof.to_bytes().unwrap();
+        bytes[0] = PROOF_VERSION + 1;
+        assert!(matches!(
+            ProofOfPossession::from_bytes(&bytes),
+            Err(QAuthError::InvalidInput(_))
+        ));
     }
 
     #[test]
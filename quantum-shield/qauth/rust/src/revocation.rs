@@ -6,14 +6,40 @@ use crate::error::{ErrorCode, QAuthError, Result};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
+#[cfg(feature = "tokio")]
+mod async_store;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "tokio")]
+pub use async_store::{AsyncRevocationChecker, AsyncRevocationStore, InMemoryAsyncRevocationStore};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteRevocationStore;
+
 /// Default cache TTL in seconds
 pub const DEFAULT_CACHE_TTL_SECONDS: i64 = 300; // 5 minutes
 
-/// Maximum offline validity in seconds
+/// Default maximum offline validity in seconds
+///
+/// A cached revocation status older than this is not trusted on its own;
+/// the store must be consulted again. Configurable per checker via
+/// `RevocationChecker::with_offline_validity`.
 pub const MAX_OFFLINE_VALIDITY_SECONDS: i64 = 300; // 5 minutes
 
+/// Default time a loaded bloom filter is trusted, in seconds
+///
+/// A filter older than this no longer rules out revocations; reload it with
+/// `RevocationChecker::refresh_bloom_filter_if_stale`.
+pub const DEFAULT_BLOOM_FILTER_TTL_SECONDS: i64 = 300; // 5 minutes
+
+/// Default TTL of the recently-validated cache (disabled)
+///
+/// Enable per checker with `RevocationChecker::with_validated_ttl`.
+pub const DEFAULT_VALIDATED_TTL_SECONDS: i64 = 0;
+
 /// Revocation reason
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -120,6 +146,8 @@ pub struct RevocationCache {
     entries: RwLock<HashMap<[u8; 16], CachedStatus>>,
     /// Subject-level revocation times
     subject_revocations: RwLock<HashMap<Vec<u8>, DateTime<Utc>>>,
+    /// Revocation IDs seen per subject, for invalidation
+    subject_tokens: RwLock<HashMap<Vec<u8>, HashSet<[u8; 16]>>>,
     /// Cache TTL
     ttl: Duration,
 }
@@ -130,6 +158,7 @@ impl RevocationCache {
         Self {
             entries: RwLock::new(HashMap::new()),
             subject_revocations: RwLock::new(HashMap::new()),
+            subject_tokens: RwLock::new(HashMap::new()),
             ttl: Duration::seconds(DEFAULT_CACHE_TTL_SECONDS),
         }
     }
@@ -139,15 +168,28 @@ impl RevocationCache {
         Self {
             entries: RwLock::new(HashMap::new()),
             subject_revocations: RwLock::new(HashMap::new()),
+            subject_tokens: RwLock::new(HashMap::new()),
             ttl: Duration::seconds(ttl_seconds),
         }
     }
 
     /// Get cached status
     pub fn get(&self, revocation_id: &[u8; 16]) -> Option<RevocationStatus> {
+        self.get_with_max_age(revocation_id, self.ttl, Utc::now())
+    }
+
+    /// Get cached status only if, at `now`, it is younger than both the TTL
+    /// and `max_age`
+    pub fn get_with_max_age(
+        &self,
+        revocation_id: &[u8; 16],
+        max_age: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<RevocationStatus> {
         let entries = self.entries.read().unwrap();
         if let Some(cached) = entries.get(revocation_id) {
-            if Utc::now() - cached.cached_at < self.ttl {
+            let age = now - cached.cached_at;
+            if age < self.ttl && age < max_age {
                 return Some(cached.status.clone());
             }
         }
@@ -181,11 +223,38 @@ impl RevocationCache {
         revocations.insert(subject_id, Utc::now());
     }
 
+    /// Record that a revocation ID belongs to a subject
+    pub fn track_subject(&self, revocation_id: [u8; 16], subject_id: &[u8]) {
+        let mut subject_tokens = self.subject_tokens.write().unwrap();
+        subject_tokens
+            .entry(subject_id.to_vec())
+            .or_default()
+            .insert(revocation_id);
+    }
+
+    /// Drop cached statuses for a subject's tokens and mark the subject revoked
+    pub fn invalidate_subject(&self, subject_id: &[u8]) {
+        let tokens = self.subject_tokens.write().unwrap().remove(subject_id);
+        if let Some(tokens) = tokens {
+            let mut entries = self.entries.write().unwrap();
+            for revocation_id in &tokens {
+                entries.remove(revocation_id);
+            }
+        }
+        self.revoke_subject(subject_id.to_vec());
+    }
+
     /// Clean up expired entries
     pub fn cleanup(&self) {
         let now = Utc::now();
         let mut entries = self.entries.write().unwrap();
         entries.retain(|_, cached| now - cached.cached_at < self.ttl);
+
+        let mut subject_tokens = self.subject_tokens.write().unwrap();
+        for tokens in subject_tokens.values_mut() {
+            tokens.retain(|id| entries.contains_key(id));
+        }
+        subject_tokens.retain(|_, tokens| !tokens.is_empty());
     }
 }
 
@@ -387,84 +456,430 @@ impl RevocationStore for InMemoryRevocationStore {
     }
 }
 
-/// Revocation checker with caching
-pub struct RevocationChecker {
-    store: Arc<dyn RevocationStore>,
+/// Counters updated by `RevocationChecker::is_revoked`
+#[derive(Default)]
+struct RevocationMetrics {
+    validated_hits: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    bloom_negatives: AtomicU64,
+    bloom_false_positives: AtomicU64,
+    store_queries: AtomicU64,
+    store_errors: AtomicU64,
+    revoked: AtomicU64,
+    not_revoked: AtomicU64,
+}
+
+impl RevocationMetrics {
+    fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_decision(&self, revoked: bool) {
+        if revoked {
+            Self::increment(&self.revoked);
+        } else {
+            Self::increment(&self.not_revoked);
+        }
+    }
+
+    fn snapshot(&self) -> RevocationMetricsSnapshot {
+        RevocationMetricsSnapshot {
+            validated_hits: self.validated_hits.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            bloom_negatives: self.bloom_negatives.load(Ordering::Relaxed),
+            bloom_false_positives: self.bloom_false_positives.load(Ordering::Relaxed),
+            store_queries: self.store_queries.load(Ordering::Relaxed),
+            store_errors: self.store_errors.load(Ordering::Relaxed),
+            revoked: self.revoked.load(Ordering::Relaxed),
+            not_revoked: self.not_revoked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of a checker's revocation counters
+///
+/// Counters are monotonic for the lifetime of the checker, suitable for
+/// exporting as Prometheus counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationMetricsSnapshot {
+    /// Lookups answered from the recently-validated cache
+    pub validated_hits: u64,
+    /// Lookups answered from the cache
+    pub cache_hits: u64,
+    /// Lookups not found (or stale) in the cache
+    pub cache_misses: u64,
+    /// Lookups the bloom filter ruled out without a store query
+    pub bloom_negatives: u64,
+    /// Bloom filter maybe-positives the store reported as not revoked
+    pub bloom_false_positives: u64,
+    /// Queries sent to the revocation store
+    pub store_queries: u64,
+    /// Store queries that failed (returned as errors, not decisions)
+    pub store_errors: u64,
+    /// Lookups that returned revoked
+    pub revoked: u64,
+    /// Lookups that returned not revoked
+    pub not_revoked: u64,
+}
+
+/// Cache, bloom filter, and counters shared by [`RevocationChecker`] and the
+/// async checker, so both look tokens up the same way
+struct RevocationLookup {
     cache: RevocationCache,
     bloom_filter: RwLock<Option<RevocationBloomFilter>>,
     bloom_filter_updated: RwLock<DateTime<Utc>>,
+    bloom_filter_ttl: Duration,
+    offline_validity: Duration,
+    /// Bumped by every `revoke`, so a lookup that raced with one does not
+    /// cache its stale "not revoked" result
+    generation: RwLock<u64>,
+    metrics: RevocationMetrics,
 }
 
-impl RevocationChecker {
-    /// Create a new checker
-    pub fn new(store: Arc<dyn RevocationStore>) -> Self {
+/// Outcome of the cache and bloom filter steps of a lookup
+enum LocalLookup {
+    /// Answered without the store
+    Decided(bool),
+    /// The store must be queried
+    QueryStore { bloom_maybe_positive: bool },
+}
+
+impl RevocationLookup {
+    fn new(cache: RevocationCache) -> Self {
         Self {
-            store,
-            cache: RevocationCache::new(),
+            cache,
             bloom_filter: RwLock::new(None),
-            bloom_filter_updated: RwLock::new(DateTime::UNIX_EPOCH.into()),
+            bloom_filter_updated: RwLock::new(DateTime::UNIX_EPOCH),
+            bloom_filter_ttl: Duration::seconds(DEFAULT_BLOOM_FILTER_TTL_SECONDS),
+            offline_validity: Duration::seconds(MAX_OFFLINE_VALIDITY_SECONDS),
+            generation: RwLock::new(0),
+            metrics: RevocationMetrics::default(),
         }
     }
 
-    /// Create with custom cache TTL
-    pub fn with_cache_ttl(store: Arc<dyn RevocationStore>, ttl_seconds: i64) -> Self {
-        Self {
-            store,
-            cache: RevocationCache::with_ttl(ttl_seconds),
-            bloom_filter: RwLock::new(None),
-            bloom_filter_updated: RwLock::new(DateTime::UNIX_EPOCH.into()),
+    fn is_bloom_filter_stale(&self, now: DateTime<Utc>) -> bool {
+        now - *self.bloom_filter_updated.read().unwrap() >= self.bloom_filter_ttl
+    }
+
+    /// Current revocation generation, read before a lookup starts
+    fn generation(&self) -> u64 {
+        *self.generation.read().unwrap()
+    }
+
+    /// Cache a status read from the bloom filter or store, unless a
+    /// revocation landed since `generation` was read
+    ///
+    /// Holding the lock `record_revocation` bumps the generation under keeps
+    /// a stale "not revoked" from overwriting the revoked status it caches.
+    fn cache_lookup_result(
+        &self,
+        revocation_id: &[u8; 16],
+        status: RevocationStatus,
+        generation: u64,
+    ) {
+        let current = self.generation.read().unwrap();
+        if status.revoked || *current == generation {
+            self.cache.set(*revocation_id, status);
         }
     }
 
-    /// Check if a token is revoked
-    pub fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<bool> {
+    /// Steps 1 and 2 of a lookup: the cache, then the bloom filter
+    fn lookup_local(
+        &self,
+        revocation_id: &[u8; 16],
+        generation: u64,
+        now: DateTime<Utc>,
+    ) -> LocalLookup {
         // 1. Check cache first
-        if let Some(status) = self.cache.get(revocation_id) {
-            return Ok(status.revoked);
+        if let Some(status) = self.cache.get_with_max_age(revocation_id, self.offline_validity, now)
+        {
+            RevocationMetrics::increment(&self.metrics.cache_hits);
+            self.metrics.record_decision(status.revoked);
+            return LocalLookup::Decided(status.revoked);
         }
+        RevocationMetrics::increment(&self.metrics.cache_misses);
 
-        // 2. Check bloom filter (quick negative check)
-        {
+        // 2. Check bloom filter (quick negative check), unless it is stale
+        let bloom_maybe_positive = if self.is_bloom_filter_stale(now) {
+            false
+        } else {
             let filter = self.bloom_filter.read().unwrap();
-            if let Some(ref bf) = *filter {
-                if !bf.might_contain(revocation_id) {
+            match *filter {
+                Some(ref bf) if !bf.might_contain(revocation_id) => {
                     // Definitely not revoked
-                    self.cache.set(*revocation_id, RevocationStatus::not_revoked());
-                    return Ok(false);
+                    let status = RevocationStatus::not_revoked();
+                    self.cache_lookup_result(revocation_id, status, generation);
+                    RevocationMetrics::increment(&self.metrics.bloom_negatives);
+                    self.metrics.record_decision(false);
+                    return LocalLookup::Decided(false);
                 }
+                Some(_) => true,
+                None => false,
             }
+        };
+
+        RevocationMetrics::increment(&self.metrics.store_queries);
+        LocalLookup::QueryStore { bloom_maybe_positive }
+    }
+
+    /// Step 3 of a lookup: record the store's answer
+    ///
+    /// A store error is counted and returned, never turned into a decision.
+    fn record_store_result(
+        &self,
+        revocation_id: &[u8; 16],
+        result: Result<RevocationStatus>,
+        bloom_maybe_positive: bool,
+        generation: u64,
+    ) -> Result<bool> {
+        let status = match result {
+            Ok(status) => status,
+            Err(e) => {
+                RevocationMetrics::increment(&self.metrics.store_errors);
+                return Err(e);
+            }
+        };
+        if bloom_maybe_positive && !status.revoked {
+            RevocationMetrics::increment(&self.metrics.bloom_false_positives);
+        }
+        let revoked = status.revoked;
+        self.cache_lookup_result(revocation_id, status, generation);
+        self.metrics.record_decision(revoked);
+
+        Ok(revoked)
+    }
+
+    /// Install a bloom filter loaded from the store
+    fn set_bloom_filter(&self, filter: RevocationBloomFilter) {
+        *self.bloom_filter.write().unwrap() = Some(filter);
+        *self.bloom_filter_updated.write().unwrap() = Utc::now();
+    }
+
+    /// Apply a revocation already written to the store
+    ///
+    /// Bumps the generation, so lookups already in flight don't cache a "not
+    /// revoked" they read before the store was updated. The generation lock
+    /// is held until the revoked status is cached.
+    fn record_revocation(&self, revocation_id: [u8; 16]) {
+        if let Some(filter) = self.bloom_filter.write().unwrap().as_mut() {
+            filter.add(&revocation_id);
+        }
+
+        let mut generation = self.generation.write().unwrap();
+        *generation += 1;
+        self.cache.set(revocation_id, RevocationStatus {
+            revoked: true,
+            revoked_at: Some(Utc::now()),
+            reason: None,
+        });
+    }
+}
+
+/// Revocation checker with caching
+pub struct RevocationChecker {
+    store: Arc<dyn RevocationStore>,
+    lookup: RevocationLookup,
+    /// Recently confirmed "not revoked" results
+    validated: RwLock<ValidatedCache>,
+    validated_ttl: Duration,
+}
+
+/// "Not revoked" results remembered by `RevocationChecker::with_validated_ttl`
+#[derive(Default)]
+struct ValidatedCache {
+    /// When each revocation ID was last confirmed not revoked
+    entries: HashMap<[u8; 16], DateTime<Utc>>,
+    /// When expired entries are next swept
+    next_sweep: DateTime<Utc>,
+}
+
+impl RevocationChecker {
+    /// Create a new checker
+    pub fn new(store: Arc<dyn RevocationStore>) -> Self {
+        Self::from_cache(store, RevocationCache::new())
+    }
+
+    /// Create with custom cache TTL
+    pub fn with_cache_ttl(store: Arc<dyn RevocationStore>, ttl_seconds: i64) -> Self {
+        Self::from_cache(store, RevocationCache::with_ttl(ttl_seconds))
+    }
+
+    fn from_cache(store: Arc<dyn RevocationStore>, cache: RevocationCache) -> Self {
+        Self {
+            store,
+            lookup: RevocationLookup::new(cache),
+            validated: RwLock::new(ValidatedCache::default()),
+            validated_ttl: Duration::seconds(DEFAULT_VALIDATED_TTL_SECONDS),
+        }
+    }
+
+    /// Set how long a cached status may be trusted before the store must be
+    /// consulted again
+    pub fn with_offline_validity(mut self, seconds: i64) -> Self {
+        self.lookup.offline_validity = Duration::seconds(seconds);
+        self
+    }
+
+    /// Set how long a loaded bloom filter is trusted
+    ///
+    /// Past this age the filter is ignored (every cache miss goes to the
+    /// store) until it is refreshed.
+    pub fn with_bloom_filter_ttl(mut self, seconds: i64) -> Self {
+        self.lookup.bloom_filter_ttl = Duration::seconds(seconds);
+        self
+    }
+
+    /// Remember "not revoked" results for `seconds`, skipping the cache,
+    /// bloom filter, and store for repeat checks of the same token
+    ///
+    /// Meant to be much shorter than the cache TTL. Revocations made through
+    /// this checker take effect immediately, and subject revocations are
+    /// checked by `is_token_revoked` before this cache. A revocation made
+    /// directly in the store is seen once the entry expires. Zero disables it.
+    pub fn with_validated_ttl(mut self, seconds: i64) -> Self {
+        self.validated_ttl = Duration::seconds(seconds);
+        self
+    }
+
+    /// TTL of the recently-validated cache
+    pub fn validated_ttl(&self) -> Duration {
+        self.validated_ttl
+    }
+
+    fn is_recently_validated(&self, revocation_id: &[u8; 16], now: DateTime<Utc>) -> bool {
+        if self.validated_ttl <= Duration::zero() {
+            return false;
         }
+        let validated = self.validated.read().unwrap();
+        matches!(
+            validated.entries.get(revocation_id),
+            Some(at) if now - *at < self.validated_ttl
+        )
+    }
+
+    fn record_validated(
+        &self,
+        revocation_id: &[u8; 16],
+        revoked: bool,
+        generation: u64,
+        now: DateTime<Utc>,
+    ) {
+        if self.validated_ttl <= Duration::zero() {
+            return;
+        }
+        // `revoke` bumps the generation before it takes this lock to drop the
+        // entry, so checking the generation under the lock cannot miss it
+        let mut validated = self.validated.write().unwrap();
+        if revoked {
+            validated.entries.remove(revocation_id);
+            return;
+        }
+        if self.lookup.generation() != generation {
+            return; // A revocation landed during the lookup
+        }
+
+        if now >= validated.next_sweep {
+            let ttl = self.validated_ttl;
+            validated.entries.retain(|_, at| now - *at < ttl);
+            validated.next_sweep = now + ttl;
+        }
+        validated.entries.insert(*revocation_id, now);
+    }
 
-        // 3. Check the store
-        let status = self.store.is_revoked(revocation_id)?;
-        self.cache.set(*revocation_id, status.clone());
+    /// Check if a token is revoked
+    ///
+    /// Cached statuses older than the offline validity window are re-checked
+    /// against the store. If the store cannot be reached its error is
+    /// returned, and callers must treat the token as revoked (fail closed).
+    pub fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<bool> {
+        self.is_revoked_at(revocation_id, Utc::now())
+    }
+
+    /// `is_revoked` with cache ages measured at `now`
+    fn is_revoked_at(&self, revocation_id: &[u8; 16], now: DateTime<Utc>) -> Result<bool> {
+        // 0. Recently confirmed not revoked
+        if self.is_recently_validated(revocation_id, now) {
+            RevocationMetrics::increment(&self.lookup.metrics.validated_hits);
+            self.lookup.metrics.record_decision(false);
+            return Ok(false);
+        }
 
-        Ok(status.revoked)
+        let generation = self.lookup.generation();
+        let revoked = match self.lookup.lookup_local(revocation_id, generation, now) {
+            LocalLookup::Decided(revoked) => revoked,
+            LocalLookup::QueryStore { bloom_maybe_positive } => {
+                // 3. Check the store
+                let result = self.store.is_revoked(revocation_id);
+                self.lookup.record_store_result(
+                    revocation_id,
+                    result,
+                    bloom_maybe_positive,
+                    generation,
+                )?
+            }
+        };
+        self.record_validated(revocation_id, revoked, generation, now);
+        Ok(revoked)
+    }
+
+    /// Snapshot of the counters recorded by `is_revoked`
+    pub fn metrics(&self) -> RevocationMetricsSnapshot {
+        self.lookup.metrics.snapshot()
     }
 
     /// Check if a token is revoked, including subject-level revocation
+    ///
+    /// Same as [`RevocationChecker::is_revoked_for_subject`].
     pub fn is_token_revoked(
         &self,
         revocation_id: &[u8; 16],
         subject_id: &[u8],
         token_iat: DateTime<Utc>,
+    ) -> Result<bool> {
+        self.is_revoked_for_subject(revocation_id, subject_id, token_iat)
+    }
+
+    /// Check if a token is revoked, either by its revocation ID or because
+    /// its subject was revoked after `token_iat`
+    ///
+    /// Subject revocations are those made through
+    /// [`RevocationChecker::revoke_subject`], or made directly in the store
+    /// and applied with [`RevocationChecker::invalidate_subject`]. Tokens
+    /// issued after the subject revocation are unaffected.
+    pub fn is_revoked_for_subject(
+        &self,
+        revocation_id: &[u8; 16],
+        subject_id: &[u8],
+        token_iat: DateTime<Utc>,
     ) -> Result<bool> {
         // Check subject-level revocation first
-        if self.cache.is_subject_revoked(subject_id, token_iat) {
+        if self.lookup.cache.is_subject_revoked(subject_id, token_iat) {
             return Ok(true);
         }
 
         // Check token-specific revocation
+        self.lookup.cache.track_subject(*revocation_id, subject_id);
         self.is_revoked(revocation_id)
     }
 
+    /// Refresh the bloom filter if it is older than the bloom filter TTL
+    ///
+    /// Returns whether the filter was reloaded. Meant to be called
+    /// periodically, e.g. from a background task.
+    pub fn refresh_bloom_filter_if_stale(&self) -> Result<bool> {
+        if !self.lookup.is_bloom_filter_stale(Utc::now()) {
+            return Ok(false);
+        }
+        self.refresh_bloom_filter()?;
+        Ok(true)
+    }
+
     /// Refresh the bloom filter
     pub fn refresh_bloom_filter(&self) -> Result<()> {
         let filter = self.store.get_bloom_filter()?;
-        let mut bf = self.bloom_filter.write().unwrap();
-        *bf = Some(filter);
-        let mut updated = self.bloom_filter_updated.write().unwrap();
-        *updated = Utc::now();
+        self.lookup.set_bloom_filter(filter);
         Ok(())
     }
 
@@ -477,28 +892,77 @@ impl RevocationChecker {
     ) -> Result<()> {
         let entry = RevocationEntry::new(revocation_id, reason, token_expiry);
         self.store.revoke(entry)?;
-
-        // Invalidate cache for this token
-        self.cache.set(revocation_id, RevocationStatus {
-            revoked: true,
-            revoked_at: Some(Utc::now()),
-            reason: None,
-        });
-
+        self.lookup.record_revocation(revocation_id);
+        self.validated.write().unwrap().entries.remove(&revocation_id);
         Ok(())
     }
 
     /// Revoke all tokens for a subject
     pub fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
         self.store.revoke_subject(subject_id, reason)?;
-        self.cache.revoke_subject(subject_id.to_vec());
+        self.invalidate_subject(subject_id);
         Ok(())
     }
+
+    /// Apply a subject revocation made directly in the store
+    ///
+    /// Clears cached statuses for the subject's tokens and records the
+    /// revocation time, so older tokens are reported revoked immediately
+    /// instead of after the cache TTL.
+    pub fn invalidate_subject(&self, subject_id: &[u8]) {
+        self.lookup.cache.invalidate_subject(subject_id);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    /// Store wrapper that can simulate an outage
+    struct FlakyStore {
+        inner: InMemoryRevocationStore,
+        available: AtomicBool,
+    }
+
+    impl FlakyStore {
+        fn new() -> Self {
+            Self {
+                inner: InMemoryRevocationStore::new(),
+                available: AtomicBool::new(true),
+            }
+        }
+
+        fn check_available(&self) -> Result<()> {
+            if self.available.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(QAuthError::RevocationError("store unreachable".into()))
+            }
+        }
+    }
+
+    impl RevocationStore for FlakyStore {
+        fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<RevocationStatus> {
+            self.check_available()?;
+            self.inner.is_revoked(revocation_id)
+        }
+
+        fn revoke(&self, entry: RevocationEntry) -> Result<()> {
+            self.check_available()?;
+            self.inner.revoke(entry)
+        }
+
+        fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
+            self.check_available()?;
+            self.inner.revoke_subject(subject_id, reason)
+        }
+
+        fn get_bloom_filter(&self) -> Result<RevocationBloomFilter> {
+            self.check_available()?;
+            self.inner.get_bloom_filter()
+        }
+    }
 
     #[test]
     fn test_revocation_store() {
@@ -599,6 +1063,84 @@ mod tests {
         assert!(checker.is_revoked(&revocation_id).unwrap());
     }
 
+    #[test]
+    fn test_validated_cache_short_circuits_repeat_checks() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store).with_validated_ttl(5);
+        assert_eq!(checker.validated_ttl(), Duration::seconds(5));
+
+        let revocation_id: [u8; 16] = rand::random();
+        for _ in 0..3 {
+            assert!(!checker.is_revoked(&revocation_id).unwrap());
+        }
+
+        let metrics = checker.metrics();
+        assert_eq!(metrics.store_queries, 1);
+        assert_eq!(metrics.validated_hits, 2);
+        assert_eq!(metrics.cache_hits, 0);
+
+        // A revocation inside the window still takes effect
+        checker
+            .revoke(
+                revocation_id,
+                RevocationReason::TokenCompromised,
+                Utc::now() + Duration::hours(1),
+            )
+            .unwrap();
+        assert!(checker.is_revoked(&revocation_id).unwrap());
+        assert_eq!(checker.metrics().validated_hits, 2);
+    }
+
+    #[test]
+    fn test_validated_cache_respects_subject_revocation() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store).with_validated_ttl(5);
+
+        let subject_id = b"user-789";
+        let revocation_id: [u8; 16] = rand::random();
+        let token_iat = Utc::now() - Duration::minutes(5);
+
+        assert!(!checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+        assert!(!checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+        assert_eq!(checker.metrics().validated_hits, 1);
+
+        checker
+            .revoke_subject(subject_id, RevocationReason::PasswordChanged)
+            .unwrap();
+        assert!(checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+    }
+
+    #[test]
+    fn test_validated_cache_disabled_by_default() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store);
+
+        let revocation_id: [u8; 16] = rand::random();
+        assert!(!checker.is_revoked(&revocation_id).unwrap());
+        assert!(!checker.is_revoked(&revocation_id).unwrap());
+        assert_eq!(checker.metrics().validated_hits, 0);
+        assert_eq!(checker.metrics().cache_hits, 1);
+    }
+
+    #[test]
+    fn test_lookup_racing_revoke_does_not_cache_stale_result() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store).with_validated_ttl(5);
+        let revocation_id: [u8; 16] = rand::random();
+
+        // A lookup read "not revoked" before the revocation, then finished after it
+        let generation = checker.lookup.generation();
+        checker
+            .revoke(revocation_id, RevocationReason::UserLogout, Utc::now() + Duration::hours(1))
+            .unwrap();
+        let stale = RevocationStatus::not_revoked();
+        checker.lookup.cache_lookup_result(&revocation_id, stale, generation);
+        checker.record_validated(&revocation_id, false, generation, Utc::now());
+
+        assert!(checker.is_revoked(&revocation_id).unwrap());
+        assert_eq!(checker.metrics().validated_hits, 0);
+    }
+
     #[test]
     fn test_subject_revocation() {
         let store = Arc::new(InMemoryRevocationStore::new());
@@ -619,9 +1161,244 @@ mod tests {
         // Old token should now be revoked
         assert!(checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
 
-        // New token (issued after revocation) would not be affected
+        // New token (issued after revocation) is not affected
         let new_token_iat = Utc::now() + Duration::seconds(1);
         let new_revocation_id: [u8; 16] = rand::random();
-        // Note: In a real scenario, we'd need to wait or mock time
+        assert!(!checker
+            .is_revoked_for_subject(&new_revocation_id, subject_id, new_token_iat)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_revoked_for_subject() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store);
+
+        let subject_id = b"user-321";
+        let other_subject = b"user-654";
+        let old_id: [u8; 16] = rand::random();
+        let old_iat = Utc::now() - Duration::hours(1);
+
+        assert!(!checker.is_revoked_for_subject(&old_id, subject_id, old_iat).unwrap());
+
+        checker
+            .revoke_subject(subject_id, RevocationReason::TokenCompromised)
+            .unwrap();
+
+        // Older tokens of the subject are revoked, other subjects are not
+        assert!(checker.is_revoked_for_subject(&old_id, subject_id, old_iat).unwrap());
+        assert!(!checker.is_revoked_for_subject(&old_id, other_subject, old_iat).unwrap());
+
+        // A token issued afterwards passes unless revoked individually
+        let new_id: [u8; 16] = rand::random();
+        let new_iat = Utc::now() + Duration::seconds(1);
+        assert!(!checker.is_revoked_for_subject(&new_id, subject_id, new_iat).unwrap());
+
+        checker
+            .revoke(new_id, RevocationReason::UserLogout, Utc::now() + Duration::hours(1))
+            .unwrap();
+        assert!(checker.is_revoked_for_subject(&new_id, subject_id, new_iat).unwrap());
+    }
+
+    #[test]
+    fn test_invalidate_subject_overrides_cached_status() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker =
+            RevocationChecker::with_cache_ttl(store.clone(), 3600).with_offline_validity(3600);
+
+        let subject_id = b"user-456";
+        let revocation_id: [u8; 16] = rand::random();
+        let token_iat = Utc::now() - Duration::minutes(5);
+
+        // Not revoked, and now cached
+        assert!(!checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+        assert!(!checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+        assert_eq!(checker.metrics().cache_hits, 1);
+
+        // Revoked in the store behind the checker's back, then propagated
+        store
+            .revoke_subject(subject_id, RevocationReason::AccountDisabled)
+            .unwrap();
+        checker.invalidate_subject(subject_id);
+
+        assert!(checker.is_token_revoked(&revocation_id, subject_id, token_iat).unwrap());
+
+        // The stale per-token entry was dropped, so a direct lookup misses the cache
+        let misses = checker.metrics().cache_misses;
+        checker.is_revoked(&revocation_id).unwrap();
+        assert_eq!(checker.metrics().cache_misses, misses + 1);
+    }
+
+    #[test]
+    fn test_offline_validity_window() {
+        let store = Arc::new(FlakyStore::new());
+        let checker = RevocationChecker::new(store.clone()).with_offline_validity(1);
+        let revocation_id: [u8; 16] = rand::random();
+
+        // Populate the cache, then revoke behind the checker's back
+        assert!(!checker.is_revoked(&revocation_id).unwrap());
+        store
+            .revoke(RevocationEntry::new(
+                revocation_id,
+                RevocationReason::AdminRevoked,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+
+        // Within the window the cached status is used
+        assert!(!checker.is_revoked(&revocation_id).unwrap());
+
+        // Past the window the store is consulted again
+        let later = Utc::now() + Duration::seconds(2);
+        assert!(checker.is_revoked_at(&revocation_id, later).unwrap());
+    }
+
+    #[test]
+    fn test_offline_validity_fails_closed() {
+        let store = Arc::new(FlakyStore::new());
+        let checker = RevocationChecker::new(store.clone()).with_offline_validity(0);
+        let revocation_id: [u8; 16] = rand::random();
+
+        assert!(!checker.is_revoked(&revocation_id).unwrap());
+
+        // Store goes away; a stale cache entry must not be trusted
+        store.available.store(false, Ordering::SeqCst);
+        assert!(matches!(
+            checker.is_revoked(&revocation_id),
+            Err(QAuthError::RevocationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_metrics_count_hits_and_misses() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store.clone());
+        assert_eq!(checker.metrics(), RevocationMetricsSnapshot::default());
+
+        let clean_id: [u8; 16] = rand::random();
+        let revoked_id: [u8; 16] = rand::random();
+        store
+            .revoke(RevocationEntry::new(
+                revoked_id,
+                RevocationReason::AdminRevoked,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+
+        // Two misses that go to the store, then two cache hits
+        assert!(!checker.is_revoked(&clean_id).unwrap());
+        assert!(checker.is_revoked(&revoked_id).unwrap());
+        assert!(!checker.is_revoked(&clean_id).unwrap());
+        assert!(checker.is_revoked(&revoked_id).unwrap());
+
+        let metrics = checker.metrics();
+        assert_eq!(metrics.cache_misses, 2);
+        assert_eq!(metrics.cache_hits, 2);
+        assert_eq!(metrics.store_queries, 2);
+        assert_eq!(metrics.store_errors, 0);
+        assert_eq!(metrics.revoked, 2);
+        assert_eq!(metrics.not_revoked, 2);
+
+        // With a bloom filter loaded, unknown IDs are (almost always) ruled out
+        // without a store query; a maybe-positive that the store clears counts
+        // as a false positive
+        checker.refresh_bloom_filter().unwrap();
+        let unknown_id: [u8; 16] = rand::random();
+        assert!(!checker.is_revoked(&unknown_id).unwrap());
+
+        let metrics = checker.metrics();
+        assert_eq!(metrics.cache_misses, 3);
+        assert_eq!(metrics.bloom_negatives + metrics.bloom_false_positives, 1);
+        assert_eq!(metrics.not_revoked, 3);
+    }
+
+    /// Random ID the store's current bloom filter rules out
+    fn id_outside_filter(store: &dyn RevocationStore) -> [u8; 16] {
+        let filter = store.get_bloom_filter().unwrap();
+        loop {
+            let id: [u8; 16] = rand::random();
+            if !filter.might_contain(&id) {
+                return id;
+            }
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_skips_store() {
+        let store = Arc::new(FlakyStore::new());
+        let checker = RevocationChecker::new(store.clone());
+        checker.refresh_bloom_filter().unwrap();
+        assert!(!checker.refresh_bloom_filter_if_stale().unwrap());
+
+        // With the store down, only the filter can answer "not revoked"
+        let unknown_id = id_outside_filter(store.as_ref());
+        store.available.store(false, Ordering::SeqCst);
+        assert!(!checker.is_revoked(&unknown_id).unwrap());
+
+        let metrics = checker.metrics();
+        assert_eq!(metrics.bloom_negatives, 1);
+        assert_eq!(metrics.store_queries, 0);
+    }
+
+    #[test]
+    fn test_bloom_filter_refresh_sees_store_revocations() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::with_cache_ttl(store.clone(), 0);
+        checker.refresh_bloom_filter().unwrap();
+
+        // Revoked directly in the store, then picked up by a refresh
+        let revoked_id = id_outside_filter(store.as_ref());
+        store
+            .revoke(RevocationEntry::new(
+                revoked_id,
+                RevocationReason::AdminRevoked,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+        checker.refresh_bloom_filter().unwrap();
+        assert!(checker.is_revoked(&revoked_id).unwrap());
+
+        // Revocations made through the checker are added to the loaded
+        // filter, so they hold after the cache entry expires
+        let checker_revoked = id_outside_filter(store.as_ref());
+        checker
+            .revoke(checker_revoked, RevocationReason::UserLogout, Utc::now() + Duration::hours(1))
+            .unwrap();
+        assert!(checker.is_revoked(&checker_revoked).unwrap());
+    }
+
+    #[test]
+    fn test_stale_bloom_filter_is_ignored() {
+        let store = Arc::new(InMemoryRevocationStore::new());
+        let checker = RevocationChecker::with_cache_ttl(store.clone(), 0).with_bloom_filter_ttl(0);
+        checker.refresh_bloom_filter().unwrap();
+
+        let revoked_id = id_outside_filter(store.as_ref());
+        store
+            .revoke(RevocationEntry::new(
+                revoked_id,
+                RevocationReason::AdminRevoked,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+
+        // The filter predates the revocation, but is not trusted
+        assert!(checker.is_revoked(&revoked_id).unwrap());
+        assert_eq!(checker.metrics().bloom_negatives, 0);
+        assert!(checker.refresh_bloom_filter_if_stale().unwrap());
+    }
+
+    #[test]
+    fn test_metrics_count_store_errors() {
+        let store = Arc::new(FlakyStore::new());
+        let checker = RevocationChecker::new(store.clone());
+        store.available.store(false, Ordering::SeqCst);
+
+        assert!(checker.is_revoked(&rand::random()).is_err());
+
+        let metrics = checker.metrics();
+        assert_eq!(metrics.store_queries, 1);
+        assert_eq!(metrics.store_errors, 1);
+        assert_eq!(metrics.revoked, 0);
     }
 }
@@ -0,0 +1,318 @@
+//! Async revocation store and checker
+//!
+//! For revocation services reached over the network (Redis, HTTP), where a
+//! blocking [`RevocationStore`](super::RevocationStore) would stall the
+//! request handler. The checker shares its cache, bloom filter and lookup
+//! rules with [`RevocationChecker`](super::RevocationChecker).
+
+use super::{
+    InMemoryRevocationStore, LocalLookup, RevocationBloomFilter, RevocationCache,
+    RevocationEntry, RevocationLookup, RevocationMetricsSnapshot, RevocationReason,
+    RevocationStatus, RevocationStore,
+};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+/// Async revocation store trait
+#[async_trait]
+pub trait AsyncRevocationStore: Send + Sync {
+    /// Check if a token is revoked
+    async fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<RevocationStatus>;
+
+    /// Revoke a token
+    async fn revoke(&self, entry: RevocationEntry) -> Result<()>;
+
+    /// Revoke all tokens for a subject
+    async fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()>;
+
+    /// Get bloom filter of revoked tokens
+    async fn get_bloom_filter(&self) -> Result<RevocationBloomFilter>;
+}
+
+/// In-memory async revocation store (for testing)
+#[derive(Default)]
+pub struct InMemoryAsyncRevocationStore {
+    inner: InMemoryRevocationStore,
+}
+
+impl InMemoryAsyncRevocationStore {
+    /// Create a new in-memory store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clean up expired revocations
+    pub fn cleanup(&self) {
+        self.inner.cleanup();
+    }
+}
+
+#[async_trait]
+impl AsyncRevocationStore for InMemoryAsyncRevocationStore {
+    async fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<RevocationStatus> {
+        self.inner.is_revoked(revocation_id)
+    }
+
+    async fn revoke(&self, entry: RevocationEntry) -> Result<()> {
+        self.inner.revoke(entry)
+    }
+
+    async fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
+        self.inner.revoke_subject(subject_id, reason)
+    }
+
+    async fn get_bloom_filter(&self) -> Result<RevocationBloomFilter> {
+        self.inner.get_bloom_filter()
+    }
+}
+
+/// Revocation checker with caching over an [`AsyncRevocationStore`]
+pub struct AsyncRevocationChecker {
+    store: Arc<dyn AsyncRevocationStore>,
+    lookup: RevocationLookup,
+}
+
+impl AsyncRevocationChecker {
+    /// Create a new checker
+    pub fn new(store: Arc<dyn AsyncRevocationStore>) -> Self {
+        Self::from_cache(store, RevocationCache::new())
+    }
+
+    /// Create with custom cache TTL
+    pub fn with_cache_ttl(store: Arc<dyn AsyncRevocationStore>, ttl_seconds: i64) -> Self {
+        Self::from_cache(store, RevocationCache::with_ttl(ttl_seconds))
+    }
+
+    fn from_cache(store: Arc<dyn AsyncRevocationStore>, cache: RevocationCache) -> Self {
+        Self {
+            store,
+            lookup: RevocationLookup::new(cache),
+        }
+    }
+
+    /// Set how long a cached status may be trusted before the store must be
+    /// consulted again
+    pub fn with_offline_validity(mut self, seconds: i64) -> Self {
+        self.lookup.offline_validity = Duration::seconds(seconds);
+        self
+    }
+
+    /// Set how long a loaded bloom filter is trusted
+    pub fn with_bloom_filter_ttl(mut self, seconds: i64) -> Self {
+        self.lookup.bloom_filter_ttl = Duration::seconds(seconds);
+        self
+    }
+
+    /// Check if a token is revoked
+    ///
+    /// Consults the cache, then the bloom filter, then the store. If the
+    /// store cannot be reached its error is returned, and callers must treat
+    /// the token as revoked (fail closed).
+    pub async fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<bool> {
+        let generation = self.lookup.generation();
+        match self.lookup.lookup_local(revocation_id, generation, Utc::now()) {
+            LocalLookup::Decided(revoked) => Ok(revoked),
+            LocalLookup::QueryStore { bloom_maybe_positive } => {
+                let result = self.store.is_revoked(revocation_id).await;
+                self.lookup.record_store_result(
+                    revocation_id,
+                    result,
+                    bloom_maybe_positive,
+                    generation,
+                )
+            }
+        }
+    }
+
+    /// Check if a token is revoked, either by its revocation ID or because
+    /// its subject was revoked after `token_iat`
+    ///
+    /// Same rules as the sync `RevocationChecker::is_revoked_for_subject`.
+    pub async fn is_revoked_for_subject(
+        &self,
+        revocation_id: &[u8; 16],
+        subject_id: &[u8],
+        token_iat: DateTime<Utc>,
+    ) -> Result<bool> {
+        if self.lookup.cache.is_subject_revoked(subject_id, token_iat) {
+            return Ok(true);
+        }
+
+        self.lookup.cache.track_subject(*revocation_id, subject_id);
+        self.is_revoked(revocation_id).await
+    }
+
+    /// Snapshot of the counters recorded by `is_revoked`
+    pub fn metrics(&self) -> RevocationMetricsSnapshot {
+        self.lookup.metrics.snapshot()
+    }
+
+    /// Refresh the bloom filter if it is older than the bloom filter TTL
+    ///
+    /// Returns whether the filter was reloaded.
+    pub async fn refresh_bloom_filter_if_stale(&self) -> Result<bool> {
+        if !self.lookup.is_bloom_filter_stale(Utc::now()) {
+            return Ok(false);
+        }
+        self.refresh_bloom_filter().await?;
+        Ok(true)
+    }
+
+    /// Refresh the bloom filter
+    pub async fn refresh_bloom_filter(&self) -> Result<()> {
+        let filter = self.store.get_bloom_filter().await?;
+        self.lookup.set_bloom_filter(filter);
+        Ok(())
+    }
+
+    /// Revoke a token
+    pub async fn revoke(
+        &self,
+        revocation_id: [u8; 16],
+        reason: RevocationReason,
+        token_expiry: DateTime<Utc>,
+    ) -> Result<()> {
+        let entry = RevocationEntry::new(revocation_id, reason, token_expiry);
+        self.store.revoke(entry).await?;
+        self.lookup.record_revocation(revocation_id);
+        Ok(())
+    }
+
+    /// Revoke all tokens for a subject
+    pub async fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
+        self.store.revoke_subject(subject_id, reason).await?;
+        self.invalidate_subject(subject_id);
+        Ok(())
+    }
+
+    /// Apply a subject revocation made directly in the store
+    pub fn invalidate_subject(&self, subject_id: &[u8]) {
+        self.lookup.cache.invalidate_subject(subject_id);
+    }
+
+    /// Drop cached statuses older than the cache TTL
+    pub fn cleanup(&self) {
+        self.lookup.cache.cleanup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Notify;
+
+    /// Store whose lookups read the inner store, then wait for `release`
+    #[derive(Default)]
+    struct GatedStore {
+        inner: InMemoryAsyncRevocationStore,
+        entered: Notify,
+        release: Notify,
+    }
+
+    #[async_trait]
+    impl AsyncRevocationStore for GatedStore {
+        async fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<RevocationStatus> {
+            let status = self.inner.is_revoked(revocation_id).await;
+            self.entered.notify_one();
+            self.release.notified().await;
+            status
+        }
+
+        async fn revoke(&self, entry: RevocationEntry) -> Result<()> {
+            self.inner.revoke(entry).await
+        }
+
+        async fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
+            self.inner.revoke_subject(subject_id, reason).await
+        }
+
+        async fn get_bloom_filter(&self) -> Result<RevocationBloomFilter> {
+            self.inner.get_bloom_filter().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_checker_revoke_cache_cleanup() {
+        let store = Arc::new(InMemoryAsyncRevocationStore::new());
+        let checker = AsyncRevocationChecker::new(store.clone());
+        let revocation_id: [u8; 16] = rand::random();
+        let expired_id: [u8; 16] = rand::random();
+
+        // Unknown token goes to the store
+        assert!(!checker.is_revoked(&revocation_id).await.unwrap());
+        assert_eq!(checker.metrics().store_queries, 1);
+
+        // Revoking updates the cache, so the next check is a cache hit
+        checker
+            .revoke(
+                revocation_id,
+                RevocationReason::TokenCompromised,
+                Utc::now() + Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        assert!(checker.is_revoked(&revocation_id).await.unwrap());
+        let metrics = checker.metrics();
+        assert_eq!(metrics.cache_hits, 1);
+        assert_eq!(metrics.store_queries, 1);
+
+        // Revocations of already expired tokens are dropped by cleanup
+        checker
+            .revoke(expired_id, RevocationReason::UserLogout, Utc::now() - Duration::minutes(1))
+            .await
+            .unwrap();
+        store.cleanup();
+        checker.cleanup();
+        assert!(!store.is_revoked(&expired_id).await.unwrap().revoked);
+        assert!(store.is_revoked(&revocation_id).await.unwrap().revoked);
+        assert!(checker.is_revoked(&revocation_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_checker_subject_revocation() {
+        let store = Arc::new(InMemoryAsyncRevocationStore::new());
+        let checker = AsyncRevocationChecker::new(store);
+        let revocation_id: [u8; 16] = rand::random();
+        let issued_at = Utc::now() - Duration::minutes(5);
+
+        assert!(!checker
+            .is_revoked_for_subject(&revocation_id, b"user-123", issued_at)
+            .await
+            .unwrap());
+
+        checker
+            .revoke_subject(b"user-123", RevocationReason::PasswordChanged)
+            .await
+            .unwrap();
+        assert!(checker
+            .is_revoked_for_subject(&revocation_id, b"user-123", issued_at)
+            .await
+            .unwrap());
+        assert!(!checker
+            .is_revoked_for_subject(&revocation_id, b"user-123", Utc::now() + Duration::minutes(1))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_during_lookup_is_not_overwritten() {
+        let store = Arc::new(GatedStore::default());
+        let checker = AsyncRevocationChecker::new(store.clone());
+        let revocation_id: [u8; 16] = rand::random();
+
+        // The lookup reads "not revoked", then the revocation lands before it finishes
+        let (stale, _) = tokio::join!(checker.is_revoked(&revocation_id), async {
+            store.entered.notified().await;
+            let expiry = Utc::now() + Duration::hours(1);
+            checker.revoke(revocation_id, RevocationReason::UserLogout, expiry).await.unwrap();
+            store.release.notify_one();
+        });
+        assert!(!stale.unwrap());
+
+        // The revoked status cached by revoke survives, so no store query is needed
+        assert!(checker.is_revoked(&revocation_id).await.unwrap());
+        assert_eq!(checker.metrics().cache_hits, 1);
+    }
+}
@@ -0,0 +1,270 @@
+//! SQLite-backed revocation store
+//!
+//! Persists revocations across restarts. Revocation IDs are stored as hex,
+//! timestamps as Unix milliseconds, and reasons as their JSON encoding.
+
+use super::{
+    RevocationBloomFilter, RevocationEntry, RevocationReason, RevocationStatus, RevocationStore,
+};
+use crate::error::{QAuthError, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS revocations (
+        revocation_id TEXT PRIMARY KEY,
+        reason TEXT NOT NULL,
+        revoked_at INTEGER NOT NULL,
+        subject_id BLOB,
+        token_expiry INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS revocations_token_expiry ON revocations (token_expiry);
+    CREATE TABLE IF NOT EXISTS subject_revocations (
+        subject_id BLOB PRIMARY KEY,
+        reason TEXT NOT NULL,
+        revoked_at INTEGER NOT NULL
+    );
+";
+
+fn db_error(err: rusqlite::Error) -> QAuthError {
+    QAuthError::RevocationError(format!("sqlite: {}", err))
+}
+
+fn encode_reason(reason: &RevocationReason) -> Result<String> {
+    serde_json::to_string(reason).map_err(|e| QAuthError::RevocationError(e.to_string()))
+}
+
+fn decode_reason(reason: &str) -> Result<RevocationReason> {
+    serde_json::from_str(reason).map_err(|e| QAuthError::RevocationError(e.to_string()))
+}
+
+fn decode_time(millis: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| QAuthError::RevocationError("invalid timestamp in store".into()))
+}
+
+/// Revocation store persisted in a SQLite database
+pub struct SqliteRevocationStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRevocationStore {
+    /// Open (or create) a store in the database file at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// Create a store in a private in-memory database
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    /// Use an existing connection, creating the tables if needed
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| QAuthError::RevocationError("sqlite connection poisoned".into()))
+    }
+
+    /// When all tokens of `subject_id` were revoked, if they were
+    pub fn subject_revoked_at(&self, subject_id: &[u8]) -> Result<Option<DateTime<Utc>>> {
+        let revoked_at: Option<i64> = self
+            .conn()?
+            .query_row(
+                "SELECT revoked_at FROM subject_revocations WHERE subject_id = ?1",
+                params![subject_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)?;
+        revoked_at.map(decode_time).transpose()
+    }
+
+    /// Delete revocations whose tokens have expired
+    ///
+    /// Returns the number of rows removed.
+    pub fn cleanup(&self) -> Result<usize> {
+        self.conn()?
+            .execute(
+                "DELETE FROM revocations WHERE token_expiry <= ?1",
+                params![Utc::now().timestamp_millis()],
+            )
+            .map_err(db_error)
+    }
+}
+
+impl RevocationStore for SqliteRevocationStore {
+    fn is_revoked(&self, revocation_id: &[u8; 16]) -> Result<RevocationStatus> {
+        let row: Option<(String, i64)> = self
+            .conn()?
+            .query_row(
+                "SELECT reason, revoked_at FROM revocations WHERE revocation_id = ?1",
+                params![hex::encode(revocation_id)],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(db_error)?;
+
+        match row {
+            Some((reason, revoked_at)) => Ok(RevocationStatus {
+                revoked: true,
+                revoked_at: Some(decode_time(revoked_at)?),
+                reason: Some(decode_reason(&reason)?),
+            }),
+            None => Ok(RevocationStatus::not_revoked()),
+        }
+    }
+
+    fn revoke(&self, entry: RevocationEntry) -> Result<()> {
+        self.conn()?
+            .execute(
+                "INSERT INTO revocations
+                     (revocation_id, reason, revoked_at, subject_id, token_expiry)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (revocation_id) DO UPDATE SET
+                     reason = excluded.reason,
+                     revoked_at = excluded.revoked_at,
+                     subject_id = excluded.subject_id,
+                     token_expiry = excluded.token_expiry",
+                params![
+                    hex::encode(entry.revocation_id),
+                    encode_reason(&entry.reason)?,
+                    entry.revoked_at.timestamp_millis(),
+                    entry.subject_id,
+                    entry.token_expiry.timestamp_millis(),
+                ],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn revoke_subject(&self, subject_id: &[u8], reason: RevocationReason) -> Result<()> {
+        self.conn()?
+            .execute(
+                "INSERT INTO subject_revocations (subject_id, reason, revoked_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT (subject_id) DO UPDATE SET
+                     reason = excluded.reason,
+                     revoked_at = excluded.revoked_at",
+                params![subject_id, encode_reason(&reason)?, Utc::now().timestamp_millis()],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn get_bloom_filter(&self) -> Result<RevocationBloomFilter> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare("SELECT revocation_id FROM revocations WHERE token_expiry > ?1")
+            .map_err(db_error)?;
+        let ids = stmt
+            .query_map(params![Utc::now().timestamp_millis()], |row| row.get::<_, String>(0))
+            .map_err(db_error)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        let mut filter = RevocationBloomFilter::new(ids.len().max(100), 0.01);
+        for id in ids {
+            let mut revocation_id = [0u8; 16];
+            hex::decode_to_slice(&id, &mut revocation_id)
+                .map_err(|_| QAuthError::RevocationError("invalid revocation ID in store".into()))?;
+            filter.add(&revocation_id);
+        }
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_revoke_and_lookup() {
+        let store = SqliteRevocationStore::open_in_memory().unwrap();
+        let revocation_id: [u8; 16] = rand::random();
+
+        assert!(!store.is_revoked(&revocation_id).unwrap().revoked);
+
+        let entry = RevocationEntry::new(
+            revocation_id,
+            RevocationReason::Other("key rotation".into()),
+            Utc::now() + Duration::hours(1),
+        )
+        .with_subject(b"user-123".to_vec());
+        store.revoke(entry.clone()).unwrap();
+
+        let status = store.is_revoked(&revocation_id).unwrap();
+        assert!(status.revoked);
+        assert_eq!(status.reason, Some(RevocationReason::Other("key rotation".into())));
+        assert_eq!(
+            status.revoked_at.unwrap().timestamp_millis(),
+            entry.revoked_at.timestamp_millis()
+        );
+
+        // Revoking again updates the row in place
+        store
+            .revoke(RevocationEntry::new(
+                revocation_id,
+                RevocationReason::AdminRevoked,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+        let status = store.is_revoked(&revocation_id).unwrap();
+        assert_eq!(status.reason, Some(RevocationReason::AdminRevoked));
+
+        assert!(store.get_bloom_filter().unwrap().might_contain(&revocation_id));
+    }
+
+    #[test]
+    fn test_subject_revocation() {
+        let store = SqliteRevocationStore::open_in_memory().unwrap();
+
+        assert_eq!(store.subject_revoked_at(b"user-123").unwrap(), None);
+        store
+            .revoke_subject(b"user-123", RevocationReason::PasswordChanged)
+            .unwrap();
+        assert!(store.subject_revoked_at(b"user-123").unwrap().is_some());
+        assert_eq!(store.subject_revoked_at(b"user-456").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cleanup_removes_expired() {
+        let store = SqliteRevocationStore::open_in_memory().unwrap();
+        let expired: [u8; 16] = rand::random();
+        let live: [u8; 16] = rand::random();
+
+        store
+            .revoke(RevocationEntry::new(
+                expired,
+                RevocationReason::UserLogout,
+                Utc::now() - Duration::minutes(1),
+            ))
+            .unwrap();
+        store
+            .revoke(RevocationEntry::new(
+                live,
+                RevocationReason::UserLogout,
+                Utc::now() + Duration::hours(1),
+            ))
+            .unwrap();
+
+        // Expired rows are left out of the bloom filter even before cleanup
+        let filter = store.get_bloom_filter().unwrap();
+        assert!(filter.might_contain(&live));
+
+        assert_eq!(store.cleanup().unwrap(), 1);
+        assert!(!store.is_revoked(&expired).unwrap().revoked);
+        assert!(store.is_revoked(&live).unwrap().revoked);
+        assert_eq!(store.cleanup().unwrap(), 0);
+    }
+}
@@ -4,13 +4,18 @@
 
 use crate::crypto::{
     sha256, DualSignature, EncryptedData, EncryptionKey, IssuerSigningKeys, IssuerVerifyingKeys,
-    DUAL_SIGNATURE_SIZE, KEY_ID_SIZE,
+    SignaturePurpose, DUAL_SIGNATURE_SIZE, KEY_ID_SIZE, XCHACHA20_NONCE_SIZE, XCHACHA20_TAG_SIZE,
 };
 use crate::error::{ErrorCode, QAuthError, Result};
+use crate::policy::{EvaluationContext, EvaluationResult, PolicyEngine};
+use crate::proof::{ProofOfPossession, ProofValidator};
+use crate::revocation::RevocationChecker;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// QToken protocol version
 pub const QTOKEN_VERSION: u8 = 0x01;
@@ -21,6 +26,15 @@ pub const HEADER_SIZE: usize = 42;
 /// Proof binding size in bytes (fixed)
 pub const PROOF_BINDING_SIZE: usize = 96;
 
+/// Audience sentinel for tokens valid at any audience (see `QTokenBuilder::any_audience`)
+///
+/// Only validators built with `QTokenValidator::accept_any_audience(true)`
+/// accept it.
+pub const ANY_AUDIENCE: &str = "*";
+
+/// Domain separator for context hashing
+const CONTEXT_HASH_DOMAIN: &[u8] = b"QAuth-context-v1";
+
 /// Token types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -143,6 +157,16 @@ pub struct QTokenPayload {
     /// Custom claims
     #[serde(default)]
     pub cst: HashMap<String, serde_json::Value>,
+    /// Confirmation claim (RFC 7800), set when bound to a client key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<Confirmation>,
+}
+
+/// Confirmation claim (RFC 7800)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirmation {
+    /// JWK SHA-256 thumbprint of the bound key (RFC 7638, base64url)
+    pub jkt: String,
 }
 
 impl QTokenPayload {
@@ -167,6 +191,7 @@ impl QTokenPayload {
             pol: policy_ref,
             ctx: [0u8; 32],
             cst: HashMap::new(),
+            cnf: None,
         }
     }
 
@@ -182,6 +207,12 @@ impl QTokenPayload {
         self
     }
 
+    /// Set confirmation claim
+    pub fn with_confirmation(mut self, cnf: Option<Confirmation>) -> Self {
+        self.cnf = cnf;
+        self
+    }
+
     /// Serialize to CBOR bytes
     pub fn to_cbor(&self) -> Result<Vec<u8>> {
         let mut buf = Vec::new();
@@ -195,6 +226,11 @@ impl QTokenPayload {
         ciborium::from_reader(bytes).map_err(|e| QAuthError::SerializationError(e.to_string()))
     }
 
+    /// Subject as UTF-8, or `None` for binary subjects
+    pub fn subject_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.sub).ok()
+    }
+
     /// Check if token is expired
     pub fn is_expired(&self) -> bool {
         Utc::now().timestamp() > self.exp
@@ -254,6 +290,44 @@ impl ProofBinding {
     }
 }
 
+/// Compute the canonical context hash for a set of attributes
+///
+/// Attributes are sorted by name (then value) and each is length-prefixed,
+/// so issuer and verifier agree regardless of attribute order.
+pub fn context_hash(attributes: &[(&str, &[u8])]) -> [u8; 32] {
+    let mut sorted = attributes.to_vec();
+    sorted.sort();
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(CONTEXT_HASH_DOMAIN);
+    hasher.update((sorted.len() as u32).to_be_bytes());
+    for (name, value) in sorted {
+        hasher.update((name.len() as u32).to_be_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((value.len() as u32).to_be_bytes());
+        hasher.update(value);
+    }
+    hasher.finalize().into()
+}
+
+/// JWK SHA-256 thumbprint (RFC 7638) of an Ed25519 public key, base64url-encoded
+///
+/// This is the `jkt` value used by DPoP and RFC 7800 `cnf` claims.
+pub fn jwk_thumbprint(ed25519_public_key: &[u8; 32]) -> String {
+    // Required members in lexicographic order, no whitespace
+    let jwk = format!(
+        r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#,
+        URL_SAFE_NO_PAD.encode(ed25519_public_key)
+    );
+    URL_SAFE_NO_PAD.encode(sha256(jwk.as_bytes()))
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Number of `decrypt_payload` calls on this thread, for tests
+    static PAYLOAD_DECRYPTIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
 /// Complete QToken
 pub struct QToken {
     /// Token header
@@ -277,7 +351,17 @@ impl QToken {
     ) -> Result<Self> {
         // Create header
         let header = QTokenHeader::new(token_type, signing_keys.key_id());
+        Self::create_with_header(header, payload, binding, signing_keys, encryption_key)
+    }
 
+    /// Create a new QToken with a caller-supplied header
+    pub(crate) fn create_with_header(
+        header: QTokenHeader,
+        payload: &QTokenPayload,
+        binding: ProofBinding,
+        signing_keys: &IssuerSigningKeys,
+        encryption_key: &EncryptionKey,
+    ) -> Result<Self> {
         // Serialize payload to CBOR
         let payload_bytes = payload.to_cbor()?;
 
@@ -291,7 +375,7 @@ impl QToken {
         message.extend_from_slice(&encrypted_payload.to_bytes());
 
         // Sign with dual signature
-        let signature = signing_keys.sign(&message);
+        let signature = signing_keys.sign_for(SignaturePurpose::QToken, &message);
 
         Ok(Self {
             header,
@@ -383,8 +467,17 @@ impl QToken {
 
     /// Verify the token signatures
     pub fn verify_signatures(&self, verifying_keys: &IssuerVerifyingKeys) -> Result<()> {
+        self.verify_signatures_with_key_id(verifying_keys, &verifying_keys.key_id())
+    }
+
+    /// Verify the token signatures against a precomputed key ID
+    pub(crate) fn verify_signatures_with_key_id(
+        &self,
+        verifying_keys: &IssuerVerifyingKeys,
+        key_id: &[u8; KEY_ID_SIZE],
+    ) -> Result<()> {
         // Verify key ID matches
-        if self.header.key_id != verifying_keys.key_id() {
+        if &self.header.key_id != key_id {
             return Err(ErrorCode::InvalidIssuer.into());
         }
 
@@ -397,14 +490,20 @@ impl QToken {
         message.extend_from_slice(&encrypted_bytes);
 
         // Verify dual signature
-        verifying_keys.verify(&message, &self.signature)
+        verifying_keys.verify_for(SignaturePurpose::QToken, &message, &self.signature)
             .map_err(|_| ErrorCode::SignatureFailed)?;
 
         Ok(())
     }
 
     /// Decrypt and extract the payload
+    ///
+    /// Each call decrypts again. After validation, read the payload from
+    /// [`ValidatedToken`] instead.
     pub fn decrypt_payload(&self, encryption_key: &EncryptionKey) -> Result<QTokenPayload> {
+        #[cfg(test)]
+        PAYLOAD_DECRYPTIONS.with(|count| count.set(count.get() + 1));
+
         let header_bytes = self.header.to_bytes();
         let payload_bytes = encryption_key
             .decrypt(&self.encrypted_payload, &header_bytes)
@@ -445,6 +544,7 @@ pub struct QTokenBuilder {
     context: [u8; 32],
     device_key: [u8; 32],
     client_key: [u8; 32],
+    confirmation: Option<Confirmation>,
     ip_hash: Option<[u8; 32]>,
 }
 
@@ -462,6 +562,7 @@ impl QTokenBuilder {
             context: [0u8; 32],
             device_key: [0u8; 32],
             client_key: [0u8; 32],
+            confirmation: None,
             ip_hash: None,
         }
     }
@@ -487,6 +588,12 @@ impl QTokenBuilder {
         self
     }
 
+    /// Set a UTF-8 subject, e.g. a user id
+    pub fn subject_string(mut self, sub: &str) -> Self {
+        self.subject = sub.as_bytes().to_vec();
+        self
+    }
+
     /// Set issuer
     pub fn issuer(mut self, iss: impl Into<String>) -> Self {
         self.issuer = iss.into();
@@ -499,6 +606,18 @@ impl QTokenBuilder {
         self
     }
 
+    /// Make the token valid at any audience
+    ///
+    /// Replaces any audiences added so far with the `ANY_AUDIENCE` sentinel.
+    /// A token with no audience at all is treated as misconfigured and
+    /// rejected, so unrestricted tokens must be requested explicitly. Such
+    /// tokens only pass validators that opt in with
+    /// [`QTokenValidator::accept_any_audience`].
+    pub fn any_audience(mut self) -> Self {
+        self.audience = vec![ANY_AUDIENCE.to_string()];
+        self
+    }
+
     /// Set policy reference
     pub fn policy_ref(mut self, pol: impl Into<String>) -> Self {
         self.policy_ref = pol.into();
@@ -523,6 +642,11 @@ impl QTokenBuilder {
         self
     }
 
+    /// Set context hash from attributes (see [`context_hash`])
+    pub fn context_from(self, attributes: &[(&str, &[u8])]) -> Self {
+        self.context(context_hash(attributes))
+    }
+
     /// Set device key (will be hashed)
     pub fn device_key(mut self, key: &[u8]) -> Self {
         self.device_key = sha256(key);
@@ -530,8 +654,14 @@ impl QTokenBuilder {
     }
 
     /// Set client key (will be hashed)
+    ///
+    /// An Ed25519 key (32 bytes) is also recorded as a `cnf` claim carrying
+    /// its JWK thumbprint.
     pub fn client_key(mut self, key: &[u8]) -> Self {
         self.client_key = sha256(key);
+        self.confirmation = <&[u8; 32]>::try_from(key).ok().map(|pk| Confirmation {
+            jkt: jwk_thumbprint(pk),
+        });
         self
     }
 
@@ -541,21 +671,47 @@ impl QTokenBuilder {
         self
     }
 
+    /// Expected length of the encoded (base64url) token, without signing
+    ///
+    /// Serializes the payload to CBOR and adds the fixed-size parts: header,
+    /// payload length, XChaCha20 nonce and tag, dual signature, and proof
+    /// binding. Cheap enough to reject oversized requests before paying for
+    /// `build`. Timestamps are taken now, so a build much later could differ
+    /// by a byte or two.
+    pub fn estimated_size(&self) -> Result<usize> {
+        let payload_len = self.payload().to_cbor()?.len();
+        let raw_len = HEADER_SIZE
+            + 2
+            + XCHACHA20_NONCE_SIZE
+            + payload_len
+            + XCHACHA20_TAG_SIZE
+            + DUAL_SIGNATURE_SIZE
+            + PROOF_BINDING_SIZE;
+
+        // Unpadded base64
+        Ok((raw_len * 4 + 2) / 3)
+    }
+
+    fn payload(&self) -> QTokenPayload {
+        QTokenPayload::new(
+            self.subject.clone(),
+            self.issuer.clone(),
+            self.audience.clone(),
+            self.policy_ref.clone(),
+            self.validity_seconds,
+        )
+        .with_claims(self.claims.clone())
+        .with_context(self.context)
+        .with_confirmation(self.confirmation.clone())
+    }
+
     /// Build the token
     pub fn build(
         self,
         signing_keys: &IssuerSigningKeys,
         encryption_key: &EncryptionKey,
     ) -> Result<QToken> {
-        let payload = QTokenPayload::new(
-            self.subject,
-            self.issuer,
-            self.audience,
-            self.policy_ref,
-            self.validity_seconds,
-        )
-        .with_claims(self.claims)
-        .with_context(self.context);
+        let payload = self.payload();
 
         let binding = ProofBinding::new(self.device_key, self.client_key, self.ip_hash);
 
@@ -564,19 +720,126 @@ impl QTokenBuilder {
 }
 
 /// Token validation result
+///
+/// Validation decrypts the payload exactly once; it is kept here so callers
+/// checking binding or policy afterwards don't decrypt it again.
 pub struct ValidatedToken {
     pub header: QTokenHeader,
     pub payload: QTokenPayload,
     pub binding: ProofBinding,
 }
 
+impl ValidatedToken {
+    /// JWK thumbprint from the `cnf` claim, if the token is key-bound
+    pub fn confirmation_thumbprint(&self) -> Option<&str> {
+        self.payload.cnf.as_ref().map(|cnf| cnf.jkt.as_str())
+    }
+}
+
+/// Outcome of a single validation check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckResult {
+    /// Check ran and passed
+    Passed,
+    /// Check ran and failed with the given code
+    Failed(ErrorCode),
+    /// Check was not run (missing input, or an earlier step it depends on failed)
+    Skipped,
+}
+
+impl CheckResult {
+    fn from_check(result: std::result::Result<(), ErrorCode>) -> Self {
+        match result {
+            Ok(()) => Self::Passed,
+            Err(code) => Self::Failed(code),
+        }
+    }
+
+    /// Whether the check ran and passed
+    pub fn is_passed(&self) -> bool {
+        matches!(self, Self::Passed)
+    }
+
+    /// Whether the check ran and failed
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+/// Per-check results of validating a token
+///
+/// Unlike `QTokenValidator::validate`, which stops at the first failure,
+/// every applicable check is recorded. Payload checks are skipped when the
+/// payload cannot be decrypted.
+#[derive(Debug, Clone)]
+pub struct TokenVerificationReport {
+    /// Key ID and dual signature
+    pub signature: CheckResult,
+    /// Header timestamp skew (skipped unless enabled)
+    pub header_timestamp: CheckResult,
+    /// Payload decryption
+    pub decryption: CheckResult,
+    /// Expiration (`exp`, with clock skew)
+    pub expiry: CheckResult,
+    /// Not-before (`nbf`, with clock skew)
+    pub not_before: CheckResult,
+    /// Issuer match
+    pub issuer: CheckResult,
+    /// Audience match
+    pub audience: CheckResult,
+    /// Revocation status (skipped without a checker)
+    pub revocation: CheckResult,
+    /// Client key binding (skipped without a client key)
+    pub binding: CheckResult,
+    /// Policy reference (skipped without a policy engine)
+    pub policy: CheckResult,
+}
+
+impl TokenVerificationReport {
+    fn checks(&self) -> [CheckResult; 10] {
+        [
+            self.signature,
+            self.header_timestamp,
+            self.decryption,
+            self.expiry,
+            self.not_before,
+            self.issuer,
+            self.audience,
+            self.revocation,
+            self.binding,
+            self.policy,
+        ]
+    }
+
+    /// True if no check failed
+    pub fn is_valid(&self) -> bool {
+        !self.checks().iter().any(CheckResult::is_failed)
+    }
+
+    /// Error codes of all failed checks, in check order
+    pub fn failures(&self) -> Vec<ErrorCode> {
+        self.checks()
+            .iter()
+            .filter_map(|check| match check {
+                CheckResult::Failed(code) => Some(*code),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 /// Token validator
 pub struct QTokenValidator {
     verifying_keys: IssuerVerifyingKeys,
     encryption_key: EncryptionKey,
     expected_issuer: String,
-    expected_audience: String,
+    expected_audiences: Vec<String>,
     clock_skew_seconds: i64,
+    header_skew_seconds: Option<i64>,
+    key_id: [u8; KEY_ID_SIZE],
+    policy_engine: Option<Arc<PolicyEngine>>,
+    require_proof: bool,
+    accept_any_audience: bool,
 }
 
 impl QTokenValidator {
@@ -587,48 +850,138 @@ impl QTokenValidator {
         expected_issuer: String,
         expected_audience: String,
     ) -> Self {
+        let key_id = verifying_keys.key_id();
         Self {
             verifying_keys,
             encryption_key,
             expected_issuer,
-            expected_audience,
+            expected_audiences: vec![expected_audience],
             clock_skew_seconds: 60, // 1 minute default
+            header_skew_seconds: None,
+            key_id,
+            policy_engine: None,
+            require_proof: false,
+            accept_any_audience: false,
         }
     }
 
+    /// Accept tokens for any of `auds`
+    ///
+    /// Replaces the audience given to [`QTokenValidator::new`]. A token passes
+    /// if at least one of its `aud` entries exactly matches one of `auds`.
+    pub fn with_audiences(mut self, auds: Vec<String>) -> Self {
+        self.expected_audiences = auds;
+        self
+    }
+
+    /// Accept tokens issued for any audience
+    ///
+    /// Disabled by default, so a token carrying the `ANY_AUDIENCE` sentinel
+    /// is rejected with `ErrorCode::InvalidAudience`. Enable it only for
+    /// services meant to honour unrestricted tokens.
+    pub fn accept_any_audience(mut self, accept: bool) -> Self {
+        self.accept_any_audience = accept;
+        self
+    }
+
     /// Set allowed clock skew
     pub fn with_clock_skew(mut self, seconds: i64) -> Self {
         self.clock_skew_seconds = seconds;
         self
     }
 
+    /// Reject tokens whose header timestamp is more than `seconds` from now
+    ///
+    /// Disabled by default. The header timestamp is the issuance time, so
+    /// this suits short-lived tokens; a far-off value suggests a clock-skewed
+    /// or replayed issuance.
+    pub fn with_header_skew(mut self, seconds: i64) -> Self {
+        self.header_skew_seconds = Some(seconds);
+        self
+    }
+
+    /// Require `pol` to reference a policy loaded in `engine`
+    ///
+    /// Disabled by default. When set, tokens whose policy is unknown to the
+    /// engine or outside its `valid_from`/`valid_until` window are rejected
+    /// with `ErrorCode::UnknownPolicy`.
+    pub fn with_policy_engine(mut self, engine: Arc<PolicyEngine>) -> Self {
+        self.policy_engine = Some(engine);
+        self
+    }
+
+    /// Require every token to be presented with a proof of possession
+    ///
+    /// Disabled by default. When set, [`QTokenValidator::validate`] (and the
+    /// methods built on it) fail with `QAuthError::ProofRequired`; tokens
+    /// must go through [`QTokenValidator::validate_with_proof`] instead.
+    pub fn require_proof(mut self, required: bool) -> Self {
+        self.require_proof = required;
+        self
+    }
+
     /// Validate a token
     pub fn validate(&self, token: &QToken) -> Result<ValidatedToken> {
+        if self.require_proof {
+            return Err(QAuthError::ProofRequired);
+        }
+        self.validate_token(token)
+    }
+
+    /// Validate a token together with its proof of possession
+    ///
+    /// Runs token validation, checks that the token is bound to the key of
+    /// `proof_validator`, then validates the proof against the request and
+    /// the encoded token. Token failures are reported as
+    /// `QAuthError::TokenValidation`, a key not matching the binding as
+    /// `ErrorCode::BindingMismatch`, and a bad proof as
+    /// `QAuthError::InvalidProof`.
+    ///
+    /// Reuse one `ProofValidator` per client key so its replay cache sees
+    /// every proof from that client.
+    pub fn validate_with_proof(
+        &self,
+        token: &QToken,
+        proof: &ProofOfPossession,
+        proof_validator: &ProofValidator,
+        method: &str,
+        uri: &str,
+        body: Option<&[u8]>,
+    ) -> Result<ValidatedToken> {
+        let validated = self.validate_token(token)?;
+        token.verify_binding(&proof_validator.client_public_key(), None)?;
+        proof_validator.validate(proof, method, uri, body, token.encode().as_bytes())?;
+        Ok(validated)
+    }
+
+    fn validate_token(&self, token: &QToken) -> Result<ValidatedToken> {
         // 1. Verify signatures
-        token.verify_signatures(&self.verifying_keys)?;
+        token.verify_signatures_with_key_id(&self.verifying_keys, &self.key_id)?;
+
+        // 1b. Check header timestamp (authenticated by the signature)
+        if self.header_skew_seconds.is_some() {
+            self.check_header_timestamp(&token.header, Utc::now().timestamp_millis())?;
+        }
 
         // 2. Decrypt payload
         let payload = token.decrypt_payload(&self.encryption_key)?;
 
         // 3. Check expiration (with clock skew)
         let now = Utc::now().timestamp();
-        if now > payload.exp + self.clock_skew_seconds {
-            return Err(ErrorCode::TokenExpired.into());
-        }
+        self.check_expiry(&payload, now)?;
 
         // 4. Check not-before (with clock skew)
-        if now < payload.nbf - self.clock_skew_seconds {
-            return Err(ErrorCode::TokenNotYetValid.into());
-        }
+        self.check_not_before(&payload, now)?;
 
         // 5. Verify issuer
-        if payload.iss != self.expected_issuer {
-            return Err(ErrorCode::InvalidIssuer.into());
-        }
+        self.check_issuer(&payload)?;
 
         // 6. Verify audience
-        if !payload.aud.contains(&self.expected_audience) {
-            return Err(ErrorCode::InvalidAudience.into());
+        self.check_audience(&payload)?;
+
+        // 7. Verify policy reference
+        if self.policy_engine.is_some() {
+            self.check_policy(&payload, Utc::now())?;
         }
 
         Ok(ValidatedToken {
@@ -638,11 +991,199 @@ impl QTokenValidator {
         })
     }
 
+    /// Run every validation check and report each outcome
+    ///
+    /// Revocation and binding are reported as skipped; use
+    /// [`QTokenValidator::validate_report_with`] to include them.
+    pub fn validate_report(&self, token: &QToken) -> TokenVerificationReport {
+        self.validate_report_with(token, None, None)
+    }
+
+    /// Run every validation check, including revocation and client key binding
+    /// when a checker or client key is supplied
+    pub fn validate_report_with(
+        &self,
+        token: &QToken,
+        revocation_checker: Option<&RevocationChecker>,
+        client_key: Option<&[u8; 32]>,
+    ) -> TokenVerificationReport {
+        let signature = match token.verify_signatures_with_key_id(&self.verifying_keys, &self.key_id) {
+            Ok(()) => CheckResult::Passed,
+            Err(QAuthError::TokenValidation { code }) => CheckResult::Failed(code),
+            Err(_) => CheckResult::Failed(ErrorCode::SignatureFailed),
+        };
+
+        let header_timestamp = match self.header_skew_seconds {
+            Some(_) => CheckResult::from_check(
+                self.check_header_timestamp(&token.header, Utc::now().timestamp_millis()),
+            ),
+            None => CheckResult::Skipped,
+        };
+
+        let binding = match client_key {
+            Some(key) => match token.verify_binding(key, None) {
+                Ok(()) => CheckResult::Passed,
+                Err(_) => CheckResult::Failed(ErrorCode::BindingMismatch),
+            },
+            None => CheckResult::Skipped,
+        };
+
+        let payload = match token.decrypt_payload(&self.encryption_key) {
+            Ok(payload) => payload,
+            Err(_) => {
+                return TokenVerificationReport {
+                    signature,
+                    header_timestamp,
+                    decryption: CheckResult::Failed(ErrorCode::DecryptionFailed),
+                    expiry: CheckResult::Skipped,
+                    not_before: CheckResult::Skipped,
+                    issuer: CheckResult::Skipped,
+                    audience: CheckResult::Skipped,
+                    revocation: CheckResult::Skipped,
+                    binding,
+                    policy: CheckResult::Skipped,
+                }
+            }
+        };
+
+        let now = Utc::now().timestamp();
+        let revocation = match revocation_checker {
+            Some(checker) => {
+                let iat = DateTime::from_timestamp(payload.iat, 0).unwrap_or_default();
+                match checker.is_revoked_for_subject(&payload.rid, &payload.sub, iat) {
+                    Ok(false) => CheckResult::Passed,
+                    // Fail closed if revocation status cannot be determined
+                    Ok(true) | Err(_) => CheckResult::Failed(ErrorCode::TokenRevoked),
+                }
+            }
+            None => CheckResult::Skipped,
+        };
+
+        let policy = match self.policy_engine {
+            Some(_) => CheckResult::from_check(self.check_policy(&payload, Utc::now())),
+            None => CheckResult::Skipped,
+        };
+
+        TokenVerificationReport {
+            signature,
+            header_timestamp,
+            decryption: CheckResult::Passed,
+            expiry: CheckResult::from_check(self.check_expiry(&payload, now)),
+            not_before: CheckResult::from_check(self.check_not_before(&payload, now)),
+            issuer: CheckResult::from_check(self.check_issuer(&payload)),
+            audience: CheckResult::from_check(self.check_audience(&payload)),
+            revocation,
+            binding,
+            policy,
+        }
+    }
+
+    fn check_header_timestamp(
+        &self,
+        header: &QTokenHeader,
+        now_ms: i64,
+    ) -> std::result::Result<(), ErrorCode> {
+        if let Some(skew) = self.header_skew_seconds {
+            let delta_ms = (header.timestamp as i128 - now_ms as i128).abs();
+            if delta_ms > skew as i128 * 1000 {
+                return Err(ErrorCode::InvalidTimestamp);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_expiry(&self, payload: &QTokenPayload, now: i64) -> std::result::Result<(), ErrorCode> {
+        if now > payload.exp + self.clock_skew_seconds {
+            return Err(ErrorCode::TokenExpired);
+        }
+        Ok(())
+    }
+
+    fn check_not_before(&self, payload: &QTokenPayload, now: i64) -> std::result::Result<(), ErrorCode> {
+        if now < payload.nbf - self.clock_skew_seconds {
+            return Err(ErrorCode::TokenNotYetValid);
+        }
+        Ok(())
+    }
+
+    fn check_issuer(&self, payload: &QTokenPayload) -> std::result::Result<(), ErrorCode> {
+        if payload.iss != self.expected_issuer {
+            return Err(ErrorCode::InvalidIssuer);
+        }
+        Ok(())
+    }
+
+    fn check_audience(&self, payload: &QTokenPayload) -> std::result::Result<(), ErrorCode> {
+        match payload.aud.as_slice() {
+            // No audience is a misconfiguration, not "any audience"
+            [] => Err(ErrorCode::InvalidAudience),
+            // The sentinel only means unrestricted when it stands alone
+            [only] if only == ANY_AUDIENCE && self.accept_any_audience => Ok(()),
+            aud if aud.iter().any(|a| a == ANY_AUDIENCE) => Err(ErrorCode::InvalidAudience),
+            aud if aud.iter().any(|a| self.expected_audiences.contains(a)) => Ok(()),
+            _ => Err(ErrorCode::InvalidAudience),
+        }
+    }
+
+    fn check_policy(
+        &self,
+        payload: &QTokenPayload,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<(), ErrorCode> {
+        let Some(engine) = &self.policy_engine else {
+            return Ok(());
+        };
+        let policy = engine
+            .get_policy(&payload.pol)
+            .ok_or(ErrorCode::UnknownPolicy)?;
+        if policy.valid_from.is_some_and(|from| now < from)
+            || policy.valid_until.is_some_and(|until| now > until)
+        {
+            return Err(ErrorCode::UnknownPolicy);
+        }
+        Ok(())
+    }
+
+    /// Validate a token, then evaluate its policy against `context`
+    ///
+    /// Requires a policy engine (see [`QTokenValidator::with_policy_engine`]).
+    /// The evaluation result is returned as-is; a deny is not an error.
+    pub fn validate_and_evaluate(
+        &self,
+        token: &QToken,
+        context: &EvaluationContext,
+    ) -> Result<(ValidatedToken, EvaluationResult)> {
+        let engine = self
+            .policy_engine
+            .as_ref()
+            .ok_or_else(|| QAuthError::PolicyError("No policy engine configured".into()))?;
+        let validated = self.validate(token)?;
+        let result = engine.evaluate(&validated.payload.pol, context)?;
+        Ok((validated, result))
+    }
+
     /// Validate a token string
     pub fn validate_string(&self, token_str: &str) -> Result<ValidatedToken> {
         let token = QToken::decode(token_str)?;
         self.validate(&token)
     }
+
+    /// Validate many token strings, returning one result per input in order
+    ///
+    /// The verifying keys, their key ID, and the decryption key are shared
+    /// across the batch; each token is still verified individually. With the
+    /// `rayon` feature the tokens are validated in parallel.
+    pub fn validate_batch(&self, tokens: &[&str]) -> Vec<Result<ValidatedToken>> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            tokens.par_iter().map(|token| self.validate_string(token)).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            tokens.iter().map(|token| self.validate_string(token)).collect()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -655,6 +1196,25 @@ mod tests {
         (signing_keys, encryption_key)
     }
 
+    /// Validator for the issuer and audience the tests' tokens use
+    fn test_validator(
+        signing_keys: &IssuerSigningKeys,
+        encryption_key: &EncryptionKey,
+    ) -> QTokenValidator {
+        let verifying_keys = IssuerVerifyingKeys::from_bytes(
+            &signing_keys.ed25519.public_key_bytes(),
+            &signing_keys.mldsa.public_key_bytes(),
+        )
+        .unwrap();
+
+        QTokenValidator::new(
+            verifying_keys,
+            EncryptionKey::from_bytes(&encryption_key.to_bytes()),
+            "https://auth.example.com".into(),
+            "https://api.example.com".into(),
+        )
+    }
+
     #[test]
     fn test_token_creation_and_validation() {
         let (signing_keys, encryption_key) = setup_keys();
@@ -718,7 +1278,7 @@ mod tests {
     }
 
     #[test]
-    fn test_full_validation() {
+    fn test_malformed_length_field_rejected() {
         let (signing_keys, encryption_key) = setup_keys();
 
         let token = QTokenBuilder::access_token()
@@ -730,23 +1290,80 @@ mod tests {
             .build(&signing_keys, &encryption_key)
             .unwrap();
 
-        let verifying_keys = IssuerVerifyingKeys::from_bytes(
-            &signing_keys.ed25519.public_key_bytes(),
-            &signing_keys.mldsa.public_key_bytes(),
-        )
-        .unwrap();
+        // Length field claims far more ciphertext than the buffer holds
+        let mut bytes = token.to_bytes();
+        bytes[HEADER_SIZE..HEADER_SIZE + 2].copy_from_slice(&u16::MAX.to_be_bytes());
+        assert!(QToken::from_bytes(&bytes).is_err());
 
-        let validator = QTokenValidator::new(
-            verifying_keys,
-            EncryptionKey::from_bytes(encryption_key.to_bytes()),
-            "https://auth.example.com".into(),
-            "https://api.example.com".into(),
-        );
+        // Length field too small to hold a nonce and tag
+        let mut bytes = token.to_bytes();
+        bytes[HEADER_SIZE..HEADER_SIZE + 2].copy_from_slice(&4u16.to_be_bytes());
+        assert!(QToken::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_full_validation() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .client_key(b"client-key")
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let validator = test_validator(&signing_keys, &encryption_key);
 
         let validated = validator.validate(&token).unwrap();
         assert_eq!(validated.payload.sub, b"user-123");
     }
 
+    #[test]
+    fn test_validation_decrypts_payload_once() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let engine = policy_engine("2099-01-01T00:00:00Z");
+        let validator = test_validator(&signing_keys, &encryption_key).with_policy_engine(engine);
+        let token = token_with_policy(&signing_keys, &encryption_key, "urn:qauth:policy:default");
+        let decryptions = || PAYLOAD_DECRYPTIONS.with(|count| count.get());
+
+        let before = decryptions();
+        validator.validate(&token).unwrap();
+        assert_eq!(decryptions() - before, 1);
+
+        let before = decryptions();
+        validator.validate_and_evaluate(&token, &EvaluationContext::default()).unwrap();
+        assert_eq!(decryptions() - before, 1);
+
+        let before = decryptions();
+        let store = Arc::new(crate::revocation::InMemoryRevocationStore::new());
+        let checker = RevocationChecker::new(store);
+        let client_key = [0u8; 32];
+        validator.validate_report_with(&token, Some(&checker), Some(&client_key));
+        assert_eq!(decryptions() - before, 1);
+    }
+
+    #[test]
+    fn test_estimated_size_matches_encoded() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let builder = QTokenBuilder::access_token()
+            .subject_string("user-123")
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .audience("https://admin.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .claim("roles", serde_json::json!(["reader", "editor"]))
+            .claim("tenant", serde_json::json!("acme"))
+            .client_key(&[7u8; 32]);
+
+        let estimated = builder.estimated_size().unwrap();
+        let actual = builder.build(&signing_keys, &encryption_key).unwrap().encode().len();
+
+        assert!(estimated.abs_diff(actual) <= 4, "estimated {}, actual {}", estimated, actual);
+    }
+
     #[test]
     fn test_wrong_audience_fails() {
         let (signing_keys, encryption_key) = setup_keys();
@@ -760,18 +1377,8 @@ mod tests {
             .build(&signing_keys, &encryption_key)
             .unwrap();
 
-        let verifying_keys = IssuerVerifyingKeys::from_bytes(
-            &signing_keys.ed25519.public_key_bytes(),
-            &signing_keys.mldsa.public_key_bytes(),
-        )
-        .unwrap();
-
-        let validator = QTokenValidator::new(
-            verifying_keys,
-            EncryptionKey::from_bytes(encryption_key.to_bytes()),
-            "https://auth.example.com".into(),
-            "https://wrong-api.example.com".into(), // Wrong audience
-        );
+        let validator = test_validator(&signing_keys, &encryption_key)
+            .with_audiences(vec!["https://wrong-api.example.com".into()]); // Wrong audience
 
         let result = validator.validate(&token);
         assert!(matches!(
@@ -780,6 +1387,410 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_multiple_audiences() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://billing.example.com")
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let validator = test_validator(&signing_keys, &encryption_key).with_audiences(vec![
+            "https://api.example.com".into(),
+            "https://billing.example.com".into(),
+        ]);
+        assert!(validator.validate(&token).is_ok());
+
+        // Matching is exact per entry, with no prefix or suffix matching
+        let validator = test_validator(&signing_keys, &encryption_key).with_audiences(vec![
+            "https://api.example.com".into(),
+            "https://billing.example.com/v2".into(),
+            "billing.example.com".into(),
+        ]);
+        assert!(matches!(
+            validator.validate(&token),
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidAudience })
+        ));
+    }
+
+    #[test]
+    fn test_any_audience_accepted() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://other.example.com")
+            .any_audience()
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        // Rejected unless the validator opts in
+        let validator = test_validator(&signing_keys, &encryption_key);
+        assert!(matches!(
+            validator.validate(&token),
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidAudience })
+        ));
+
+        let validator = validator.accept_any_audience(true);
+        let validated = validator.validate(&token).unwrap();
+        assert_eq!(validated.payload.aud, vec![ANY_AUDIENCE.to_string()]);
+    }
+
+    #[test]
+    fn test_empty_audience_rejected() {
+        let (signing_keys, encryption_key) = setup_keys();
+        // Even a validator that accepts unrestricted tokens rejects these
+        let validator =
+            test_validator(&signing_keys, &encryption_key).accept_any_audience(true);
+
+        let empty = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+        assert!(matches!(
+            validator.validate(&empty),
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidAudience })
+        ));
+
+        // The sentinel mixed with real audiences is ambiguous, so it is rejected too
+        let mixed = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .audience(ANY_AUDIENCE)
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+        assert!(matches!(
+            validator.validate(&mixed),
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidAudience })
+        ));
+    }
+
+    fn policy_engine(valid_until: &str) -> Arc<PolicyEngine> {
+        let mut engine = PolicyEngine::new();
+        engine
+            .load_policy_json(&format!(
+                r#"{{
+                    "id": "urn:qauth:policy:default",
+                    "version": "2026-01-30",
+                    "issuer": "https://auth.example.com",
+                    "valid_until": "{valid_until}",
+                    "rules": [
+                        {{
+                            "id": "read-projects",
+                            "effect": "allow",
+                            "resources": ["projects/*"],
+                            "actions": ["read"]
+                        }}
+                    ]
+                }}"#
+            ))
+            .unwrap();
+        Arc::new(engine)
+    }
+
+    fn token_with_policy(
+        signing_keys: &IssuerSigningKeys,
+        encryption_key: &EncryptionKey,
+        policy: &str,
+    ) -> QToken {
+        QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref(policy)
+            .build(signing_keys, encryption_key)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_reference_known() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let engine = policy_engine("2099-01-01T00:00:00Z");
+        let validator = test_validator(&signing_keys, &encryption_key).with_policy_engine(engine);
+
+        let token = token_with_policy(&signing_keys, &encryption_key, "urn:qauth:policy:default");
+        assert!(validator.validate(&token).is_ok());
+        assert!(validator.validate_report(&token).policy.is_passed());
+    }
+
+    #[test]
+    fn test_policy_reference_unknown_or_expired() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let engine = policy_engine("2099-01-01T00:00:00Z");
+        let validator = test_validator(&signing_keys, &encryption_key).with_policy_engine(engine);
+        let token = token_with_policy(&signing_keys, &encryption_key, "urn:qauth:policy:unloaded");
+        assert!(matches!(
+            validator.validate(&token),
+            Err(QAuthError::TokenValidation { code: ErrorCode::UnknownPolicy })
+        ));
+        assert_eq!(
+            validator.validate_report(&token).failures(),
+            vec![ErrorCode::UnknownPolicy]
+        );
+
+        // A loaded policy past its validity window no longer vouches for tokens
+        let engine = policy_engine("2020-01-01T00:00:00Z");
+        let validator = test_validator(&signing_keys, &encryption_key).with_policy_engine(engine);
+        let token = token_with_policy(&signing_keys, &encryption_key, "urn:qauth:policy:default");
+        assert!(matches!(
+            validator.validate(&token),
+            Err(QAuthError::TokenValidation { code: ErrorCode::UnknownPolicy })
+        ));
+    }
+
+    #[test]
+    fn test_validate_and_evaluate() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let engine = policy_engine("2099-01-01T00:00:00Z");
+        let validator = test_validator(&signing_keys, &encryption_key).with_policy_engine(engine);
+        let token = token_with_policy(&signing_keys, &encryption_key, "urn:qauth:policy:default");
+
+        let mut context = EvaluationContext::default();
+        context.resource.path = "projects/42".into();
+        context.request.action = "read".into();
+        let (validated, result) = validator.validate_and_evaluate(&token, &context).unwrap();
+        assert_eq!(validated.payload.pol, "urn:qauth:policy:default");
+        assert_eq!(result.effect, crate::policy::Effect::Allow);
+
+        context.request.action = "delete".into();
+        let (_, result) = validator.validate_and_evaluate(&token, &context).unwrap();
+        assert_eq!(result.effect, crate::policy::Effect::Deny);
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let build = |audience: &str, validity: i64| {
+            QTokenBuilder::access_token()
+                .subject(b"user-123".to_vec())
+                .issuer("https://auth.example.com")
+                .audience(audience)
+                .policy_ref("urn:qauth:policy:default")
+                .client_key(b"client-key")
+                .validity_seconds(validity)
+                .build(&signing_keys, &encryption_key)
+                .unwrap()
+                .encode()
+        };
+
+        let valid = build("https://api.example.com", 3600);
+        let expired = build("https://api.example.com", -3600);
+        let wrong_audience = build("https://other.example.com", 3600);
+
+        let validator = test_validator(&signing_keys, &encryption_key);
+
+        let results = validator.validate_batch(&[&valid, &expired, &wrong_audience, "garbage", &valid]);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap().payload.sub, b"user-123");
+        assert!(matches!(
+            results[1],
+            Err(QAuthError::TokenValidation { code: ErrorCode::TokenExpired })
+        ));
+        assert!(matches!(
+            results[2],
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidAudience })
+        ));
+        assert!(results[3].is_err());
+        assert!(results[4].is_ok());
+    }
+
+    #[test]
+    fn test_validate_report_flags_all_failures() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let client_key = [7u8; 32];
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://other.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .client_key(&client_key)
+            .validity_seconds(-3600)
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let validator = test_validator(&signing_keys, &encryption_key);
+
+        let report = validator.validate_report(&token);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.failures(),
+            vec![ErrorCode::TokenExpired, ErrorCode::InvalidAudience]
+        );
+        assert!(report.signature.is_passed());
+        assert!(report.issuer.is_passed());
+        assert_eq!(report.revocation, CheckResult::Skipped);
+        assert_eq!(report.binding, CheckResult::Skipped);
+
+        let report = validator.validate_report_with(&token, None, Some(&[8u8; 32]));
+        assert_eq!(report.binding, CheckResult::Failed(ErrorCode::BindingMismatch));
+        let report = validator.validate_report_with(&token, None, Some(&client_key));
+        assert!(report.binding.is_passed());
+    }
+
+    #[test]
+    fn test_jwk_thumbprint_rfc8037() {
+        // RFC 8037 Appendix A.3
+        let x = URL_SAFE_NO_PAD
+            .decode("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo")
+            .unwrap();
+        let key: [u8; 32] = x.try_into().unwrap();
+        assert_eq!(
+            jwk_thumbprint(&key),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+    }
+
+    #[test]
+    fn test_confirmation_claim_matches_binding() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let (_, client_public_key) = crate::proof::ProofGenerator::generate();
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .client_key(&client_public_key)
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let validator = test_validator(&signing_keys, &encryption_key);
+
+        let validated = validator.validate(&token).unwrap();
+        assert_eq!(
+            validated.confirmation_thumbprint(),
+            Some(jwk_thumbprint(&client_public_key).as_str())
+        );
+
+        // The same key satisfies the native binding
+        assert!(token.verify_binding(&client_public_key, None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_with_proof() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let (proof_generator, client_public_key) = crate::proof::ProofGenerator::generate();
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .client_key(&client_public_key)
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+        let token_string = token.encode();
+
+        let validator = test_validator(&signing_keys, &encryption_key).require_proof(true);
+
+        // Token alone is rejected
+        assert!(matches!(validator.validate(&token), Err(QAuthError::ProofRequired)));
+
+        // Token with a valid proof passes
+        let proof_validator = ProofValidator::new(&client_public_key).unwrap();
+        let proof = proof_generator.create_proof(
+            "POST",
+            "/api/resource",
+            Some(b"body"),
+            token_string.as_bytes(),
+        );
+        assert!(validator
+            .validate_with_proof(
+                &token,
+                &proof,
+                &proof_validator,
+                "POST",
+                "/api/resource",
+                Some(b"body"),
+            )
+            .is_ok());
+
+        // Proof signed by another key fails the binding cross-check
+        let (other_generator, other_public_key) = crate::proof::ProofGenerator::generate();
+        let other_proof =
+            other_generator.create_proof("GET", "/api/resource", None, token_string.as_bytes());
+        let other_validator = ProofValidator::new(&other_public_key).unwrap();
+        assert!(matches!(
+            validator.validate_with_proof(
+                &token,
+                &other_proof,
+                &other_validator,
+                "GET",
+                "/api/resource",
+                None,
+            ),
+            Err(QAuthError::TokenValidation { code: ErrorCode::BindingMismatch })
+        ));
+
+        // ...and the proof itself is rejected under the bound key
+        assert!(matches!(
+            validator.validate_with_proof(
+                &token,
+                &other_proof,
+                &proof_validator,
+                "GET",
+                "/api/resource",
+                None,
+            ),
+            Err(QAuthError::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_header_timestamp_skew() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token_at = |offset_ms: i64| {
+            let mut header = QTokenHeader::new(TokenType::Access, signing_keys.key_id());
+            header.timestamp = (Utc::now().timestamp_millis() + offset_ms) as u64;
+            let payload = QTokenPayload::new(
+                b"user-123".to_vec(),
+                "https://auth.example.com".into(),
+                vec!["https://api.example.com".into()],
+                "urn:qauth:policy:default".into(),
+                3600,
+            );
+            let binding = ProofBinding::new([0u8; 32], [0u8; 32], None);
+            QToken::create_with_header(header, &payload, binding, &signing_keys, &encryption_key)
+                .unwrap()
+        };
+
+        let validator = test_validator(&signing_keys, &encryption_key);
+
+        let far_future = token_at(24 * 3600 * 1000);
+
+        // Disabled by default: the header timestamp is not checked
+        assert!(validator.validate(&far_future).is_ok());
+        assert_eq!(
+            validator.validate_report(&far_future).header_timestamp,
+            CheckResult::Skipped
+        );
+
+        let validator = validator.with_header_skew(300);
+
+        // Within skew
+        assert!(validator.validate(&token_at(-60 * 1000)).is_ok());
+
+        // Far-future header is rejected
+        assert!(matches!(
+            validator.validate(&far_future),
+            Err(QAuthError::TokenValidation { code: ErrorCode::InvalidTimestamp })
+        ));
+        assert_eq!(
+            validator.validate_report(&far_future).failures(),
+            vec![ErrorCode::InvalidTimestamp]
+        );
+    }
+
     #[test]
     fn test_expired_token_fails() {
         let (signing_keys, encryption_key) = setup_keys();
@@ -794,18 +1805,7 @@ mod tests {
             .build(&signing_keys, &encryption_key)
             .unwrap();
 
-        let verifying_keys = IssuerVerifyingKeys::from_bytes(
-            &signing_keys.ed25519.public_key_bytes(),
-            &signing_keys.mldsa.public_key_bytes(),
-        )
-        .unwrap();
-
-        let validator = QTokenValidator::new(
-            verifying_keys,
-            EncryptionKey::from_bytes(encryption_key.to_bytes()),
-            "https://auth.example.com".into(),
-            "https://api.example.com".into(),
-        );
+        let validator = test_validator(&signing_keys, &encryption_key);
 
         let result = validator.validate(&token);
         assert!(matches!(
@@ -813,4 +1813,72 @@ mod tests {
             Err(QAuthError::TokenValidation { code: ErrorCode::TokenExpired })
         ));
     }
+
+    #[test]
+    fn test_context_hash_canonical() {
+        let a = context_hash(&[("tenant", &b"acme"[..]), ("region", &b"eu-west-1"[..])]);
+        let b = context_hash(&[("region", &b"eu-west-1"[..]), ("tenant", &b"acme"[..])]);
+        assert_eq!(a, b);
+
+        // Differing values differ
+        let c = context_hash(&[("tenant", &b"acme"[..]), ("region", &b"us-east-1"[..])]);
+        assert_ne!(a, c);
+
+        // Length prefixes keep name/value boundaries unambiguous
+        let d = context_hash(&[("ab", &b"c"[..])]);
+        let e = context_hash(&[("a", &b"bc"[..])]);
+        assert_ne!(d, e);
+    }
+
+    #[test]
+    fn test_subject_string_roundtrip() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token = QTokenBuilder::access_token()
+            .subject_string("user-123")
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let payload = token.decrypt_payload(&encryption_key).unwrap();
+        assert_eq!(payload.sub, b"user-123");
+        assert_eq!(payload.subject_str(), Some("user-123"));
+    }
+
+    #[test]
+    fn test_binary_subject_has_no_str() {
+        let (signing_keys, encryption_key) = setup_keys();
+
+        let token = QTokenBuilder::access_token()
+            .subject(vec![0xff, 0xfe, 0x00, 0x01])
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let payload = token.decrypt_payload(&encryption_key).unwrap();
+        assert_eq!(payload.sub, vec![0xff, 0xfe, 0x00, 0x01]);
+        assert_eq!(payload.subject_str(), None);
+    }
+
+    #[test]
+    fn test_builder_context_from() {
+        let (signing_keys, encryption_key) = setup_keys();
+        let attrs = [("tenant", &b"acme"[..]), ("region", &b"eu-west-1"[..])];
+
+        let token = QTokenBuilder::access_token()
+            .subject(b"user-123".to_vec())
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .policy_ref("urn:qauth:policy:default")
+            .context_from(&attrs)
+            .build(&signing_keys, &encryption_key)
+            .unwrap();
+
+        let payload = token.decrypt_payload(&encryption_key).unwrap();
+        assert_eq!(payload.ctx, context_hash(&[attrs[1], attrs[0]]));
+    }
 }
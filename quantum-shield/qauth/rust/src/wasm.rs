@@ -269,14 +269,9 @@ impl WasmTokenValidator {
     #[wasm_bindgen]
     pub fn validate(&self, token_string: &str) -> Result<String, JsError> {
         // Parse public keys
-        let ed25519_pk: [u8; 32] = self
-            .ed25519_public_key
-            .as_slice()
-            .try_into()
-            .map_err(|_| JsError::new("Invalid Ed25519 public key size"))?;
-
-        let verifying_keys = IssuerVerifyingKeys::from_bytes(&ed25519_pk, &self.mldsa_public_key)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+        let verifying_keys =
+            IssuerVerifyingKeys::from_bytes(&self.ed25519_public_key, &self.mldsa_public_key)
+                .map_err(|e| JsError::new(&e.to_string()))?;
 
         let enc_key: [u8; 32] = self
             .encryption_key
@@ -284,7 +279,7 @@ impl WasmTokenValidator {
             .try_into()
             .map_err(|_| JsError::new("Invalid encryption key size"))?;
 
-        let encryption_key = EncryptionKey::from_bytes(enc_key);
+        let encryption_key = EncryptionKey::from_bytes(&enc_key);
 
         // Decode token
         let token = QToken::decode(token_string).map_err(|e| JsError::new(&e.to_string()))?;
@@ -0,0 +1,59 @@
+//! Sequential vs parallel QShieldSign batch verification.
+//!
+//! Run with: `cargo bench --features parallel --bench parallel_sign`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use quantum_shield::sign::{QShieldSignPublicKey, QShieldSignature};
+use quantum_shield::QShieldSign;
+
+const BATCH_SIZE: usize = 64;
+const DISTINCT_SIGNERS: usize = 4;
+
+fn bench_verify_batch(c: &mut Criterion) {
+    // Signing with SLH-DSA is slow, so reuse a few signatures across the batch
+    let keypairs = QShieldSign::generate_keypairs(DISTINCT_SIGNERS).unwrap();
+    let messages: Vec<Vec<u8>> = (0..DISTINCT_SIGNERS)
+        .map(|i| format!("audit record {i}").into_bytes())
+        .collect();
+    let signatures: Vec<QShieldSignature> = keypairs
+        .iter()
+        .zip(&messages)
+        .map(|((_, secret_key), message)| QShieldSign::sign(secret_key, message).unwrap())
+        .collect();
+
+    let items: Vec<(&QShieldSignPublicKey, &[u8], &QShieldSignature)> = (0..BATCH_SIZE)
+        .map(|i| {
+            let k = i % DISTINCT_SIGNERS;
+            (&keypairs[k].0, messages[k].as_slice(), &signatures[k])
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("verify_batch");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| QShieldSign::verify_batch(&items).unwrap())
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| QShieldSign::verify_batch_parallel(&items).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_generate_keypairs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_keypairs");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            (0..DISTINCT_SIGNERS)
+                .map(|_| QShieldSign::generate_keypair().unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| QShieldSign::generate_keypairs(DISTINCT_SIGNERS).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify_batch, bench_generate_keypairs);
+criterion_main!(benches);
@@ -63,6 +63,10 @@ pub enum QShieldError {
     #[error("Invalid nonce")]
     InvalidNonce,
 
+    /// A nonce repeated within the guard window; the RNG is likely broken
+    #[error("Nonce reuse detected")]
+    NonceReuseDetected,
+
     /// Unsupported algorithm or version
     #[error("Unsupported algorithm: {0}")]
     UnsupportedAlgorithm(String),
@@ -108,6 +112,22 @@ pub enum QShieldError {
     /// Internal error (should never happen in normal operation)
     #[error("Internal error")]
     InternalError,
+
+    /// Empty message refused by `SignConfig::reject_empty`
+    #[error("Empty message")]
+    EmptyMessage,
+
+    /// Message timestamp outside the accepted freshness window
+    #[error("Message expired")]
+    Expired,
+
+    /// Message nonce seen before
+    #[error("Replay detected")]
+    ReplayDetected,
+
+    /// Power-on self-test failed for the named primitive
+    #[error("Self-test failed: {0}")]
+    SelfTestFailed(&'static str),
 }
 
 impl QShieldError {
@@ -138,6 +158,7 @@ impl QShieldError {
                 Self::InvalidCiphertext => "Invalid ciphertext",
                 Self::InvalidSignature => "Invalid signature",
                 Self::InvalidNonce => "Invalid nonce",
+                Self::NonceReuseDetected => "Nonce reuse detected",
                 Self::UnsupportedAlgorithm(_) => "Unsupported algorithm",
                 Self::VersionMismatch { .. } => "Version mismatch",
                 Self::HandshakeFailed(_) => "Handshake failed",
@@ -146,6 +167,10 @@ impl QShieldError {
                 Self::RngFailed => "RNG failed",
                 Self::NotSupported => "Not supported",
                 Self::InternalError => "Internal error",
+                Self::SelfTestFailed(_) => "Self-test failed",
+                Self::EmptyMessage => "Empty message",
+                Self::Expired => "Message expired",
+                Self::ReplayDetected => "Replay detected",
                 _ => "Operation failed",
             }
         }
@@ -6,4 +6,4 @@
 
 mod qshield_kdf;
 
-pub use qshield_kdf::{domains, DerivedKey, KdfConfig, QShieldKDF, SessionKeys};
+pub use qshield_kdf::{domains, DerivedKey, KdfConfig, Prk, QShieldKDF, SessionKeys, PRK_SIZE};
@@ -1,16 +1,20 @@
 //! QShieldKDF - Quantum-resistant Key Derivation Function
 //!
 //! A custom key derivation function that provides:
-//! - HKDF-SHA3-512 for key material combination
+//! - HKDF-SHA3-512 for key material combination, with separate extract/expand steps
 //! - SHAKE-256 for arbitrary-length key expansion
-//! - Argon2id for password-based key derivation
+//! - Argon2id for password-based key derivation and PHC password hashing
+//! - TLS-style HKDF-Expand-Label for protocol key schedules
 //! - Quantum-resistant salt generation
 //! - Domain separation for different use cases
 
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec};
 
-use argon2::{Argon2, Algorithm, Version, Params};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2, Algorithm, Version, Params,
+};
 use hkdf::Hkdf;
 use sha3::{Sha3_512, Shake256, digest::{ExtendableOutput, Update, XofReader}};
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -32,6 +36,8 @@ pub mod domains {
     pub const SESSION: &[u8] = b"QShieldSession-v1";
     /// Password-based key derivation
     pub const PASSWORD: &[u8] = b"QShieldPassword-v1";
+    /// Prefix for `expand_label` labels
+    pub const LABEL_PREFIX: &[u8] = b"qshield ";
 }
 
 /// QShieldKDF configuration
@@ -127,6 +133,30 @@ impl AsRef<[u8]> for DerivedKey {
     }
 }
 
+/// Size of an HKDF-SHA3-512 pseudorandom key
+pub const PRK_SIZE: usize = 64;
+
+/// Pseudorandom key produced by [`QShieldKDF::extract`], zeroized on drop
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct Prk {
+    prk: [u8; PRK_SIZE],
+}
+
+impl Prk {
+    /// Wrap an existing pseudorandom key (must be `PRK_SIZE` bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let prk = bytes
+            .try_into()
+            .map_err(|_| QShieldError::KeyDerivationFailed)?;
+        Ok(Self { prk })
+    }
+
+    /// Get the key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.prk
+    }
+}
+
 /// QShieldKDF - Quantum-resistant Key Derivation Function
 pub struct QShieldKDF {
     config: KdfConfig,
@@ -189,6 +219,57 @@ impl QShieldKDF {
         Ok(DerivedKey::new(okm))
     }
 
+    /// HKDF-Extract with SHA3-512
+    ///
+    /// Together with [`QShieldKDF::expand_prk`] this splits [`QShieldKDF::derive`]
+    /// into its two steps, so key schedules can mix in further extract steps
+    /// (e.g. feeding a PRK back in as the salt of the next extract).
+    pub fn extract(&self, salt: &[u8], ikm: &[u8]) -> Prk {
+        let (prk_bytes, _) = Hkdf::<Sha3_512>::extract(Some(salt), ikm);
+        let mut prk = [0u8; PRK_SIZE];
+        prk.copy_from_slice(&prk_bytes);
+        Prk { prk }
+    }
+
+    /// HKDF-Expand with SHA3-512
+    ///
+    /// Unlike [`QShieldKDF::expand`], which is a SHAKE-256 expansion, this is
+    /// the standard HKDF step. `len` is limited to 255 * 64 bytes.
+    pub fn expand_prk(&self, prk: &Prk, info: &[u8], len: usize) -> Result<DerivedKey> {
+        let hk =
+            Hkdf::<Sha3_512>::from_prk(&prk.prk).map_err(|_| QShieldError::KeyDerivationFailed)?;
+        let mut okm = vec![0u8; len];
+
+        hk.expand(info, &mut okm)
+            .map_err(|_| QShieldError::KeyDerivationFailed)?;
+
+        Ok(DerivedKey::new(okm))
+    }
+
+    /// Derive several named keys from one extract
+    ///
+    /// Each `(label, len)` entry yields `expand_prk(extract(salt, ikm), label, len)`,
+    /// in order. Labels are the HKDF info and must be distinct, so every key
+    /// is independent of the others; a repeated label is rejected.
+    pub fn derive_many(
+        &self,
+        ikm: &[u8],
+        salt: &[u8],
+        labels: &[(&str, usize)],
+    ) -> Result<Vec<DerivedKey>> {
+        for (i, (label, _)) in labels.iter().enumerate() {
+            if labels[..i].iter().any(|(seen, _)| seen == label) {
+                return Err(QShieldError::KeyDerivationFailed);
+            }
+        }
+
+        let prk = self.extract(salt, ikm);
+        labels
+            .iter()
+            .map(|(label, len)| self.expand_prk(&prk, label.as_bytes(), *len))
+            .collect()
+    }
+
     /// Derive a key with quantum-resistant salt generation
     ///
     /// This variant always generates a fresh quantum-resistant salt and
@@ -247,6 +328,33 @@ impl QShieldKDF {
         Ok(DerivedKey::new(output))
     }
 
+    /// Expand a secret with a structured label (TLS 1.3 HKDF-Expand-Label style)
+    ///
+    /// The HKDF info is encoded as:
+    ///
+    /// ```text
+    /// length (u16 BE) || label_len (u8) || "qshield " || label || context_len (u8) || context
+    /// ```
+    ///
+    /// The secret is extracted with an empty salt before expansion, so secrets
+    /// of any length are accepted.
+    ///
+    /// # Arguments
+    /// * `secret` - Input secret
+    /// * `label` - Purpose label (at most 247 bytes)
+    /// * `context` - Context, typically a transcript hash (at most 255 bytes)
+    /// * `length` - Desired output length in bytes
+    pub fn expand_label(
+        &self,
+        secret: &[u8],
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> Result<DerivedKey> {
+        let info = label_info(label, context, length)?;
+        self.derive(secret, Some(&[]), &info, length)
+    }
+
     /// Derive a key from a password using Argon2id
     ///
     /// # Arguments
@@ -293,6 +401,51 @@ impl QShieldKDF {
         Ok(DerivedKey::new(final_key))
     }
 
+    /// Hash a password for storage as a PHC-format Argon2id string
+    ///
+    /// The result (`$argon2id$v=19$m=...,t=...,p=...$salt$hash`) embeds a
+    /// random salt and this KDF's cost parameters, so it can be stored as-is
+    /// and later checked with [`QShieldKDF::verify_password`].
+    pub fn hash_password(&self, password: &[u8]) -> Result<String> {
+        let params = Params::new(
+            self.config.memory_cost,
+            self.config.time_cost,
+            self.config.parallelism,
+            None,
+        )
+        .map_err(|_| QShieldError::KeyDerivationFailed)?;
+
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut salt_bytes = quantum_salt(16)?;
+        let salt = SaltString::encode_b64(&salt_bytes)
+            .map_err(|_| QShieldError::KeyDerivationFailed)?;
+        salt_bytes.zeroize();
+
+        let hash = argon2
+            .hash_password(password, &salt)
+            .map_err(|_| QShieldError::KeyDerivationFailed)?;
+
+        Ok(hash.to_string())
+    }
+
+    /// Verify a password against a PHC-format hash from [`QShieldKDF::hash_password`]
+    ///
+    /// Cost parameters are read from the hash string, and the final comparison
+    /// is constant-time. Malformed hashes and non-Argon2id hashes return `false`.
+    pub fn verify_password(&self, password: &[u8], hash: &str) -> bool {
+        let parsed = match PasswordHash::new(hash) {
+            Ok(parsed) => parsed,
+            Err(_) => return false,
+        };
+
+        if parsed.algorithm != argon2::ARGON2ID_IDENT {
+            return false;
+        }
+
+        Argon2::default().verify_password(password, &parsed).is_ok()
+    }
+
     /// Derive encryption and authentication keys from a shared secret
     ///
     /// Returns (encryption_key, auth_key)
@@ -318,11 +471,7 @@ impl QShieldKDF {
         shared_secret: &[u8],
         handshake_hash: &[u8],
     ) -> Result<SessionKeys> {
-        let mut context = Vec::new();
-        context.extend_from_slice(domains::SESSION);
-        context.extend_from_slice(handshake_hash);
-
-        let master = self.derive(shared_secret, None, &context, 128)?;
+        let master = self.expand_label(shared_secret, "session keys", handshake_hash, 128)?;
 
         let keys = master.split(&[32, 32, 12, 12, 32])?;
 
@@ -335,12 +484,57 @@ impl QShieldKDF {
         })
     }
 
+    /// Derive separate keys for each direction of a session
+    ///
+    /// Every key is its own `expand_label` output over HKDF-SHA3-512, so the
+    /// client-to-server and server-to-client keys never share AEAD key or
+    /// nonce space. Write keys are 64 bytes (AES-256 key || ChaCha20 key).
+    /// A client encrypts with `client_write_key` and decrypts with
+    /// `server_write_key`; a server does the reverse.
+    pub fn derive_directional(
+        &self,
+        shared_secret: &[u8],
+        handshake_hash: &[u8],
+    ) -> Result<SessionKeys> {
+        let expand = |label: &str, len: usize| {
+            self.expand_label(shared_secret, label, handshake_hash, len)
+        };
+
+        Ok(SessionKeys {
+            client_write_key: expand("client to server key", 64)?,
+            server_write_key: expand("server to client key", 64)?,
+            client_iv: expand("client to server iv", 12)?,
+            server_iv: expand("server to client iv", 12)?,
+            resumption_secret: expand("resumption", 32)?,
+        })
+    }
+
     /// Generate a quantum-resistant salt
     pub fn generate_salt(&self, len: usize) -> Result<Vec<u8>> {
         quantum_salt(len)
     }
 }
 
+/// Encode the HKDF info for `QShieldKDF::expand_label`
+fn label_info(label: &str, context: &[u8], length: usize) -> Result<Vec<u8>> {
+    let label_len = domains::LABEL_PREFIX.len() + label.len();
+    if length > u16::MAX as usize
+        || label_len > u8::MAX as usize
+        || context.len() > u8::MAX as usize
+    {
+        return Err(QShieldError::KeyDerivationFailed);
+    }
+
+    let mut info = Vec::with_capacity(2 + 1 + label_len + 1 + context.len());
+    info.extend_from_slice(&(length as u16).to_be_bytes());
+    info.push(label_len as u8);
+    info.extend_from_slice(domains::LABEL_PREFIX);
+    info.extend_from_slice(label.as_bytes());
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+    Ok(info)
+}
+
 /// Session keys derived for the handshake protocol
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SessionKeys {
@@ -414,6 +608,32 @@ mod tests {
         assert_eq!(key.as_bytes(), key2.as_bytes());
     }
 
+    #[test]
+    fn test_hash_and_verify_password() {
+        let kdf = QShieldKDF::with_config(KdfConfig::low_memory());
+        let password = b"correct horse battery staple";
+
+        let hash = kdf.hash_password(password).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=16384,t=4,p=2$"));
+
+        assert!(kdf.verify_password(password, &hash));
+        assert!(!kdf.verify_password(b"wrong password", &hash));
+        assert!(!kdf.verify_password(password, "not a phc string"));
+    }
+
+    #[test]
+    fn test_hash_password_random_salt() {
+        let kdf = QShieldKDF::with_config(KdfConfig::low_memory());
+        let password = b"same password";
+
+        let hash1 = kdf.hash_password(password).unwrap();
+        let hash2 = kdf.hash_password(password).unwrap();
+        assert_ne!(hash1, hash2);
+
+        assert!(kdf.verify_password(password, &hash1));
+        assert!(kdf.verify_password(password, &hash2));
+    }
+
     #[test]
     fn test_encryption_keys() {
         let kdf = QShieldKDF::new();
@@ -442,6 +662,136 @@ mod tests {
         assert_eq!(session_keys.client_iv.len(), 12);
         assert_eq!(session_keys.server_iv.len(), 12);
         assert_eq!(session_keys.resumption_secret.len(), 32);
+
+        // Both peers derive the same keys
+        let again = kdf
+            .derive_session_keys(shared_secret, handshake_hash)
+            .unwrap();
+        assert_eq!(
+            session_keys.client_write_key.as_bytes(),
+            again.client_write_key.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_directional_session_keys() {
+        let kdf = QShieldKDF::new();
+        let shared_secret = b"shared secret from handshake";
+        let handshake_hash = b"hash of handshake transcript";
+
+        let keys = kdf.derive_directional(shared_secret, handshake_hash).unwrap();
+        assert_eq!(keys.client_write_key.len(), 64);
+        assert_eq!(keys.server_write_key.len(), 64);
+        assert_ne!(keys.client_write_key.as_bytes(), keys.server_write_key.as_bytes());
+        assert_ne!(keys.client_iv.as_bytes(), keys.server_iv.as_bytes());
+
+        // Each key is the labelled expansion it is documented as
+        let expected = kdf
+            .expand_label(shared_secret, "server to client key", handshake_hash, 64)
+            .unwrap();
+        assert_eq!(keys.server_write_key.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_expand_label_encoding() {
+        assert_eq!(
+            hex::encode(label_info("key", b"", 32).unwrap()),
+            "00200b71736869656c64206b657900"
+        );
+
+        let context: Vec<u8> = (0u8..32).collect();
+        assert_eq!(
+            hex::encode(label_info("iv", &context, 12).unwrap()),
+            "000c0a71736869656c6420697620\
+             000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        );
+
+        // Oversized labels and contexts are rejected
+        assert!(label_info(&"x".repeat(248), b"", 32).is_err());
+        assert!(label_info("key", &[0u8; 256], 32).is_err());
+    }
+
+    #[test]
+    fn test_expand_label_known_answers() {
+        let kdf = QShieldKDF::new();
+        let secret = [0x0bu8; 32];
+
+        let key = kdf.expand_label(&secret, "key", b"", 32).unwrap();
+        assert_eq!(
+            hex::encode(key.as_bytes()),
+            "db0e408dd9c839dcf42d7e55a3b0a1789047674a3f216bbcf9b3c04c3753ed1c"
+        );
+
+        let context: Vec<u8> = (0u8..32).collect();
+        let iv = kdf.expand_label(&secret, "iv", &context, 12).unwrap();
+        assert_eq!(hex::encode(iv.as_bytes()), "ac475fbc7308a4d65a4d81c6");
+    }
+
+    #[test]
+    fn test_extract_then_expand_matches_derive() {
+        let kdf = QShieldKDF::new();
+        let ikm = [0x0bu8; 22];
+        let salt: Vec<u8> = (0u8..13).collect();
+        let info: Vec<u8> = (0xf0u8..0xfa).collect();
+
+        let prk = kdf.extract(&salt, &ikm);
+        assert_eq!(
+            hex::encode(prk.as_bytes()),
+            "e1c543094f64f3d6c6658a94a94e3818ba13d0b3e77074b80f88f32e6b8433b7\
+             03536cb500753967fae2ea977e11e4dd4f45389807cdf255b395e46807c87d5d"
+        );
+
+        let okm = kdf.expand_prk(&prk, &info, 42).unwrap();
+        assert_eq!(
+            hex::encode(okm.as_bytes()),
+            "40e9f17e9bf2ef99425c2b23ccdf20a018ea5513f9ae68e1ea8c626deb57dfa4\
+             d56c27ccf2a2a24488a5"
+        );
+
+        let derived = kdf.derive(&ikm, Some(&salt), &info, 42).unwrap();
+        assert_eq!(okm.as_bytes(), derived.as_bytes());
+
+        // A PRK round-trips through its bytes
+        let restored = Prk::from_bytes(prk.as_bytes()).unwrap();
+        let again = kdf.expand_prk(&restored, &info, 42).unwrap();
+        assert_eq!(okm.as_bytes(), again.as_bytes());
+        assert!(Prk::from_bytes(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_derive_many_matches_individual_expands() {
+        let kdf = QShieldKDF::new();
+        let labels = [("send", 32), ("recv", 32), ("commit", 32)];
+
+        let keys = kdf.derive_many(b"shared secret", b"salt", &labels).unwrap();
+        assert_eq!(keys.len(), labels.len());
+
+        let prk = kdf.extract(b"salt", b"shared secret");
+        for (key, (label, len)) in keys.iter().zip(labels) {
+            let single = kdf.expand_prk(&prk, label.as_bytes(), len).unwrap();
+            assert_eq!(key.as_bytes(), single.as_bytes());
+        }
+
+        assert_ne!(keys[0].as_bytes(), keys[1].as_bytes());
+        assert_ne!(keys[0].as_bytes(), keys[2].as_bytes());
+        assert_ne!(keys[1].as_bytes(), keys[2].as_bytes());
+
+        // Repeated labels would yield identical keys
+        assert!(kdf
+            .derive_many(b"shared secret", b"salt", &[("send", 32), ("send", 16)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_expand_prk_length_limit() {
+        let kdf = QShieldKDF::new();
+        let prk = kdf.extract(b"salt", b"ikm");
+
+        assert_eq!(
+            kdf.expand_prk(&prk, b"info", 255 * PRK_SIZE).unwrap().len(),
+            255 * PRK_SIZE
+        );
+        assert!(kdf.expand_prk(&prk, b"info", 255 * PRK_SIZE + 1).is_err());
     }
 
     #[test]
@@ -211,6 +211,12 @@ impl QShieldSharedSecret {
     }
 }
 
+impl AsRef<[u8]> for QShieldSharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
 /// QShieldKEM - Hybrid Key Encapsulation Mechanism
 ///
 /// Combines X25519 and ML-KEM-768 with HKDF-SHA3-512 key combination.
@@ -29,3 +29,6 @@ pub use hybrid::{
 };
 pub use ml_kem::{MlKemCiphertext, MlKemPublicKey, MlKemSecretKey};
 pub use x25519::{X25519Ciphertext, X25519PublicKey, X25519SecretKey};
+
+pub(crate) use ml_kem::{MlKem, ML_KEM_CIPHERTEXT_SIZE};
+pub(crate) use x25519::{X25519Kem, X25519_PUBLIC_KEY_SIZE};
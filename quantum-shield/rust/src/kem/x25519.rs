@@ -105,8 +105,14 @@ impl X25519SecretKey {
     }
 
     /// Perform Diffie-Hellman key exchange
+    ///
+    /// Fails with `InvalidKey` if `their_public` is a low-order point, which
+    /// would make the shared secret all zeros regardless of our key.
     pub fn diffie_hellman(&self, their_public: &X25519PublicKey) -> Result<X25519SharedSecret> {
         let shared = self.key.diffie_hellman(&their_public.key);
+        if !shared.was_contributory() {
+            return Err(QShieldError::InvalidKey);
+        }
         Ok(X25519SharedSecret {
             secret: *shared.as_bytes(),
         })
@@ -202,13 +208,17 @@ impl X25519Kem {
 
     /// Encapsulate a shared secret to a public key
     ///
-    /// Returns (ciphertext, shared_secret)
+    /// Returns (ciphertext, shared_secret). Low-order public keys are
+    /// rejected with `InvalidKey`.
     pub fn encapsulate(public_key: &X25519PublicKey) -> Result<(X25519Ciphertext, X25519SharedSecret)> {
         let mut rng = SecureRng::new();
         let ephemeral_secret = EphemeralSecret::random_from_rng(&mut rng);
         let ephemeral_public = PublicKey::from(&ephemeral_secret);
 
         let shared = ephemeral_secret.diffie_hellman(&public_key.key);
+        if !shared.was_contributory() {
+            return Err(QShieldError::InvalidKey);
+        }
 
         let ciphertext = X25519Ciphertext {
             ephemeral_public: X25519PublicKey { key: ephemeral_public },
@@ -250,6 +260,37 @@ mod tests {
         assert_eq!(shared_secret_enc.as_bytes(), shared_secret_dec.as_bytes());
     }
 
+    /// Low-order Curve25519 points (u-coordinates, little-endian)
+    fn low_order_points() -> Vec<[u8; 32]> {
+        let mut order_one = [0u8; 32];
+        order_one[0] = 1;
+        let order_eight: [u8; 32] = [
+            0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f,
+            0xc4, 0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16,
+            0x5f, 0x49, 0xb8, 0x00,
+        ];
+        vec![[0u8; 32], order_one, order_eight]
+    }
+
+    #[test]
+    fn test_low_order_points_rejected() {
+        let (_, secret_key) = X25519Kem::generate_keypair().unwrap();
+
+        for point in low_order_points() {
+            let public_key = X25519PublicKey::from_bytes(&point).unwrap();
+            assert!(matches!(
+                X25519Kem::encapsulate(&public_key),
+                Err(QShieldError::InvalidKey)
+            ));
+
+            let ciphertext = X25519Ciphertext::new(public_key);
+            assert!(matches!(
+                X25519Kem::decapsulate(&secret_key, &ciphertext),
+                Err(QShieldError::InvalidKey)
+            ));
+        }
+    }
+
     #[test]
     fn test_serialization() {
         let (public_key, _) = X25519Kem::generate_keypair().unwrap();
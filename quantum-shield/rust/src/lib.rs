@@ -19,8 +19,9 @@
 //!
 //! ## Example
 //!
-//! ```rust,ignore
+//! ```rust
 //! use quantum_shield::{QShieldKEM, QuantumShield};
+//! # fn main() -> quantum_shield::Result<()> {
 //!
 //! // Generate a key pair
 //! let (public_key, secret_key) = QShieldKEM::generate_keypair()?;
@@ -34,6 +35,10 @@
 //! // Use for symmetric encryption
 //! let cipher = QuantumShield::new(&shared_secret)?;
 //! let encrypted = cipher.encrypt(b"Hello, quantum world!")?;
+//! # assert_eq!(decapsulated.as_bytes(), shared_secret.as_bytes());
+//! # assert_eq!(cipher.decrypt(&encrypted)?, b"Hello, quantum world!");
+//! # Ok(())
+//! # }
 //! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -51,11 +56,14 @@ pub mod sign;
 pub mod symmetric;
 pub mod utils;
 
+mod self_test;
+
 // Re-export main types for convenience
 pub use error::{QShieldError, Result};
 pub use kdf::QShieldKDF;
 pub use kem::QShieldKEM;
-pub use protocol::{QShieldHandshake, QShieldMessage};
+pub use protocol::{QShieldEnvelope, QShieldHandshake, QShieldMessage};
+pub use self_test::self_test;
 pub use sign::QShieldSign;
 pub use symmetric::QuantumShield;
 
@@ -63,7 +71,12 @@ pub use symmetric::QuantumShield;
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Protocol version for cryptographic agility
-pub const PROTOCOL_VERSION: u8 = 1;
+///
+/// Version 2 added hello timestamps to the handshake wire format and
+/// transcript. Version 3 added the clear counter to message frames, bound
+/// with the session ID as message AAD, and encrypts the ServerFinished
+/// confirmation with the session ID as AAD.
+pub const PROTOCOL_VERSION: u8 = 3;
 
 /// Supported algorithm suite identifiers
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
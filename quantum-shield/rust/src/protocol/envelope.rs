@@ -0,0 +1,588 @@
+//! QShieldEnvelope - Multi-Recipient Envelope Format
+//!
+//! A payload is encrypted once under a random content key, and that key is
+//! wrapped separately for each recipient. The container layout is shared with
+//! the WASM SDK's `QShieldEnvelope`, so either SDK can parse what the other
+//! produced:
+//!
+//! ```text
+//! | Version (1) | Suite (1) | Count (2) |
+//! | Key ID (32) | Wrapped Len (4) | Wrapped Key |   (Count times)
+//! | Payload Len (4) | Payload |
+//! ```
+//!
+//! All integers are little-endian. A key ID is SHA3-256 over the recipient's
+//! raw `x25519 || ml_kem` public key, which is the WASM recipient ID as well.
+//!
+//! The suite byte names the construction that wrapped the keys and encrypted
+//! the payload. Both SDKs seal with the shared `Hybrid` suite, built only from
+//! primitives each of them has:
+//!
+//! ```text
+//! kek         = HKDF-SHA3-256(salt: "QShieldEnvelope-v1",
+//!                             ikm:  x25519_ss || ml_kem_ss,
+//!                             info: key_id || eph_x25519_pk || ml_kem_ct)
+//! wrapped_key = eph_x25519_pk (32) || ml_kem_ct (1088)
+//!               || ChaCha20-Poly1305(kek, zero nonce, aad: key_id, content_key)
+//!
+//! aes || chacha = HKDF-SHA3-256(salt: "QShieldEnvelope-v1", ikm: content_key,
+//!                               info: "payload")
+//! header        = everything before Payload Len: version, suite, count, and
+//!                 every key ID and length-prefixed wrapped key
+//! payload       = ChaCha20-Poly1305(chacha, zero nonce, aad: header,
+//!                   AES-256-GCM(aes, zero nonce, aad: header, plaintext))
+//! ```
+//!
+//! Every key above encrypts exactly one message, so the fixed nonces are
+//! never reused. Binding the header means a recipient list, suite or version
+//! changed after sealing fails to open. The older per-SDK suites are still
+//! opened by the SDK that produced them; their payloads are bound only to the
+//! constant `"QShieldEnvelope-v1"`.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use hkdf::Hkdf;
+use sha3::{Digest, Sha3_256};
+use zeroize::Zeroizing;
+
+use crate::error::{QShieldError, Result};
+use crate::kem::{
+    MlKem, MlKemCiphertext, QShieldKEM, QShieldKEMCiphertext, QShieldKEMPublicKey,
+    QShieldKEMSecretKey, X25519Ciphertext, X25519Kem, ML_KEM_CIPHERTEXT_SIZE,
+    X25519_PUBLIC_KEY_SIZE,
+};
+use crate::symmetric::{AesGcmCipher, ChaCha20Cipher, QuantumShield};
+use crate::utils::rng::fill_random;
+use crate::utils::serialize::{
+    read_fixed, read_length_prefixed, write_length_prefixed, Deserialize,
+};
+
+/// Envelope format version
+pub const ENVELOPE_VERSION: u8 = 0x01;
+
+/// Size of a recipient key ID
+pub const ENVELOPE_KEY_ID_SIZE: usize = 32;
+
+/// Content key size
+const CONTENT_KEY_SIZE: usize = 32;
+
+/// `Hybrid` HKDF salt, and the payload AAD of legacy `Core` envelopes
+const ENVELOPE_LABEL: &[u8] = b"QShieldEnvelope-v1";
+
+/// Nonce for `Hybrid` keys, each of which encrypts a single message
+const SINGLE_USE_NONCE: [u8; 12] = [0u8; 12];
+
+/// `Hybrid` wrapped key: ephemeral X25519 key, ML-KEM ciphertext, sealed content key
+const HYBRID_WRAPPED_KEY_SIZE: usize =
+    X25519_PUBLIC_KEY_SIZE + ML_KEM_CIPHERTEXT_SIZE + CONTENT_KEY_SIZE + 16;
+
+/// Smallest encoding of one recipient: key ID and an empty wrapped key
+const MIN_RECIPIENT_SIZE: usize = ENVELOPE_KEY_ID_SIZE + 4;
+
+/// Bounds enforced while decoding untrusted envelopes
+#[derive(Debug, Clone)]
+pub struct EnvelopeLimits {
+    /// Most recipients an envelope may list
+    pub max_recipients: usize,
+    /// Largest encoded envelope accepted, in bytes
+    pub max_size: usize,
+}
+
+impl Default for EnvelopeLimits {
+    fn default() -> Self {
+        Self {
+            max_recipients: 1024,
+            max_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Construction used to wrap content keys and encrypt the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EnvelopeSuite {
+    /// `QShieldKEM` key wrapping with `QuantumShield` encryption (this crate)
+    Core = 0x01,
+    /// `QShieldHybridKEM::seal` key wrapping with `QShieldCipher` (WASM SDK)
+    Wasm = 0x02,
+    /// X25519 + ML-KEM-768 key wrapping with an AES-GCM/ChaCha20 cascade,
+    /// implemented by both SDKs
+    Hybrid = 0x03,
+}
+
+impl TryFrom<u8> for EnvelopeSuite {
+    type Error = QShieldError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0x01 => Ok(Self::Core),
+            0x02 => Ok(Self::Wasm),
+            0x03 => Ok(Self::Hybrid),
+            _ => Err(QShieldError::ParseError),
+        }
+    }
+}
+
+/// A content key wrapped for one recipient
+#[derive(Clone)]
+pub struct EnvelopeRecipient {
+    /// SHA3-256 of the recipient's raw public key
+    pub key_id: [u8; ENVELOPE_KEY_ID_SIZE],
+    /// Wrapped content key, opaque outside its suite
+    pub wrapped_key: Vec<u8>,
+}
+
+/// Multi-recipient encrypted envelope
+#[derive(Clone)]
+pub struct QShieldEnvelope {
+    suite: EnvelopeSuite,
+    recipients: Vec<EnvelopeRecipient>,
+    payload: Vec<u8>,
+}
+
+impl QShieldEnvelope {
+    /// Key ID identifying `public_key` in an envelope
+    pub fn key_id(public_key: &QShieldKEMPublicKey) -> [u8; ENVELOPE_KEY_ID_SIZE] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(public_key.x25519.as_bytes());
+        hasher.update(public_key.ml_kem.as_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Encrypt `plaintext` once and wrap the content key for each recipient
+    ///
+    /// Envelopes are sealed with the `Hybrid` suite, so the WASM SDK can open
+    /// them too.
+    ///
+    /// Fails with `EncryptionFailed` for zero or more than `u16::MAX` recipients.
+    pub fn seal(recipients: &[&QShieldKEMPublicKey], plaintext: &[u8]) -> Result<Self> {
+        if recipients.is_empty() || recipients.len() > u16::MAX as usize {
+            return Err(QShieldError::EncryptionFailed);
+        }
+
+        let mut content_key = Zeroizing::new([0u8; CONTENT_KEY_SIZE]);
+        fill_random(&mut *content_key)?;
+
+        let recipients = recipients
+            .iter()
+            .map(|public_key| {
+                let key_id = Self::key_id(public_key);
+                let wrapped_key = hybrid_wrap(public_key, &key_id, &content_key)?;
+                Ok(EnvelopeRecipient { key_id, wrapped_key })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut envelope = Self {
+            suite: EnvelopeSuite::Hybrid,
+            recipients,
+            payload: Vec::new(),
+        };
+        envelope.payload =
+            hybrid_encrypt_payload(&content_key[..], plaintext, &envelope.encode_header())?;
+        Ok(envelope)
+    }
+
+    /// Decrypt the payload as the holder of `public_key` and `secret_key`
+    ///
+    /// Opens `Hybrid` envelopes from either SDK and `Core` envelopes from
+    /// older versions of this crate. Returns `NotSupported` for the WASM-only
+    /// suite and `DecryptionFailed` if the key pair is not a recipient, or if
+    /// a `Hybrid` envelope's header was altered after sealing.
+    pub fn open(
+        &self,
+        public_key: &QShieldKEMPublicKey,
+        secret_key: &QShieldKEMSecretKey,
+    ) -> Result<Vec<u8>> {
+        let key_id = Self::key_id(public_key);
+        let recipient = || {
+            self.recipients
+                .iter()
+                .find(|recipient| recipient.key_id == key_id)
+                .ok_or(QShieldError::DecryptionFailed)
+        };
+
+        match self.suite {
+            EnvelopeSuite::Hybrid => {
+                let content_key = hybrid_unwrap(secret_key, &key_id, &recipient()?.wrapped_key)?;
+                hybrid_decrypt_payload(&content_key, &self.payload, &self.encode_header())
+            }
+            EnvelopeSuite::Core => {
+                let recipient = recipient()?;
+                let mut offset = 0;
+                let kem_ciphertext = read_length_prefixed(&recipient.wrapped_key, &mut offset)?;
+                let ciphertext = QShieldKEMCiphertext::deserialize(&kem_ciphertext)?;
+                let shared_secret = QShieldKEM::decapsulate(secret_key, &ciphertext)?;
+
+                let content_key = Zeroizing::new(
+                    QuantumShield::new(&shared_secret)?
+                        .decrypt_with_aad(&recipient.wrapped_key[offset..], &key_id)?,
+                );
+                QuantumShield::new(&content_key)?.decrypt_with_aad(&self.payload, ENVELOPE_LABEL)
+            }
+            EnvelopeSuite::Wasm => Err(QShieldError::NotSupported),
+        }
+    }
+
+    /// Suite the envelope was sealed with
+    pub fn suite(&self) -> EnvelopeSuite {
+        self.suite
+    }
+
+    /// Wrapped keys, in the order they were sealed
+    pub fn recipients(&self) -> &[EnvelopeRecipient] {
+        &self.recipients
+    }
+
+    /// Encrypted payload
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Encode to the shared wire format
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = self.encode_header();
+        write_length_prefixed(&self.payload, &mut buf);
+        buf
+    }
+
+    /// Everything before the payload, authenticated as `Hybrid` payload AAD
+    fn encode_header(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.push(ENVELOPE_VERSION);
+        buf.push(self.suite as u8);
+        buf.extend_from_slice(&(self.recipients.len() as u16).to_le_bytes());
+        for recipient in &self.recipients {
+            buf.extend_from_slice(&recipient.key_id);
+            write_length_prefixed(&recipient.wrapped_key, &mut buf);
+        }
+        buf
+    }
+
+    /// Decode from the shared wire format with the default `EnvelopeLimits`
+    ///
+    /// The input must be consumed exactly and list at least one recipient.
+    /// Envelopes of either suite decode; `open` checks the suite.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Self::decode_with_limits(data, &EnvelopeLimits::default())
+    }
+
+    /// Decode from the shared wire format, applying `limits`
+    ///
+    /// The size limit and the recipient count are checked before anything is
+    /// allocated, and the count must fit in the bytes actually present.
+    pub fn decode_with_limits(data: &[u8], limits: &EnvelopeLimits) -> Result<Self> {
+        if data.len() > limits.max_size {
+            return Err(QShieldError::ParseError);
+        }
+
+        let mut offset = 0;
+
+        let [version, suite] = read_fixed::<2>(data, &mut offset)
+            .map_err(|_| QShieldError::ParseError)?;
+        if version != ENVELOPE_VERSION {
+            return Err(QShieldError::VersionMismatch {
+                expected: ENVELOPE_VERSION,
+                actual: version,
+            });
+        }
+        let suite = EnvelopeSuite::try_from(suite)?;
+
+        let count = u16::from_le_bytes(
+            read_fixed::<2>(data, &mut offset).map_err(|_| QShieldError::ParseError)?,
+        ) as usize;
+        if count == 0 || count > limits.max_recipients {
+            return Err(QShieldError::ParseError);
+        }
+        if count * MIN_RECIPIENT_SIZE > data.len() - offset {
+            return Err(QShieldError::ParseError);
+        }
+
+        let mut recipients = Vec::with_capacity(count);
+        for _ in 0..count {
+            let key_id = read_fixed(data, &mut offset).map_err(|_| QShieldError::ParseError)?;
+            let wrapped_key = read_length_prefixed(data, &mut offset)?;
+            recipients.push(EnvelopeRecipient { key_id, wrapped_key });
+        }
+
+        let payload = read_length_prefixed(data, &mut offset)?;
+        if offset != data.len() {
+            return Err(QShieldError::ParseError);
+        }
+
+        Ok(Self {
+            suite,
+            recipients,
+            payload,
+        })
+    }
+}
+
+/// `Hybrid` key-encryption key for one recipient
+fn hybrid_kek(
+    x25519_shared: &[u8],
+    ml_kem_shared: &[u8],
+    key_id: &[u8],
+    ephemeral_public: &[u8],
+    ml_kem_ciphertext: &[u8],
+) -> Result<Zeroizing<[u8; CONTENT_KEY_SIZE]>> {
+    let mut ikm = Zeroizing::new([0u8; 64]);
+    ikm[..32].copy_from_slice(x25519_shared);
+    ikm[32..].copy_from_slice(ml_kem_shared);
+
+    let mut kek = Zeroizing::new([0u8; CONTENT_KEY_SIZE]);
+    Hkdf::<Sha3_256>::new(Some(ENVELOPE_LABEL), &*ikm)
+        .expand_multi_info(&[key_id, ephemeral_public, ml_kem_ciphertext], &mut *kek)
+        .map_err(|_| QShieldError::KeyDerivationFailed)?;
+    Ok(kek)
+}
+
+/// Wrap `content_key` for one recipient under the `Hybrid` suite
+fn hybrid_wrap(
+    public_key: &QShieldKEMPublicKey,
+    key_id: &[u8],
+    content_key: &[u8; CONTENT_KEY_SIZE],
+) -> Result<Vec<u8>> {
+    let (x25519_ct, x25519_ss) = X25519Kem::encapsulate(&public_key.x25519)?;
+    let (ml_kem_ct, ml_kem_ss) = MlKem::encapsulate(&public_key.ml_kem)?;
+    let ml_kem_ct = ml_kem_ct.as_bytes();
+
+    let kek = hybrid_kek(
+        x25519_ss.as_bytes(),
+        ml_kem_ss.as_bytes(),
+        key_id,
+        x25519_ct.as_bytes(),
+        &ml_kem_ct,
+    )?;
+    let sealed_key = ChaCha20Cipher::new(&*kek)?.encrypt_with_nonce(
+        content_key,
+        &SINGLE_USE_NONCE,
+        Some(key_id),
+    )?;
+
+    let mut wrapped_key = Vec::with_capacity(HYBRID_WRAPPED_KEY_SIZE);
+    wrapped_key.extend_from_slice(x25519_ct.as_bytes());
+    wrapped_key.extend_from_slice(&ml_kem_ct);
+    wrapped_key.extend_from_slice(&sealed_key);
+    Ok(wrapped_key)
+}
+
+/// Recover the content key from a `Hybrid` wrapped key
+fn hybrid_unwrap(
+    secret_key: &QShieldKEMSecretKey,
+    key_id: &[u8],
+    wrapped_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+    if wrapped_key.len() != HYBRID_WRAPPED_KEY_SIZE {
+        return Err(QShieldError::InvalidCiphertext);
+    }
+    let (ephemeral_public, rest) = wrapped_key.split_at(X25519_PUBLIC_KEY_SIZE);
+    let (ml_kem_ct, sealed_key) = rest.split_at(ML_KEM_CIPHERTEXT_SIZE);
+
+    let x25519_ct = X25519Ciphertext::from_bytes(ephemeral_public)?;
+    let x25519_ss = X25519Kem::decapsulate(&secret_key.x25519, &x25519_ct)?;
+    let ml_kem_ss =
+        MlKem::decapsulate(&secret_key.ml_kem, &MlKemCiphertext::from_bytes(ml_kem_ct)?)?;
+
+    let kek = hybrid_kek(
+        x25519_ss.as_bytes(),
+        ml_kem_ss.as_bytes(),
+        key_id,
+        ephemeral_public,
+        ml_kem_ct,
+    )?;
+    ChaCha20Cipher::new(&*kek)?
+        .decrypt_with_nonce(sealed_key, &SINGLE_USE_NONCE, Some(key_id))
+        .map(Zeroizing::new)
+}
+
+/// AES-256-GCM and ChaCha20-Poly1305 keys for a `Hybrid` payload
+fn hybrid_payload_ciphers(content_key: &[u8]) -> Result<(AesGcmCipher, ChaCha20Cipher)> {
+    let mut keys = Zeroizing::new([0u8; 64]);
+    Hkdf::<Sha3_256>::new(Some(ENVELOPE_LABEL), content_key)
+        .expand(b"payload", &mut *keys)
+        .map_err(|_| QShieldError::KeyDerivationFailed)?;
+    Ok((AesGcmCipher::new(&keys[..32])?, ChaCha20Cipher::new(&keys[32..])?))
+}
+
+fn hybrid_encrypt_payload(content_key: &[u8], plaintext: &[u8], header: &[u8]) -> Result<Vec<u8>> {
+    let (aes, chacha) = hybrid_payload_ciphers(content_key)?;
+    let inner = aes.encrypt_with_nonce(plaintext, &SINGLE_USE_NONCE, Some(header))?;
+    chacha.encrypt_with_nonce(&inner, &SINGLE_USE_NONCE, Some(header))
+}
+
+fn hybrid_decrypt_payload(content_key: &[u8], payload: &[u8], header: &[u8]) -> Result<Vec<u8>> {
+    let (aes, chacha) = hybrid_payload_ciphers(content_key)?;
+    let inner = chacha.decrypt_with_nonce(payload, &SINGLE_USE_NONCE, Some(header))?;
+    aes.decrypt_with_nonce(&inner, &SINGLE_USE_NONCE, Some(header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kem::{MlKemPublicKey, MlKemSecretKey, X25519PublicKey, X25519SecretKey};
+
+    /// Two-recipient envelope shared with the WASM SDK's tests
+    fn test_vector() -> Vec<u8> {
+        [
+            &[0x01, 0x02, 0x02, 0x00][..],
+            &[0x11; 32],
+            &[0x02, 0x00, 0x00, 0x00, 0xaa, 0xbb],
+            &[0x22; 32],
+            &[0x01, 0x00, 0x00, 0x00, 0xcc],
+            &[0x03, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_envelope_two_recipients() {
+        let (alice_pk, alice_sk) = QShieldKEM::generate_keypair().unwrap();
+        let (bob_pk, bob_sk) = QShieldKEM::generate_keypair().unwrap();
+        let (carol_pk, carol_sk) = QShieldKEM::generate_keypair().unwrap();
+
+        let envelope = QShieldEnvelope::seal(&[&alice_pk, &bob_pk], b"for alice and bob").unwrap();
+        let decoded = QShieldEnvelope::decode(&envelope.encode()).unwrap();
+        assert_eq!(decoded.suite(), EnvelopeSuite::Hybrid);
+        assert_eq!(decoded.recipients().len(), 2);
+        assert_eq!(decoded.recipients()[0].key_id, QShieldEnvelope::key_id(&alice_pk));
+
+        assert_eq!(decoded.open(&alice_pk, &alice_sk).unwrap(), b"for alice and bob");
+        assert_eq!(decoded.open(&bob_pk, &bob_sk).unwrap(), b"for alice and bob");
+        assert!(matches!(
+            decoded.open(&carol_pk, &carol_sk),
+            Err(QShieldError::DecryptionFailed)
+        ));
+
+        // The header is authenticated: dropping a recipient breaks the payload
+        let mut dropped = decoded.clone();
+        dropped.recipients.pop();
+        assert!(matches!(
+            dropped.open(&alice_pk, &alice_sk),
+            Err(QShieldError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_hybrid_vector() {
+        // Also opened by the WASM SDK's tests
+        let public_key = include_bytes!("../../../test-vectors/envelope/recipient_public_key.bin");
+        let secret_key = include_bytes!("../../../test-vectors/envelope/recipient_secret_key.bin");
+        let envelope = include_bytes!("../../../test-vectors/envelope/hybrid_envelope.bin");
+
+        let public_key = QShieldKEMPublicKey {
+            x25519: X25519PublicKey::from_bytes(&public_key[..32]).unwrap(),
+            ml_kem: MlKemPublicKey::from_bytes(&public_key[32..]).unwrap(),
+        };
+        let secret_key = QShieldKEMSecretKey {
+            x25519: X25519SecretKey::from_bytes(&secret_key[..32]).unwrap(),
+            ml_kem: MlKemSecretKey::from_bytes(&secret_key[32..]).unwrap(),
+        };
+
+        let decoded = QShieldEnvelope::decode(envelope).unwrap();
+        assert_eq!(decoded.suite(), EnvelopeSuite::Hybrid);
+        assert_eq!(decoded.recipients()[0].wrapped_key.len(), HYBRID_WRAPPED_KEY_SIZE);
+        assert_eq!(decoded.open(&public_key, &secret_key).unwrap(), b"sealed by the core crate");
+
+        let mut tampered = decoded.clone();
+        tampered.payload[0] ^= 1;
+        assert!(tampered.open(&public_key, &secret_key).is_err());
+    }
+
+    #[test]
+    fn test_envelope_wire_format() {
+        let envelope = QShieldEnvelope {
+            suite: EnvelopeSuite::Wasm,
+            recipients: vec![
+                EnvelopeRecipient { key_id: [0x11; 32], wrapped_key: vec![0xaa, 0xbb] },
+                EnvelopeRecipient { key_id: [0x22; 32], wrapped_key: vec![0xcc] },
+            ],
+            payload: vec![0x01, 0x02, 0x03],
+        };
+        assert_eq!(envelope.encode(), test_vector());
+
+        let decoded = QShieldEnvelope::decode(&test_vector()).unwrap();
+        assert_eq!(decoded.suite(), EnvelopeSuite::Wasm);
+        assert_eq!(decoded.recipients()[1].wrapped_key, vec![0xcc]);
+        assert_eq!(decoded.payload(), &[0x01, 0x02, 0x03]);
+
+        // Parsed, but sealed by the WASM SDK's construction
+        let (pk, sk) = QShieldKEM::generate_keypair().unwrap();
+        assert!(matches!(decoded.open(&pk, &sk), Err(QShieldError::NotSupported)));
+    }
+
+    #[test]
+    fn test_envelope_malformed() {
+        let vector = test_vector();
+        for len in 0..vector.len() {
+            assert!(QShieldEnvelope::decode(&vector[..len]).is_err());
+        }
+
+        let mut trailing = vector.clone();
+        trailing.push(0);
+        assert!(QShieldEnvelope::decode(&trailing).is_err());
+
+        let mut version = vector.clone();
+        version[0] = 0x02;
+        assert!(matches!(
+            QShieldEnvelope::decode(&version),
+            Err(QShieldError::VersionMismatch { expected: 1, actual: 2 })
+        ));
+
+        let mut suite = vector.clone();
+        suite[1] = 0x7f;
+        assert!(QShieldEnvelope::decode(&suite).is_err());
+
+        let mut empty = vector;
+        empty[2] = 0x00;
+        assert!(QShieldEnvelope::decode(&empty).is_err());
+    }
+
+    #[test]
+    fn test_envelope_limits() {
+        let vector = test_vector();
+        let limits = EnvelopeLimits { max_recipients: 2, max_size: vector.len() };
+        assert!(QShieldEnvelope::decode_with_limits(&vector, &limits).is_ok());
+
+        let one_recipient = EnvelopeLimits { max_recipients: 1, ..limits.clone() };
+        assert!(matches!(
+            QShieldEnvelope::decode_with_limits(&vector, &one_recipient),
+            Err(QShieldError::ParseError)
+        ));
+
+        let too_small = EnvelopeLimits { max_size: vector.len() - 1, ..limits };
+        assert!(matches!(
+            QShieldEnvelope::decode_with_limits(&vector, &too_small),
+            Err(QShieldError::ParseError)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_huge_recipient_count() {
+        // Claims 65535 recipients in a few bytes
+        let mut header = vec![0x01, 0x01, 0xff, 0xff];
+        header.extend_from_slice(&[0u8; 40]);
+        assert!(matches!(QShieldEnvelope::decode(&header), Err(QShieldError::ParseError)));
+
+        // Still rejected when the configured limit allows it: the bytes aren't there
+        let permissive = EnvelopeLimits { max_recipients: usize::MAX, max_size: usize::MAX };
+        assert!(matches!(
+            QShieldEnvelope::decode_with_limits(&header, &permissive),
+            Err(QShieldError::ParseError)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_wrapped_length_overflow() {
+        // First recipient's wrapped key claims u32::MAX bytes
+        let mut vector = test_vector();
+        vector[4 + 32..4 + 36].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(QShieldEnvelope::decode(&vector), Err(QShieldError::ParseError)));
+
+        // Payload length running past the end
+        let mut vector = test_vector();
+        let payload_len = vector.len() - 7;
+        vector[payload_len..payload_len + 4].copy_from_slice(&0x0100_0000u32.to_le_bytes());
+        assert!(matches!(QShieldEnvelope::decode(&vector), Err(QShieldError::ParseError)));
+    }
+}
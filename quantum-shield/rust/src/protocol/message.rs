@@ -12,10 +12,20 @@ use alloc::vec::Vec;
 use crate::error::{QShieldError, Result};
 use crate::symmetric::QuantumShield;
 use crate::utils::serialize::{
-    read_length_prefixed, read_u64, write_length_prefixed, write_u64,
+    read_fixed, read_length_prefixed, read_u64, write_length_prefixed, write_u64,
     Deserialize, Header, ObjectType, Serialize,
 };
 use crate::PROTOCOL_VERSION;
+use zeroize::Zeroize;
+
+/// Largest serialized `QShieldMessage` frame accepted, header included
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Content flag: an 8-byte timestamp follows the counter
+const FLAG_TIMESTAMP: u8 = 0x01;
+
+/// Counters below the high-water mark that a `ReplayWindow` still tracks
+pub const REPLAY_WINDOW_SIZE: u64 = 64;
 
 /// Message type identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,6 +41,8 @@ pub enum MessageType {
     Heartbeat = 0x04,
     /// Error notification
     Error = 0x05,
+    /// Acknowledgement; payload is the last received counter (u64 LE)
+    Ack = 0x06,
 }
 
 impl TryFrom<u8> for MessageType {
@@ -43,6 +55,7 @@ impl TryFrom<u8> for MessageType {
             0x03 => Ok(Self::KeyUpdate),
             0x04 => Ok(Self::Heartbeat),
             0x05 => Ok(Self::Error),
+            0x06 => Ok(Self::Ack),
             _ => Err(QShieldError::ParseError),
         }
     }
@@ -112,9 +125,34 @@ impl MessageContent {
         }
     }
 
+    /// Create an acknowledgement of everything up to `last_received`
+    pub fn ack(counter: u64, last_received: u64) -> Self {
+        Self {
+            message_type: MessageType::Ack,
+            counter,
+            timestamp: None,
+            payload: last_received.to_le_bytes().to_vec(),
+        }
+    }
+
+    /// The acknowledged counter carried by an `Ack` message
+    ///
+    /// Returns `ParseError` for any other message type or a malformed payload.
+    pub fn ack_last_received(&self) -> Result<u64> {
+        if self.message_type != MessageType::Ack {
+            return Err(QShieldError::ParseError);
+        }
+        let bytes: [u8; 8] = self
+            .payload
+            .as_slice()
+            .try_into()
+            .map_err(|_| QShieldError::ParseError)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
     /// Serialize to bytes (for encryption)
     fn to_bytes(&self) -> Vec<u8> {
-        let flags: u8 = if self.timestamp.is_some() { 0x01 } else { 0x00 };
+        let flags: u8 = if self.timestamp.is_some() { FLAG_TIMESTAMP } else { 0x00 };
 
         let mut buf = Vec::new();
         buf.push(self.message_type as u8);
@@ -132,54 +170,43 @@ impl MessageContent {
     }
 
     /// Deserialize from bytes
+    ///
+    /// The frame must be consumed exactly, carry no unknown flags, and have a
+    /// payload shaped for its message type: control messages are empty and an
+    /// `Ack` carries exactly one counter.
     fn from_bytes(data: &[u8]) -> Result<Self> {
-        if data.len() < 10 {
+        let mut offset = 0;
+
+        let [type_byte, flags] = read_fixed::<2>(data, &mut offset)
+            .map_err(|_| QShieldError::ParseError)?;
+        let message_type = MessageType::try_from(type_byte)?;
+        if flags & !FLAG_TIMESTAMP != 0 {
             return Err(QShieldError::ParseError);
         }
 
-        let message_type = MessageType::try_from(data[0])?;
-        let flags = data[1];
-        let counter = u64::from_le_bytes([
-            data[2], data[3], data[4], data[5], data[6], data[7], data[8], data[9],
-        ]);
+        let counter = read_u64(data, &mut offset).map_err(|_| QShieldError::ParseError)?;
 
-        let mut offset = 10;
-
-        let timestamp = if flags & 0x01 != 0 {
-            if offset + 8 > data.len() {
-                return Err(QShieldError::ParseError);
-            }
-            let ts = u64::from_le_bytes([
-                data[offset],
-                data[offset + 1],
-                data[offset + 2],
-                data[offset + 3],
-                data[offset + 4],
-                data[offset + 5],
-                data[offset + 6],
-                data[offset + 7],
-            ]);
-            offset += 8;
-            Some(ts)
+        let timestamp = if flags & FLAG_TIMESTAMP != 0 {
+            Some(read_u64(data, &mut offset).map_err(|_| QShieldError::ParseError)?)
         } else {
             None
         };
 
-        if offset + 4 > data.len() {
+        let payload = read_length_prefixed(data, &mut offset)?;
+        if offset != data.len() {
             return Err(QShieldError::ParseError);
         }
-        let payload_len = u32::from_le_bytes([
-            data[offset],
-            data[offset + 1],
-            data[offset + 2],
-            data[offset + 3],
-        ]) as usize;
-        offset += 4;
-
-        if offset + payload_len > data.len() {
+
+        let payload_ok = match message_type {
+            MessageType::Data | MessageType::Error => true,
+            MessageType::Close | MessageType::KeyUpdate | MessageType::Heartbeat => {
+                payload.is_empty()
+            }
+            MessageType::Ack => payload.len() == 8,
+        };
+        if !payload_ok {
             return Err(QShieldError::ParseError);
         }
-        let payload = data[offset..offset + payload_len].to_vec();
 
         Ok(Self {
             message_type,
@@ -197,12 +224,17 @@ pub struct QShieldMessage {
     pub version: u8,
     /// Session ID (for multiplexing)
     pub session_id: [u8; 16],
+    /// Counter of the encrypted content, authenticated as AAD
+    pub counter: u64,
     /// Encrypted content
     pub encrypted: Vec<u8>,
 }
 
 impl QShieldMessage {
     /// Create a new message by encrypting content
+    ///
+    /// The session ID and the content's counter are bound as AAD, so the
+    /// ciphertext cannot be moved into another session or counter slot.
     pub fn seal(
         cipher: &QuantumShield,
         session_id: &[u8; 16],
@@ -210,17 +242,21 @@ impl QShieldMessage {
     ) -> Result<Self> {
         let plaintext = content.to_bytes();
 
-        // Use session_id as AAD for additional binding
-        let encrypted = cipher.encrypt_with_aad(&plaintext, session_id)?;
+        let encrypted =
+            cipher.encrypt_with_aad(&plaintext, &message_aad(session_id, content.counter))?;
 
         Ok(Self {
             version: PROTOCOL_VERSION,
             session_id: *session_id,
+            counter: content.counter,
             encrypted,
         })
     }
 
     /// Decrypt and verify message content
+    ///
+    /// Fails with `AuthenticationFailed` if the counter in the frame does not
+    /// match the one that was encrypted.
     pub fn open(&self, cipher: &QuantumShield) -> Result<MessageContent> {
         if self.version != PROTOCOL_VERSION {
             return Err(QShieldError::VersionMismatch {
@@ -229,10 +265,14 @@ impl QShieldMessage {
             });
         }
 
-        // Decrypt with session_id as AAD
-        let plaintext = cipher.decrypt_with_aad(&self.encrypted, &self.session_id)?;
+        let aad = message_aad(&self.session_id, self.counter);
+        let plaintext = cipher.decrypt_with_aad(&self.encrypted, &aad)?;
 
-        MessageContent::from_bytes(&plaintext)
+        let content = MessageContent::from_bytes(&plaintext)?;
+        if content.counter != self.counter {
+            return Err(QShieldError::AuthenticationFailed);
+        }
+        Ok(content)
     }
 
     /// Get the truncated session ID for display
@@ -243,15 +283,24 @@ impl QShieldMessage {
     }
 }
 
+/// AAD for a message: `session_id || counter` (u64 LE)
+fn message_aad(session_id: &[u8; 16], counter: u64) -> [u8; 24] {
+    let mut aad = [0u8; 24];
+    aad[..16].copy_from_slice(session_id);
+    aad[16..].copy_from_slice(&counter.to_le_bytes());
+    aad
+}
+
 impl Serialize for QShieldMessage {
     fn serialize(&self) -> Result<Vec<u8>> {
-        let payload_size = 1 + 16 + 4 + self.encrypted.len();
+        let payload_size = 1 + 16 + 8 + 4 + self.encrypted.len();
         let header = Header::new(ObjectType::EncryptedMessage, payload_size);
 
         let mut buf = Vec::with_capacity(Header::SIZE + payload_size);
         buf.extend_from_slice(&header.to_bytes());
         buf.push(self.version);
         buf.extend_from_slice(&self.session_id);
+        write_u64(self.counter, &mut buf);
         write_length_prefixed(&self.encrypted, &mut buf);
 
         Ok(buf)
@@ -259,37 +308,111 @@ impl Serialize for QShieldMessage {
 }
 
 impl Deserialize for QShieldMessage {
+    /// Parse an untrusted frame
+    ///
+    /// Frames over `MAX_MESSAGE_SIZE`, or whose header or length prefix
+    /// disagrees with the bytes actually present, are rejected with
+    /// `ParseError` before anything is allocated. A frame from another
+    /// protocol version is rejected with `VersionMismatch` before the rest is
+    /// parsed, since its layout may differ.
     fn deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() > MAX_MESSAGE_SIZE {
+            return Err(QShieldError::ParseError);
+        }
+
         let header = Header::from_bytes(data)?;
         if header.object_type != ObjectType::EncryptedMessage {
             return Err(QShieldError::ParseError);
         }
+        if header.payload_len as usize != data.len() - Header::SIZE {
+            return Err(QShieldError::ParseError);
+        }
 
         let mut offset = Header::SIZE;
 
-        if offset >= data.len() {
-            return Err(QShieldError::ParseError);
+        let [version] = read_fixed::<1>(data, &mut offset).map_err(|_| QShieldError::ParseError)?;
+        if version != PROTOCOL_VERSION {
+            return Err(QShieldError::VersionMismatch {
+                expected: PROTOCOL_VERSION,
+                actual: version,
+            });
         }
-        let version = data[offset];
-        offset += 1;
+        let session_id: [u8; 16] =
+            read_fixed(data, &mut offset).map_err(|_| QShieldError::ParseError)?;
+        let counter = read_u64(data, &mut offset).map_err(|_| QShieldError::ParseError)?;
 
-        if offset + 16 > data.len() {
+        let encrypted = read_length_prefixed(data, &mut offset)?;
+        if offset != data.len() {
             return Err(QShieldError::ParseError);
         }
-        let mut session_id = [0u8; 16];
-        session_id.copy_from_slice(&data[offset..offset + 16]);
-        offset += 16;
-
-        let encrypted = read_length_prefixed(data, &mut offset)?;
 
         Ok(Self {
             version,
             session_id,
+            counter,
             encrypted,
         })
     }
 }
 
+/// Sliding replay window over received message counters
+///
+/// Accepts each counter at most once, in any order, as long as it is within
+/// `REPLAY_WINDOW_SIZE` of the highest counter seen. Counters above the
+/// high-water mark always advance it.
+#[derive(Debug, Clone, Default, Zeroize)]
+pub struct ReplayWindow {
+    // Highest counter seen; only meaningful once `seen` is non-zero
+    highest: u64,
+    // Bit i set: counter `highest - i` has been seen
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// Create an empty window
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check that `counter` is new and inside the window, without recording it
+    pub fn check(&self, counter: u64) -> Result<()> {
+        if self.seen == 0 || counter > self.highest {
+            return Ok(());
+        }
+        let age = self.highest - counter;
+        if age >= REPLAY_WINDOW_SIZE || self.seen & (1 << age) != 0 {
+            return Err(QShieldError::ReplayDetected);
+        }
+        Ok(())
+    }
+
+    /// Check `counter` and record it as seen
+    ///
+    /// Only call this for authenticated messages, so a forged counter
+    /// cannot move the window.
+    pub fn accept(&mut self, counter: u64) -> Result<()> {
+        self.check(counter)?;
+
+        if self.seen == 0 {
+            self.seen = 1;
+            self.highest = counter;
+        } else if counter > self.highest {
+            let shift = counter - self.highest;
+            self.seen = if shift >= REPLAY_WINDOW_SIZE { 0 } else { self.seen << shift };
+            self.seen |= 1;
+            self.highest = counter;
+        } else {
+            self.seen |= 1 << (self.highest - counter);
+        }
+        Ok(())
+    }
+
+    /// Highest counter accepted so far, if any
+    pub fn highest(&self) -> Option<u64> {
+        (self.seen != 0).then_some(self.highest)
+    }
+}
+
 /// Message channel for send/receive with replay protection
 pub struct MessageChannel {
     cipher: QuantumShield,
@@ -297,6 +420,7 @@ pub struct MessageChannel {
     send_counter: u64,
     recv_counter: u64,
     recv_window: u64,
+    peer_acked: Option<u64>,
 }
 
 impl MessageChannel {
@@ -312,6 +436,7 @@ impl MessageChannel {
             send_counter: 0,
             recv_counter: 0,
             recv_window: 1024, // Accept messages up to 1024 ahead
+            peer_acked: None,
         }
     }
 
@@ -345,7 +470,30 @@ impl MessageChannel {
         Ok(msg)
     }
 
+    /// Send an authenticated keepalive with no payload
+    pub fn send_heartbeat(&mut self) -> Result<QShieldMessage> {
+        self.send_control(MessageType::Heartbeat)
+    }
+
+    /// Acknowledge the last message received on this channel
+    ///
+    /// Returns `NotSupported` if nothing has been received yet.
+    pub fn send_ack(&mut self) -> Result<QShieldMessage> {
+        let last_received = self
+            .recv_counter
+            .checked_sub(1)
+            .ok_or(QShieldError::NotSupported)?;
+        let content = MessageContent::ack(self.send_counter, last_received);
+        let msg = QShieldMessage::seal(&self.cipher, &self.session_id, &content)?;
+        self.send_counter += 1;
+        Ok(msg)
+    }
+
     /// Receive and verify a message
+    ///
+    /// Heartbeats and acks consume a counter like any other message, so they
+    /// are replay-protected and never leave a gap in the data stream. An ack
+    /// is only honored if it acknowledges a counter this side has sent.
     pub fn receive(&mut self, msg: &QShieldMessage) -> Result<MessageContent> {
         // Verify session ID
         if msg.session_id != self.session_id {
@@ -366,6 +514,15 @@ impl MessageChannel {
             return Err(QShieldError::AuthenticationFailed);
         }
 
+        // Validate acks before any state changes
+        if content.message_type == MessageType::Ack {
+            let last_received = content.ack_last_received()?;
+            if last_received >= self.send_counter {
+                return Err(QShieldError::AuthenticationFailed);
+            }
+            self.peer_acked = self.peer_acked.max(Some(last_received));
+        }
+
         // Update counter (sliding window would be more sophisticated)
         if content.counter >= self.recv_counter {
             self.recv_counter = content.counter + 1;
@@ -384,6 +541,11 @@ impl MessageChannel {
         self.recv_counter
     }
 
+    /// Highest counter the peer has acknowledged, if any
+    pub fn peer_acked(&self) -> Option<u64> {
+        self.peer_acked
+    }
+
     /// Get session ID
     pub fn session_id(&self) -> &[u8; 16] {
         &self.session_id
@@ -429,12 +591,35 @@ mod tests {
 
         assert_eq!(msg.version, deserialized.version);
         assert_eq!(msg.session_id, deserialized.session_id);
+        assert_eq!(msg.counter, deserialized.counter);
 
         let opened = deserialized.open(&cipher).unwrap();
         assert_eq!(opened.counter, 42);
         assert_eq!(opened.payload, b"Test payload");
     }
 
+    #[test]
+    fn test_message_bound_to_session_and_counter() {
+        let cipher = test_cipher();
+        let other_session = [0xaau8; 16];
+
+        let content = MessageContent::data(3, b"bound".to_vec());
+        let msg = QShieldMessage::seal(&cipher, &test_session_id(), &content).unwrap();
+        assert_eq!(msg.counter, 3);
+
+        // Same key and counter, but spliced into another session
+        let mut spliced = msg.clone();
+        spliced.session_id = other_session;
+        assert!(spliced.open(&cipher).is_err());
+
+        // Same session, but claiming another counter
+        let mut recounted = msg.clone();
+        recounted.counter = 4;
+        assert!(recounted.open(&cipher).is_err());
+
+        assert_eq!(msg.open(&cipher).unwrap().payload, b"bound");
+    }
+
     #[test]
     fn test_message_channel() {
         let cipher1 = test_cipher();
@@ -473,6 +658,36 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_replay_window() {
+        let mut window = ReplayWindow::new();
+        assert_eq!(window.highest(), None);
+
+        // Out of order within the window is fine
+        window.accept(100).unwrap();
+        window.accept(97).unwrap();
+        window.accept(101).unwrap();
+        window.accept(99).unwrap();
+        assert_eq!(window.highest(), Some(101));
+
+        // Duplicates are rejected, including the high-water mark itself
+        for counter in [97, 99, 100, 101] {
+            assert!(matches!(window.accept(counter), Err(QShieldError::ReplayDetected)));
+        }
+
+        // Unseen counters at the bottom edge of the window are still accepted
+        window.accept(101 - (REPLAY_WINDOW_SIZE - 1)).unwrap();
+
+        // Anything below the window is rejected, seen or not
+        assert!(matches!(
+            window.accept(101 - REPLAY_WINDOW_SIZE),
+            Err(QShieldError::ReplayDetected)
+        ));
+        window.accept(1000).unwrap();
+        assert!(window.accept(98).is_err());
+        assert!(window.check(1000 - (REPLAY_WINDOW_SIZE - 1)).is_ok());
+    }
+
     #[test]
     fn test_wrong_session_id() {
         let cipher1 = test_cipher();
@@ -522,4 +737,147 @@ mod tests {
         let content = receiver.receive(&close).unwrap();
         assert_eq!(content.message_type, MessageType::Close);
     }
+
+    #[test]
+    fn test_heartbeat_and_ack_keep_data_in_sequence() {
+        let session_id = [0u8; 32];
+        let mut alice = MessageChannel::new(test_cipher(), session_id);
+        let mut bob = MessageChannel::new(test_cipher(), session_id);
+
+        // Nothing to acknowledge yet
+        assert!(bob.send_ack().is_err());
+
+        let first = alice.send(b"first").unwrap();
+        let heartbeat = alice.send_heartbeat().unwrap();
+        let second = alice.send(b"second").unwrap();
+
+        assert_eq!(bob.receive(&first).unwrap().payload, b"first");
+        let content = bob.receive(&heartbeat).unwrap();
+        assert_eq!(content.message_type, MessageType::Heartbeat);
+        assert!(content.payload.is_empty());
+        assert_eq!(bob.receive(&second).unwrap().payload, b"second");
+
+        // Bob acks everything; Alice learns the last counter Bob saw
+        let ack = bob.send_ack().unwrap();
+        let content = alice.receive(&ack).unwrap();
+        assert_eq!(content.ack_last_received().unwrap(), 2);
+        assert_eq!(alice.peer_acked(), Some(2));
+
+        // Data continues normally in both directions after the control traffic
+        let reply = bob.send(b"reply").unwrap();
+        assert_eq!(alice.receive(&reply).unwrap().payload, b"reply");
+        let third = alice.send(b"third").unwrap();
+        assert_eq!(bob.receive(&third).unwrap().payload, b"third");
+
+        // A replayed ack is rejected like any other replay
+        assert!(alice.receive(&ack).is_err());
+    }
+
+    #[test]
+    fn test_ack_for_unsent_counter_rejected() {
+        let session_id = [0u8; 32];
+        let mut alice = MessageChannel::new(test_cipher(), session_id);
+        let cipher = test_cipher();
+        let short_id: [u8; 16] = session_id[..16].try_into().unwrap();
+
+        // Alice has sent nothing, so an ack of counter 5 is bogus
+        let bogus = QShieldMessage::seal(&cipher, &short_id, &MessageContent::ack(0, 5)).unwrap();
+        assert!(alice.receive(&bogus).is_err());
+        assert_eq!(alice.recv_counter(), 0);
+        assert_eq!(alice.peer_acked(), None);
+    }
+
+    fn serialized_message() -> Vec<u8> {
+        let content = MessageContent::data(7, b"frame".to_vec());
+        let msg = QShieldMessage::seal(&test_cipher(), &test_session_id(), &content).unwrap();
+        msg.serialize().unwrap()
+    }
+
+    #[test]
+    fn test_unknown_message_type_rejected() {
+        let mut plaintext = MessageContent::data(0, b"x".to_vec()).to_bytes();
+        assert!(MessageContent::from_bytes(&plaintext).is_ok());
+
+        plaintext[0] = 0x7f;
+        assert!(matches!(
+            MessageContent::from_bytes(&plaintext),
+            Err(QShieldError::ParseError)
+        ));
+
+        // Unknown flag bits and mis-shaped control payloads are malformed too
+        let mut flagged = MessageContent::heartbeat(0).to_bytes();
+        flagged[1] = 0x80;
+        assert!(MessageContent::from_bytes(&flagged).is_err());
+
+        let mut heartbeat = MessageContent::heartbeat(0);
+        heartbeat.payload = vec![0u8; 4];
+        assert!(MessageContent::from_bytes(&heartbeat.to_bytes()).is_err());
+
+        let mut ack = MessageContent::ack(0, 1);
+        ack.payload.push(0);
+        assert!(MessageContent::from_bytes(&ack.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_oversized_length_prefix_rejected() {
+        // Inner payload length pointing far past the end of the plaintext
+        let mut plaintext = MessageContent::data(0, b"abc".to_vec()).to_bytes();
+        plaintext[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            MessageContent::from_bytes(&plaintext),
+            Err(QShieldError::ParseError)
+        ));
+
+        // Outer ciphertext length prefix
+        let mut frame = serialized_message();
+        let prefix = Header::SIZE + 1 + 16 + 8;
+        frame[prefix..prefix + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            QShieldMessage::deserialize(&frame),
+            Err(QShieldError::ParseError)
+        ));
+
+        // Header claiming more payload than the frame holds
+        let mut frame = serialized_message();
+        frame[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            QShieldMessage::deserialize(&frame),
+            Err(QShieldError::ParseError)
+        ));
+    }
+
+    #[test]
+    fn test_old_version_frame_rejected() {
+        // A version 2 frame: no clear counter after the session ID
+        let mut frame = serialized_message();
+        let counter = Header::SIZE + 1 + 16;
+        frame.drain(counter..counter + 8);
+        frame[Header::SIZE] = 2;
+        let header = Header::new(ObjectType::EncryptedMessage, frame.len() - Header::SIZE);
+        frame[..Header::SIZE].copy_from_slice(&header.to_bytes());
+
+        assert!(matches!(
+            QShieldMessage::deserialize(&frame),
+            Err(QShieldError::VersionMismatch { expected: PROTOCOL_VERSION, actual: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_frame_rejected() {
+        let frame = serialized_message();
+        assert!(QShieldMessage::deserialize(&frame).is_ok());
+
+        for len in 0..frame.len() {
+            assert!(QShieldMessage::deserialize(&frame[..len]).is_err());
+        }
+
+        let mut extended = frame;
+        extended.push(0);
+        assert!(QShieldMessage::deserialize(&extended).is_err());
+
+        let plaintext = MessageContent::data_with_timestamp(0, 1, b"abc".to_vec()).to_bytes();
+        for len in 0..plaintext.len() {
+            assert!(MessageContent::from_bytes(&plaintext[..len]).is_err());
+        }
+    }
 }
@@ -7,19 +7,32 @@
 //!
 //! 1. **Handshake**: Authenticated key exchange using QShieldKEM + QShieldSign
 //! 2. **Message Format**: Encrypted messages with authentication and replay protection
+//! 3. **Envelope**: One payload encrypted to many recipients, in a format shared with the WASM SDK
 //!
 //! ## Security Properties
 //!
 //! - Forward secrecy via ephemeral key exchange
 //! - Mutual authentication via dual signatures
-//! - Replay protection via message counters
+//! - Replay protection via message counters, hello timestamps and nonces
 //! - Integrity via authenticated encryption
 
+mod envelope;
 mod handshake;
 mod message;
 
+pub use envelope::{
+    EnvelopeLimits, EnvelopeRecipient, EnvelopeSuite, QShieldEnvelope, ENVELOPE_KEY_ID_SIZE,
+    ENVELOPE_VERSION,
+};
+
 pub use handshake::{
     QShieldHandshake, HandshakeState, HandshakeRole,
     ClientHello, ServerHello, ClientFinished, ServerFinished, EstablishedSession,
+    HANDSHAKE_MAX_AGE_SECONDS,
+};
+#[cfg(feature = "std")]
+pub use handshake::HelloReplayCache;
+pub use message::{
+    QShieldMessage, MessageType, MessageContent, MessageChannel, ReplayWindow, MAX_MESSAGE_SIZE,
+    REPLAY_WINDOW_SIZE,
 };
-pub use message::{QShieldMessage, MessageType, MessageContent, MessageChannel};
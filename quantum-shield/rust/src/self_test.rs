@@ -0,0 +1,172 @@
+//! Power-on self-test
+//!
+//! Runs every primitive once with fixed inputs and checks the results, in the
+//! style of a FIPS 140 power-on self-test. Call [`self_test`] at startup and
+//! refuse to serve traffic if it fails:
+//!
+//! ```rust,ignore
+//! quantum_shield::self_test().expect("QuantumShield self-test failed");
+//! ```
+//!
+//! Every primitive gets a known-answer test against vectors stored in
+//! `src/self_test/`: KDF output, KEM decapsulation, signature verification,
+//! and deterministic cascade encryption. Key generation, KEM encapsulation,
+//! and signing are randomized, so they also get pairwise-consistency checks:
+//! a round trip must succeed and a tampered input must be rejected.
+
+use crate::error::{QShieldError, Result};
+use crate::kdf::QShieldKDF;
+use crate::kem::{QShieldKEM, QShieldKEMCiphertext, QShieldKEMSecretKey};
+use crate::sign::{QShieldSign, QShieldSignPublicKey, QShieldSignature};
+use crate::symmetric::QuantumShield;
+use crate::utils::serialize::Deserialize;
+
+const KDF_SECRET: [u8; 32] = [0x0b; 32];
+
+/// `expand_label(0x0b * 32, "key", "", 32)`
+const KDF_EXPECTED: [u8; 32] = [
+    0xdb, 0x0e, 0x40, 0x8d, 0xd9, 0xc8, 0x39, 0xdc, 0xf4, 0x2d, 0x7e, 0x55, 0xa3, 0xb0, 0xa1, 0x78,
+    0x90, 0x47, 0x67, 0x4a, 0x3f, 0x21, 0x6b, 0xbc, 0xf9, 0xb3, 0xc0, 0x4c, 0x37, 0x53, 0xed, 0x1c,
+];
+
+/// Serialized hybrid KEM secret key, a ciphertext to it, and the shared secret
+const KEM_SECRET_KEY: &[u8] = include_bytes!("self_test/kem_secret_key.bin");
+const KEM_CIPHERTEXT: &[u8] = include_bytes!("self_test/kem_ciphertext.bin");
+const KEM_SHARED_SECRET: &[u8] = include_bytes!("self_test/kem_shared_secret.bin");
+
+/// Serialized dual-signature public key and its signature over `SIGN_MESSAGE`
+const SIGN_PUBLIC_KEY: &[u8] = include_bytes!("self_test/sign_public_key.bin");
+const SIGN_SIGNATURE: &[u8] = include_bytes!("self_test/sign_signature.bin");
+
+const SIGN_MESSAGE: &[u8] = b"QuantumShield self-test: sign";
+const CIPHER_PLAINTEXT: &[u8] = b"QuantumShield self-test: cascade";
+const CIPHER_AAD: &[u8] = b"QuantumShield self-test: aad";
+
+/// `encrypt_deterministic(CIPHER_PLAINTEXT, CIPHER_AAD)` under `KDF_SECRET`
+const CIPHER_EXPECTED: [u8; 88] = [
+    0x9b, 0xc6, 0xf5, 0x21, 0x7f, 0x1b, 0xcc, 0xf6, 0x2c, 0x32, 0x11, 0x97, 0xec, 0x7e, 0xc9, 0xc4,
+    0xba, 0x6f, 0xf9, 0x0c, 0xce, 0xe8, 0xbe, 0xce, 0xd4, 0xe7, 0x23, 0x96, 0xe7, 0x08, 0x43, 0x72,
+    0xbc, 0x38, 0x74, 0xb8, 0xcc, 0x7f, 0x8b, 0x72, 0x45, 0x90, 0x5b, 0x08, 0x08, 0x54, 0xad, 0xba,
+    0x88, 0x68, 0xda, 0xf5, 0xfe, 0xb4, 0x13, 0xb5, 0x16, 0x12, 0xad, 0x40, 0x51, 0xf8, 0x38, 0x04,
+    0xfc, 0x84, 0x27, 0x60, 0xaa, 0x4f, 0x1f, 0x50, 0x56, 0x08, 0xc9, 0xb2, 0x09, 0x48, 0x2f, 0x72,
+    0x8b, 0xcd, 0xa7, 0xd2, 0x73, 0x9e, 0x55, 0xf7,
+];
+
+/// Run the self-test over KDF, KEM, signatures, and cascade encryption
+///
+/// Returns `QShieldError::SelfTestFailed` naming the first primitive that
+/// misbehaved.
+pub fn self_test() -> Result<()> {
+    test_kdf()?;
+    test_kem()?;
+    test_sign()?;
+    test_cascade()?;
+    Ok(())
+}
+
+fn fail(primitive: &'static str) -> QShieldError {
+    QShieldError::SelfTestFailed(primitive)
+}
+
+fn test_kdf() -> Result<()> {
+    let key = QShieldKDF::new()
+        .expand_label(&KDF_SECRET, "key", b"", KDF_EXPECTED.len())
+        .map_err(|_| fail("QShieldKDF"))?;
+
+    if key.as_bytes() != KDF_EXPECTED {
+        return Err(fail("QShieldKDF"));
+    }
+    Ok(())
+}
+
+fn test_kem() -> Result<()> {
+    let secret_key = QShieldKEMSecretKey::deserialize(KEM_SECRET_KEY)
+        .map_err(|_| fail("QShieldKEM known answer"))?;
+    let ciphertext = QShieldKEMCiphertext::deserialize(KEM_CIPHERTEXT)
+        .map_err(|_| fail("QShieldKEM known answer"))?;
+    let shared_secret = QShieldKEM::decapsulate(&secret_key, &ciphertext)
+        .map_err(|_| fail("QShieldKEM known answer"))?;
+    if shared_secret.as_bytes() != KEM_SHARED_SECRET {
+        return Err(fail("QShieldKEM known answer"));
+    }
+
+    let (public_key, secret_key) =
+        QShieldKEM::generate_keypair().map_err(|_| fail("QShieldKEM keygen"))?;
+    let (ciphertext, encapsulated) =
+        QShieldKEM::encapsulate(&public_key).map_err(|_| fail("QShieldKEM encapsulate"))?;
+    let decapsulated = QShieldKEM::decapsulate(&secret_key, &ciphertext)
+        .map_err(|_| fail("QShieldKEM decapsulate"))?;
+
+    if encapsulated.as_bytes() != decapsulated.as_bytes() {
+        return Err(fail("QShieldKEM"));
+    }
+    Ok(())
+}
+
+fn test_sign() -> Result<()> {
+    let public_key = QShieldSignPublicKey::deserialize(SIGN_PUBLIC_KEY)
+        .map_err(|_| fail("QShieldSign known answer"))?;
+    let signature = QShieldSignature::deserialize(SIGN_SIGNATURE)
+        .map_err(|_| fail("QShieldSign known answer"))?;
+    let valid = QShieldSign::verify(&public_key, SIGN_MESSAGE, &signature)
+        .map_err(|_| fail("QShieldSign known answer"))?;
+    let forged = QShieldSign::verify(&public_key, CIPHER_PLAINTEXT, &signature)
+        .map_err(|_| fail("QShieldSign known answer"))?;
+    if !valid || forged {
+        return Err(fail("QShieldSign known answer"));
+    }
+
+    let (public_key, secret_key) =
+        QShieldSign::generate_keypair().map_err(|_| fail("QShieldSign keygen"))?;
+    let signature =
+        QShieldSign::sign(&secret_key, SIGN_MESSAGE).map_err(|_| fail("QShieldSign sign"))?;
+
+    let valid = QShieldSign::verify(&public_key, SIGN_MESSAGE, &signature)
+        .map_err(|_| fail("QShieldSign verify"))?;
+    let forged = QShieldSign::verify(&public_key, CIPHER_PLAINTEXT, &signature)
+        .map_err(|_| fail("QShieldSign verify"))?;
+
+    if !valid || forged {
+        return Err(fail("QShieldSign"));
+    }
+    Ok(())
+}
+
+fn test_cascade() -> Result<()> {
+    let cipher = QuantumShield::new(KDF_SECRET).map_err(|_| fail("QuantumShield"))?;
+    let expected = cipher
+        .encrypt_deterministic(CIPHER_PLAINTEXT, CIPHER_AAD)
+        .map_err(|_| fail("QuantumShield known answer"))?;
+    if expected != CIPHER_EXPECTED {
+        return Err(fail("QuantumShield known answer"));
+    }
+
+    let mut ciphertext = cipher
+        .encrypt_with_aad(CIPHER_PLAINTEXT, CIPHER_AAD)
+        .map_err(|_| fail("QuantumShield encrypt"))?;
+
+    let plaintext = cipher
+        .decrypt_with_aad(&ciphertext, CIPHER_AAD)
+        .map_err(|_| fail("QuantumShield decrypt"))?;
+    if plaintext != CIPHER_PLAINTEXT {
+        return Err(fail("QuantumShield"));
+    }
+
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 0x01;
+    if cipher.decrypt_with_aad(&ciphertext, CIPHER_AAD).is_ok() {
+        return Err(fail("QuantumShield authentication"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert!(self_test().is_ok());
+    }
+}
+
@@ -7,6 +7,8 @@
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use sha3::{Digest, Sha3_256};
 use zeroize::ZeroizeOnDrop;
 
@@ -167,6 +169,16 @@ impl QShieldSignature {
         }
     }
 
+    /// ML-DSA component bytes
+    pub fn mldsa_component(&self) -> Vec<u8> {
+        self.ml_dsa.as_bytes()
+    }
+
+    /// SLH-DSA component bytes
+    pub fn slhdsa_component(&self) -> Vec<u8> {
+        self.slh_dsa.as_bytes()
+    }
+
     /// Get the total signature size in bytes
     pub fn size(&self) -> usize {
         ML_DSA_SIGNATURE_SIZE + SLH_DSA_SIGNATURE_SIZE + if self.timestamp.is_some() { 8 } else { 0 }
@@ -242,6 +254,33 @@ impl Deserialize for QShieldSignature {
     }
 }
 
+/// Signing and verification options
+#[derive(Debug, Clone, Default)]
+pub struct SignConfig {
+    /// Fail with `QShieldError::EmptyMessage` instead of signing or
+    /// verifying an empty message
+    ///
+    /// Off by default, since empty messages are valid for both algorithms.
+    /// Turn it on where an empty buffer can only mean a bug upstream, such
+    /// as a failed read, so it is caught at signing time rather than as a
+    /// confusing verification failure later.
+    pub reject_empty: bool,
+}
+
+impl SignConfig {
+    /// Configuration that rejects empty messages
+    pub fn reject_empty() -> Self {
+        Self { reject_empty: true }
+    }
+
+    fn check(&self, message: &[u8]) -> Result<()> {
+        if self.reject_empty && message.is_empty() {
+            return Err(QShieldError::EmptyMessage);
+        }
+        Ok(())
+    }
+}
+
 /// QShieldSign - Dual Digital Signature Scheme
 ///
 /// Combines ML-DSA-65 and SLH-DSA-SHA2-128s for defense-in-depth.
@@ -264,6 +303,9 @@ impl QShieldSign {
 
     /// Sign a message with both algorithms
     ///
+    /// Empty messages are signed like any other; use `sign_with_config` with
+    /// `SignConfig::reject_empty()` to refuse them.
+    ///
     /// # Arguments
     /// * `secret_key` - The signing key
     /// * `message` - The message to sign
@@ -281,6 +323,16 @@ impl QShieldSign {
         Ok(QShieldSignature::new(ml_dsa_sig, slh_dsa_sig))
     }
 
+    /// Sign a message, applying `config`
+    pub fn sign_with_config(
+        secret_key: &QShieldSignSecretKey,
+        message: &[u8],
+        config: &SignConfig,
+    ) -> Result<QShieldSignature> {
+        config.check(message)?;
+        Self::sign(secret_key, message)
+    }
+
     /// Sign a message with both algorithms and a timestamp
     ///
     /// # Arguments
@@ -321,6 +373,22 @@ impl QShieldSign {
         message: &[u8],
         signature: &QShieldSignature,
     ) -> Result<bool> {
+        // Verify both signatures - both must pass
+        let (ml_dsa_valid, slh_dsa_valid) = Self::verify_detailed(public_key, message, signature)?;
+        Ok(ml_dsa_valid && slh_dsa_valid)
+    }
+
+    /// Verify each component of a dual signature separately
+    ///
+    /// Returns `(ml_dsa_valid, slh_dsa_valid)`. Both components are always
+    /// checked. Only `(true, true)` is a valid signature; a result where just
+    /// one algorithm fails points at a forged or corrupted component and is
+    /// worth logging.
+    pub fn verify_detailed(
+        public_key: &QShieldSignPublicKey,
+        message: &[u8],
+        signature: &QShieldSignature,
+    ) -> Result<(bool, bool)> {
         // Recreate the message hash
         let message_hash = if let Some(timestamp) = signature.timestamp {
             Self::hash_message_with_timestamp(message, timestamp)
@@ -328,11 +396,66 @@ impl QShieldSign {
             Self::hash_message(message)
         };
 
-        // Verify both signatures - both must pass
         let ml_dsa_valid = MlDsa::verify(&public_key.ml_dsa, &message_hash, &signature.ml_dsa)?;
         let slh_dsa_valid = SlhDsa::verify(&public_key.slh_dsa, &message_hash, &signature.slh_dsa)?;
 
-        Ok(ml_dsa_valid && slh_dsa_valid)
+        Ok((ml_dsa_valid, slh_dsa_valid))
+    }
+
+    /// Verify a dual signature, applying `config`
+    ///
+    /// With `reject_empty` set, an empty message is an error rather than a
+    /// verification result.
+    pub fn verify_with_config(
+        public_key: &QShieldSignPublicKey,
+        message: &[u8],
+        signature: &QShieldSignature,
+        config: &SignConfig,
+    ) -> Result<bool> {
+        config.check(message)?;
+        Self::verify(public_key, message, signature)
+    }
+
+    /// Verify many signatures, returning one result per item in input order
+    ///
+    /// Each item is `(public_key, message, signature)`. An error from any
+    /// item (e.g. a malformed key) fails the whole batch.
+    pub fn verify_batch(
+        items: &[(&QShieldSignPublicKey, &[u8], &QShieldSignature)],
+    ) -> Result<Vec<bool>> {
+        items
+            .iter()
+            .map(|(public_key, message, signature)| Self::verify(public_key, message, signature))
+            .collect()
+    }
+
+    /// Parallel `verify_batch` using the rayon thread pool
+    ///
+    /// Results are identical to `verify_batch`, in the same order.
+    #[cfg(feature = "parallel")]
+    pub fn verify_batch_parallel(
+        items: &[(&QShieldSignPublicKey, &[u8], &QShieldSignature)],
+    ) -> Result<Vec<bool>> {
+        items
+            .par_iter()
+            .map(|(public_key, message, signature)| Self::verify(public_key, message, signature))
+            .collect()
+    }
+
+    /// Generate `n` independent key pairs in parallel
+    ///
+    /// Every key pair draws from the operating system RNG used by
+    /// `generate_keypair`, which is safe to call from any thread. No seeded
+    /// variant is offered: `pqcrypto` reads its keygen seed from the OS RNG
+    /// inside the C implementations, with no way to supply one per index.
+    #[cfg(feature = "parallel")]
+    pub fn generate_keypairs(
+        n: usize,
+    ) -> Result<Vec<(QShieldSignPublicKey, QShieldSignSecretKey)>> {
+        (0..n)
+            .into_par_iter()
+            .map(|_| Self::generate_keypair())
+            .collect()
     }
 
     /// Hash a message for signing
@@ -410,6 +533,41 @@ mod tests {
         assert!(valid);
     }
 
+    #[test]
+    fn test_empty_message_signs_by_default() {
+        let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
+
+        let signature = QShieldSign::sign(&secret_key, b"").unwrap();
+        assert!(QShieldSign::verify(&public_key, b"", &signature).unwrap());
+
+        let config = SignConfig::default();
+        let signature = QShieldSign::sign_with_config(&secret_key, b"", &config).unwrap();
+        assert!(QShieldSign::verify_with_config(&public_key, b"", &signature, &config).unwrap());
+    }
+
+    #[test]
+    fn test_reject_empty_message() {
+        let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
+        let config = SignConfig::reject_empty();
+
+        assert!(matches!(
+            QShieldSign::sign_with_config(&secret_key, b"", &config),
+            Err(QShieldError::EmptyMessage)
+        ));
+
+        let signature = QShieldSign::sign(&secret_key, b"").unwrap();
+        assert!(matches!(
+            QShieldSign::verify_with_config(&public_key, b"", &signature, &config),
+            Err(QShieldError::EmptyMessage)
+        ));
+
+        // Non-empty messages are unaffected
+        let signature = QShieldSign::sign_with_config(&secret_key, b"data", &config).unwrap();
+        assert!(
+            QShieldSign::verify_with_config(&public_key, b"data", &signature, &config).unwrap()
+        );
+    }
+
     #[test]
     fn test_invalid_signature() {
         let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
@@ -422,6 +580,54 @@ mod tests {
         assert!(!valid);
     }
 
+    #[test]
+    fn test_signature_components() {
+        let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
+        let message = b"Component check";
+        let signature = QShieldSign::sign(&secret_key, message).unwrap();
+
+        let ml_dsa_bytes = signature.mldsa_component();
+        let slh_dsa_bytes = signature.slhdsa_component();
+        assert_eq!(ml_dsa_bytes.len(), ML_DSA_SIGNATURE_SIZE);
+        assert_eq!(slh_dsa_bytes.len(), SLH_DSA_SIGNATURE_SIZE);
+
+        // Each component verifies on its own over the signed hash
+        let message_hash = QShieldSign::hash_message(message);
+        let ml_dsa = MlDsaSignature::from_bytes(&ml_dsa_bytes).unwrap();
+        let slh_dsa = SlhDsaSignature::from_bytes(&slh_dsa_bytes).unwrap();
+        assert!(MlDsa::verify(&public_key.ml_dsa, &message_hash, &ml_dsa).unwrap());
+        assert!(SlhDsa::verify(&public_key.slh_dsa, &message_hash, &slh_dsa).unwrap());
+
+        assert_eq!(
+            QShieldSign::verify_detailed(&public_key, message, &signature).unwrap(),
+            (true, true)
+        );
+    }
+
+    #[test]
+    fn test_verify_detailed_detects_forged_component() {
+        let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
+        let (_, other_secret_key) = QShieldSign::generate_keypair().unwrap();
+        let message = b"Detailed verification";
+
+        let genuine = QShieldSign::sign(&secret_key, message).unwrap();
+        let other = QShieldSign::sign(&other_secret_key, message).unwrap();
+
+        let forged_ml_dsa = QShieldSignature::new(other.ml_dsa.clone(), genuine.slh_dsa.clone());
+        assert_eq!(
+            QShieldSign::verify_detailed(&public_key, message, &forged_ml_dsa).unwrap(),
+            (false, true)
+        );
+        assert!(!QShieldSign::verify(&public_key, message, &forged_ml_dsa).unwrap());
+
+        let forged_slh_dsa = QShieldSignature::new(genuine.ml_dsa.clone(), other.slh_dsa);
+        assert_eq!(
+            QShieldSign::verify_detailed(&public_key, message, &forged_slh_dsa).unwrap(),
+            (true, false)
+        );
+        assert!(!QShieldSign::verify(&public_key, message, &forged_slh_dsa).unwrap());
+    }
+
     #[test]
     fn test_signature_serialization() {
         let (_, secret_key) = QShieldSign::generate_keypair().unwrap();
@@ -447,6 +653,44 @@ mod tests {
         assert!(!valid);
     }
 
+    #[test]
+    fn test_verify_batch() {
+        let (public_key, secret_key) = QShieldSign::generate_keypair().unwrap();
+        let signature = QShieldSign::sign(&secret_key, b"first").unwrap();
+
+        let items: [(&QShieldSignPublicKey, &[u8], &QShieldSignature); 2] = [
+            (&public_key, b"first", &signature),
+            (&public_key, b"second", &signature),
+        ];
+        let results = QShieldSign::verify_batch(&items).unwrap();
+        assert_eq!(results, vec![true, false]);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_parallel_verify_batch_matches_sequential() {
+        let keypairs = QShieldSign::generate_keypairs(3).unwrap();
+        let messages: [&[u8]; 3] = [b"alpha", b"beta", b"gamma"];
+        let signatures: Vec<_> = keypairs
+            .iter()
+            .zip(messages)
+            .map(|((_, secret_key), message)| QShieldSign::sign(secret_key, message).unwrap())
+            .collect();
+
+        // Valid items interleaved with wrong-message and wrong-key items
+        let mut items = Vec::new();
+        for (i, signature) in signatures.iter().enumerate() {
+            items.push((&keypairs[i].0, messages[i], signature));
+            items.push((&keypairs[i].0, messages[(i + 1) % 3], signature));
+            items.push((&keypairs[(i + 1) % 3].0, messages[i], signature));
+        }
+
+        let sequential = QShieldSign::verify_batch(&items).unwrap();
+        let parallel = QShieldSign::verify_batch_parallel(&items).unwrap();
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential.iter().filter(|valid| **valid).count(), 3);
+    }
+
     #[test]
     fn test_fingerprint() {
         let (pk1, _) = QShieldSign::generate_keypair().unwrap();
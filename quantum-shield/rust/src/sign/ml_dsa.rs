@@ -17,7 +17,7 @@ use crate::utils::serialize::{Deserialize, Serialize};
 pub const ML_DSA_PUBLIC_KEY_SIZE: usize = 1952;
 
 /// ML-DSA-65 secret key size in bytes
-pub const ML_DSA_SECRET_KEY_SIZE: usize = 4016;
+pub const ML_DSA_SECRET_KEY_SIZE: usize = 4032;
 
 /// ML-DSA-65 signature size in bytes (Dilithium3)
 pub const ML_DSA_SIGNATURE_SIZE: usize = 3309;
@@ -101,7 +101,7 @@ impl MlDsaSecretKey {
 
 impl Clone for MlDsaSecretKey {
     fn clone(&self) -> Self {
-        Self::from_bytes(&self.key.as_bytes()).unwrap()
+        Self { key: self.key }
     }
 }
 
@@ -194,6 +194,20 @@ mod tests {
         assert_eq!(public_key.as_bytes().len(), ML_DSA_PUBLIC_KEY_SIZE);
     }
 
+    #[test]
+    fn test_secret_key_clone_and_bytes_roundtrip() {
+        let (public_key, secret_key) = MlDsa::generate_keypair().unwrap();
+        assert_eq!(secret_key.as_bytes().len(), ML_DSA_SECRET_KEY_SIZE);
+
+        let cloned = secret_key.clone();
+        let restored = MlDsaSecretKey::from_bytes(&secret_key.as_bytes()).unwrap();
+        assert_eq!(cloned.as_bytes(), secret_key.as_bytes());
+        assert_eq!(restored.as_bytes(), secret_key.as_bytes());
+
+        let signature = MlDsa::sign(&cloned, b"cloned").unwrap();
+        assert!(MlDsa::verify(&public_key, b"cloned", &signature).unwrap());
+    }
+
     #[test]
     fn test_sign_verify() {
         let (public_key, secret_key) = MlDsa::generate_keypair().unwrap();
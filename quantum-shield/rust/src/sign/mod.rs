@@ -17,7 +17,7 @@ mod ml_dsa;
 mod slh_dsa;
 
 pub use dual::{
-    QShieldSign, QShieldSignPublicKey, QShieldSignSecretKey, QShieldSignature,
+    QShieldSign, QShieldSignPublicKey, QShieldSignSecretKey, QShieldSignature, SignConfig,
 };
 pub use ml_dsa::{MlDsaPublicKey, MlDsaSecretKey, MlDsaSignature};
 pub use slh_dsa::{SlhDsaPublicKey, SlhDsaSecretKey, SlhDsaSignature};
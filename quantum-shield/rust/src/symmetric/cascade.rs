@@ -11,18 +11,22 @@
 //! - Separate nonces for each layer
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
 
+use hmac::{Hmac, Mac};
+use sha3::Sha3_256;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::error::{QShieldError, Result};
 use crate::kdf::QShieldKDF;
+use crate::AlgorithmSuite;
 use crate::utils::serialize::{
     read_length_prefixed, write_length_prefixed, Deserialize, Header, ObjectType, Serialize,
 };
 
 use super::aes_gcm::{AesGcmCipher, AES_KEY_SIZE, AES_NONCE_SIZE, AES_TAG_SIZE};
 use super::chacha::{ChaCha20Cipher, CHACHA_KEY_SIZE, CHACHA_NONCE_SIZE, CHACHA_TAG_SIZE};
+use super::nonce::{NonceGuard, NonceSource, OsNonceSource};
 
 /// Total key size needed for cascading encryption (AES + ChaCha20)
 pub const QSHIELD_KEY_SIZE: usize = AES_KEY_SIZE + CHACHA_KEY_SIZE;
@@ -30,6 +34,14 @@ pub const QSHIELD_KEY_SIZE: usize = AES_KEY_SIZE + CHACHA_KEY_SIZE;
 /// Encryption overhead (nonce + tag for each cipher)
 pub const QSHIELD_OVERHEAD: usize = AES_NONCE_SIZE + AES_TAG_SIZE + CHACHA_NONCE_SIZE + CHACHA_TAG_SIZE;
 
+/// KDF label for the MAC key behind `encrypt_deterministic` nonces
+const SIV_KEY_LABEL: &[u8] = b"QuantumShield-siv-v1";
+
+/// `EncryptedData` flag: message ID present
+const FLAG_MESSAGE_ID: u16 = 0x01;
+/// `EncryptedData` flag: algorithm suite byte present
+const FLAG_SUITE: u16 = 0x02;
+
 /// Encrypted data with metadata
 #[derive(Clone)]
 pub struct EncryptedData {
@@ -37,6 +49,11 @@ pub struct EncryptedData {
     pub ciphertext: Vec<u8>,
     /// Optional message ID for deduplication
     pub message_id: Option<[u8; 16]>,
+    /// Algorithm suite the data was sealed under
+    ///
+    /// `None` for data produced before suites were recorded; such data is
+    /// only accepted by a cipher configured for `AlgorithmSuite::Default`.
+    pub suite: Option<AlgorithmSuite>,
 }
 
 impl EncryptedData {
@@ -45,6 +62,7 @@ impl EncryptedData {
         Self {
             ciphertext,
             message_id: None,
+            suite: None,
         }
     }
 
@@ -53,20 +71,34 @@ impl EncryptedData {
         Self {
             ciphertext,
             message_id: Some(message_id),
+            suite: None,
         }
     }
 }
 
 impl Serialize for EncryptedData {
     fn serialize(&self) -> Result<Vec<u8>> {
-        let flags: u16 = if self.message_id.is_some() { 0x01 } else { 0x00 };
+        let mut flags: u16 = 0;
+        if self.message_id.is_some() {
+            flags |= FLAG_MESSAGE_ID;
+        }
+        if self.suite.is_some() {
+            flags |= FLAG_SUITE;
+        }
 
-        let payload_size = 2 + 4 + self.ciphertext.len() + if self.message_id.is_some() { 16 } else { 0 };
+        let payload_size = 2
+            + if self.suite.is_some() { 1 } else { 0 }
+            + 4
+            + self.ciphertext.len()
+            + if self.message_id.is_some() { 16 } else { 0 };
         let header = Header::new(ObjectType::EncryptedMessage, payload_size);
 
         let mut buf = Vec::with_capacity(Header::SIZE + payload_size);
         buf.extend_from_slice(&header.to_bytes());
         buf.extend_from_slice(&flags.to_le_bytes());
+        if let Some(suite) = self.suite {
+            buf.push(suite as u8);
+        }
         write_length_prefixed(&self.ciphertext, &mut buf);
 
         if let Some(id) = &self.message_id {
@@ -92,9 +124,17 @@ impl Deserialize for EncryptedData {
         let flags = u16::from_le_bytes([data[offset], data[offset + 1]]);
         offset += 2;
 
+        let suite = if flags & FLAG_SUITE != 0 {
+            let byte = *data.get(offset).ok_or(QShieldError::ParseError)?;
+            offset += 1;
+            Some(AlgorithmSuite::try_from(byte)?)
+        } else {
+            None
+        };
+
         let ciphertext = read_length_prefixed(data, &mut offset)?;
 
-        let message_id = if flags & 0x01 != 0 {
+        let message_id = if flags & FLAG_MESSAGE_ID != 0 {
             if offset + 16 > data.len() {
                 return Err(QShieldError::ParseError);
             }
@@ -108,6 +148,7 @@ impl Deserialize for EncryptedData {
         Ok(Self {
             ciphertext,
             message_id,
+            suite,
         })
     }
 }
@@ -123,6 +164,14 @@ pub struct QuantumShield {
     chacha: ChaCha20Cipher,
     aes_key: [u8; AES_KEY_SIZE],
     chacha_key: [u8; CHACHA_KEY_SIZE],
+    #[zeroize(skip)]
+    suite: AlgorithmSuite,
+    #[zeroize(skip)]
+    nonce_source: Box<dyn NonceSource>,
+    #[zeroize(skip)]
+    aes_nonce_guard: NonceGuard,
+    #[zeroize(skip)]
+    chacha_nonce_guard: NonceGuard,
 }
 
 impl QuantumShield {
@@ -132,8 +181,10 @@ impl QuantumShield {
     /// using HKDF-SHA3-512.
     ///
     /// # Arguments
-    /// * `shared_secret` - Key material (any length, will be expanded)
-    pub fn new(shared_secret: &[u8]) -> Result<Self> {
+    /// * `shared_secret` - Key material (any length, will be expanded); a
+    ///   `QShieldSharedSecret` can be passed directly
+    pub fn new(shared_secret: impl AsRef<[u8]>) -> Result<Self> {
+        let shared_secret = shared_secret.as_ref();
         if shared_secret.is_empty() {
             return Err(QShieldError::InvalidKey);
         }
@@ -164,6 +215,10 @@ impl QuantumShield {
             chacha,
             aes_key,
             chacha_key,
+            suite: AlgorithmSuite::default(),
+            nonce_source: Box::new(OsNonceSource),
+            aes_nonce_guard: NonceGuard::default(),
+            chacha_nonce_guard: NonceGuard::default(),
         })
     }
 
@@ -181,9 +236,32 @@ impl QuantumShield {
             chacha,
             aes_key: *aes_key,
             chacha_key: *chacha_key,
+            suite: AlgorithmSuite::default(),
+            nonce_source: Box::new(OsNonceSource),
+            aes_nonce_guard: NonceGuard::default(),
+            chacha_nonce_guard: NonceGuard::default(),
         })
     }
 
+    /// Set the algorithm suite recorded in, and required of, sealed data
+    pub fn with_suite(mut self, suite: AlgorithmSuite) -> Self {
+        self.suite = suite;
+        self
+    }
+
+    /// Get the configured algorithm suite
+    pub fn suite(&self) -> AlgorithmSuite {
+        self.suite
+    }
+
+    /// Draw layer nonces from `source` instead of the OS RNG
+    ///
+    /// Mainly useful for testing; the source must still produce unique nonces.
+    pub fn with_nonce_source(mut self, source: impl NonceSource + 'static) -> Self {
+        self.nonce_source = Box::new(source);
+        self
+    }
+
     /// Encrypt data using cascading encryption
     ///
     /// Data is encrypted first with AES-256-GCM, then with ChaCha20-Poly1305.
@@ -194,13 +272,7 @@ impl QuantumShield {
     /// # Returns
     /// Cascaded ciphertext
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
-        // First layer: AES-256-GCM
-        let aes_encrypted = self.aes.encrypt(plaintext, None)?;
-
-        // Second layer: ChaCha20-Poly1305
-        let cascade_encrypted = self.chacha.encrypt(&aes_encrypted, None)?;
-
-        Ok(cascade_encrypted)
+        self.encrypt_with_aad(plaintext, &[])
     }
 
     /// Encrypt data with additional authenticated data
@@ -214,11 +286,93 @@ impl QuantumShield {
     /// # Returns
     /// Cascaded ciphertext
     pub fn encrypt_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
-        // First layer: AES-256-GCM with AAD
-        let aes_encrypted = self.aes.encrypt(plaintext, Some(aad))?;
+        self.encrypt_layers(plaintext, Some(aad))
+    }
+
+    /// Encrypt deterministically (nonce-misuse-resistant, SIV-style)
+    ///
+    /// Both layer nonces are taken from an HMAC-SHA3-256 over the AAD and
+    /// plaintext, keyed with a MAC key derived from this cipher's keys, so no
+    /// randomness is consumed and distinct messages only share a nonce if the
+    /// MAC collides. Identical `(plaintext, aad)` pairs produce identical
+    /// ciphertexts, which reveals repeated messages; use `encrypt_with_aad`
+    /// unless that is acceptable. The output decrypts with `decrypt_with_aad`.
+    ///
+    /// # Arguments
+    /// * `plaintext` - Data to encrypt
+    /// * `aad` - Additional authenticated data (also binds the nonce)
+    ///
+    /// # Returns
+    /// Cascaded ciphertext
+    pub fn encrypt_deterministic(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let mut tag = self.synthetic_iv(plaintext, aad)?;
+
+        let mut aes_nonce = [0u8; AES_NONCE_SIZE];
+        let mut chacha_nonce = [0u8; CHACHA_NONCE_SIZE];
+        aes_nonce.copy_from_slice(&tag[..AES_NONCE_SIZE]);
+        chacha_nonce.copy_from_slice(&tag[AES_NONCE_SIZE..AES_NONCE_SIZE + CHACHA_NONCE_SIZE]);
+        tag.zeroize();
+
+        // Repeated nonces are expected here, so the guards are bypassed
+        self.seal_layers(plaintext, &aes_nonce, &chacha_nonce, Some(aad))
+    }
+
+    /// HMAC-SHA3-256 over `len(aad) || aad || plaintext` under the SIV key
+    fn synthetic_iv(&self, plaintext: &[u8], aad: &[u8]) -> Result<[u8; 32]> {
+        let mut current_keys = Vec::with_capacity(QSHIELD_KEY_SIZE);
+        current_keys.extend_from_slice(&self.aes_key);
+        current_keys.extend_from_slice(&self.chacha_key);
+
+        let siv_key = QShieldKDF::new().derive(&current_keys, Some(&[]), SIV_KEY_LABEL, 32);
+        current_keys.zeroize();
+        let siv_key = siv_key?;
+
+        let mut mac = <Hmac<Sha3_256> as Mac>::new_from_slice(siv_key.as_bytes())
+            .map_err(|_| QShieldError::KeyDerivationFailed)?;
+        mac.update(&(aad.len() as u64).to_le_bytes());
+        mac.update(aad);
+        mac.update(plaintext);
+
+        let mut tag = [0u8; 32];
+        tag.copy_from_slice(&mac.finalize().into_bytes());
+        Ok(tag)
+    }
+
+    /// Cascade encrypt with fresh nonces from the configured source
+    ///
+    /// Each layer's output is `nonce || ciphertext || tag`, as produced by
+    /// the layer ciphers' own `encrypt`.
+    fn encrypt_layers(&self, plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut aes_nonce = [0u8; AES_NONCE_SIZE];
+        let mut chacha_nonce = [0u8; CHACHA_NONCE_SIZE];
+        self.nonce_source.fill_nonce(&mut aes_nonce)?;
+        self.nonce_source.fill_nonce(&mut chacha_nonce)?;
+
+        self.aes_nonce_guard.check(&aes_nonce)?;
+        self.chacha_nonce_guard.check(&chacha_nonce)?;
+
+        self.seal_layers(plaintext, &aes_nonce, &chacha_nonce, aad)
+    }
+
+    /// Run both layers with the given nonces
+    fn seal_layers(
+        &self,
+        plaintext: &[u8],
+        aes_nonce: &[u8; AES_NONCE_SIZE],
+        chacha_nonce: &[u8; CHACHA_NONCE_SIZE],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        // First layer: AES-256-GCM
+        let aes_ct = self.aes.encrypt_with_nonce(plaintext, aes_nonce, aad)?;
+        let mut aes_encrypted = aes_nonce.to_vec();
+        aes_encrypted.extend(aes_ct);
 
-        // Second layer: ChaCha20-Poly1305 with AAD
-        let cascade_encrypted = self.chacha.encrypt(&aes_encrypted, Some(aad))?;
+        // Second layer: ChaCha20-Poly1305
+        let chacha_ct = self
+            .chacha
+            .encrypt_with_nonce(&aes_encrypted, chacha_nonce, aad)?;
+        let mut cascade_encrypted = chacha_nonce.to_vec();
+        cascade_encrypted.extend(chacha_ct);
 
         Ok(cascade_encrypted)
     }
@@ -231,13 +385,7 @@ impl QuantumShield {
     /// # Returns
     /// Decrypted plaintext
     pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
-        // First remove ChaCha20 layer
-        let aes_encrypted = self.chacha.decrypt(ciphertext, None)?;
-
-        // Then remove AES layer
-        let plaintext = self.aes.decrypt(&aes_encrypted, None)?;
-
-        Ok(plaintext)
+        self.decrypt_with_aad(ciphertext, &[])
     }
 
     /// Decrypt ciphertext with additional authenticated data
@@ -259,14 +407,50 @@ impl QuantumShield {
     }
 
     /// Encrypt into an EncryptedData structure
+    ///
+    /// The configured suite is recorded in the output and authenticated as
+    /// AAD, so it cannot be rewritten without failing decryption.
     pub fn seal(&self, plaintext: &[u8]) -> Result<EncryptedData> {
-        let ciphertext = self.encrypt(plaintext)?;
-        Ok(EncryptedData::new(ciphertext))
+        self.seal_with_aad(plaintext, &[])
+    }
+
+    /// Encrypt into an EncryptedData structure, binding caller context
+    ///
+    /// `aad` is authenticated after the suite byte and must be passed again
+    /// to `open_with_aad`. Empty `aad` is the same as `seal`.
+    pub fn seal_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Result<EncryptedData> {
+        let ciphertext = self.encrypt_with_aad(plaintext, &suite_aad(self.suite, aad))?;
+        Ok(EncryptedData {
+            ciphertext,
+            message_id: None,
+            suite: Some(self.suite),
+        })
     }
 
     /// Decrypt from an EncryptedData structure
+    ///
+    /// Returns `UnsupportedAlgorithm` if the data was sealed under a different
+    /// suite than this cipher is configured for.
     pub fn open(&self, data: &EncryptedData) -> Result<Vec<u8>> {
-        self.decrypt(&data.ciphertext)
+        self.open_with_aad(data, &[])
+    }
+
+    /// Decrypt from an EncryptedData structure sealed with `seal_with_aad`
+    ///
+    /// Fails unless `aad` matches the value given when sealing.
+    pub fn open_with_aad(&self, data: &EncryptedData, aad: &[u8]) -> Result<Vec<u8>> {
+        let suite = data.suite.unwrap_or_default();
+        if suite != self.suite {
+            return Err(QShieldError::UnsupportedAlgorithm(format!(
+                "Expected algorithm suite {:?}, got {:?}",
+                self.suite, suite
+            )));
+        }
+
+        match data.suite {
+            Some(suite) => self.decrypt_with_aad(&data.ciphertext, &suite_aad(suite, aad)),
+            None => self.decrypt_with_aad(&data.ciphertext, aad),
+        }
     }
 
     /// Get the encryption overhead
@@ -309,14 +493,52 @@ impl QuantumShield {
         self.aes = AesGcmCipher::new(&self.aes_key)?;
         self.chacha = ChaCha20Cipher::new(&self.chacha_key)?;
 
+        // Nonces may safely repeat under the new keys
+        self.aes_nonce_guard.clear();
+        self.chacha_nonce_guard.clear();
+
         Ok(())
     }
 }
 
+/// AAD for `EncryptedData`: the suite byte followed by the caller's AAD
+fn suite_aad(suite: AlgorithmSuite, aad: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + aad.len());
+    out.push(suite as u8);
+    out.extend_from_slice(aad);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(all(feature = "std", any(debug_assertions, feature = "nonce-guard")))]
+    fn test_nonce_guard_detects_broken_rng() {
+        // Stands in for a host whose RNG returns all zeros
+        struct ZeroNonceSource;
+
+        impl NonceSource for ZeroNonceSource {
+            fn fill_nonce(&self, buf: &mut [u8]) -> Result<()> {
+                buf.fill(0);
+                Ok(())
+            }
+        }
+
+        let cipher = QuantumShield::new(b"nonce guard key")
+            .unwrap()
+            .with_nonce_source(ZeroNonceSource);
+
+        let first = cipher.encrypt(b"first").unwrap();
+        assert_eq!(cipher.decrypt(&first).unwrap(), b"first");
+
+        assert!(matches!(
+            cipher.encrypt(b"second"),
+            Err(QShieldError::NonceReuseDetected)
+        ));
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let shared_secret = b"this is a test shared secret for encryption";
@@ -358,6 +580,60 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_empty_aad_matches_no_aad() {
+        let cipher = QuantumShield::new(b"test key material").unwrap();
+        let plaintext = b"Hello!";
+
+        let ciphertext = cipher.encrypt(plaintext).unwrap();
+        assert_eq!(cipher.decrypt_with_aad(&ciphertext, &[]).unwrap(), plaintext);
+        let ciphertext = cipher.encrypt_with_aad(plaintext, &[]).unwrap();
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), plaintext);
+
+        let sealed = cipher.seal(plaintext).unwrap();
+        assert_eq!(cipher.open_with_aad(&sealed, &[]).unwrap(), plaintext);
+        let sealed = cipher.seal_with_aad(plaintext, &[]).unwrap();
+        assert_eq!(cipher.open(&sealed).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_seal_with_aad() {
+        let cipher = QuantumShield::new(b"test key material").unwrap();
+
+        let sealed = cipher.seal_with_aad(b"Hello!", b"session-1").unwrap();
+        assert_eq!(cipher.open_with_aad(&sealed, b"session-1").unwrap(), b"Hello!");
+        assert!(cipher.open_with_aad(&sealed, b"session-2").is_err());
+        assert!(cipher.open(&sealed).is_err());
+
+        // Data without a recorded suite binds the caller's AAD alone
+        let legacy = EncryptedData::new(cipher.encrypt_with_aad(b"legacy", b"ctx").unwrap());
+        assert_eq!(cipher.open_with_aad(&legacy, b"ctx").unwrap(), b"legacy");
+        assert!(cipher.open(&legacy).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_deterministic() {
+        let cipher = QuantumShield::new(b"test key material").unwrap();
+        let plaintext = b"Same message";
+
+        let ct1 = cipher.encrypt_deterministic(plaintext, b"aad").unwrap();
+        let ct2 = cipher.encrypt_deterministic(plaintext, b"aad").unwrap();
+        assert_eq!(ct1, ct2);
+        assert_eq!(ct1.len(), plaintext.len() + QSHIELD_OVERHEAD);
+        assert_eq!(cipher.decrypt_with_aad(&ct1, b"aad").unwrap(), plaintext);
+
+        // Differing AAD or plaintext yields different nonces
+        let other_aad = cipher.encrypt_deterministic(plaintext, b"other").unwrap();
+        let other_pt = cipher.encrypt_deterministic(b"Some message", b"aad").unwrap();
+        assert_ne!(ct1[..CHACHA_NONCE_SIZE], other_aad[..CHACHA_NONCE_SIZE]);
+        assert_ne!(ct1[..CHACHA_NONCE_SIZE], other_pt[..CHACHA_NONCE_SIZE]);
+
+        // The randomized default is unchanged
+        let random1 = cipher.encrypt_with_aad(plaintext, b"aad").unwrap();
+        let random2 = cipher.encrypt_with_aad(plaintext, b"aad").unwrap();
+        assert_ne!(random1, random2);
+    }
+
     #[test]
     fn test_cascade_overhead() {
         let shared_secret = b"test key";
@@ -396,6 +672,46 @@ mod tests {
         assert_eq!(plaintext.as_slice(), decrypted.as_slice());
     }
 
+    #[test]
+    fn test_suite_roundtrip() {
+        for suite in [AlgorithmSuite::Default, AlgorithmSuite::HighSecurity, AlgorithmSuite::Compact] {
+            let cipher = QuantumShield::new(b"test key material").unwrap().with_suite(suite);
+
+            let encrypted = cipher.seal(b"Suite message").unwrap();
+            let deserialized = EncryptedData::deserialize(&encrypted.serialize().unwrap()).unwrap();
+
+            assert_eq!(deserialized.suite, Some(suite));
+            assert_eq!(cipher.open(&deserialized).unwrap(), b"Suite message");
+        }
+    }
+
+    #[test]
+    fn test_cross_suite_rejected() {
+        let compact = QuantumShield::new(b"test key material")
+            .unwrap()
+            .with_suite(AlgorithmSuite::Compact);
+        let high = QuantumShield::new(b"test key material")
+            .unwrap()
+            .with_suite(AlgorithmSuite::HighSecurity);
+
+        let encrypted = compact.seal(b"Compact message").unwrap();
+        assert!(matches!(
+            high.open(&encrypted),
+            Err(QShieldError::UnsupportedAlgorithm(_))
+        ));
+
+        // Relabelling the suite is caught by authentication
+        let mut relabelled = encrypted.clone();
+        relabelled.suite = Some(AlgorithmSuite::HighSecurity);
+        assert!(high.open(&relabelled).is_err());
+
+        // Unlabelled legacy data is only accepted under the default suite
+        let legacy = EncryptedData::new(compact.encrypt(b"legacy").unwrap());
+        assert!(compact.open(&legacy).is_err());
+        let default = QuantumShield::new(b"test key material").unwrap();
+        assert_eq!(default.open(&legacy).unwrap(), b"legacy");
+    }
+
     #[test]
     fn test_key_rotation() {
         let shared_secret = b"test key material";
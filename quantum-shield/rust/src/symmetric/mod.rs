@@ -15,7 +15,9 @@
 mod aes_gcm;
 mod cascade;
 mod chacha;
+mod nonce;
 
 pub use aes_gcm::AesGcmCipher;
 pub use cascade::{QuantumShield, EncryptedData};
 pub use chacha::ChaCha20Cipher;
+pub use nonce::{NonceSource, OsNonceSource};
@@ -0,0 +1,83 @@
+//! Nonce sources for the cascade cipher
+//!
+//! `QuantumShield` draws both layer nonces from a [`NonceSource`], which is
+//! the OS RNG unless replaced. In debug builds, or with the `nonce-guard`
+//! feature, each cipher also remembers its most recent nonces and refuses to
+//! encrypt if the source hands one back again. That is a tripwire for broken
+//! entropy (e.g. a host whose `getrandom` returns zeros), not a nonce manager:
+//! a healthy RNG never trips it.
+
+use crate::error::Result;
+use crate::utils::rng::SecureRng;
+
+#[cfg(all(feature = "std", any(debug_assertions, feature = "nonce-guard")))]
+use crate::error::QShieldError;
+
+/// Source of random AEAD nonces
+pub trait NonceSource: Send + Sync {
+    /// Fill `buf` with a fresh nonce
+    fn fill_nonce(&self, buf: &mut [u8]) -> Result<()>;
+}
+
+/// Nonces from the operating system RNG (the default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsNonceSource;
+
+impl NonceSource for OsNonceSource {
+    fn fill_nonce(&self, buf: &mut [u8]) -> Result<()> {
+        SecureRng::new().fill_bytes(buf)
+    }
+}
+
+/// Number of recent nonces remembered per cipher
+#[cfg(all(feature = "std", any(debug_assertions, feature = "nonce-guard")))]
+pub(crate) const NONCE_GUARD_CAPACITY: usize = 64;
+
+/// Ring buffer of recently used nonces
+#[cfg(all(feature = "std", any(debug_assertions, feature = "nonce-guard")))]
+#[derive(Default)]
+pub(crate) struct NonceGuard {
+    recent: std::sync::Mutex<std::collections::VecDeque<Vec<u8>>>,
+}
+
+#[cfg(all(feature = "std", any(debug_assertions, feature = "nonce-guard")))]
+impl NonceGuard {
+    /// Record `nonce`, failing if it was among the last `NONCE_GUARD_CAPACITY`
+    pub(crate) fn check(&self, nonce: &[u8]) -> Result<()> {
+        let mut recent = self
+            .recent
+            .lock()
+            .map_err(|_| QShieldError::InternalError)?;
+
+        if recent.iter().any(|seen| seen.as_slice() == nonce) {
+            return Err(QShieldError::NonceReuseDetected);
+        }
+
+        if recent.len() == NONCE_GUARD_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(nonce.to_vec());
+        Ok(())
+    }
+
+    /// Forget all recorded nonces (e.g. after a key change)
+    pub(crate) fn clear(&self) {
+        if let Ok(mut recent) = self.recent.lock() {
+            recent.clear();
+        }
+    }
+}
+
+/// Guard disabled: release builds without the `nonce-guard` feature
+#[cfg(not(all(feature = "std", any(debug_assertions, feature = "nonce-guard"))))]
+#[derive(Default)]
+pub(crate) struct NonceGuard;
+
+#[cfg(not(all(feature = "std", any(debug_assertions, feature = "nonce-guard"))))]
+impl NonceGuard {
+    pub(crate) fn check(&self, _nonce: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    pub(crate) fn clear(&self) {}
+}
@@ -7,11 +7,16 @@
 use alloc::{string::String, vec::Vec};
 
 use crate::error::{QShieldError, Result};
-use crate::PROTOCOL_VERSION;
 
 /// Magic bytes identifying QuantumShield data
 pub const MAGIC: &[u8; 8] = b"QSHIELD\x00";
 
+/// Version of the object container described by [`Header`]
+///
+/// Separate from `PROTOCOL_VERSION`, so that handshake and message changes
+/// do not invalidate stored keys, signatures and ciphertexts.
+pub const FORMAT_VERSION: u8 = 1;
+
 /// Trait for serializable types
 pub trait Serialize {
     /// Serialize to bytes
@@ -34,7 +39,7 @@ pub trait Deserialize: Sized {
 pub struct Header {
     /// Magic bytes
     pub magic: [u8; 8],
-    /// Protocol version
+    /// Container format version
     pub version: u8,
     /// Object type
     pub object_type: ObjectType,
@@ -52,7 +57,7 @@ impl Header {
     pub fn new(object_type: ObjectType, payload_len: usize) -> Self {
         Self {
             magic: *MAGIC,
-            version: PROTOCOL_VERSION,
+            version: FORMAT_VERSION,
             object_type,
             flags: 0,
             payload_len: payload_len as u32,
@@ -87,9 +92,9 @@ impl Header {
         }
 
         let version = data[8];
-        if version != PROTOCOL_VERSION {
+        if version != FORMAT_VERSION {
             return Err(QShieldError::VersionMismatch {
-                expected: PROTOCOL_VERSION,
+                expected: FORMAT_VERSION,
                 actual: version,
             });
         }
@@ -152,8 +157,11 @@ pub fn write_length_prefixed(data: &[u8], buf: &mut Vec<u8>) {
 }
 
 /// Read a length-prefixed byte slice
+///
+/// The length is checked against the remaining input before allocating.
 pub fn read_length_prefixed(data: &[u8], offset: &mut usize) -> Result<Vec<u8>> {
-    if *offset + 4 > data.len() {
+    let start = offset.checked_add(4).ok_or(QShieldError::ParseError)?;
+    if start > data.len() {
         return Err(QShieldError::ParseError);
     }
 
@@ -164,14 +172,13 @@ pub fn read_length_prefixed(data: &[u8], offset: &mut usize) -> Result<Vec<u8>>
         data[*offset + 3],
     ]) as usize;
 
-    *offset += 4;
-
-    if *offset + len > data.len() {
+    let end = start.checked_add(len).ok_or(QShieldError::ParseError)?;
+    if end > data.len() {
         return Err(QShieldError::ParseError);
     }
 
-    let result = data[*offset..*offset + len].to_vec();
-    *offset += len;
+    let result = data[start..end].to_vec();
+    *offset = end;
 
     Ok(result)
 }
@@ -183,16 +190,17 @@ pub fn write_fixed<const N: usize>(data: &[u8; N], buf: &mut Vec<u8>) {
 
 /// Read a fixed-size array
 pub fn read_fixed<const N: usize>(data: &[u8], offset: &mut usize) -> Result<[u8; N]> {
-    if *offset + N > data.len() {
+    let end = offset.saturating_add(N);
+    if end > data.len() {
         return Err(QShieldError::BufferTooSmall {
-            needed: *offset + N,
+            needed: end,
             got: data.len(),
         });
     }
 
     let mut result = [0u8; N];
-    result.copy_from_slice(&data[*offset..*offset + N]);
-    *offset += N;
+    result.copy_from_slice(&data[*offset..end]);
+    *offset = end;
 
     Ok(result)
 }
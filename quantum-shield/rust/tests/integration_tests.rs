@@ -46,6 +46,22 @@ fn test_end_to_end_encryption() {
     assert_eq!(response.as_slice(), decrypted_response.as_slice());
 }
 
+/// Test the crate-level documentation example exactly as written
+#[test]
+fn test_documented_example() -> quantum_shield::Result<()> {
+    let (public_key, secret_key) = QShieldKEM::generate_keypair()?;
+    let (ciphertext, shared_secret) = QShieldKEM::encapsulate(&public_key)?;
+    let decapsulated = QShieldKEM::decapsulate(&secret_key, &ciphertext)?;
+
+    let cipher = QuantumShield::new(&shared_secret)?;
+    let encrypted = cipher.encrypt(b"Hello, quantum world!")?;
+
+    // The decapsulated secret yields the same cipher
+    let peer_cipher = QuantumShield::new(&decapsulated)?;
+    assert_eq!(peer_cipher.decrypt(&encrypted)?, b"Hello, quantum world!");
+    Ok(())
+}
+
 /// Test signed and encrypted message flow
 #[test]
 fn test_signed_encrypted_message() {
@@ -130,13 +146,13 @@ fn test_full_handshake_protocol() {
     for (i, msg) in messages.iter().enumerate() {
         if i % 2 == 0 {
             // Client to server
-            let encrypted = client_session.cipher.encrypt(msg).unwrap();
-            let decrypted = server_session.cipher.decrypt(&encrypted).unwrap();
+            let encrypted = client_session.send_cipher.encrypt(msg).unwrap();
+            let decrypted = server_session.recv_cipher.decrypt(&encrypted).unwrap();
             assert_eq!(*msg, decrypted.as_slice());
         } else {
             // Server to client
-            let encrypted = server_session.cipher.encrypt(msg).unwrap();
-            let decrypted = client_session.cipher.decrypt(&encrypted).unwrap();
+            let encrypted = server_session.send_cipher.encrypt(msg).unwrap();
+            let decrypted = client_session.recv_cipher.decrypt(&encrypted).unwrap();
             assert_eq!(*msg, decrypted.as_slice());
         }
     }
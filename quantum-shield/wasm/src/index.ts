@@ -31,6 +31,7 @@
 import wasmInit, {
   QShieldCipher as WasmQShieldCipher,
   QShieldSession as WasmQShieldSession,
+  SessionRole as WasmSessionRole,
   QShieldKeyExchange as WasmQShieldKeyExchange,
   QShieldHybridKEM as WasmQShieldHybridKEM,
   QShieldSign as WasmQShieldSign,
@@ -38,6 +39,7 @@ import wasmInit, {
   DualSignature as WasmDualSignature,
   HybridEncapsulation as WasmHybridEncapsulation,
   HybridCipherResult as WasmHybridCipherResult,
+  QShieldGroupMessage as WasmQShieldGroupMessage,
   secure_compare,
   info,
   demo,
@@ -81,6 +83,7 @@ export function isInitialized(): boolean {
 export {
   WasmQShieldCipher as QShieldCipher,
   WasmQShieldSession as QShieldSession,
+  WasmSessionRole as SessionRole,
   WasmQShieldKeyExchange as QShieldKeyExchange,
   WasmQShieldHybridKEM as QShieldHybridKEM,
   WasmQShieldSign as QShieldSign,
@@ -88,6 +91,7 @@ export {
   WasmDualSignature as DualSignature,
   WasmHybridEncapsulation as HybridEncapsulation,
   WasmHybridCipherResult as HybridCipherResult,
+  WasmQShieldGroupMessage as QShieldGroupMessage,
 };
 
 // Re-export utility functions
@@ -8,7 +8,7 @@
 //! 3. **Length hiding** with random padding (traffic analysis protection)
 //! 4. **Associated Data (AAD)** support for context binding
 //! 5. **Forward secrecy sessions** with HMAC-SHA3-256 key ratcheting
-//! 6. **Hybrid KEM** — X25519 + ML-KEM-768 (NIST FIPS 203, Level 3)
+//! 6. **Hybrid KEM** — X25519 + ML-KEM-768 (NIST FIPS 203, Level 3), or ML-KEM-512/1024 by suite
 //! 7. **Dual signatures** — ML-DSA-65 (FIPS 204) + SLH-DSA-SHAKE-128f (FIPS 205)
 //!
 //! # Security Model
@@ -23,22 +23,27 @@ use aes_gcm::{
 };
 use chacha20poly1305::{ChaCha20Poly1305, Nonce as ChaChaNonce};
 use x25519_dalek::{StaticSecret, PublicKey as X25519PublicKey};
-use fips203::ml_kem_768;
+use fips203::{ml_kem_1024, ml_kem_512, ml_kem_768};
 use fips203::traits::{Decaps, Encaps, KeyGen, SerDes};
 use fips204::ml_dsa_65;
+use fips204::Ph as DsaPh;
 use fips204::traits::{Signer as DsaSigner, Verifier as DsaVerifier, SerDes as DsaSerDes};
 use fips205::slh_dsa_shake_128f;
+use fips205::Ph as SlhPh;
 use fips205::traits::{Signer as SlhSigner, Verifier as SlhVerifier, SerDes as SlhSerDes};
 
 type MlDsaSignature = <ml_dsa_65::PrivateKey as DsaSigner>::Signature;
 type SlhDsaSignature = <slh_dsa_shake_128f::PrivateKey as SlhSigner>::Signature;
+/// Decoded authenticated header: `(name, value)` pairs in encoded order.
+type HeaderFields = Vec<(String, Vec<u8>)>;
 use hkdf::Hkdf;
-use sha3::{Sha3_256, Sha3_512};
+use sha3::{Digest, Sha3_256, Sha3_512};
 use hmac::{Hmac, Mac};
 use argon2::{Argon2, Algorithm, Version, Params};
 use zeroize::Zeroize;
-use subtle::ConstantTimeEq;
+use subtle::{ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::cell::Cell;
 
 // ============================================================================
 // CONSTANTS
@@ -47,8 +52,25 @@ use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 const AES_KEY_SIZE: usize = 32;
 const CHACHA_KEY_SIZE: usize = 32;
 const NONCE_SIZE: usize = 12;
-const VERSION_BYTE: u8 = 0x05; // Version 5 — Post-Quantum with Dual Signatures
-const HEADER_SIZE: usize = 1 + NONCE_SIZE + NONCE_SIZE; // version + 2 nonces
+const VERSION_BYTE: u8 = 0x08; // Version 8 — key commitment in the header
+const V7_VERSION_BYTE: u8 = 0x07; // Version 7 — AAD length bound into the layer AAD
+const V6_VERSION_BYTE: u8 = 0x06; // Version 6 — authenticated flags byte, unframed AAD
+const KEY_COMMITMENT_SIZE: usize = 32;
+const KEY_COMMITMENT_LABEL: &[u8] = b"QShield-key-commitment-v1"; // HMAC key for the commitment key
+const HEADER_SIZE: usize = 2 + KEY_COMMITMENT_SIZE + 2 * NONCE_SIZE; // version + flags + commitment + nonces
+const V6_HEADER_SIZE: usize = 2 + NONCE_SIZE + NONCE_SIZE; // versions 6-7: version + flags + 2 nonces
+const LEGACY_HEADER_SIZE: usize = 1 + NONCE_SIZE + NONCE_SIZE; // versions 1-5: version + 2 nonces
+
+const SEQUENCED_VERSION_BYTE: u8 = 0x86; // Version 6 layout with sequence-derived nonces: version + flags
+const SEQUENCED_HEADER_SIZE: usize = 2;
+
+const STREAM_VERSION_BYTE: u8 = 0x96; // Chunked stream header: version + flags + stream nonce
+const STREAM_HEADER_SIZE: usize = 2 + 2 * NONCE_SIZE;
+
+// Header flags (version 6+)
+const FLAG_PADDED: u8 = 0x01;
+const FLAG_KDF_SHA3_256: u8 = 0x02; // keys derived with KdfHash::Sha3_256
+const KNOWN_FLAGS: u8 = FLAG_PADDED | FLAG_KDF_SHA3_256;
 
 // Argon2id parameters — 19MB is WASM-safe while remaining GPU resistant
 const ARGON2_MEMORY_KB: u32 = 19456;
@@ -59,6 +81,70 @@ const ARGON2_PARALLELISM: u32 = 1;
 const MIN_PADDING: usize = 16;
 const PADDING_BLOCK_SIZE: usize = 64;
 
+// Dual signature wire format
+const MLDSA_SIGNATURE_SIZE: usize = 3309;
+const SLHDSA_SIGNATURE_SIZE: usize = 17088;
+const SIGNATURE_FORMAT_DUAL: u8 = 0x01; // [format][mldsa_len: u32 LE][mldsa][slhdsa]
+const DEFAULT_SIGNATURE_CONTEXT: &[u8] = b"QShield-DualSign-v1"; // FIPS 204/205 context string
+const PREHASH_SIGNATURE_CONTEXT: &[u8] = b"QShield-DualSign-prehash-v1"; // followed by the digest OID
+const COUNTERSIGNATURE_CONTEXT: &[u8] = b"QShield-DualSign-countersign-v1"; // signature ∥ timestamp
+const COSE_SIGN_TAG: u64 = 98; // CBOR tag for COSE_Sign (RFC 9052)
+const COSE_ALG_MLDSA65: i64 = -49; // ML-DSA-65 in the IANA COSE Algorithms registry
+const COSE_ALG_SLHDSA_SHAKE_128F: i64 = -65537; // private-use placeholder until registered
+const COSE_HEADER_ALG: i64 = 1;
+const CBOR_MAX_DEPTH: usize = 16; // nesting accepted when skipping unknown header values
+const SIGNING_SECRET_KEY_SIZE: usize = 4 + ml_dsa_65::SK_LEN + slh_dsa_shake_128f::SK_LEN; // [mldsa_len: u32 LE][mldsa sk][slhdsa sk]
+
+// Group messaging
+const GROUP_MESSAGE_VERSION: u8 = 0x02; // header authenticated as the payload's AAD
+const LEGACY_GROUP_MESSAGE_VERSION: u8 = 0x01; // unauthenticated header, still decrypts
+const CONTENT_KEY_SIZE: usize = 32;
+const RECIPIENT_ID_SIZE: usize = 32; // SHA3-256 of the recipient's hybrid public key
+
+// Envelopes, in the format shared with the core crate's QShieldEnvelope
+const ENVELOPE_VERSION: u8 = 0x01;
+const ENVELOPE_SUITE_CORE: u8 = 0x01; // QShieldKEM + QuantumShield (core crate)
+const ENVELOPE_SUITE_WASM: u8 = 0x02; // QShieldHybridKEM::seal + QShieldCipher
+const ENVELOPE_SUITE_HYBRID: u8 = 0x03; // X25519 + ML-KEM-768 wrapping, sealed by both SDKs
+const ENVELOPE_LABEL: &[u8] = b"QShieldEnvelope-v1"; // hybrid-suite HKDF salt
+const ENVELOPE_SINGLE_USE_NONCE: [u8; 12] = [0u8; 12]; // each hybrid-suite key encrypts once
+const ENVELOPE_HYBRID_WRAPPED_KEY_SIZE: usize =
+    32 + ml_kem_768::CT_LEN + CONTENT_KEY_SIZE + 16; // eph X25519 pk, ML-KEM ct, sealed key
+const ENVELOPE_MAX_RECIPIENTS: usize = 1024; // default decode limits, as in the core crate
+const ENVELOPE_MAX_SIZE: usize = 16 * 1024 * 1024;
+const ENVELOPE_MIN_RECIPIENT_SIZE: usize = RECIPIENT_ID_SIZE + 4; // id + empty wrapped key
+
+// Session messages: [version][msg_num: u64 LE][cipher output]
+const SESSION_FORMAT_SYMMETRIC: u8 = 0x01; // Symmetric hash ratchet
+// 0x02 is reserved for a future double-ratchet format
+const SESSION_MESSAGE_NUMBER_SIZE: usize = 8;
+const MAX_SKIPPED_MESSAGE_KEYS: usize = 1000; // per session; also caps one decrypt_with_skip gap
+
+// Authenticated headers
+const MAX_HEADER_FIELDS: usize = u16::MAX as usize;
+
+// Hybrid KEM ciphertext
+const HYBRID_KEM_VERSION: u8 = 0x02; // Transcript-bound combiner
+const LEGACY_KEM_CIPHERTEXT_SIZE: usize = 32 + 1088; // Unversioned v1: [x25519 pk][ML-KEM ct]
+const CLASSICAL_KEM_VERSION: u8 = 0x80; // X25519 only, for peers without an ML-KEM key
+const CLASSICAL_KEM_CIPHERTEXT_SIZE: usize = 1 + 32; // [tag][x25519 pk]
+
+// Hybrid KEM suites, numbered as the core crate's AlgorithmSuite
+const KEM_SUITE_DEFAULT: u8 = 0x01; // ML-KEM-768
+const KEM_SUITE_HIGH_SECURITY: u8 = 0x02; // ML-KEM-1024
+const KEM_SUITE_COMPACT: u8 = 0x03; // ML-KEM-512
+const KEM_SUITES: [u8; 3] = [KEM_SUITE_DEFAULT, KEM_SUITE_HIGH_SECURITY, KEM_SUITE_COMPACT];
+const LEGACY_HYBRID_PUBLIC_KEY_SIZE: usize = 32 + 1184; // Unprefixed: [x25519 pk][ML-KEM-768 ek]
+
+// Key derivation salts
+const KEY_DERIVATION_SALT: &[u8] = b"QShield-v4-pq";
+const MIN_SECRET_SIZE: usize = 16; // shorter secrets need from_bytes_with_options(.., true)
+const TEST_KEY_DERIVATION_SALT: &[u8] = b"QShield-insecure-test-key"; // never matches production keys
+
+// Substrings of secrets that look copied from tests or benchmarks
+#[cfg(all(feature = "test-key-warning", debug_assertions, target_arch = "wasm32"))]
+const KNOWN_TEST_KEY_MARKERS: &[&[u8]] = &[b"test-key", b"benchmark-key", b"tamper-test"];
+
 // ============================================================================
 // INITIALIZATION
 // ============================================================================
@@ -73,18 +159,56 @@ pub fn init() {
 // QSHIELD CIPHER — Cascading AES-256-GCM + ChaCha20-Poly1305
 // ============================================================================
 
+/// Hash used by HKDF when deriving a cipher from raw key bytes.
+///
+/// SHA3-512 is the default. SHA3-256 is cheaper on constrained targets and
+/// matches the hash `QShieldSession` uses for its chains. The choice changes
+/// every derived key, so both peers must agree on it; version 6+ ciphertexts
+/// record it in their flags so a mismatch is reported as such.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KdfHash {
+    /// HKDF-SHA3-512
+    #[default]
+    Sha3_512,
+    /// HKDF-SHA3-256
+    Sha3_256,
+}
+
+impl KdfHash {
+    /// Header flag recording this hash (none for the default)
+    fn flag(self) -> u8 {
+        match self {
+            KdfHash::Sha3_512 => 0,
+            KdfHash::Sha3_256 => FLAG_KDF_SHA3_256,
+        }
+    }
+}
+
 /// Cascading dual-layer symmetric cipher.
 ///
 /// Encrypts data first with AES-256-GCM, then wraps the result with
 /// ChaCha20-Poly1305. An attacker must break BOTH ciphers to recover plaintext.
 ///
-/// Keys are derived via Argon2id (from password) or HKDF-SHA3-512 (from bytes).
+/// Keys are derived via Argon2id (from password) or HKDF (from bytes;
+/// SHA3-512 unless another `KdfHash` is chosen).
 /// Length hiding padding is enabled by default for traffic analysis protection.
 #[wasm_bindgen]
 pub struct QShieldCipher {
     aes_cipher: Aes256Gcm,
     chacha_cipher: ChaCha20Poly1305,
     enable_padding: bool,
+    kdf_hash: KdfHash,
+    // Argon2id [memory KiB, iterations, parallelism] for password ciphers
+    argon2_params: Option<[u32; 3]>,
+    // Per-cipher nonce bases for sequenced encryption: [aes base][chacha base]
+    nonce_base: [u8; 2 * NONCE_SIZE],
+    // HMAC key committing version 8+ ciphertexts to this cipher's layer keys
+    commitment_key: [u8; KEY_COMMITMENT_SIZE],
+    last_sequence: Cell<Option<u64>>,
+    // Chunked streams in progress, one per direction
+    encrypt_stream: Cell<Option<StreamState>>,
+    decrypt_stream: Cell<Option<StreamState>>,
 }
 
 #[wasm_bindgen]
@@ -99,17 +223,36 @@ impl QShieldCipher {
     /// Create a cipher from a password with explicit padding control.
     #[wasm_bindgen]
     pub fn from_password_with_options(password: &str, enable_padding: bool) -> Result<QShieldCipher, JsValue> {
+        Self::from_password_with_params(
+            password,
+            ARGON2_MEMORY_KB,
+            ARGON2_ITERATIONS,
+            ARGON2_PARALLELISM,
+            enable_padding,
+        )
+    }
+
+    /// Create a cipher from a password with explicit Argon2id costs.
+    ///
+    /// The defaults (19456 KiB, 3 iterations, parallelism 1) give the same
+    /// keys as `from_password_with_options`. Data encrypted under other costs
+    /// can only be decrypted with the same costs, so store `argon2_params`
+    /// alongside it.
+    #[wasm_bindgen]
+    pub fn from_password_with_params(
+        password: &str,
+        memory_kb: u32,
+        iterations: u32,
+        parallelism: u32,
+        enable_padding: bool,
+    ) -> Result<QShieldCipher, JsValue> {
         let mut salt = [0u8; 16];
         let salt_hkdf = Hkdf::<Sha3_256>::new(None, password.as_bytes());
         salt_hkdf.expand(b"QShield-salt-v4-pq", &mut salt)
             .map_err(|_| JsValue::from_str("Salt derivation failed"))?;
 
-        let params = Params::new(
-            ARGON2_MEMORY_KB,
-            ARGON2_ITERATIONS,
-            ARGON2_PARALLELISM,
-            Some(64)
-        ).map_err(|_| JsValue::from_str("Invalid Argon2 parameters"))?;
+        let params = Params::new(memory_kb, iterations, parallelism, Some(64))
+            .map_err(|e| JsValue::from_str(&format!("Invalid Argon2 parameters: {}", e)))?;
 
         let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
 
@@ -120,42 +263,110 @@ impl QShieldCipher {
         let aes_cipher = Aes256Gcm::new(GenericArray::from_slice(&key_material[..32]));
         let chacha_cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&key_material[32..]));
 
+        let mut nonce_base = [0u8; 2 * NONCE_SIZE];
+        Hkdf::<Sha3_256>::new(None, &key_material)
+            .expand(b"QShield-nonce-base", &mut nonce_base)
+            .map_err(|_| JsValue::from_str("Key derivation failed"))?;
+
+        let commitment_key = commitment_key(&key_material[..32], &key_material[32..]);
         key_material.zeroize();
 
-        Ok(QShieldCipher { aes_cipher, chacha_cipher, enable_padding })
+        Ok(QShieldCipher {
+            aes_cipher,
+            chacha_cipher,
+            enable_padding,
+            kdf_hash: KdfHash::default(),
+            argon2_params: Some([memory_kb, iterations, parallelism]),
+            nonce_base,
+            commitment_key,
+            last_sequence: Cell::new(None),
+            encrypt_stream: Cell::new(None),
+            decrypt_stream: Cell::new(None),
+        })
     }
 
     /// Create a cipher from raw key bytes using HKDF-SHA3-512.
     /// Length hiding is enabled by default.
+    ///
+    /// The secret must be at least 16 bytes, to catch short constants used
+    /// as keys by mistake. Use `from_bytes_with_options` to accept shorter
+    /// secrets deliberately.
     #[wasm_bindgen]
     pub fn from_bytes(secret: &[u8]) -> Result<QShieldCipher, JsValue> {
-        let hk = Hkdf::<Sha3_512>::new(Some(b"QShield-v4-pq"), secret);
+        Self::from_bytes_with_context(secret, &[])
+    }
 
-        let mut aes_key = [0u8; AES_KEY_SIZE];
-        let mut chacha_key = [0u8; CHACHA_KEY_SIZE];
+    /// Create a cipher from raw key bytes, optionally accepting secrets
+    /// shorter than 16 bytes.
+    ///
+    /// HKDF cannot add entropy: a short secret gives keys no stronger than
+    /// the secret itself. Only set `allow_short` when that is understood.
+    #[wasm_bindgen]
+    pub fn from_bytes_with_options(secret: &[u8], allow_short: bool) -> Result<QShieldCipher, JsValue> {
+        if !allow_short {
+            check_secret_length(secret)?;
+        }
 
-        hk.expand(b"AES-256-GCM-layer", &mut aes_key)
-            .map_err(|_| JsValue::from_str("Key derivation failed"))?;
-        hk.expand(b"ChaCha20-Poly1305-layer", &mut chacha_key)
-            .map_err(|_| JsValue::from_str("Key derivation failed"))?;
+        #[cfg(all(feature = "test-key-warning", debug_assertions, target_arch = "wasm32"))]
+        warn_if_test_key(secret);
 
-        let aes_cipher = Aes256Gcm::new(GenericArray::from_slice(&aes_key));
-        let chacha_cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&chacha_key));
+        Self::derive_from_bytes(KEY_DERIVATION_SALT, secret, &[], KdfHash::default())
+    }
 
-        aes_key.zeroize();
-        chacha_key.zeroize();
+    /// Create a cipher from raw key bytes using HKDF with the given hash.
+    ///
+    /// `from_bytes` is `from_bytes_with_hash(secret, KdfHash.Sha3_512)`. A
+    /// cipher derived with one hash cannot decrypt data from the other.
+    #[wasm_bindgen]
+    pub fn from_bytes_with_hash(secret: &[u8], hash: KdfHash) -> Result<QShieldCipher, JsValue> {
+        check_secret_length(secret)?;
+
+        #[cfg(all(feature = "test-key-warning", debug_assertions, target_arch = "wasm32"))]
+        warn_if_test_key(secret);
+
+        Self::derive_from_bytes(KEY_DERIVATION_SALT, secret, &[], hash)
+    }
+
+    /// Create a cipher from raw key bytes bound to an application context.
+    ///
+    /// The context is appended to each layer's HKDF info, so the same secret
+    /// yields independent ciphers for different contexts. An empty context is
+    /// equivalent to `from_bytes`.
+    #[wasm_bindgen]
+    pub fn from_bytes_with_context(secret: &[u8], context: &[u8]) -> Result<QShieldCipher, JsValue> {
+        check_secret_length(secret)?;
 
-        Ok(QShieldCipher { aes_cipher, chacha_cipher, enable_padding: true })
+        #[cfg(all(feature = "test-key-warning", debug_assertions, target_arch = "wasm32"))]
+        warn_if_test_key(secret);
+
+        Self::derive_from_bytes(KEY_DERIVATION_SALT, secret, context, KdfHash::default())
+    }
+
+    /// Create a cipher for tests and benchmarks only.
+    ///
+    /// The key is derived from `label` under a dedicated salt, so it never
+    /// equals a key produced by `from_bytes`. Do not use in production.
+    #[wasm_bindgen]
+    pub fn insecure_test_key(label: &str) -> Result<QShieldCipher, JsValue> {
+        Self::derive_from_bytes(TEST_KEY_DERIVATION_SALT, label.as_bytes(), &[], KdfHash::default())
     }
 
     /// Encrypt data with additional authenticated data (AAD).
     /// AAD is authenticated but not encrypted — useful for binding ciphertext to context.
+    ///
+    /// The version and flags bytes and the AAD length are authenticated
+    /// alongside the AAD, so the padding flag cannot be flipped and the AAD
+    /// cannot be re-split with the surrounding framing without failing
+    /// decryption. The header also carries a commitment to the layer keys,
+    /// so the ciphertext only decrypts under the keys that produced it. The
+    /// commitment covers the message's nonces, so it differs per message and
+    /// does not link ciphertexts made under the same key.
     #[wasm_bindgen]
     pub fn encrypt_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, JsValue> {
-        let padded = if self.enable_padding {
-            self.apply_padding(plaintext)
+        let (padded, flags) = if self.enable_padding {
+            (self.apply_padding(plaintext), FLAG_PADDED | self.kdf_hash.flag())
         } else {
-            plaintext.to_vec()
+            (plaintext.to_vec(), self.kdf_hash.flag())
         };
 
         let mut aes_nonce = [0u8; NONCE_SIZE];
@@ -165,21 +376,15 @@ impl QShieldCipher {
         getrandom::getrandom(&mut chacha_nonce)
             .map_err(|_| JsValue::from_str("RNG failed"))?;
 
-        // Layer 1: AES-256-GCM
-        let aes_payload = Payload { msg: &padded, aad };
-        let aes_ct = self.aes_cipher
-            .encrypt(AesNonce::from_slice(&aes_nonce), aes_payload)
-            .map_err(|_| JsValue::from_str("AES encryption failed"))?;
-
-        // Layer 2: ChaCha20-Poly1305
-        let chacha_payload = Payload { msg: &aes_ct, aad };
-        let chacha_ct = self.chacha_cipher
-            .encrypt(ChaChaNonce::from_slice(&chacha_nonce), chacha_payload)
-            .map_err(|_| JsValue::from_str("ChaCha encryption failed"))?;
+        let layer_aad = length_bound_aad(VERSION_BYTE, flags, aad);
+        let chacha_ct = self.seal_layers(&padded, &aes_nonce, &chacha_nonce, &layer_aad)?;
+        let commitment = self.key_commitment(flags, &aes_nonce, &chacha_nonce);
 
-        // Format: [version][aes_nonce][chacha_nonce][ciphertext]
+        // Format: [version][flags][key_commitment][aes_nonce][chacha_nonce][ciphertext]
         let mut result = Vec::with_capacity(HEADER_SIZE + chacha_ct.len());
         result.push(VERSION_BYTE);
+        result.push(flags);
+        result.extend_from_slice(&commitment);
         result.extend_from_slice(&aes_nonce);
         result.extend_from_slice(&chacha_nonce);
         result.extend_from_slice(&chacha_ct);
@@ -188,37 +393,249 @@ impl QShieldCipher {
     }
 
     /// Decrypt data with additional authenticated data (AAD).
+    ///
+    /// Version 6+ ciphertexts carry an authenticated padding flag that is
+    /// honored regardless of this cipher's own padding setting. Older versions
+    /// fall back to the local setting. Version 6 and 7 ciphertexts, written
+    /// before the AAD length was bound and the keys committed, are still
+    /// accepted. A version 8 ciphertext made under other keys fails with
+    /// "Key commitment mismatch" before any AEAD work.
     #[wasm_bindgen]
     pub fn decrypt_with_aad(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, JsValue> {
-        if ciphertext.len() < HEADER_SIZE + 32 {
+        if ciphertext.len() < LEGACY_HEADER_SIZE + 32 {
             return Err(JsValue::from_str("Ciphertext too short"));
         }
 
         let version = ciphertext[0];
-        if version != VERSION_BYTE && version != 0x04 && version != 0x03 && version != 0x02 && version != 0x01 {
-            return Err(JsValue::from_str("Unsupported version"));
+        let (header_size, padded, layer_aad) = match version {
+            VERSION_BYTE | V7_VERSION_BYTE | V6_VERSION_BYTE => {
+                let header_size = if version == VERSION_BYTE { HEADER_SIZE } else { V6_HEADER_SIZE };
+                if ciphertext.len() < header_size + 32 {
+                    return Err(JsValue::from_str("Ciphertext too short"));
+                }
+                let flags = ciphertext[1];
+                self.check_flags(flags)?;
+                if version == VERSION_BYTE {
+                    self.check_key_commitment(&ciphertext[..HEADER_SIZE])?;
+                }
+                let layer_aad = if version == V6_VERSION_BYTE {
+                    versioned_aad(version, flags, aad)
+                } else {
+                    length_bound_aad(version, flags, aad)
+                };
+                (header_size, flags & FLAG_PADDED != 0, layer_aad)
+            }
+            0x03..=0x05 => (LEGACY_HEADER_SIZE, self.enable_padding, aad.to_vec()),
+            0x01..=0x02 => (LEGACY_HEADER_SIZE, false, aad.to_vec()),
+            _ => return Err(JsValue::from_str("Unsupported version")),
+        };
+
+        let aes_nonce = &ciphertext[header_size - 2 * NONCE_SIZE..header_size - NONCE_SIZE];
+        let chacha_nonce = &ciphertext[header_size - NONCE_SIZE..header_size];
+        let encrypted = &ciphertext[header_size..];
+
+        let padded_plaintext = self.open_layers(encrypted, aes_nonce, chacha_nonce, &layer_aad)?;
+
+        if padded {
+            self.remove_padding(&padded_plaintext)
+        } else {
+            Ok(padded_plaintext)
+        }
+    }
+
+    /// Encrypt with nonces derived from a sequence number instead of sent in the clear.
+    ///
+    /// Both layer nonces are the per-cipher nonce base XORed with `sender`
+    /// and `sequence`, saving 24 bytes per message. A repeated (sender,
+    /// sequence) pair under one key would break both AEAD layers, so:
+    ///
+    /// - every party or instance encrypting under the key must use its own
+    ///   `sender` (e.g. 0 for the client and 1 for the server);
+    /// - sequences must strictly increase per sender. This instance rejects
+    ///   a reused or smaller sequence, but cannot see other instances or
+    ///   earlier runs: persist `last_sequence` and restore it with
+    ///   `resume_sequence` before encrypting again after a restart.
+    ///
+    /// Format: [version][flags][ciphertext]
+    #[wasm_bindgen]
+    pub fn encrypt_with_nonce(
+        &self,
+        plaintext: &[u8],
+        aad: &[u8],
+        sender: u32,
+        sequence: u64,
+    ) -> Result<Vec<u8>, JsValue> {
+        if matches!(self.last_sequence.get(), Some(last) if sequence <= last) {
+            return Err(JsValue::from_str("Sequence number already used"));
         }
 
-        let aes_nonce = &ciphertext[1..1 + NONCE_SIZE];
-        let chacha_nonce = &ciphertext[1 + NONCE_SIZE..HEADER_SIZE];
-        let encrypted = &ciphertext[HEADER_SIZE..];
+        let (padded, flags) = if self.enable_padding {
+            (self.apply_padding(plaintext), FLAG_PADDED | self.kdf_hash.flag())
+        } else {
+            (plaintext.to_vec(), self.kdf_hash.flag())
+        };
 
-        // Reverse order: ChaCha20 first, then AES
-        let chacha_payload = Payload { msg: encrypted, aad };
-        let aes_ct = self.chacha_cipher
-            .decrypt(ChaChaNonce::from_slice(chacha_nonce), chacha_payload)
-            .map_err(|_| JsValue::from_str("Decryption failed"))?;
+        let (aes_nonce, chacha_nonce) = self.sequence_nonces(sender, sequence);
+        let layer_aad = versioned_aad(SEQUENCED_VERSION_BYTE, flags, aad);
+        let chacha_ct = self.seal_layers(&padded, &aes_nonce, &chacha_nonce, &layer_aad)?;
+        self.last_sequence.set(Some(sequence));
 
-        let aes_payload = Payload { msg: &aes_ct, aad };
-        let padded = self.aes_cipher
-            .decrypt(AesNonce::from_slice(aes_nonce), aes_payload)
-            .map_err(|_| JsValue::from_str("Decryption failed"))?;
+        let mut result = Vec::with_capacity(SEQUENCED_HEADER_SIZE + chacha_ct.len());
+        result.push(SEQUENCED_VERSION_BYTE);
+        result.push(flags);
+        result.extend_from_slice(&chacha_ct);
+
+        Ok(result)
+    }
 
-        if self.enable_padding && (version == VERSION_BYTE || version == 0x04 || version == 0x03) {
-            self.remove_padding(&padded)
+    /// Decrypt data produced by `encrypt_with_nonce` with the same sender and sequence.
+    #[wasm_bindgen]
+    pub fn decrypt_with_nonce(
+        &self,
+        ciphertext: &[u8],
+        aad: &[u8],
+        sender: u32,
+        sequence: u64,
+    ) -> Result<Vec<u8>, JsValue> {
+        if ciphertext.len() < SEQUENCED_HEADER_SIZE + 32 {
+            return Err(JsValue::from_str("Ciphertext too short"));
+        }
+        if ciphertext[0] != SEQUENCED_VERSION_BYTE {
+            return Err(JsValue::from_str("Unsupported version"));
+        }
+        let flags = ciphertext[1];
+        self.check_flags(flags)?;
+
+        let (aes_nonce, chacha_nonce) = self.sequence_nonces(sender, sequence);
+        let layer_aad = versioned_aad(SEQUENCED_VERSION_BYTE, flags, aad);
+        let padded_plaintext = self.open_layers(
+            &ciphertext[SEQUENCED_HEADER_SIZE..],
+            &aes_nonce,
+            &chacha_nonce,
+            &layer_aad,
+        )?;
+
+        if flags & FLAG_PADDED != 0 {
+            self.remove_padding(&padded_plaintext)
         } else {
-            Ok(padded)
+            Ok(padded_plaintext)
+        }
+    }
+
+    /// Highest sequence this instance has encrypted with `encrypt_with_nonce`.
+    ///
+    /// Persist this to resume safely after a restart.
+    #[wasm_bindgen]
+    pub fn last_sequence(&self) -> Option<u64> {
+        self.last_sequence.get()
+    }
+
+    /// Refuse sequences up to and including `last_used` from now on.
+    ///
+    /// Call with the persisted `last_sequence` before encrypting after a
+    /// restart. The floor never moves backwards.
+    #[wasm_bindgen]
+    pub fn resume_sequence(&self, last_used: u64) {
+        let floor = self.last_sequence.get().map_or(last_used, |last| last.max(last_used));
+        self.last_sequence.set(Some(floor));
+    }
+
+    /// Start a chunked encryption stream and return its header.
+    ///
+    /// The header carries fresh random nonce material for the stream; send it
+    /// once, ahead of the chunks. Calling this again abandons any stream in
+    /// progress on this cipher.
+    ///
+    /// Format: [version][flags][stream nonce (24)]
+    #[wasm_bindgen]
+    pub fn begin_stream(&self) -> Result<Vec<u8>, JsValue> {
+        let mut nonce_base = [0u8; 2 * NONCE_SIZE];
+        getrandom::getrandom(&mut nonce_base)
+            .map_err(|_| JsValue::from_str("RNG failed"))?;
+        let flags = self.kdf_hash.flag();
+        self.encrypt_stream.set(Some(StreamState::new(nonce_base, flags)));
+
+        let mut header = Vec::with_capacity(STREAM_HEADER_SIZE);
+        header.push(STREAM_VERSION_BYTE);
+        header.push(flags);
+        header.extend_from_slice(&nonce_base);
+        Ok(header)
+    }
+
+    /// Encrypt the next chunk of the stream started by `begin_stream`.
+    ///
+    /// Chunks are numbered from 0 and must be encrypted in order, the last
+    /// one with `is_final` set. Each chunk's layer nonces are the stream nonce
+    /// XORed with `chunk_index`, and the final flag is authenticated, so
+    /// reordered or truncated streams fail to decrypt. Chunks are not padded.
+    #[wasm_bindgen]
+    pub fn encrypt_chunk(&self, chunk: &[u8], chunk_index: u64, is_final: bool) -> Result<Vec<u8>, JsValue> {
+        let mut state = next_stream_state(&self.encrypt_stream, chunk_index)?;
+
+        let (aes_nonce, chacha_nonce) = layer_nonces(&state.nonce_base, chunk_index);
+        let layer_aad = versioned_aad(STREAM_VERSION_BYTE, state.flags, &[is_final as u8]);
+        let ciphertext = self.seal_layers(chunk, &aes_nonce, &chacha_nonce, &layer_aad)?;
+
+        state.advance(is_final);
+        self.encrypt_stream.set(Some(state));
+        Ok(ciphertext)
+    }
+
+    /// Start decrypting a stream from the header returned by `begin_stream`.
+    #[wasm_bindgen]
+    pub fn begin_decrypt_stream(&self, header: &[u8]) -> Result<(), JsValue> {
+        if header.len() != STREAM_HEADER_SIZE {
+            return Err(JsValue::from_str("Invalid stream header"));
+        }
+        if header[0] != STREAM_VERSION_BYTE {
+            return Err(JsValue::from_str("Unsupported version"));
+        }
+        let flags = header[1];
+        if flags & FLAG_PADDED != 0 {
+            return Err(JsValue::from_str("Unsupported header flags"));
         }
+        self.check_flags(flags)?;
+
+        let mut nonce_base = [0u8; 2 * NONCE_SIZE];
+        nonce_base.copy_from_slice(&header[2..]);
+        self.decrypt_stream.set(Some(StreamState::new(nonce_base, flags)));
+        Ok(())
+    }
+
+    /// Decrypt the next chunk of the stream started by `begin_decrypt_stream`.
+    ///
+    /// Chunks must arrive in order from index 0. Pass `is_final` for the last
+    /// chunk received: a stream cut short fails here, because its last chunk
+    /// was not encrypted as final. A receiver that cannot tell which chunk is
+    /// last must call `finish_decrypt_stream` once the input ends.
+    #[wasm_bindgen]
+    pub fn decrypt_chunk(&self, chunk: &[u8], chunk_index: u64, is_final: bool) -> Result<Vec<u8>, JsValue> {
+        let mut state = next_stream_state(&self.decrypt_stream, chunk_index)?;
+
+        let (aes_nonce, chacha_nonce) = layer_nonces(&state.nonce_base, chunk_index);
+        let layer_aad = versioned_aad(STREAM_VERSION_BYTE, state.flags, &[is_final as u8]);
+        let plaintext = self.open_layers(chunk, &aes_nonce, &chacha_nonce, &layer_aad)?;
+
+        state.advance(is_final);
+        self.decrypt_stream.set(Some(state));
+        Ok(plaintext)
+    }
+
+    /// End the stream started by `begin_decrypt_stream`.
+    ///
+    /// Fails if no chunk was decrypted as final, so a stream truncated at a
+    /// chunk boundary is caught even when every chunk was passed with
+    /// `is_final` false. The stream is closed either way.
+    #[wasm_bindgen]
+    pub fn finish_decrypt_stream(&self) -> Result<(), JsValue> {
+        let state = self
+            .decrypt_stream
+            .take()
+            .ok_or_else(|| JsValue::from_str("No stream in progress"))?;
+        if !state.finished {
+            return Err(JsValue::from_str("Stream ended without a final chunk"));
+        }
+        Ok(())
     }
 
     /// Encrypt data (no AAD).
@@ -267,18 +684,33 @@ impl QShieldCipher {
         result
     }
 
+    /// Strip `[len: u32 LE][content][padding]`.
+    ///
+    /// The length check and the copy touch every byte of the buffer whatever
+    /// the length prefix says, and all malformed inputs get the same error, so
+    /// neither timing nor the error reveals where the content ends.
     fn remove_padding(&self, padded: &[u8]) -> Result<Vec<u8>, JsValue> {
         if padded.len() < 4 {
-            return Err(JsValue::from_str("Invalid padded data"));
+            return Err(JsValue::from_str("Invalid padding"));
         }
 
-        let original_len = u32::from_le_bytes([padded[0], padded[1], padded[2], padded[3]]) as usize;
+        let (prefix, body) = padded.split_at(4);
+        let original_len = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as u64;
+        let valid = !original_len.ct_gt(&(body.len() as u64));
 
-        if original_len > padded.len() - 4 {
-            return Err(JsValue::from_str("Invalid padding length"));
+        let mut content = vec![0u8; body.len()];
+        for (i, (out, &byte)) in content.iter_mut().zip(body).enumerate() {
+            let in_content = original_len.ct_gt(&(i as u64));
+            *out = u8::conditional_select(&0, &byte, in_content);
         }
 
-        Ok(padded[4..4 + original_len].to_vec())
+        if !bool::from(valid) {
+            content.zeroize();
+            return Err(JsValue::from_str("Invalid padding"));
+        }
+        // Only reached with a valid prefix, so this reveals just the content length
+        content.truncate(original_len as usize);
+        Ok(content)
     }
 
     /// Get the encryption overhead in bytes.
@@ -296,152 +728,919 @@ impl QShieldCipher {
     pub fn has_length_hiding(&self) -> bool {
         self.enable_padding
     }
-}
 
-// ============================================================================
-// FORWARD SECRECY SESSION — Key ratcheting with HMAC-SHA3-256
-// ============================================================================
+    /// HKDF hash the keys were derived with.
+    ///
+    /// Password ciphers take their keys straight from Argon2id and report
+    /// `Sha3_512`, the hash their (unflagged) ciphertext headers record.
+    #[wasm_bindgen]
+    pub fn kdf_hash(&self) -> KdfHash {
+        self.kdf_hash
+    }
 
-/// Forward secrecy session with automatic key ratcheting.
-///
-/// Each message uses a unique derived key. After encryption/decryption,
-/// the chain key is ratcheted forward using HMAC-SHA3-256, making it
-/// impossible to decrypt past messages even if the current key is compromised.
-#[wasm_bindgen]
-pub struct QShieldSession {
-    chain_key: [u8; 32],
-    message_count: u64,
+    /// Argon2id `[memory_kb, iterations, parallelism]` of a password cipher,
+    /// for passing back to `from_password_with_params` on decryption.
+    /// Undefined for ciphers derived from bytes.
+    #[wasm_bindgen(getter)]
+    pub fn argon2_params(&self) -> Option<Vec<u32>> {
+        self.argon2_params.map(|params| params.to_vec())
+    }
 }
 
-#[wasm_bindgen]
-impl QShieldSession {
-    /// Create a new session from a shared secret (e.g., from KEM).
-    #[wasm_bindgen(constructor)]
-    pub fn new(shared_secret: &[u8]) -> Result<QShieldSession, JsValue> {
-        let hk = Hkdf::<Sha3_256>::new(Some(b"QShield-session-v1"), shared_secret);
-        let mut chain_key = [0u8; 32];
-        hk.expand(b"chain-key-init", &mut chain_key)
-            .map_err(|_| JsValue::from_str("Session init failed"))?;
+impl QShieldCipher {
+    /// Cascade encrypt: AES-256-GCM, then ChaCha20-Poly1305, both under `layer_aad`.
+    fn seal_layers(
+        &self,
+        plaintext: &[u8],
+        aes_nonce: &[u8],
+        chacha_nonce: &[u8],
+        layer_aad: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        // Layer 1: AES-256-GCM
+        let aes_payload = Payload { msg: plaintext, aad: layer_aad };
+        let aes_ct = self.aes_cipher
+            .encrypt(AesNonce::from_slice(aes_nonce), aes_payload)
+            .map_err(|_| JsValue::from_str("AES encryption failed"))?;
 
-        Ok(QShieldSession { chain_key, message_count: 0 })
+        // Layer 2: ChaCha20-Poly1305
+        let chacha_payload = Payload { msg: &aes_ct, aad: layer_aad };
+        self.chacha_cipher
+            .encrypt(ChaChaNonce::from_slice(chacha_nonce), chacha_payload)
+            .map_err(|_| JsValue::from_str("ChaCha encryption failed"))
     }
 
-    /// Encrypt a message with automatic key ratcheting.
-    #[wasm_bindgen]
-    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        let (message_key, new_chain_key) = self.ratchet()?;
-        self.chain_key = new_chain_key;
-        self.message_count += 1;
-
-        let cipher = QShieldCipher::from_bytes(&message_key)?;
-
-        let mut result = Vec::with_capacity(8 + plaintext.len() + cipher.overhead());
-        result.extend_from_slice(&(self.message_count - 1).to_le_bytes());
-        result.extend_from_slice(&cipher.encrypt(plaintext)?);
+    /// Cascade decrypt in reverse order: ChaCha20 first, then AES.
+    fn open_layers(
+        &self,
+        ciphertext: &[u8],
+        aes_nonce: &[u8],
+        chacha_nonce: &[u8],
+        layer_aad: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let chacha_payload = Payload { msg: ciphertext, aad: layer_aad };
+        let aes_ct = self.chacha_cipher
+            .decrypt(ChaChaNonce::from_slice(chacha_nonce), chacha_payload)
+            .map_err(|_| JsValue::from_str("Decryption failed"))?;
 
-        Ok(result)
+        let aes_payload = Payload { msg: &aes_ct, aad: layer_aad };
+        self.aes_cipher
+            .decrypt(AesNonce::from_slice(aes_nonce), aes_payload)
+            .map_err(|_| JsValue::from_str("Decryption failed"))
     }
 
-    /// Decrypt a message with automatic key ratcheting.
-    /// Messages must be decrypted in order.
-    #[wasm_bindgen]
-    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        if ciphertext.len() < 8 {
-            return Err(JsValue::from_str("Invalid session message"));
+    /// Layer nonces for `sender` and `sequence`, from this cipher's nonce base.
+    ///
+    /// The sequence occupies the last 8 nonce bytes and the sender the first 4.
+    fn sequence_nonces(&self, sender: u32, sequence: u64) -> ([u8; NONCE_SIZE], [u8; NONCE_SIZE]) {
+        let (mut aes_nonce, mut chacha_nonce) = layer_nonces(&self.nonce_base, sequence);
+        for (i, b) in sender.to_be_bytes().iter().enumerate() {
+            aes_nonce[i] ^= b;
+            chacha_nonce[i] ^= b;
         }
+        (aes_nonce, chacha_nonce)
+    }
 
-        let msg_num = u64::from_le_bytes([
-            ciphertext[0], ciphertext[1], ciphertext[2], ciphertext[3],
-            ciphertext[4], ciphertext[5], ciphertext[6], ciphertext[7],
-        ]);
+    /// Key commitment for one version 8 message: HMAC-SHA3-256 under the
+    /// commitment key over [version][flags][aes_nonce][chacha_nonce].
+    fn key_commitment(
+        &self,
+        flags: u8,
+        aes_nonce: &[u8],
+        chacha_nonce: &[u8],
+    ) -> [u8; KEY_COMMITMENT_SIZE] {
+        let mut mac = <Hmac<Sha3_256> as Mac>::new_from_slice(&self.commitment_key)
+            .expect("HMAC accepts any key length");
+        mac.update(&[VERSION_BYTE, flags]);
+        mac.update(aes_nonce);
+        mac.update(chacha_nonce);
+        mac.finalize().into_bytes().into()
+    }
 
-        if msg_num != self.message_count {
-            return Err(JsValue::from_str("Message out of order"));
+    /// Reject a version 8 `header` committed to keys other than this
+    /// cipher's, in constant time.
+    fn check_key_commitment(&self, header: &[u8]) -> Result<(), JsValue> {
+        let (commitment, nonces) = header[2..HEADER_SIZE].split_at(KEY_COMMITMENT_SIZE);
+        let (aes_nonce, chacha_nonce) = nonces.split_at(NONCE_SIZE);
+        let expected = self.key_commitment(header[1], aes_nonce, chacha_nonce);
+        if !bool::from(commitment.ct_eq(&expected)) {
+            return Err(JsValue::from_str("Key commitment mismatch"));
         }
-
-        let (message_key, new_chain_key) = self.ratchet()?;
-        self.chain_key = new_chain_key;
-        self.message_count += 1;
-
-        let cipher = QShieldCipher::from_bytes(&message_key)?;
-        cipher.decrypt(&ciphertext[8..])
+        Ok(())
     }
 
-    /// Get the current message count.
-    #[wasm_bindgen(getter)]
-    pub fn message_count(&self) -> u64 {
-        self.message_count
+    /// Reject unknown header flags, and flags naming a different KDF hash.
+    fn check_flags(&self, flags: u8) -> Result<(), JsValue> {
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(JsValue::from_str("Unsupported header flags"));
+        }
+        if flags & FLAG_KDF_SHA3_256 != self.kdf_hash.flag() {
+            return Err(JsValue::from_str("KDF hash mismatch"));
+        }
+        Ok(())
     }
 
-    fn ratchet(&self) -> Result<([u8; 32], [u8; 32]), JsValue> {
-        type HmacSha3 = Hmac<Sha3_256>;
+    /// Derive both layer keys from `secret` via HKDF under `salt`.
+    fn derive_from_bytes(
+        salt: &[u8],
+        secret: &[u8],
+        context: &[u8],
+        hash: KdfHash,
+    ) -> Result<QShieldCipher, JsValue> {
+        let expand = |info: &[&[u8]], okm: &mut [u8]| {
+            let result = match hash {
+                KdfHash::Sha3_512 => {
+                    Hkdf::<Sha3_512>::new(Some(salt), secret).expand_multi_info(info, okm)
+                }
+                KdfHash::Sha3_256 => {
+                    Hkdf::<Sha3_256>::new(Some(salt), secret).expand_multi_info(info, okm)
+                }
+            };
+            result.map_err(|_| JsValue::from_str("Key derivation failed"))
+        };
 
-        let mut mac = <HmacSha3 as Mac>::new_from_slice(&self.chain_key)
-            .map_err(|_| JsValue::from_str("HMAC init failed"))?;
-        mac.update(b"message-key");
-        mac.update(&self.message_count.to_le_bytes());
-        let message_key: [u8; 32] = mac.finalize().into_bytes().into();
+        let mut aes_key = [0u8; AES_KEY_SIZE];
+        let mut chacha_key = [0u8; CHACHA_KEY_SIZE];
 
-        let mut mac = <HmacSha3 as Mac>::new_from_slice(&self.chain_key)
-            .map_err(|_| JsValue::from_str("HMAC init failed"))?;
-        mac.update(b"chain-key-next");
-        let new_chain_key: [u8; 32] = mac.finalize().into_bytes().into();
+        expand(&[b"AES-256-GCM-layer", context], &mut aes_key)?;
+        expand(&[b"ChaCha20-Poly1305-layer", context], &mut chacha_key)?;
 
-        Ok((message_key, new_chain_key))
-    }
-}
+        let mut nonce_base = [0u8; 2 * NONCE_SIZE];
+        expand(&[b"nonce-base", context], &mut nonce_base)?;
 
-// ============================================================================
-// CLASSICAL KEY EXCHANGE — X25519 (backward compatibility)
-// ============================================================================
+        let aes_cipher = Aes256Gcm::new(GenericArray::from_slice(&aes_key));
+        let chacha_cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&chacha_key));
+        let commitment_key = commitment_key(&aes_key, &chacha_key);
 
-/// Classical X25519 Diffie-Hellman key exchange.
-///
-/// Provided for backward compatibility. For new applications,
-/// prefer `QShieldHybridKEM` which adds post-quantum security.
-#[wasm_bindgen]
-pub struct QShieldKeyExchange {
-    secret: StaticSecret,
-    public: X25519PublicKey,
-}
+        aes_key.zeroize();
+        chacha_key.zeroize();
 
-#[wasm_bindgen]
-impl QShieldKeyExchange {
-    /// Generate a new X25519 keypair.
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> QShieldKeyExchange {
-        let secret = StaticSecret::random_from_rng(rand_core::OsRng);
-        let public = X25519PublicKey::from(&secret);
-        QShieldKeyExchange { secret, public }
+        Ok(QShieldCipher {
+            aes_cipher,
+            chacha_cipher,
+            enable_padding: true,
+            kdf_hash: hash,
+            argon2_params: None,
+            nonce_base,
+            commitment_key,
+            last_sequence: Cell::new(None),
+            encrypt_stream: Cell::new(None),
+            decrypt_stream: Cell::new(None),
+        })
     }
 
-    /// Get the raw public key bytes (32 bytes).
-    #[wasm_bindgen(getter)]
-    pub fn public_key(&self) -> Vec<u8> {
-        self.public.as_bytes().to_vec()
+    /// Encrypt with a self-describing header that is stored in the clear and
+    /// authenticated together with `aad`.
+    ///
+    /// Fields are sorted by name and length-prefixed, so the header encoding
+    /// is canonical and cannot be confused with the AAD that follows it.
+    /// Duplicate field names are rejected.
+    ///
+    /// Format: [header_len: u32 LE][header][cascade ciphertext]
+    /// Header: [count: u16 LE]([name_len: u16 LE][name][value_len: u32 LE][value])*
+    pub fn encrypt_with_header(
+        &self,
+        plaintext: &[u8],
+        header_fields: &[(&str, &[u8])],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let header = encode_header_fields(header_fields)?;
+        let ciphertext = self.encrypt_with_aad(plaintext, &header_aad(&header, aad))?;
+
+        let mut result = Vec::with_capacity(4 + header.len() + ciphertext.len());
+        result.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        result.extend_from_slice(&header);
+        result.extend_from_slice(&ciphertext);
+        Ok(result)
     }
 
-    /// Get the public key as base64.
-    #[wasm_bindgen(getter)]
-    pub fn public_key_base64(&self) -> String {
-        BASE64.encode(self.public.as_bytes())
-    }
+    /// Decrypt data produced by `encrypt_with_header`, returning the header
+    /// fields (sorted by name) and the plaintext.
+    ///
+    /// The header is only returned once it has been authenticated.
+    pub fn decrypt_with_header(
+        &self,
+        data: &[u8],
+        aad: &[u8],
+    ) -> Result<(HeaderFields, Vec<u8>), JsValue> {
+        if data.len() < 4 {
+            return Err(JsValue::from_str("Ciphertext too short"));
+        }
 
-    /// Derive a cipher from a peer's public key via ECDH.
-    #[wasm_bindgen]
-    pub fn derive_cipher(&self, peer_public_key: &[u8]) -> Result<QShieldCipher, JsValue> {
-        if peer_public_key.len() != 32 {
-            return Err(JsValue::from_str("Invalid public key length"));
+        let header_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if data.len() - 4 < header_len {
+            return Err(JsValue::from_str("Invalid header length"));
         }
 
-        let mut pk_bytes = [0u8; 32];
-        pk_bytes.copy_from_slice(peer_public_key);
-        let peer_pk = X25519PublicKey::from(pk_bytes);
-        let shared_secret = self.secret.diffie_hellman(&peer_pk);
+        let (header, ciphertext) = data[4..].split_at(header_len);
+        let plaintext = self.decrypt_with_aad(ciphertext, &header_aad(header, aad))?;
+        let fields = decode_header_fields(header)?;
 
-        QShieldCipher::from_bytes(shared_secret.as_bytes())
+        Ok((fields, plaintext))
     }
-}
+
+    /// Encrypt with AAD given as named fields instead of a raw byte string.
+    ///
+    /// The fields are canonicalized like `encrypt_with_header` (sorted by
+    /// name, length-prefixed) but are not stored in the ciphertext; the
+    /// decrypting side must supply the same fields, in any order.
+    pub fn encrypt_with_named_aad(
+        &self,
+        plaintext: &[u8],
+        aad_fields: &[(&str, &[u8])],
+    ) -> Result<Vec<u8>, JsValue> {
+        let aad = encode_header_fields(aad_fields)?;
+        self.encrypt_with_aad(plaintext, &aad)
+    }
+
+    /// Decrypt data produced by `encrypt_with_named_aad`.
+    ///
+    /// The canonical AAD is rebuilt from `aad_fields`, so field order does not
+    /// matter. An AEAD authentication failure is reported as a named-AAD
+    /// mismatch; other errors (bad format, unsupported version, wrong key
+    /// commitment) pass through unchanged.
+    pub fn decrypt_with_named_aad(
+        &self,
+        ciphertext: &[u8],
+        aad_fields: &[(&str, &[u8])],
+    ) -> Result<Vec<u8>, JsValue> {
+        let aad = encode_header_fields(aad_fields)?;
+        self.decrypt_with_aad(ciphertext, &aad).map_err(|e| {
+            if e.as_string().as_deref() == Some("Decryption failed") {
+                JsValue::from_str("Named AAD fields do not authenticate")
+            } else {
+                e
+            }
+        })
+    }
+
+    /// Encrypt with the header and nonces returned separately from the ciphertext.
+    ///
+    /// For storage schemes that keep the nonces in their own column. The two
+    /// parts concatenated are exactly the `encrypt_with_aad` output, and the
+    /// header is authenticated the same way.
+    ///
+    /// Returns: ([version][flags][aes_nonce][chacha_nonce], ciphertext)
+    pub fn encrypt_detached(
+        &self,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), JsValue> {
+        let mut header = self.encrypt_with_aad(plaintext, aad)?;
+        let ciphertext = header.split_off(HEADER_SIZE);
+        Ok((header, ciphertext))
+    }
+
+    /// Decrypt data produced by `encrypt_detached`.
+    ///
+    /// The header must be exactly the size its version byte calls for, so
+    /// bytes cannot be shifted between the header and the ciphertext.
+    pub fn decrypt_detached(
+        &self,
+        header: &[u8],
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let expected_size = match header.first() {
+            Some(&VERSION_BYTE) => HEADER_SIZE,
+            Some(&V7_VERSION_BYTE) | Some(&V6_VERSION_BYTE) => V6_HEADER_SIZE,
+            Some(0x01..=0x05) => LEGACY_HEADER_SIZE,
+            Some(_) => return Err(JsValue::from_str("Unsupported version")),
+            None => return Err(JsValue::from_str("Invalid header length")),
+        };
+        if header.len() != expected_size {
+            return Err(JsValue::from_str("Invalid header length"));
+        }
+
+        let mut combined = Vec::with_capacity(header.len() + ciphertext.len());
+        combined.extend_from_slice(header);
+        combined.extend_from_slice(ciphertext);
+        self.decrypt_with_aad(&combined, aad)
+    }
+}
+
+/// Reject secrets shorter than `MIN_SECRET_SIZE`.
+fn check_secret_length(secret: &[u8]) -> Result<(), JsValue> {
+    if secret.len() < MIN_SECRET_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Secret too short: expected at least {} bytes, got {}",
+            MIN_SECRET_SIZE,
+            secret.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Layer nonces for `counter`: each nonce base XORed with the big-endian counter.
+fn layer_nonces(nonce_base: &[u8; 2 * NONCE_SIZE], counter: u64) -> ([u8; NONCE_SIZE], [u8; NONCE_SIZE]) {
+    let mut aes_nonce = [0u8; NONCE_SIZE];
+    let mut chacha_nonce = [0u8; NONCE_SIZE];
+    aes_nonce.copy_from_slice(&nonce_base[..NONCE_SIZE]);
+    chacha_nonce.copy_from_slice(&nonce_base[NONCE_SIZE..]);
+
+    for (i, b) in counter.to_be_bytes().iter().enumerate() {
+        aes_nonce[NONCE_SIZE - 8 + i] ^= b;
+        chacha_nonce[NONCE_SIZE - 8 + i] ^= b;
+    }
+    (aes_nonce, chacha_nonce)
+}
+
+/// Progress of a chunked stream in one direction.
+#[derive(Clone, Copy)]
+struct StreamState {
+    nonce_base: [u8; 2 * NONCE_SIZE],
+    flags: u8,
+    next_index: u64,
+    finished: bool,
+}
+
+impl StreamState {
+    fn new(nonce_base: [u8; 2 * NONCE_SIZE], flags: u8) -> Self {
+        StreamState { nonce_base, flags, next_index: 0, finished: false }
+    }
+
+    fn advance(&mut self, is_final: bool) {
+        self.next_index += 1;
+        self.finished = is_final;
+    }
+}
+
+/// The stream in `slot`, if it is open and expects `chunk_index` next.
+fn next_stream_state(slot: &Cell<Option<StreamState>>, chunk_index: u64) -> Result<StreamState, JsValue> {
+    let state = slot.get().ok_or_else(|| JsValue::from_str("No stream in progress"))?;
+    if state.finished {
+        return Err(JsValue::from_str("Stream already finished"));
+    }
+    if chunk_index != state.next_index {
+        return Err(JsValue::from_str("Chunk out of order"));
+    }
+    Ok(state)
+}
+
+/// Key committing the per-message key commitments: HMAC-SHA3-256 keyed with
+/// `KEY_COMMITMENT_LABEL` over [aes_key][chacha_key]. Never sent.
+fn commitment_key(aes_key: &[u8], chacha_key: &[u8]) -> [u8; KEY_COMMITMENT_SIZE] {
+    let mut mac = <Hmac<Sha3_256> as Mac>::new_from_slice(KEY_COMMITMENT_LABEL)
+        .expect("HMAC accepts any key length");
+    mac.update(aes_key);
+    mac.update(chacha_key);
+    mac.finalize().into_bytes().into()
+}
+
+/// Layer AAD for version 6 and the sequenced and stream formats:
+/// [version][flags][aad]
+fn versioned_aad(version: u8, flags: u8, aad: &[u8]) -> Vec<u8> {
+    let mut combined = Vec::with_capacity(2 + aad.len());
+    combined.push(version);
+    combined.push(flags);
+    combined.extend_from_slice(aad);
+    combined
+}
+
+/// Layer AAD for version 7+: [version][flags][aad_len: u64 BE][aad]
+fn length_bound_aad(version: u8, flags: u8, aad: &[u8]) -> Vec<u8> {
+    let mut combined = Vec::with_capacity(2 + 8 + aad.len());
+    combined.push(version);
+    combined.push(flags);
+    combined.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+    combined.extend_from_slice(aad);
+    combined
+}
+
+/// Log a console warning when a secret looks like a test or benchmark key.
+#[cfg(all(feature = "test-key-warning", debug_assertions, target_arch = "wasm32"))]
+fn warn_if_test_key(secret: &[u8]) {
+    let lower = secret.to_ascii_lowercase();
+    let looks_like_test_key = KNOWN_TEST_KEY_MARKERS
+        .iter()
+        .any(|marker| lower.windows(marker.len()).any(|w| w == *marker));
+
+    if looks_like_test_key {
+        web_sys::console::warn_1(&JsValue::from_str(
+            "QShieldCipher: secret looks like a test/benchmark key; use a random key in production",
+        ));
+    }
+}
+
+/// AAD for header-authenticated encryption: [header_len: u32 LE][header][aad]
+fn header_aad(header: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut combined = Vec::with_capacity(4 + header.len() + aad.len());
+    combined.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    combined.extend_from_slice(header);
+    combined.extend_from_slice(aad);
+    combined
+}
+
+fn encode_header_fields(fields: &[(&str, &[u8])]) -> Result<Vec<u8>, JsValue> {
+    if fields.len() > MAX_HEADER_FIELDS {
+        return Err(JsValue::from_str("Too many header fields"));
+    }
+
+    let mut sorted = fields.to_vec();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    if sorted.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(JsValue::from_str("Duplicate header field"));
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(sorted.len() as u16).to_le_bytes());
+    for (name, value) in sorted {
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| JsValue::from_str("Header field name too long"))?;
+        let value_len = u32::try_from(value.len())
+            .map_err(|_| JsValue::from_str("Header field value too long"))?;
+
+        header.extend_from_slice(&name_len.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        header.extend_from_slice(&value_len.to_le_bytes());
+        header.extend_from_slice(value);
+    }
+    Ok(header)
+}
+
+fn decode_header_fields(header: &[u8]) -> Result<HeaderFields, JsValue> {
+    let invalid = || JsValue::from_str("Invalid header format");
+
+    if header.len() < 2 {
+        return Err(invalid());
+    }
+
+    let count = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let mut offset = 2;
+    let mut fields = Vec::with_capacity(count.min(header.len() / 6));
+
+    for _ in 0..count {
+        if header.len() - offset < 2 {
+            return Err(invalid());
+        }
+        let name_len = u16::from_le_bytes([header[offset], header[offset + 1]]) as usize;
+        offset += 2;
+
+        if header.len() - offset < name_len + 4 {
+            return Err(invalid());
+        }
+        let name = String::from_utf8(header[offset..offset + name_len].to_vec())
+            .map_err(|_| invalid())?;
+        offset += name_len;
+
+        let len_bytes = &header[offset..offset + 4];
+        let value_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+        offset += 4;
+
+        if header.len() - offset < value_len {
+            return Err(invalid());
+        }
+        fields.push((name, header[offset..offset + value_len].to_vec()));
+        offset += value_len;
+    }
+
+    if offset != header.len() {
+        return Err(invalid());
+    }
+
+    Ok(fields)
+}
+
+// ============================================================================
+// BASE64 STREAMING — Encode ciphertext chunk by chunk for downloads
+// ============================================================================
+
+/// Incremental base64 encoder.
+///
+/// Feed ciphertext in arbitrary slices (the output of `encrypt`, or one
+/// `encrypt_with_nonce` record at a time) and write each returned piece out
+/// as it arrives. The pieces concatenate to exactly the standard padded
+/// base64 that `encrypt_string` produces, without holding the whole string.
+#[wasm_bindgen]
+pub struct QShieldBase64Encoder {
+    pending: Vec<u8>, // fewer than 3 bytes awaiting a complete group
+}
+
+#[wasm_bindgen]
+impl QShieldBase64Encoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> QShieldBase64Encoder {
+        QShieldBase64Encoder { pending: Vec::with_capacity(3) }
+    }
+
+    /// Encode the next chunk, holding back a trailing partial group.
+    #[wasm_bindgen]
+    pub fn update(&mut self, chunk: &[u8]) -> String {
+        let mut encoded = String::new();
+        let mut rest = chunk;
+
+        if !self.pending.is_empty() {
+            let take = (3 - self.pending.len()).min(rest.len());
+            self.pending.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.pending.len() < 3 {
+                return encoded;
+            }
+            BASE64.encode_string(&self.pending, &mut encoded);
+            self.pending.clear();
+        }
+
+        let complete = rest.len() - rest.len() % 3;
+        BASE64.encode_string(&rest[..complete], &mut encoded);
+        self.pending.extend_from_slice(&rest[complete..]);
+        encoded
+    }
+
+    /// Encode any held-back bytes with padding and reset for a new stream.
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> String {
+        let encoded = BASE64.encode(&self.pending);
+        self.pending.clear();
+        encoded
+    }
+}
+
+impl Default for QShieldBase64Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Incremental base64 decoder, the inverse of `QShieldBase64Encoder`.
+///
+/// Accepts the same input as `decrypt_string` split at any point. Decoded
+/// bytes are returned as soon as a full 4-character group is available.
+#[wasm_bindgen]
+pub struct QShieldBase64Decoder {
+    pending: String, // fewer than 4 characters awaiting a complete group
+    padded: bool,    // a padded group was decoded; nothing may follow it
+}
+
+#[wasm_bindgen]
+impl QShieldBase64Decoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> QShieldBase64Decoder {
+        QShieldBase64Decoder { pending: String::with_capacity(4), padded: false }
+    }
+
+    /// Decode the next chunk of base64 text.
+    #[wasm_bindgen]
+    pub fn update(&mut self, chunk: &str) -> Result<Vec<u8>, JsValue> {
+        if !chunk.is_ascii() || (self.padded && !chunk.is_empty()) {
+            return Err(JsValue::from_str("Invalid base64"));
+        }
+
+        self.pending.push_str(chunk);
+        let complete = self.pending.len() - self.pending.len() % 4;
+        let decoded = BASE64.decode(&self.pending[..complete])
+            .map_err(|_| JsValue::from_str("Invalid base64"))?;
+
+        self.padded |= self.pending[..complete].ends_with('=');
+        self.pending.drain(..complete);
+        Ok(decoded)
+    }
+
+    /// Check that the stream ended on a group boundary and reset for a new stream.
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> Result<(), JsValue> {
+        let truncated = !self.pending.is_empty();
+        self.pending.clear();
+        self.padded = false;
+        if truncated {
+            return Err(JsValue::from_str("Truncated base64"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for QShieldBase64Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// FORWARD SECRECY SESSION — Key ratcheting with HMAC-SHA3-256
+// ============================================================================
+
+/// Role of a peer in a directional session.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionRole {
+    /// The peer that started the exchange (e.g. the KEM encapsulator).
+    Initiator,
+    /// The peer that answered it.
+    Responder,
+}
+
+/// Forward secrecy session with automatic key ratcheting.
+///
+/// Each message uses a unique derived key. After encryption/decryption,
+/// the chain key is ratcheted forward using HMAC-SHA3-256, making it
+/// impossible to decrypt past messages even if the current key is compromised.
+///
+/// Sessions created with `new` share one chain between both directions;
+/// `new_with_directions` keeps independent send and receive chains.
+///
+/// Messages start with a format version byte. Unversioned messages from
+/// older releases are rejected unless `set_accept_legacy_format(true)` is set.
+///
+/// `decrypt_with_skip` tolerates dropped and reordered messages by keeping
+/// the keys of messages skipped over until they arrive.
+#[wasm_bindgen]
+pub struct QShieldSession {
+    send: ChainState,
+    recv: Option<ChainState>,
+    accept_legacy_format: bool,
+    // Keys of skipped-over messages, oldest first
+    skipped_keys: Vec<(u64, [u8; 32])>,
+}
+
+#[derive(Clone)]
+struct ChainState {
+    chain_key: [u8; 32],
+    message_count: u64,
+}
+
+#[wasm_bindgen]
+impl QShieldSession {
+    /// Create a new session from a shared secret (e.g., from KEM).
+    #[wasm_bindgen(constructor)]
+    pub fn new(shared_secret: &[u8]) -> Result<QShieldSession, JsValue> {
+        let chain_key = derive_chain_key(shared_secret, b"chain-key-init")?;
+
+        Ok(QShieldSession {
+            send: ChainState { chain_key, message_count: 0 },
+            recv: None,
+            accept_legacy_format: false,
+            skipped_keys: Vec::new(),
+        })
+    }
+
+    /// Create a session with separate send and receive chains.
+    ///
+    /// Each direction is derived with its own HKDF label, so the initiator's
+    /// send chain is the responder's receive chain and vice versa. Both peers
+    /// can encrypt concurrently without ever reusing a message key.
+    #[wasm_bindgen]
+    pub fn new_with_directions(shared_secret: &[u8], role: SessionRole) -> Result<QShieldSession, JsValue> {
+        let initiator = derive_chain_key(shared_secret, b"chain-key-initiator")?;
+        let responder = derive_chain_key(shared_secret, b"chain-key-responder")?;
+
+        let (send_key, recv_key) = match role {
+            SessionRole::Initiator => (initiator, responder),
+            SessionRole::Responder => (responder, initiator),
+        };
+
+        Ok(QShieldSession {
+            send: ChainState { chain_key: send_key, message_count: 0 },
+            recv: Some(ChainState { chain_key: recv_key, message_count: 0 }),
+            accept_legacy_format: false,
+            skipped_keys: Vec::new(),
+        })
+    }
+
+    /// Encrypt a message with automatic key ratcheting.
+    #[wasm_bindgen]
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let (msg_num, message_key) = self.send.advance()?;
+
+        let cipher = QShieldCipher::from_bytes(&message_key)?;
+
+        let capacity = 1 + SESSION_MESSAGE_NUMBER_SIZE + plaintext.len() + cipher.overhead();
+        let mut result = Vec::with_capacity(capacity);
+        result.push(SESSION_FORMAT_SYMMETRIC);
+        result.extend_from_slice(&msg_num.to_le_bytes());
+        result.extend_from_slice(&cipher.encrypt(plaintext)?);
+
+        Ok(result)
+    }
+
+    /// Decrypt a message with automatic key ratcheting.
+    /// Messages must be decrypted in order.
+    #[wasm_bindgen]
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let chain = self.recv.as_mut().unwrap_or(&mut self.send);
+        let expected = chain.message_count;
+
+        let (msg_num, body) =
+            split_session_ciphertext(ciphertext, self.accept_legacy_format, |n| n == expected)?;
+
+        if msg_num != expected {
+            return Err(JsValue::from_str("Message out of order"));
+        }
+
+        let (_, message_key) = chain.advance()?;
+
+        let cipher = QShieldCipher::from_bytes(&message_key)?;
+        cipher.decrypt(body)
+    }
+
+    /// Decrypt a message that may arrive after later ones, or never.
+    ///
+    /// A message up to `max_skip` ahead of the receive chain ratchets it
+    /// forward, keeping the keys of the messages skipped over; those messages
+    /// can then still be decrypted when they arrive. The chain only moves if
+    /// the message decrypts. At most 1000 skipped keys are kept (the oldest
+    /// are dropped first), and `max_skip` is capped at the same value.
+    #[wasm_bindgen]
+    pub fn decrypt_with_skip(&mut self, ciphertext: &[u8], max_skip: u64) -> Result<Vec<u8>, JsValue> {
+        let chain = self.recv.as_mut().unwrap_or(&mut self.send);
+        let expected = chain.message_count;
+        let max_skip = max_skip.min(MAX_SKIPPED_MESSAGE_KEYS as u64);
+        let skipped_keys = &mut self.skipped_keys;
+
+        let (msg_num, body) = split_session_ciphertext(ciphertext, self.accept_legacy_format, |n| {
+            n.checked_sub(expected).is_some_and(|gap| gap <= max_skip)
+                || skipped_keys.iter().any(|(skipped, _)| *skipped == n)
+        })?;
+
+        // An earlier message, delivered late
+        if msg_num < expected {
+            let index = skipped_keys
+                .iter()
+                .position(|(skipped, _)| *skipped == msg_num)
+                .ok_or_else(|| JsValue::from_str("Message out of order"))?;
+            let plaintext = QShieldCipher::from_bytes(&skipped_keys[index].1)?.decrypt(body)?;
+            skipped_keys.remove(index).1.zeroize();
+            return Ok(plaintext);
+        }
+
+        if msg_num - expected > max_skip {
+            return Err(JsValue::from_str("Too many skipped messages"));
+        }
+
+        // Ratchet a copy, so a forged message number cannot move the chain
+        let mut ratcheted = chain.clone();
+        let mut skipped = Vec::new();
+        while ratcheted.message_count < msg_num {
+            skipped.push(ratcheted.advance()?);
+        }
+        let (_, message_key) = ratcheted.advance()?;
+        let plaintext = QShieldCipher::from_bytes(&message_key)?.decrypt(body)?;
+
+        *chain = ratcheted;
+        skipped_keys.extend(skipped);
+        if skipped_keys.len() > MAX_SKIPPED_MESSAGE_KEYS {
+            let excess = skipped_keys.len() - MAX_SKIPPED_MESSAGE_KEYS;
+            skipped_keys.drain(..excess).for_each(|(_, mut key)| key.zeroize());
+        }
+        Ok(plaintext)
+    }
+
+    /// Number of skipped-message keys held for late delivery.
+    #[wasm_bindgen(getter)]
+    pub fn skipped_message_count(&self) -> usize {
+        self.skipped_keys.len()
+    }
+
+    /// Accept unversioned session messages from older releases on decrypt.
+    ///
+    /// Off by default. Enable only while peers are migrating; new messages are
+    /// always written in the versioned format.
+    #[wasm_bindgen]
+    pub fn set_accept_legacy_format(&mut self, accept: bool) {
+        self.accept_legacy_format = accept;
+    }
+
+    /// Get the current message count (sent and received).
+    #[wasm_bindgen(getter)]
+    pub fn message_count(&self) -> u64 {
+        self.send.message_count + self.recv.as_ref().map_or(0, |recv| recv.message_count)
+    }
+}
+
+impl ChainState {
+    /// Derive the current message key, ratchet the chain forward, and
+    /// return the message number the key belongs to.
+    fn advance(&mut self) -> Result<(u64, [u8; 32]), JsValue> {
+        let (message_key, new_chain_key) = self.ratchet()?;
+        let msg_num = self.message_count;
+        self.chain_key = new_chain_key;
+        self.message_count += 1;
+        Ok((msg_num, message_key))
+    }
+
+    fn ratchet(&self) -> Result<([u8; 32], [u8; 32]), JsValue> {
+        type HmacSha3 = Hmac<Sha3_256>;
+
+        let mut mac = <HmacSha3 as Mac>::new_from_slice(&self.chain_key)
+            .map_err(|_| JsValue::from_str("HMAC init failed"))?;
+        mac.update(b"message-key");
+        mac.update(&self.message_count.to_le_bytes());
+        let message_key: [u8; 32] = mac.finalize().into_bytes().into();
+
+        let mut mac = <HmacSha3 as Mac>::new_from_slice(&self.chain_key)
+            .map_err(|_| JsValue::from_str("HMAC init failed"))?;
+        mac.update(b"chain-key-next");
+        let new_chain_key: [u8; 32] = mac.finalize().into_bytes().into();
+
+        Ok((message_key, new_chain_key))
+    }
+}
+
+/// Split a versioned (or, if accepted, legacy) session message into its
+/// message number and cipher output.
+///
+/// An unversioned message number can start with the version byte, so when
+/// legacy messages are accepted the versioned reading is only taken if its
+/// number is `plausible` for the receive chain.
+fn split_session_ciphertext(
+    ciphertext: &[u8],
+    accept_legacy_format: bool,
+    plausible: impl Fn(u64) -> bool,
+) -> Result<(u64, &[u8]), JsValue> {
+    let versioned = parse_session_message(ciphertext.get(1..).unwrap_or_default());
+    match (ciphertext.first(), versioned) {
+        (Some(&SESSION_FORMAT_SYMMETRIC), Some((msg_num, body)))
+            if plausible(msg_num) || !accept_legacy_format =>
+        {
+            Ok((msg_num, body))
+        }
+        _ if accept_legacy_format => parse_session_message(ciphertext)
+            .ok_or_else(|| JsValue::from_str("Invalid session message")),
+        (Some(_), Some(_)) => Err(JsValue::from_str("Unsupported session message version")),
+        _ => Err(JsValue::from_str("Invalid session message")),
+    }
+}
+
+/// Split a session message body into its message number and cipher output.
+fn parse_session_message(data: &[u8]) -> Option<(u64, &[u8])> {
+    if data.len() < SESSION_MESSAGE_NUMBER_SIZE {
+        return None;
+    }
+    let (msg_num, body) = data.split_at(SESSION_MESSAGE_NUMBER_SIZE);
+    Some((u64::from_le_bytes(msg_num.try_into().ok()?), body))
+}
+
+fn derive_chain_key(shared_secret: &[u8], label: &[u8]) -> Result<[u8; 32], JsValue> {
+    let hk = Hkdf::<Sha3_256>::new(Some(b"QShield-session-v1"), shared_secret);
+    let mut chain_key = [0u8; 32];
+    hk.expand(label, &mut chain_key)
+        .map_err(|_| JsValue::from_str("Session init failed"))?;
+    Ok(chain_key)
+}
+
+// ============================================================================
+// CLASSICAL KEY EXCHANGE — X25519 (backward compatibility)
+// ============================================================================
+
+/// Classical X25519 Diffie-Hellman key exchange.
+///
+/// Provided for backward compatibility. For new applications,
+/// prefer `QShieldHybridKEM` which adds post-quantum security.
+#[wasm_bindgen]
+pub struct QShieldKeyExchange {
+    secret: StaticSecret,
+    public: X25519PublicKey,
+}
+
+#[wasm_bindgen]
+impl QShieldKeyExchange {
+    /// Generate a new X25519 keypair.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> QShieldKeyExchange {
+        let secret = StaticSecret::random_from_rng(rand_core::OsRng);
+        let public = X25519PublicKey::from(&secret);
+        QShieldKeyExchange { secret, public }
+    }
+
+    /// Get the raw public key bytes (32 bytes).
+    #[wasm_bindgen(getter)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public.as_bytes().to_vec()
+    }
+
+    /// Get the public key as base64.
+    #[wasm_bindgen(getter)]
+    pub fn public_key_base64(&self) -> String {
+        BASE64.encode(self.public.as_bytes())
+    }
+
+    /// Derive a cipher from a peer's public key via ECDH.
+    ///
+    /// A low-order peer key is rejected, as in `QShieldHybridKEM`, since it
+    /// would make the shared secret all zeros.
+    #[wasm_bindgen]
+    pub fn derive_cipher(&self, peer_public_key: &[u8]) -> Result<QShieldCipher, JsValue> {
+        if peer_public_key.len() != 32 {
+            return Err(JsValue::from_str("Invalid public key length"));
+        }
+
+        let mut pk_bytes = [0u8; 32];
+        pk_bytes.copy_from_slice(peer_public_key);
+        let peer_pk = X25519PublicKey::from(pk_bytes);
+        let shared_secret = self.secret.diffie_hellman(&peer_pk);
+        if !shared_secret.was_contributory() {
+            return Err(JsValue::from_str("Degenerate X25519 public key"));
+        }
+
+        QShieldCipher::from_bytes(shared_secret.as_bytes())
+    }
+
+    /// Recover the shared secret from a classical ciphertext produced by
+    /// `QShieldHybridKEM.encapsulate_auto` for this keypair's public key.
+    #[wasm_bindgen]
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let shared_secret =
+            classical_decapsulate(&self.secret, self.public.as_bytes(), ciphertext)?;
+        Ok(shared_secret.to_vec())
+    }
+}
 
 impl Default for QShieldKeyExchange {
     fn default() -> Self {
@@ -450,25 +1649,34 @@ impl Default for QShieldKeyExchange {
 }
 
 // ============================================================================
-// HYBRID KEM — X25519 + ML-KEM-768 (NIST FIPS 203)
+// HYBRID KEM — X25519 + ML-KEM (NIST FIPS 203)
 // ============================================================================
 
 /// Post-Quantum Hybrid Key Encapsulation Mechanism.
 ///
-/// Combines X25519 (classical ECDH) with ML-KEM-768 (NIST FIPS 203).
+/// Combines X25519 (classical ECDH) with ML-KEM (NIST FIPS 203).
 ///
 /// **Security guarantee:** If EITHER algorithm is secure, the combined system
 /// is secure. X25519 protects against classical attacks; ML-KEM-768 provides
 /// NIST Level 3 security against quantum computers.
 ///
-/// Public key: 1216 bytes (32 X25519 + 1184 ML-KEM-768 ek)
-/// Ciphertext: 1120 bytes (32 X25519 pk + 1088 ML-KEM-768 ct)
+/// The ML-KEM parameter set follows the algorithm suite (see `with_suite`):
+/// ML-KEM-768 by default, ML-KEM-1024 for high security, ML-KEM-512 for
+/// compact keys. Both parties must use the same suite.
+///
+/// The combined secret is bound to the recipient's public key fingerprint and
+/// the full ciphertext, so a ciphertext cannot be replayed against a different
+/// key or re-wrapped without changing the derived secret.
+///
+/// Public key: [suite][32 X25519][ML-KEM ek] — 1217 bytes for ML-KEM-768.
+/// Ciphertext: [version][32 X25519 pk][ML-KEM ct] — 1121 bytes for ML-KEM-768.
+/// Legacy unprefixed 1216-byte public keys and unversioned 1120-byte
+/// ciphertexts are still accepted for the default suite.
 #[wasm_bindgen]
 pub struct QShieldHybridKEM {
     x25519_secret: StaticSecret,
     x25519_public: X25519PublicKey,
-    mlkem_dk: ml_kem_768::DecapsKey,
-    mlkem_ek: ml_kem_768::EncapsKey,
+    mlkem: MlKemKeyPair,
 }
 
 #[wasm_bindgen]
@@ -476,163 +1684,764 @@ impl QShieldHybridKEM {
     /// Generate a new hybrid keypair (X25519 + ML-KEM-768).
     #[wasm_bindgen(constructor)]
     pub fn new() -> Result<QShieldHybridKEM, JsValue> {
+        Self::with_suite(KEM_SUITE_DEFAULT)
+    }
+
+    /// Generate a new hybrid keypair for an algorithm suite.
+    ///
+    /// Suite IDs match the core crate's `AlgorithmSuite`: 0x01 ML-KEM-768
+    /// (default), 0x02 ML-KEM-1024 (high security), 0x03 ML-KEM-512 (compact).
+    #[wasm_bindgen]
+    pub fn with_suite(suite: u8) -> Result<QShieldHybridKEM, JsValue> {
+        let mlkem = MlKemKeyPair::generate(suite)?;
+
         let x25519_secret = StaticSecret::random_from_rng(rand_core::OsRng);
         let x25519_public = X25519PublicKey::from(&x25519_secret);
 
-        let mut rng = rand_core::OsRng;
-        let (mlkem_ek, mlkem_dk) = ml_kem_768::KG::try_keygen_with_rng(&mut rng)
-            .map_err(|_| JsValue::from_str("ML-KEM key generation failed"))?;
-
         Ok(QShieldHybridKEM {
             x25519_secret,
             x25519_public,
-            mlkem_dk,
-            mlkem_ek,
+            mlkem,
         })
     }
 
-    /// Get the combined public key (X25519 ∥ ML-KEM-768 ek).
-    /// 32 + 1184 = 1216 bytes.
+    /// Algorithm suite of this keypair.
+    #[wasm_bindgen(getter)]
+    pub fn suite(&self) -> u8 {
+        self.mlkem.suite()
+    }
+
+    /// Get the combined public key (suite ∥ X25519 ∥ ML-KEM ek).
+    /// 1 + 32 + 1184 = 1217 bytes for the default suite.
     #[wasm_bindgen(getter)]
     pub fn public_key(&self) -> Vec<u8> {
-        let mut combined = Vec::with_capacity(32 + 1184);
+        let ek = self.mlkem.encaps_key_bytes();
+        let mut combined = Vec::with_capacity(1 + 32 + ek.len());
+        combined.push(self.suite());
         combined.extend_from_slice(self.x25519_public.as_bytes());
-        combined.extend_from_slice(&self.mlkem_ek.clone().into_bytes());
+        combined.extend_from_slice(&ek);
+        combined
+    }
+
+    /// Get the public key as base64.
+    #[wasm_bindgen(getter)]
+    pub fn public_key_base64(&self) -> String {
+        BASE64.encode(&self.public_key())
+    }
+
+    /// Get the combined public key size for the default suite (1217 bytes).
+    #[wasm_bindgen]
+    pub fn public_key_size() -> usize {
+        1 + 32 + ml_kem_768::EK_LEN
+    }
+
+    /// Get the combined public key size for `suite`.
+    #[wasm_bindgen]
+    pub fn public_key_size_for_suite(suite: u8) -> Result<usize, JsValue> {
+        hybrid_public_key_size(suite).ok_or_else(|| unknown_kem_suite(suite))
+    }
+
+    /// SHA3-256 fingerprint of this keypair's combined public key.
+    #[wasm_bindgen(getter)]
+    pub fn public_key_fingerprint(&self) -> Vec<u8> {
+        hybrid_public_key_fingerprint(&self.public_key()).to_vec()
+    }
+
+    /// Export both private keys for a persistent identity:
+    /// [suite][x25519_len: u32 LE][X25519 sk (32)][mlkem_len: u32 LE][ML-KEM dk].
+    ///
+    /// The ML-KEM decapsulation key embeds the encapsulation key, so the
+    /// public key is fully recoverable. The returned bytes are secret; store
+    /// them encrypted.
+    #[wasm_bindgen]
+    pub fn export_secret(&self) -> Vec<u8> {
+        let mut x25519_bytes = self.x25519_secret.to_bytes();
+        let mut mlkem_bytes = self.mlkem.decaps_key_bytes();
+
+        let mut combined = Vec::with_capacity(1 + 4 + x25519_bytes.len() + 4 + mlkem_bytes.len());
+        combined.push(self.suite());
+        combined.extend_from_slice(&(x25519_bytes.len() as u32).to_le_bytes());
+        combined.extend_from_slice(&x25519_bytes);
+        combined.extend_from_slice(&(mlkem_bytes.len() as u32).to_le_bytes());
+        combined.extend_from_slice(&mlkem_bytes);
+
+        x25519_bytes.zeroize();
+        mlkem_bytes.zeroize();
         combined
     }
 
-    /// Get the public key as base64.
-    #[wasm_bindgen(getter)]
-    pub fn public_key_base64(&self) -> String {
-        BASE64.encode(&self.public_key())
+    /// Restore a keypair from `export_secret` output.
+    #[wasm_bindgen]
+    pub fn from_secret_bytes(data: &[u8]) -> Result<QShieldHybridKEM, JsValue> {
+        let (&suite, rest) = data
+            .split_first()
+            .ok_or_else(|| JsValue::from_str("Invalid KEM secret key: empty"))?;
+        let dk_len = mlkem_decaps_key_size(suite).ok_or_else(|| unknown_kem_suite(suite))?;
+
+        let (x25519_bytes, rest) = split_length_prefixed(rest)
+            .filter(|(x25519, _)| x25519.len() == 32)
+            .ok_or_else(|| JsValue::from_str("Invalid X25519 secret key length"))?;
+        let (mlkem_bytes, rest) = split_length_prefixed(rest)
+            .filter(|(mlkem, _)| mlkem.len() == dk_len)
+            .ok_or_else(|| JsValue::from_str("Invalid ML-KEM secret key length"))?;
+        if !rest.is_empty() {
+            return Err(JsValue::from_str("Invalid KEM secret key: trailing bytes"));
+        }
+
+        let mut x25519_array = [0u8; 32];
+        x25519_array.copy_from_slice(x25519_bytes);
+        let x25519_secret = StaticSecret::from(x25519_array);
+        x25519_array.zeroize();
+        let x25519_public = X25519PublicKey::from(&x25519_secret);
+
+        Ok(QShieldHybridKEM {
+            x25519_secret,
+            x25519_public,
+            mlkem: MlKemKeyPair::from_decaps_key(suite, mlkem_bytes)?,
+        })
+    }
+
+    /// Encapsulate: generate a shared secret and ciphertext for a peer's public key.
+    ///
+    /// Send the ciphertext to the peer so they can recover the same shared secret.
+    /// The peer key must be of this keypair's suite. A low-order X25519
+    /// component is rejected, since it would make the classical half of the
+    /// shared secret all zeros.
+    #[wasm_bindgen]
+    pub fn encapsulate(&self, peer_public_key: &[u8]) -> Result<HybridEncapsulation, JsValue> {
+        self.encapsulate_versioned(peer_public_key, HYBRID_KEM_VERSION)
+    }
+
+    /// Encapsulate to either a hybrid or a classical X25519 (32-byte) public
+    /// key, for deployments migrating from `QShieldKeyExchange`.
+    ///
+    /// A hybrid key gets the same ciphertext as `encapsulate`. A classical key
+    /// gets a 33-byte ciphertext tagged as X25519-only, whose secret relies on
+    /// X25519 alone and so has **no post-quantum security**: it is exposed to
+    /// anyone who records the exchange and later has a quantum computer.
+    /// Any other key length is rejected.
+    #[wasm_bindgen]
+    pub fn encapsulate_auto(&self, peer_public_key: &[u8]) -> Result<HybridEncapsulation, JsValue> {
+        match peer_public_key.len() {
+            32 => self.encapsulate_classical(peer_public_key),
+            len if is_hybrid_public_key_size(len) => self.encapsulate(peer_public_key),
+            len => Err(JsValue::from_str(&format!(
+                "Ambiguous public key length: expected 32 (classical) or {} (hybrid), got {}",
+                self.mlkem.public_key_len(),
+                len
+            ))),
+        }
+    }
+
+    /// Decapsulate a ciphertext from `encapsulate_auto`, hybrid or classical.
+    ///
+    /// Classical ciphertexts are answered with this keypair's X25519 half, for
+    /// peers that were only given `public_key.slice(1, 33)`. Such a secret has
+    /// no post-quantum security; pass `require_hybrid` to reject them once the
+    /// migration is over, so a peer cannot silently downgrade the exchange.
+    #[wasm_bindgen]
+    pub fn decapsulate_auto(
+        &self,
+        ciphertext: &[u8],
+        require_hybrid: bool,
+    ) -> Result<Vec<u8>, JsValue> {
+        match ciphertext.first() {
+            Some(&CLASSICAL_KEM_VERSION) if require_hybrid => Err(JsValue::from_str(
+                "Classical X25519-only ciphertext rejected: hybrid required",
+            )),
+            Some(&CLASSICAL_KEM_VERSION) => {
+                let shared_secret = classical_decapsulate(
+                    &self.x25519_secret,
+                    self.x25519_public.as_bytes(),
+                    ciphertext,
+                )?;
+                Ok(shared_secret.to_vec())
+            }
+            _ => self.decapsulate(ciphertext),
+        }
+    }
+
+    /// Decapsulate: recover the shared secret from a ciphertext.
+    ///
+    /// Accepts versioned ciphertexts of this keypair's suite, and legacy
+    /// unversioned ones for the default suite. Ciphertexts carrying a
+    /// low-order X25519 key are rejected.
+    #[wasm_bindgen]
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let suite = self.suite();
+        let ciphertext_size = self.mlkem.ciphertext_len();
+        let (transcript_bound, body) = match ciphertext.len() {
+            len if len == ciphertext_size && ciphertext[0] == HYBRID_KEM_VERSION => {
+                (true, &ciphertext[1..])
+            }
+            len if len == ciphertext_size => {
+                return Err(JsValue::from_str("Unsupported KEM ciphertext version"))
+            }
+            LEGACY_KEM_CIPHERTEXT_SIZE if suite == KEM_SUITE_DEFAULT => (false, ciphertext),
+            len if KEM_SUITES.iter().any(|&s| hybrid_ciphertext_size(s) == Some(len)) => {
+                return Err(JsValue::from_str(&format!(
+                    "KEM suite mismatch: ciphertext is not for suite 0x{:02x}",
+                    suite
+                )))
+            }
+            len => {
+                return Err(JsValue::from_str(&format!(
+                    "Invalid ciphertext length: expected {}, got {}",
+                    ciphertext_size, len
+                )))
+            }
+        };
+
+        let peer_x25519_pk = &body[..32];
+        let mlkem_ct = &body[32..];
+
+        // X25519 key exchange
+        let mut pk_bytes = [0u8; 32];
+        pk_bytes.copy_from_slice(peer_x25519_pk);
+        let peer_x25519 = X25519PublicKey::from(pk_bytes);
+        let x25519_shared = self.x25519_secret.diffie_hellman(&peer_x25519);
+        if !x25519_shared.was_contributory() {
+            return Err(JsValue::from_str("Degenerate X25519 public key"));
+        }
+
+        // ML-KEM decapsulation
+        let mut mlkem_shared = self.mlkem.decapsulate(mlkem_ct)?;
+
+        let fingerprint = hybrid_public_key_fingerprint(&self.public_key());
+        let transcript = transcript_bound.then_some((&fingerprint[..], ciphertext));
+
+        let shared_secret =
+            combine_hybrid_secrets(x25519_shared.as_bytes(), &mlkem_shared, transcript);
+        mlkem_shared.zeroize();
+
+        Ok(shared_secret?.to_vec())
+    }
+
+    /// One-shot: derive a cipher from a peer's public key.
+    /// Returns the cipher and the ciphertext to send to the peer.
+    #[wasm_bindgen]
+    pub fn derive_cipher(&self, peer_public_key: &[u8]) -> Result<HybridCipherResult, JsValue> {
+        let encap = self.encapsulate(peer_public_key)?;
+        let cipher = QShieldCipher::from_bytes(&encap.shared_secret)?;
+
+        Ok(HybridCipherResult {
+            cipher,
+            ciphertext: encap.ciphertext,
+        })
+    }
+
+    /// Derive a cipher from a received ciphertext (for decryption).
+    #[wasm_bindgen]
+    pub fn derive_cipher_from_ciphertext(&self, ciphertext: &[u8]) -> Result<QShieldCipher, JsValue> {
+        let shared_secret = self.decapsulate(ciphertext)?;
+        QShieldCipher::from_bytes(&shared_secret)
+    }
+
+    /// Seal a payload for a recipient: encapsulate to their public key and
+    /// encrypt the payload under the derived cipher.
+    ///
+    /// Format: [KEM ciphertext (1121 for ML-KEM-768)][QShieldCipher ciphertext]
+    #[wasm_bindgen]
+    pub fn seal(&self, recipient_public_key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let result = self.derive_cipher(recipient_public_key)?;
+        let encrypted = result.cipher.encrypt(plaintext)?;
+
+        let mut sealed = Vec::with_capacity(result.ciphertext.len() + encrypted.len());
+        sealed.extend_from_slice(&result.ciphertext);
+        sealed.extend_from_slice(&encrypted);
+        Ok(sealed)
+    }
+
+    /// Open a payload produced by `seal` for this keypair.
+    #[wasm_bindgen]
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let ciphertext_size = self.mlkem.ciphertext_len();
+        if sealed.len() < ciphertext_size {
+            return Err(JsValue::from_str("Sealed data too short"));
+        }
+
+        let (kem_ct, encrypted) = sealed.split_at(ciphertext_size);
+        let cipher = self.derive_cipher_from_ciphertext(kem_ct)?;
+        cipher.decrypt(encrypted)
+    }
+
+    /// Recover the content key from this keypair's entry in an
+    /// `encapsulate_multi` result.
+    ///
+    /// The wrapped key is a `seal` of the content key, so this decapsulates
+    /// its KEM ciphertext and decrypts the key under the derived cipher.
+    #[wasm_bindgen]
+    pub fn unwrap_content_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let mut content_key = self.open(wrapped_key)?;
+        if content_key.len() != CONTENT_KEY_SIZE {
+            content_key.zeroize();
+            return Err(JsValue::from_str("Invalid content key length"));
+        }
+        Ok(content_key)
+    }
+}
+
+impl QShieldHybridKEM {
+    /// Encapsulate one random content key to several recipients.
+    ///
+    /// The 32-byte content key is wrapped for each recipient with `seal`, so
+    /// a recipient recovers it with `unwrap_content_key`. All keys must be of
+    /// this keypair's suite; a key of the wrong length is reported by its
+    /// index before anything is encapsulated.
+    pub fn encapsulate_multi(&self, peer_public_keys: &[Vec<u8>]) -> Result<MultiEncapsulation, JsValue> {
+        if peer_public_keys.is_empty() {
+            return Err(JsValue::from_str("No recipient public keys"));
+        }
+
+        let pk_size = self.public_key_len();
+        let legacy_ok = self.suite() == KEM_SUITE_DEFAULT;
+        for (index, pk) in peer_public_keys.iter().enumerate() {
+            if pk.len() != pk_size && !(legacy_ok && pk.len() == LEGACY_HYBRID_PUBLIC_KEY_SIZE) {
+                return Err(JsValue::from_str(&format!(
+                    "Invalid public key length for recipient {}: expected {}, got {}",
+                    index,
+                    pk_size,
+                    pk.len()
+                )));
+            }
+        }
+
+        let mut content_key = [0u8; CONTENT_KEY_SIZE];
+        getrandom::getrandom(&mut content_key)
+            .map_err(|_| JsValue::from_str("RNG failed"))?;
+
+        let wrapped_keys = peer_public_keys
+            .iter()
+            .map(|pk| self.seal(pk, &content_key))
+            .collect::<Result<Vec<_>, JsValue>>();
+
+        let result = wrapped_keys.map(|wrapped_keys| MultiEncapsulation {
+            content_key: content_key.to_vec(),
+            wrapped_keys,
+        });
+        content_key.zeroize();
+        result
+    }
+}
+
+impl QShieldHybridKEM {
+    /// Size of this keypair's combined public key.
+    fn public_key_len(&self) -> usize {
+        self.mlkem.public_key_len()
+    }
+
+    /// X25519-only encapsulation to a classical public key.
+    fn encapsulate_classical(
+        &self,
+        peer_public_key: &[u8],
+    ) -> Result<HybridEncapsulation, JsValue> {
+        let mut pk_bytes = [0u8; 32];
+        pk_bytes.copy_from_slice(peer_public_key);
+        let x25519_shared = self.x25519_secret.diffie_hellman(&X25519PublicKey::from(pk_bytes));
+        if !x25519_shared.was_contributory() {
+            return Err(JsValue::from_str("Degenerate X25519 public key"));
+        }
+
+        let mut ciphertext = Vec::with_capacity(CLASSICAL_KEM_CIPHERTEXT_SIZE);
+        ciphertext.push(CLASSICAL_KEM_VERSION);
+        ciphertext.extend_from_slice(self.x25519_public.as_bytes());
+
+        let shared_secret =
+            combine_classical_secret(x25519_shared.as_bytes(), peer_public_key, &ciphertext)?;
+
+        Ok(HybridEncapsulation {
+            ciphertext,
+            shared_secret: shared_secret.to_vec(),
+        })
+    }
+
+    fn encapsulate_versioned(&self, peer_public_key: &[u8], version: u8) -> Result<HybridEncapsulation, JsValue> {
+        let (peer_suite, peer_x25519_pk, peer_mlkem_ek) = parse_hybrid_public_key(peer_public_key)?;
+        if peer_suite != self.suite() {
+            return Err(JsValue::from_str(&format!(
+                "KEM suite mismatch: keypair uses 0x{:02x}, peer key uses 0x{:02x}",
+                self.suite(),
+                peer_suite
+            )));
+        }
+
+        // X25519 key exchange
+        let mut pk_bytes = [0u8; 32];
+        pk_bytes.copy_from_slice(peer_x25519_pk);
+        let peer_x25519 = X25519PublicKey::from(pk_bytes);
+        let x25519_shared = self.x25519_secret.diffie_hellman(&peer_x25519);
+        if !x25519_shared.was_contributory() {
+            return Err(JsValue::from_str("Degenerate X25519 public key"));
+        }
+
+        // ML-KEM encapsulation
+        let (mut mlkem_shared, mlkem_ct) = mlkem_encapsulate(peer_suite, peer_mlkem_ek)?;
+
+        // Ciphertext: [version] ∥ our X25519 pk ∥ ML-KEM ciphertext
+        let mut ciphertext = Vec::with_capacity(1 + 32 + mlkem_ct.len());
+        if version == HYBRID_KEM_VERSION {
+            ciphertext.push(HYBRID_KEM_VERSION);
+        }
+        ciphertext.extend_from_slice(self.x25519_public.as_bytes());
+        ciphertext.extend_from_slice(&mlkem_ct);
+
+        let fingerprint = hybrid_public_key_fingerprint(peer_public_key);
+        let transcript = (version == HYBRID_KEM_VERSION).then_some((&fingerprint[..], &ciphertext[..]));
+
+        let shared_secret =
+            combine_hybrid_secrets(x25519_shared.as_bytes(), &mlkem_shared, transcript);
+        mlkem_shared.zeroize();
+
+        Ok(HybridEncapsulation {
+            ciphertext,
+            shared_secret: shared_secret?.to_vec(),
+        })
+    }
+}
+
+impl Default for QShieldHybridKEM {
+    fn default() -> Self {
+        Self::new().expect("Failed to create HybridKEM")
+    }
+}
+
+/// ML-KEM half of a hybrid keypair, in the parameter set of its suite.
+///
+/// Keys are boxed so every variant is pointer-sized.
+enum MlKemKeyPair {
+    Compact(Box<ml_kem_512::DecapsKey>, Box<ml_kem_512::EncapsKey>),
+    Default(Box<ml_kem_768::DecapsKey>, Box<ml_kem_768::EncapsKey>),
+    HighSecurity(Box<ml_kem_1024::DecapsKey>, Box<ml_kem_1024::EncapsKey>),
+}
+
+impl MlKemKeyPair {
+    fn generate(suite: u8) -> Result<Self, JsValue> {
+        let mut rng = rand_core::OsRng;
+        let failed = |_: &str| JsValue::from_str("ML-KEM key generation failed");
+
+        Ok(match suite {
+            KEM_SUITE_COMPACT => {
+                let (ek, dk) = ml_kem_512::KG::try_keygen_with_rng(&mut rng).map_err(failed)?;
+                Self::Compact(Box::new(dk), Box::new(ek))
+            }
+            KEM_SUITE_DEFAULT => {
+                let (ek, dk) = ml_kem_768::KG::try_keygen_with_rng(&mut rng).map_err(failed)?;
+                Self::Default(Box::new(dk), Box::new(ek))
+            }
+            KEM_SUITE_HIGH_SECURITY => {
+                let (ek, dk) = ml_kem_1024::KG::try_keygen_with_rng(&mut rng).map_err(failed)?;
+                Self::HighSecurity(Box::new(dk), Box::new(ek))
+            }
+            _ => return Err(unknown_kem_suite(suite)),
+        })
+    }
+
+    fn suite(&self) -> u8 {
+        match self {
+            Self::Compact(..) => KEM_SUITE_COMPACT,
+            Self::Default(..) => KEM_SUITE_DEFAULT,
+            Self::HighSecurity(..) => KEM_SUITE_HIGH_SECURITY,
+        }
     }
 
-    /// Get the combined public key size (1216 bytes).
-    #[wasm_bindgen]
-    pub fn public_key_size() -> usize {
-        32 + 1184
+    fn public_key_len(&self) -> usize {
+        hybrid_public_key_size(self.suite()).unwrap_or_default()
     }
 
-    /// Encapsulate: generate a shared secret and ciphertext for a peer's public key.
+    fn ciphertext_len(&self) -> usize {
+        hybrid_ciphertext_size(self.suite()).unwrap_or_default()
+    }
+
+    /// Rebuild a keypair from a decapsulation key of `suite`.
     ///
-    /// Send the ciphertext to the peer so they can recover the same shared secret.
-    #[wasm_bindgen]
-    pub fn encapsulate(&self, peer_public_key: &[u8]) -> Result<HybridEncapsulation, JsValue> {
-        if peer_public_key.len() != 32 + 1184 {
-            return Err(JsValue::from_str(&format!(
-                "Invalid hybrid public key length: expected {}, got {}",
-                32 + 1184,
-                peer_public_key.len()
-            )));
+    /// A FIPS 203 dk is dk_pke ∥ ek ∥ H(ek) ∥ z, so the encapsulation key is
+    /// the EK_LEN bytes before the final 64; its hash detects corruption.
+    fn from_decaps_key(suite: u8, dk: &[u8]) -> Result<Self, JsValue> {
+        let invalid = || JsValue::from_str("Invalid ML-KEM secret key");
+        let (ek_len, _) = mlkem_sizes(suite).ok_or_else(|| unknown_kem_suite(suite))?;
+        let end = dk.len().checked_sub(64).ok_or_else(invalid)?;
+        let ek = end
+            .checked_sub(ek_len)
+            .and_then(|start| dk.get(start..end))
+            .ok_or_else(invalid)?;
+        if Sha3_256::digest(ek).as_slice() != &dk[end..end + 32] {
+            return Err(invalid());
         }
 
-        let peer_x25519_pk = &peer_public_key[..32];
-        let peer_mlkem_ek = &peer_public_key[32..];
-
-        // X25519 key exchange
-        let mut pk_bytes = [0u8; 32];
-        pk_bytes.copy_from_slice(peer_x25519_pk);
-        let peer_x25519 = X25519PublicKey::from(pk_bytes);
-        let x25519_shared = self.x25519_secret.diffie_hellman(&peer_x25519);
+        Ok(match suite {
+            KEM_SUITE_COMPACT => Self::Compact(
+                Box::new(
+                    ml_kem_512::DecapsKey::try_from_bytes(dk.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+                Box::new(
+                    ml_kem_512::EncapsKey::try_from_bytes(ek.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+            ),
+            KEM_SUITE_DEFAULT => Self::Default(
+                Box::new(
+                    ml_kem_768::DecapsKey::try_from_bytes(dk.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+                Box::new(
+                    ml_kem_768::EncapsKey::try_from_bytes(ek.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+            ),
+            KEM_SUITE_HIGH_SECURITY => Self::HighSecurity(
+                Box::new(
+                    ml_kem_1024::DecapsKey::try_from_bytes(dk.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+                Box::new(
+                    ml_kem_1024::EncapsKey::try_from_bytes(ek.try_into().map_err(|_| invalid())?)
+                        .map_err(|_| invalid())?,
+                ),
+            ),
+            _ => return Err(unknown_kem_suite(suite)),
+        })
+    }
 
-        // ML-KEM encapsulation
-        let peer_ek = ml_kem_768::EncapsKey::try_from_bytes(peer_mlkem_ek.try_into().unwrap())
-            .map_err(|_| JsValue::from_str("Invalid ML-KEM public key"))?;
+    fn decaps_key_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Compact(dk, _) => dk.clone().into_bytes().to_vec(),
+            Self::Default(dk, _) => dk.clone().into_bytes().to_vec(),
+            Self::HighSecurity(dk, _) => dk.clone().into_bytes().to_vec(),
+        }
+    }
 
-        let mut rng = rand_core::OsRng;
-        let (mlkem_shared, mlkem_ct) = peer_ek.try_encaps_with_rng(&mut rng)
-            .map_err(|_| JsValue::from_str("ML-KEM encapsulation failed"))?;
+    fn encaps_key_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Compact(_, ek) => ek.clone().into_bytes().to_vec(),
+            Self::Default(_, ek) => ek.clone().into_bytes().to_vec(),
+            Self::HighSecurity(_, ek) => ek.clone().into_bytes().to_vec(),
+        }
+    }
 
-        // Combine shared secrets via HKDF-SHA3-512
-        let mut combined_secret = Vec::with_capacity(32 + 32);
-        combined_secret.extend_from_slice(x25519_shared.as_bytes());
-        combined_secret.extend_from_slice(&mlkem_shared.into_bytes());
+    /// Recover the 32-byte ML-KEM shared secret from a ciphertext.
+    fn decapsulate(&self, ciphertext: &[u8]) -> Result<[u8; 32], JsValue> {
+        let invalid = || JsValue::from_str("Invalid ML-KEM ciphertext");
+
+        let shared = match self {
+            Self::Compact(dk, _) => {
+                let ct = ml_kem_512::CipherText::try_from_bytes(
+                    ciphertext.try_into().map_err(|_| invalid())?,
+                )
+                .map_err(|_| invalid())?;
+                dk.clone().try_decaps(&ct)
+            }
+            Self::Default(dk, _) => {
+                let ct = ml_kem_768::CipherText::try_from_bytes(
+                    ciphertext.try_into().map_err(|_| invalid())?,
+                )
+                .map_err(|_| invalid())?;
+                dk.clone().try_decaps(&ct)
+            }
+            Self::HighSecurity(dk, _) => {
+                let ct = ml_kem_1024::CipherText::try_from_bytes(
+                    ciphertext.try_into().map_err(|_| invalid())?,
+                )
+                .map_err(|_| invalid())?;
+                dk.clone().try_decaps(&ct)
+            }
+        };
 
-        let hk = Hkdf::<Sha3_512>::new(Some(b"QShield-HybridKEM-v1"), &combined_secret);
-        let mut shared_secret = [0u8; 64];
-        hk.expand(b"hybrid-shared-secret", &mut shared_secret)
-            .map_err(|_| JsValue::from_str("HKDF expansion failed"))?;
+        shared
+            .map(|ssk| ssk.into_bytes())
+            .map_err(|_| JsValue::from_str("ML-KEM decapsulation failed"))
+    }
+}
 
-        // Ciphertext: our X25519 pk ∥ ML-KEM ciphertext
-        let mut ciphertext = Vec::with_capacity(32 + 1088);
-        ciphertext.extend_from_slice(self.x25519_public.as_bytes());
-        ciphertext.extend_from_slice(&mlkem_ct.into_bytes());
+/// Encapsulate to a serialized ML-KEM key of `suite`.
+///
+/// Returns the 32-byte ML-KEM shared secret and the ML-KEM ciphertext.
+fn mlkem_encapsulate(suite: u8, encaps_key: &[u8]) -> Result<([u8; 32], Vec<u8>), JsValue> {
+    let invalid = || JsValue::from_str("Invalid ML-KEM public key");
+    let mut rng = rand_core::OsRng;
+
+    let encapsulated = match suite {
+        KEM_SUITE_COMPACT => ml_kem_512::EncapsKey::try_from_bytes(
+            encaps_key.try_into().map_err(|_| invalid())?,
+        )
+        .map_err(|_| invalid())?
+        .try_encaps_with_rng(&mut rng)
+        .map(|(ssk, ct)| (ssk, ct.into_bytes().to_vec())),
+        KEM_SUITE_DEFAULT => ml_kem_768::EncapsKey::try_from_bytes(
+            encaps_key.try_into().map_err(|_| invalid())?,
+        )
+        .map_err(|_| invalid())?
+        .try_encaps_with_rng(&mut rng)
+        .map(|(ssk, ct)| (ssk, ct.into_bytes().to_vec())),
+        KEM_SUITE_HIGH_SECURITY => ml_kem_1024::EncapsKey::try_from_bytes(
+            encaps_key.try_into().map_err(|_| invalid())?,
+        )
+        .map_err(|_| invalid())?
+        .try_encaps_with_rng(&mut rng)
+        .map(|(ssk, ct)| (ssk, ct.into_bytes().to_vec())),
+        _ => return Err(unknown_kem_suite(suite)),
+    };
+
+    encapsulated
+        .map(|(ssk, ct)| (ssk.into_bytes(), ct))
+        .map_err(|_| JsValue::from_str("ML-KEM encapsulation failed"))
+}
 
-        combined_secret.zeroize();
+/// (ML-KEM encapsulation key, ML-KEM ciphertext) sizes for a KEM suite.
+fn mlkem_sizes(suite: u8) -> Option<(usize, usize)> {
+    match suite {
+        KEM_SUITE_COMPACT => Some((ml_kem_512::EK_LEN, ml_kem_512::CT_LEN)),
+        KEM_SUITE_DEFAULT => Some((ml_kem_768::EK_LEN, ml_kem_768::CT_LEN)),
+        KEM_SUITE_HIGH_SECURITY => Some((ml_kem_1024::EK_LEN, ml_kem_1024::CT_LEN)),
+        _ => None,
+    }
+}
 
-        Ok(HybridEncapsulation {
-            ciphertext,
-            shared_secret: shared_secret.to_vec(),
-        })
+fn mlkem_decaps_key_size(suite: u8) -> Option<usize> {
+    match suite {
+        KEM_SUITE_COMPACT => Some(ml_kem_512::DK_LEN),
+        KEM_SUITE_DEFAULT => Some(ml_kem_768::DK_LEN),
+        KEM_SUITE_HIGH_SECURITY => Some(ml_kem_1024::DK_LEN),
+        _ => None,
     }
+}
 
-    /// Decapsulate: recover the shared secret from a ciphertext.
-    #[wasm_bindgen]
-    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        if ciphertext.len() != 32 + 1088 {
-            return Err(JsValue::from_str(&format!(
-                "Invalid ciphertext length: expected {}, got {}",
-                32 + 1088,
-                ciphertext.len()
-            )));
-        }
+/// Split `[len: u32 LE][len bytes]` off the front of `data`.
+fn split_length_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (len, rest) = data.split_at(4);
+    let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
 
-        let peer_x25519_pk = &ciphertext[..32];
-        let mlkem_ct = &ciphertext[32..];
+/// Combined public key size: [suite][X25519][ML-KEM ek].
+fn hybrid_public_key_size(suite: u8) -> Option<usize> {
+    mlkem_sizes(suite).map(|(ek_len, _)| 1 + 32 + ek_len)
+}
 
-        // X25519 key exchange
-        let mut pk_bytes = [0u8; 32];
-        pk_bytes.copy_from_slice(peer_x25519_pk);
-        let peer_x25519 = X25519PublicKey::from(pk_bytes);
-        let x25519_shared = self.x25519_secret.diffie_hellman(&peer_x25519);
+/// Versioned KEM ciphertext size: [version][X25519 pk][ML-KEM ct].
+fn hybrid_ciphertext_size(suite: u8) -> Option<usize> {
+    mlkem_sizes(suite).map(|(_, ct_len)| 1 + 32 + ct_len)
+}
 
-        // ML-KEM decapsulation
-        let ct = ml_kem_768::CipherText::try_from_bytes(mlkem_ct.try_into().unwrap())
-            .map_err(|_| JsValue::from_str("Invalid ML-KEM ciphertext"))?;
+fn is_hybrid_public_key_size(len: usize) -> bool {
+    len == LEGACY_HYBRID_PUBLIC_KEY_SIZE
+        || KEM_SUITES.iter().any(|&suite| hybrid_public_key_size(suite) == Some(len))
+}
 
-        let mlkem_shared = self.mlkem_dk.clone().try_decaps(&ct)
-            .map_err(|_| JsValue::from_str("ML-KEM decapsulation failed"))?;
+fn unknown_kem_suite(suite: u8) -> JsValue {
+    JsValue::from_str(&format!("Unknown KEM suite: 0x{:02x}", suite))
+}
 
-        // Combine shared secrets
-        let mut combined_secret = Vec::with_capacity(32 + 32);
-        combined_secret.extend_from_slice(x25519_shared.as_bytes());
-        combined_secret.extend_from_slice(&mlkem_shared.into_bytes());
+/// Split a combined public key into its suite, X25519 key and ML-KEM key.
+///
+/// Unprefixed 1216-byte keys from before suites were encoded are read as
+/// the default suite.
+fn parse_hybrid_public_key(public_key: &[u8]) -> Result<(u8, &[u8], &[u8]), JsValue> {
+    if public_key.len() == LEGACY_HYBRID_PUBLIC_KEY_SIZE {
+        let (x25519, mlkem) = public_key.split_at(32);
+        return Ok((KEM_SUITE_DEFAULT, x25519, mlkem));
+    }
 
-        let hk = Hkdf::<Sha3_512>::new(Some(b"QShield-HybridKEM-v1"), &combined_secret);
-        let mut shared_secret = [0u8; 64];
-        hk.expand(b"hybrid-shared-secret", &mut shared_secret)
-            .map_err(|_| JsValue::from_str("HKDF expansion failed"))?;
+    let suite = public_key.first().copied().unwrap_or(KEM_SUITE_DEFAULT);
+    let expected = hybrid_public_key_size(suite).ok_or_else(|| unknown_kem_suite(suite))?;
+    if public_key.len() != expected {
+        return Err(JsValue::from_str(&format!(
+            "Invalid hybrid public key length: expected {}, got {}",
+            expected,
+            public_key.len()
+        )));
+    }
 
-        combined_secret.zeroize();
+    let (x25519, mlkem) = public_key[1..].split_at(32);
+    Ok((suite, x25519, mlkem))
+}
 
-        Ok(shared_secret.to_vec())
-    }
+/// SHA3-256 fingerprint of a combined public key.
+///
+/// Default-suite keys are hashed without their suite byte, so fingerprints
+/// (and group recipient IDs) match those of legacy unprefixed keys.
+fn hybrid_public_key_fingerprint(public_key: &[u8]) -> [u8; 32] {
+    let canonical = match public_key.split_first() {
+        Some((&KEM_SUITE_DEFAULT, rest)) if rest.len() == LEGACY_HYBRID_PUBLIC_KEY_SIZE => rest,
+        _ => public_key,
+    };
+    Sha3_256::digest(canonical).into()
+}
 
-    /// One-shot: derive a cipher from a peer's public key.
-    /// Returns the cipher and the ciphertext to send to the peer.
-    #[wasm_bindgen]
-    pub fn derive_cipher(&self, peer_public_key: &[u8]) -> Result<HybridCipherResult, JsValue> {
-        let encap = self.encapsulate(peer_public_key)?;
-        let cipher = QShieldCipher::from_bytes(&encap.shared_secret)?;
+/// Combine the X25519 and ML-KEM secrets via HKDF-SHA3-512.
+///
+/// With a transcript `(recipient_fingerprint, ciphertext)` both are mixed into
+/// the HKDF info; without one this is the legacy v1 combiner.
+fn combine_hybrid_secrets(
+    x25519_shared: &[u8],
+    mlkem_shared: &[u8],
+    transcript: Option<(&[u8], &[u8])>,
+) -> Result<[u8; 64], JsValue> {
+    let mut combined_secret = Vec::with_capacity(32 + 32);
+    combined_secret.extend_from_slice(x25519_shared);
+    combined_secret.extend_from_slice(mlkem_shared);
+
+    let hk = Hkdf::<Sha3_512>::new(Some(b"QShield-HybridKEM-v1"), &combined_secret);
+    let mut shared_secret = [0u8; 64];
+    let expanded = match transcript {
+        Some((fingerprint, ciphertext)) => hk.expand_multi_info(
+            &[b"hybrid-shared-secret-v2", fingerprint, ciphertext],
+            &mut shared_secret,
+        ),
+        None => hk.expand(b"hybrid-shared-secret", &mut shared_secret),
+    };
+
+    combined_secret.zeroize();
+    expanded.map_err(|_| JsValue::from_str("HKDF expansion failed"))?;
+
+    Ok(shared_secret)
+}
 
-        Ok(HybridCipherResult {
-            cipher,
-            ciphertext: encap.ciphertext,
-        })
+/// Recover a classical (X25519-only) KEM secret as the holder of `secret`.
+fn classical_decapsulate(
+    secret: &StaticSecret,
+    public_key: &[u8],
+    ciphertext: &[u8],
+) -> Result<[u8; 64], JsValue> {
+    if ciphertext.len() != CLASSICAL_KEM_CIPHERTEXT_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid ciphertext length: expected {}, got {}",
+            CLASSICAL_KEM_CIPHERTEXT_SIZE,
+            ciphertext.len()
+        )));
+    }
+    if ciphertext[0] != CLASSICAL_KEM_VERSION {
+        return Err(JsValue::from_str("Unsupported KEM ciphertext version"));
     }
 
-    /// Derive a cipher from a received ciphertext (for decryption).
-    #[wasm_bindgen]
-    pub fn derive_cipher_from_ciphertext(&self, ciphertext: &[u8]) -> Result<QShieldCipher, JsValue> {
-        let shared_secret = self.decapsulate(ciphertext)?;
-        QShieldCipher::from_bytes(&shared_secret)
+    let mut pk_bytes = [0u8; 32];
+    pk_bytes.copy_from_slice(&ciphertext[1..]);
+    let x25519_shared = secret.diffie_hellman(&X25519PublicKey::from(pk_bytes));
+    if !x25519_shared.was_contributory() {
+        return Err(JsValue::from_str("Degenerate X25519 public key"));
     }
+
+    combine_classical_secret(x25519_shared.as_bytes(), public_key, ciphertext)
 }
 
-impl Default for QShieldHybridKEM {
-    fn default() -> Self {
-        Self::new().expect("Failed to create HybridKEM")
-    }
+/// Derive the classical KEM secret, bound to the recipient key and ciphertext
+/// under a label distinct from the hybrid combiner.
+fn combine_classical_secret(
+    x25519_shared: &[u8],
+    recipient_public_key: &[u8],
+    ciphertext: &[u8],
+) -> Result<[u8; 64], JsValue> {
+    let hk = Hkdf::<Sha3_512>::new(Some(b"QShield-HybridKEM-v1"), x25519_shared);
+    let mut shared_secret = [0u8; 64];
+    hk.expand_multi_info(
+        &[b"classical-shared-secret", recipient_public_key, ciphertext],
+        &mut shared_secret,
+    )
+    .map_err(|_| JsValue::from_str("HKDF expansion failed"))?;
+    Ok(shared_secret)
 }
 
 /// Result of hybrid KEM encapsulation.
@@ -644,7 +2453,7 @@ pub struct HybridEncapsulation {
 
 #[wasm_bindgen]
 impl HybridEncapsulation {
-    /// Get the ciphertext to send to the peer (1120 bytes).
+    /// Get the ciphertext to send to the peer (1121 bytes for ML-KEM-768).
     #[wasm_bindgen(getter)]
     pub fn ciphertext(&self) -> Vec<u8> {
         self.ciphertext.clone()
@@ -663,6 +2472,50 @@ impl HybridEncapsulation {
     }
 }
 
+/// Result of multi-recipient encapsulation.
+///
+/// Holds the content key and, in recipient order, the wrapped key to send to
+/// each recipient.
+#[wasm_bindgen]
+pub struct MultiEncapsulation {
+    content_key: Vec<u8>,
+    wrapped_keys: Vec<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl MultiEncapsulation {
+    /// Get the content-encryption key (32 bytes).
+    #[wasm_bindgen(getter)]
+    pub fn content_key(&self) -> Vec<u8> {
+        self.content_key.clone()
+    }
+
+    /// Number of recipients the content key is wrapped for.
+    #[wasm_bindgen(getter)]
+    pub fn recipient_count(&self) -> usize {
+        self.wrapped_keys.len()
+    }
+
+    /// Get the wrapped key for the recipient at `index`.
+    #[wasm_bindgen]
+    pub fn wrapped_key(&self, index: usize) -> Option<Vec<u8>> {
+        self.wrapped_keys.get(index).cloned()
+    }
+}
+
+impl MultiEncapsulation {
+    /// Wrapped keys, in the order the recipients' public keys were given.
+    pub fn wrapped_keys(&self) -> &[Vec<u8>] {
+        &self.wrapped_keys
+    }
+}
+
+impl Drop for MultiEncapsulation {
+    fn drop(&mut self) {
+        self.content_key.zeroize();
+    }
+}
+
 /// Result of one-shot cipher derivation from hybrid KEM.
 #[wasm_bindgen]
 pub struct HybridCipherResult {
@@ -697,6 +2550,562 @@ impl HybridCipherResult {
     }
 }
 
+// ============================================================================
+// GROUP MESSAGING — One payload, per-recipient wrapped content keys
+// ============================================================================
+
+/// Multi-recipient encrypted message.
+///
+/// The payload is encrypted once under a random content key, and that key is
+/// sealed to each recipient with `QShieldHybridKEM::seal`. Recipients are
+/// identified by the SHA3-256 hash of their hybrid public key. Everything
+/// before the payload ciphertext is authenticated as its AAD, so recipient
+/// entries cannot be added, dropped, or reordered.
+///
+/// Format: [version][count: u16 LE]
+///         ([recipient_id (32)][wrapped_len: u32 LE][wrapped_key])*
+///         [payload ciphertext]
+#[wasm_bindgen]
+pub struct QShieldGroupMessage {
+    version: u8,
+    wrapped_keys: Vec<([u8; RECIPIENT_ID_SIZE], Vec<u8>)>,
+    ciphertext: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl QShieldGroupMessage {
+    /// Encrypt a payload for a set of recipients.
+    ///
+    /// `recipient_public_keys` is the concatenation of the recipients' hybrid
+    /// public keys, all of the sender's suite.
+    #[wasm_bindgen]
+    pub fn encrypt(
+        sender: &QShieldHybridKEM,
+        recipient_public_keys: &[u8],
+        plaintext: &[u8],
+    ) -> Result<QShieldGroupMessage, JsValue> {
+        let pk_size = sender.public_key_len();
+        if recipient_public_keys.is_empty() || !recipient_public_keys.len().is_multiple_of(pk_size) {
+            return Err(JsValue::from_str(&format!(
+                "Recipient keys must be a non-empty concatenation of {}-byte public keys",
+                pk_size
+            )));
+        }
+        if recipient_public_keys.len() / pk_size > u16::MAX as usize {
+            return Err(JsValue::from_str("Too many recipients"));
+        }
+
+        let mut content_key = [0u8; CONTENT_KEY_SIZE];
+        getrandom::getrandom(&mut content_key)
+            .map_err(|_| JsValue::from_str("RNG failed"))?;
+
+        let result = Self::encrypt_with_content_key(sender, recipient_public_keys, plaintext, &content_key);
+        content_key.zeroize();
+        result
+    }
+
+    /// Decrypt the payload as one of the recipients.
+    ///
+    /// Fails if the header was altered after encryption. Version 1 messages,
+    /// written before the header was authenticated, still decrypt.
+    #[wasm_bindgen]
+    pub fn decrypt(&self, recipient: &QShieldHybridKEM) -> Result<Vec<u8>, JsValue> {
+        let aad = if self.version == LEGACY_GROUP_MESSAGE_VERSION {
+            Vec::new()
+        } else {
+            self.header()
+        };
+        Self::decrypt_parts(&self.wrapped_keys, &self.ciphertext, recipient, &aad)
+    }
+
+    /// Number of recipients the content key is wrapped for.
+    #[wasm_bindgen(getter)]
+    pub fn recipient_count(&self) -> usize {
+        self.wrapped_keys.len()
+    }
+
+    /// Get the serialized group message.
+    #[wasm_bindgen(getter)]
+    pub fn bytes(&self) -> Vec<u8> {
+        let mut out = self.header();
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    /// Parse a serialized group message.
+    #[wasm_bindgen]
+    pub fn from_bytes(data: &[u8]) -> Result<QShieldGroupMessage, JsValue> {
+        if data.len() < 3 {
+            return Err(JsValue::from_str("Group message too short"));
+        }
+        let version = data[0];
+        if version != GROUP_MESSAGE_VERSION && version != LEGACY_GROUP_MESSAGE_VERSION {
+            return Err(JsValue::from_str("Unsupported group message version"));
+        }
+
+        let count = u16::from_le_bytes([data[1], data[2]]) as usize;
+        let mut offset = 3;
+        let mut wrapped_keys = Vec::with_capacity(count.min(data.len() / RECIPIENT_ID_SIZE));
+
+        for _ in 0..count {
+            let header_end = offset + RECIPIENT_ID_SIZE + 4;
+            if data.len() < header_end {
+                return Err(JsValue::from_str("Invalid group message format"));
+            }
+
+            let mut id = [0u8; RECIPIENT_ID_SIZE];
+            id.copy_from_slice(&data[offset..offset + RECIPIENT_ID_SIZE]);
+            let len_bytes = &data[offset + RECIPIENT_ID_SIZE..header_end];
+            let wrapped_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+
+            if data.len() - header_end < wrapped_len {
+                return Err(JsValue::from_str("Invalid group message format"));
+            }
+
+            wrapped_keys.push((id, data[header_end..header_end + wrapped_len].to_vec()));
+            offset = header_end + wrapped_len;
+        }
+
+        Ok(QShieldGroupMessage {
+            version,
+            wrapped_keys,
+            ciphertext: data[offset..].to_vec(),
+        })
+    }
+}
+
+impl QShieldGroupMessage {
+    fn encrypt_with_content_key(
+        sender: &QShieldHybridKEM,
+        recipient_public_keys: &[u8],
+        plaintext: &[u8],
+        content_key: &[u8],
+    ) -> Result<QShieldGroupMessage, JsValue> {
+        let wrapped_keys = recipient_public_keys
+            .chunks_exact(sender.public_key_len())
+            .map(|pk| Ok((recipient_id(pk), sender.seal(pk, content_key)?)))
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        let mut message = QShieldGroupMessage {
+            version: GROUP_MESSAGE_VERSION,
+            wrapped_keys,
+            ciphertext: Vec::new(),
+        };
+        message.ciphertext =
+            QShieldCipher::from_bytes(content_key)?.encrypt_with_aad(plaintext, &message.header())?;
+        Ok(message)
+    }
+
+    /// Everything before the payload ciphertext
+    fn header(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.version);
+        out.extend_from_slice(&(self.wrapped_keys.len() as u16).to_le_bytes());
+        for (id, wrapped) in &self.wrapped_keys {
+            out.extend_from_slice(id);
+            out.extend_from_slice(&(wrapped.len() as u32).to_le_bytes());
+            out.extend_from_slice(wrapped);
+        }
+        out
+    }
+
+    fn decrypt_parts(
+        wrapped_keys: &[([u8; RECIPIENT_ID_SIZE], Vec<u8>)],
+        ciphertext: &[u8],
+        recipient: &QShieldHybridKEM,
+        aad: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let id = recipient_id(&recipient.public_key());
+        let wrapped = wrapped_keys
+            .iter()
+            .find(|(rid, _)| secure_compare(rid, &id))
+            .map(|(_, wrapped)| wrapped)
+            .ok_or_else(|| JsValue::from_str("Not a recipient of this message"))?;
+
+        let mut content_key = recipient.open(wrapped)?;
+        let cipher = QShieldCipher::from_bytes(&content_key);
+        content_key.zeroize();
+
+        cipher?.decrypt_with_aad(ciphertext, aad)
+    }
+}
+
+/// Multi-recipient envelope in the format shared with the core Rust crate.
+///
+/// Both SDKs seal with the hybrid suite (0x03), so either can open what the
+/// other sealed. Its key wrapping and payload cascade are specified in the
+/// core crate's `protocol::envelope` docs. The payload is authenticated with
+/// everything before `payload_len` as AAD, so the version, suite and
+/// recipient list cannot be changed after sealing. Envelopes of the older
+/// WASM-only suite (0x02) still open here.
+///
+/// Format: [version][suite][count: u16 LE]
+///         ([recipient_id (32)][wrapped_len: u32 LE][wrapped_key])*
+///         [payload_len: u32 LE][payload]
+#[wasm_bindgen]
+pub struct QShieldEnvelope {
+    suite: u8,
+    recipients: Vec<([u8; RECIPIENT_ID_SIZE], Vec<u8>)>,
+    payload: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl QShieldEnvelope {
+    /// Encrypt a payload for a set of recipients.
+    ///
+    /// `recipient_public_keys` is the concatenation of the recipients' hybrid
+    /// public keys, all of the sender's suite. The hybrid suite is built on
+    /// ML-KEM-768, so the sender must use the default KEM suite.
+    #[wasm_bindgen]
+    pub fn seal(
+        sender: &QShieldHybridKEM,
+        recipient_public_keys: &[u8],
+        plaintext: &[u8],
+    ) -> Result<QShieldEnvelope, JsValue> {
+        if sender.suite() != KEM_SUITE_DEFAULT {
+            return Err(JsValue::from_str("Envelopes require the default KEM suite"));
+        }
+        let pk_size = sender.public_key_len();
+        if recipient_public_keys.is_empty() || !recipient_public_keys.len().is_multiple_of(pk_size) {
+            return Err(JsValue::from_str(&format!(
+                "Recipient keys must be a non-empty concatenation of {}-byte public keys",
+                pk_size
+            )));
+        }
+        if recipient_public_keys.len() / pk_size > u16::MAX as usize {
+            return Err(JsValue::from_str("Too many recipients"));
+        }
+
+        let mut content_key = [0u8; CONTENT_KEY_SIZE];
+        getrandom::getrandom(&mut content_key)
+            .map_err(|_| JsValue::from_str("RNG failed"))?;
+
+        let recipients = recipient_public_keys
+            .chunks_exact(pk_size)
+            .map(|pk| {
+                let id = recipient_id(pk);
+                Ok((id, envelope_wrap_key(pk, &id, &content_key)?))
+            })
+            .collect::<Result<Vec<_>, JsValue>>();
+        let mut envelope = QShieldEnvelope {
+            suite: ENVELOPE_SUITE_HYBRID,
+            recipients: Vec::new(),
+            payload: Vec::new(),
+        };
+        let payload = recipients.and_then(|recipients| {
+            envelope.recipients = recipients;
+            envelope_encrypt_payload(&content_key, plaintext, &envelope.header())
+        });
+        content_key.zeroize();
+
+        envelope.payload = payload?;
+        Ok(envelope)
+    }
+
+    /// Decrypt the payload as one of the recipients.
+    ///
+    /// Envelopes of the core crate's original suite (0x01) can only be
+    /// opened by that crate.
+    #[wasm_bindgen]
+    pub fn open(&self, recipient: &QShieldHybridKEM) -> Result<Vec<u8>, JsValue> {
+        match self.suite {
+            ENVELOPE_SUITE_HYBRID => {
+                let id = recipient_id(&recipient.public_key());
+                let wrapped = self
+                    .recipients
+                    .iter()
+                    .find(|(rid, _)| secure_compare(rid, &id))
+                    .map(|(_, wrapped)| wrapped)
+                    .ok_or_else(|| JsValue::from_str("Not a recipient of this message"))?;
+
+                let mut content_key = envelope_unwrap_key(recipient, &id, wrapped)?;
+                let plaintext =
+                    envelope_decrypt_payload(&content_key, &self.payload, &self.header());
+                content_key.zeroize();
+                plaintext
+            }
+            ENVELOPE_SUITE_WASM => {
+                QShieldGroupMessage::decrypt_parts(&self.recipients, &self.payload, recipient, &[])
+            }
+            _ => Err(JsValue::from_str("Unsupported envelope suite")),
+        }
+    }
+
+    /// Suite byte: 0x03 when sealed by either SDK; 0x01 and 0x02 for older
+    /// core-only and WASM-only envelopes.
+    #[wasm_bindgen(getter)]
+    pub fn suite(&self) -> u8 {
+        self.suite
+    }
+
+    /// Number of recipients the content key is wrapped for.
+    #[wasm_bindgen(getter)]
+    pub fn recipient_count(&self) -> usize {
+        self.recipients.len()
+    }
+
+    /// Whether `public_key` has a wrapped key in this envelope.
+    #[wasm_bindgen]
+    pub fn has_recipient(&self, public_key: &[u8]) -> bool {
+        let id = recipient_id(public_key);
+        self.recipients.iter().any(|(rid, _)| *rid == id)
+    }
+
+    /// Get the serialized envelope.
+    #[wasm_bindgen(getter)]
+    pub fn bytes(&self) -> Vec<u8> {
+        let mut out = self.header();
+        out.extend_from_slice(&(self.payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.payload);
+        out
+    }
+
+    /// Parse a serialized envelope from either SDK.
+    ///
+    /// Rejects envelopes over 16 MiB or listing more than 1024 recipients.
+    #[wasm_bindgen]
+    pub fn from_bytes(data: &[u8]) -> Result<QShieldEnvelope, JsValue> {
+        Self::from_bytes_with_limits(data, ENVELOPE_MAX_RECIPIENTS, ENVELOPE_MAX_SIZE)
+    }
+
+    /// Parse a serialized envelope with explicit recipient and size limits.
+    ///
+    /// Both limits are checked before any per-recipient memory is allocated.
+    #[wasm_bindgen]
+    pub fn from_bytes_with_limits(
+        data: &[u8],
+        max_recipients: usize,
+        max_size: usize,
+    ) -> Result<QShieldEnvelope, JsValue> {
+        let invalid = || JsValue::from_str("Invalid envelope format");
+
+        if data.len() > max_size {
+            return Err(JsValue::from_str("Envelope too large"));
+        }
+        if data.len() < 4 {
+            return Err(JsValue::from_str("Envelope too short"));
+        }
+        if data[0] != ENVELOPE_VERSION {
+            return Err(JsValue::from_str("Unsupported envelope version"));
+        }
+        let suite = data[1];
+        if ![ENVELOPE_SUITE_CORE, ENVELOPE_SUITE_WASM, ENVELOPE_SUITE_HYBRID].contains(&suite) {
+            return Err(JsValue::from_str("Unsupported envelope suite"));
+        }
+
+        let count = u16::from_le_bytes([data[2], data[3]]) as usize;
+        if count == 0 {
+            return Err(invalid());
+        }
+        if count > max_recipients {
+            return Err(JsValue::from_str("Too many recipients"));
+        }
+        if count * ENVELOPE_MIN_RECIPIENT_SIZE > data.len() - 4 {
+            return Err(invalid());
+        }
+
+        let mut offset = 4;
+        let mut recipients = Vec::with_capacity(count);
+        for _ in 0..count {
+            let id: [u8; RECIPIENT_ID_SIZE] = data
+                .get(offset..offset + RECIPIENT_ID_SIZE)
+                .ok_or_else(invalid)?
+                .try_into()
+                .map_err(|_| invalid())?;
+            offset += RECIPIENT_ID_SIZE;
+            let wrapped = read_envelope_field(data, &mut offset).ok_or_else(invalid)?;
+            recipients.push((id, wrapped.to_vec()));
+        }
+
+        let payload = read_envelope_field(data, &mut offset).ok_or_else(invalid)?;
+        if offset != data.len() {
+            return Err(invalid());
+        }
+
+        Ok(QShieldEnvelope { suite, recipients, payload: payload.to_vec() })
+    }
+}
+
+impl QShieldEnvelope {
+    /// Everything before the payload, authenticated as hybrid-suite payload AAD
+    fn header(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(ENVELOPE_VERSION);
+        out.push(self.suite);
+        out.extend_from_slice(&(self.recipients.len() as u16).to_le_bytes());
+        for (id, wrapped) in &self.recipients {
+            out.extend_from_slice(id);
+            out.extend_from_slice(&(wrapped.len() as u32).to_le_bytes());
+            out.extend_from_slice(wrapped);
+        }
+        out
+    }
+}
+
+/// Read a `[len: u32 LE][bytes]` field, advancing `offset` past it.
+fn read_envelope_field<'a>(data: &'a [u8], offset: &mut usize) -> Option<&'a [u8]> {
+    let len_bytes = data.get(*offset..offset.checked_add(4)?)?;
+    let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+    let start = *offset + 4;
+    let field = data.get(start..start.checked_add(len)?)?;
+    *offset = start + len;
+    Some(field)
+}
+
+fn recipient_id(public_key: &[u8]) -> [u8; RECIPIENT_ID_SIZE] {
+    hybrid_public_key_fingerprint(public_key)
+}
+
+/// Hybrid-suite key-encryption key for one recipient.
+fn envelope_kek(
+    x25519_shared: &[u8],
+    mlkem_shared: &[u8],
+    id: &[u8],
+    ephemeral_public: &[u8],
+    mlkem_ct: &[u8],
+) -> Result<[u8; CONTENT_KEY_SIZE], JsValue> {
+    let mut ikm = [0u8; 64];
+    ikm[..32].copy_from_slice(x25519_shared);
+    ikm[32..].copy_from_slice(mlkem_shared);
+
+    let mut kek = [0u8; CONTENT_KEY_SIZE];
+    let expanded = Hkdf::<Sha3_256>::new(Some(ENVELOPE_LABEL), &ikm)
+        .expand_multi_info(&[id, ephemeral_public, mlkem_ct], &mut kek);
+    ikm.zeroize();
+    expanded.map_err(|_| JsValue::from_str("HKDF expand failed"))?;
+    Ok(kek)
+}
+
+/// Wrap `content_key` for a default-suite public key under the hybrid suite:
+/// [ephemeral X25519 pk (32)][ML-KEM ct (1088)][ChaCha20-Poly1305 sealed key].
+fn envelope_wrap_key(public_key: &[u8], id: &[u8], content_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let (suite, x25519_pk, mlkem_ek) = parse_hybrid_public_key(public_key)?;
+    if suite != KEM_SUITE_DEFAULT {
+        return Err(unknown_kem_suite(suite));
+    }
+
+    let mut pk_bytes = [0u8; 32];
+    pk_bytes.copy_from_slice(x25519_pk);
+    let ephemeral = StaticSecret::random_from_rng(rand_core::OsRng);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral);
+    let x25519_shared = ephemeral.diffie_hellman(&X25519PublicKey::from(pk_bytes));
+    if !x25519_shared.was_contributory() {
+        return Err(JsValue::from_str("Degenerate X25519 public key"));
+    }
+
+    let (mut mlkem_shared, mlkem_ct) = mlkem_encapsulate(suite, mlkem_ek)?;
+    let kek = envelope_kek(
+        x25519_shared.as_bytes(),
+        &mlkem_shared,
+        id,
+        ephemeral_public.as_bytes(),
+        &mlkem_ct,
+    );
+    mlkem_shared.zeroize();
+    let mut kek = kek?;
+
+    let sealed = ChaCha20Poly1305::new(GenericArray::from_slice(&kek)).encrypt(
+        ChaChaNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+        Payload { msg: content_key, aad: id },
+    );
+    kek.zeroize();
+    let sealed = sealed.map_err(|_| JsValue::from_str("ChaCha encryption failed"))?;
+
+    let mut wrapped = Vec::with_capacity(ENVELOPE_HYBRID_WRAPPED_KEY_SIZE);
+    wrapped.extend_from_slice(ephemeral_public.as_bytes());
+    wrapped.extend_from_slice(&mlkem_ct);
+    wrapped.extend_from_slice(&sealed);
+    Ok(wrapped)
+}
+
+/// Recover the content key from a hybrid-suite wrapped key.
+fn envelope_unwrap_key(
+    recipient: &QShieldHybridKEM,
+    id: &[u8],
+    wrapped: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    if recipient.suite() != KEM_SUITE_DEFAULT {
+        return Err(JsValue::from_str("Envelopes require the default KEM suite"));
+    }
+    if wrapped.len() != ENVELOPE_HYBRID_WRAPPED_KEY_SIZE {
+        return Err(JsValue::from_str("Invalid wrapped key length"));
+    }
+    let (ephemeral_public, rest) = wrapped.split_at(32);
+    let (mlkem_ct, sealed) = rest.split_at(ml_kem_768::CT_LEN);
+
+    let mut pk_bytes = [0u8; 32];
+    pk_bytes.copy_from_slice(ephemeral_public);
+    let x25519_shared = recipient.x25519_secret.diffie_hellman(&X25519PublicKey::from(pk_bytes));
+    if !x25519_shared.was_contributory() {
+        return Err(JsValue::from_str("Degenerate X25519 public key"));
+    }
+
+    let mut mlkem_shared = recipient.mlkem.decapsulate(mlkem_ct)?;
+    let kek = envelope_kek(x25519_shared.as_bytes(), &mlkem_shared, id, ephemeral_public, mlkem_ct);
+    mlkem_shared.zeroize();
+    let mut kek = kek?;
+
+    let content_key = ChaCha20Poly1305::new(GenericArray::from_slice(&kek)).decrypt(
+        ChaChaNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+        Payload { msg: sealed, aad: id },
+    );
+    kek.zeroize();
+    content_key.map_err(|_| JsValue::from_str("Decryption failed"))
+}
+
+/// AES-256-GCM and ChaCha20-Poly1305 ciphers for a hybrid-suite payload.
+fn envelope_payload_ciphers(content_key: &[u8]) -> Result<(Aes256Gcm, ChaCha20Poly1305), JsValue> {
+    let mut keys = [0u8; 64];
+    Hkdf::<Sha3_256>::new(Some(ENVELOPE_LABEL), content_key)
+        .expand(b"payload", &mut keys)
+        .map_err(|_| JsValue::from_str("HKDF expand failed"))?;
+    let ciphers = (
+        Aes256Gcm::new(GenericArray::from_slice(&keys[..32])),
+        ChaCha20Poly1305::new(GenericArray::from_slice(&keys[32..])),
+    );
+    keys.zeroize();
+    Ok(ciphers)
+}
+
+fn envelope_encrypt_payload(
+    content_key: &[u8],
+    plaintext: &[u8],
+    header: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let (aes, chacha) = envelope_payload_ciphers(content_key)?;
+    let aes_ct = aes
+        .encrypt(
+            AesNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+            Payload { msg: plaintext, aad: header },
+        )
+        .map_err(|_| JsValue::from_str("AES encryption failed"))?;
+    chacha
+        .encrypt(
+            ChaChaNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+            Payload { msg: &aes_ct, aad: header },
+        )
+        .map_err(|_| JsValue::from_str("ChaCha encryption failed"))
+}
+
+fn envelope_decrypt_payload(
+    content_key: &[u8],
+    payload: &[u8],
+    header: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let (aes, chacha) = envelope_payload_ciphers(content_key)?;
+    let aes_ct = chacha
+        .decrypt(
+            ChaChaNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+            Payload { msg: payload, aad: header },
+        )
+        .map_err(|_| JsValue::from_str("Decryption failed"))?;
+    aes.decrypt(
+        AesNonce::from_slice(&ENVELOPE_SINGLE_USE_NONCE),
+        Payload { msg: &aes_ct, aad: header },
+    )
+    .map_err(|_| JsValue::from_str("Decryption failed"))
+}
+
 // ============================================================================
 // DUAL SIGNATURES — ML-DSA-65 + SLH-DSA-SHAKE-128f (FIPS 204/205)
 // ============================================================================
@@ -739,6 +3148,69 @@ impl QShieldSign {
         })
     }
 
+    /// Export both private keys for persistence:
+    /// [mldsa_len: u32 LE][ML-DSA-65 sk (4032)][SLH-DSA sk (64)].
+    ///
+    /// The returned bytes are secret; store them encrypted.
+    #[wasm_bindgen]
+    pub fn secret_key_bytes(&self) -> Vec<u8> {
+        let mut mldsa_bytes = self.mldsa_sk.clone().into_bytes();
+        let mut slhdsa_bytes = self.slhdsa_sk.clone().into_bytes();
+
+        let mut combined = Vec::with_capacity(SIGNING_SECRET_KEY_SIZE);
+        combined.extend_from_slice(&(mldsa_bytes.len() as u32).to_le_bytes());
+        combined.extend_from_slice(&mldsa_bytes);
+        combined.extend_from_slice(&slhdsa_bytes);
+
+        mldsa_bytes.zeroize();
+        slhdsa_bytes.zeroize();
+        combined
+    }
+
+    /// Restore a signer from `secret_key_bytes` output.
+    ///
+    /// Public keys are recomputed from the private keys.
+    #[wasm_bindgen]
+    pub fn from_secret_bytes(data: &[u8]) -> Result<QShieldSign, JsValue> {
+        if data.len() != SIGNING_SECRET_KEY_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid secret key length: expected {}, got {}",
+                SIGNING_SECRET_KEY_SIZE,
+                data.len()
+            )));
+        }
+
+        let mldsa_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if mldsa_len != ml_dsa_65::SK_LEN {
+            return Err(JsValue::from_str("Invalid ML-DSA secret key length"));
+        }
+
+        let mut mldsa_bytes = [0u8; ml_dsa_65::SK_LEN];
+        mldsa_bytes.copy_from_slice(&data[4..4 + ml_dsa_65::SK_LEN]);
+        let mut slhdsa_bytes = [0u8; slh_dsa_shake_128f::SK_LEN];
+        slhdsa_bytes.copy_from_slice(&data[4 + ml_dsa_65::SK_LEN..]);
+
+        let mldsa_sk = DsaSerDes::try_from_bytes(mldsa_bytes)
+            .map_err(|e| JsValue::from_str(&format!("Invalid ML-DSA secret key: {}", e)));
+        let slhdsa_sk = SlhSerDes::try_from_bytes(&slhdsa_bytes)
+            .map_err(|e| JsValue::from_str(&format!("Invalid SLH-DSA secret key: {}", e)));
+
+        mldsa_bytes.zeroize();
+        slhdsa_bytes.zeroize();
+
+        let mldsa_sk: ml_dsa_65::PrivateKey = mldsa_sk?;
+        let slhdsa_sk: slh_dsa_shake_128f::PrivateKey = slhdsa_sk?;
+        let mldsa_pk = DsaSigner::get_public_key(&mldsa_sk);
+        let slhdsa_pk = SlhSigner::get_public_key(&slhdsa_sk);
+
+        Ok(QShieldSign {
+            mldsa_sk,
+            mldsa_pk,
+            slhdsa_sk,
+            slhdsa_pk,
+        })
+    }
+
     /// Get the combined public key (ML-DSA-65 ∥ SLH-DSA).
     /// 1952 + 32 = 1984 bytes.
     #[wasm_bindgen(getter)]
@@ -768,8 +3240,20 @@ impl QShieldSign {
     /// Returns a `DualSignature` that can only be verified if BOTH signatures are valid.
     #[wasm_bindgen]
     pub fn sign(&self, message: &[u8]) -> Result<DualSignature, JsValue> {
-        let context = b"QShield-DualSign-v1";
+        self.sign_with_context(message, DEFAULT_SIGNATURE_CONTEXT)
+    }
 
+    /// Sign a message under an application-specific context (at most 255 bytes).
+    ///
+    /// The context is passed to both algorithms, so the signature only verifies
+    /// under the same context; use it to keep signatures from one protocol from
+    /// being accepted by another.
+    #[wasm_bindgen]
+    pub fn sign_with_context(
+        &self,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<DualSignature, JsValue> {
         let mldsa_sig: MlDsaSignature = DsaSigner::try_sign(&self.mldsa_sk, message, context)
             .map_err(|e| JsValue::from_str(&format!("ML-DSA signing failed: {}", e)))?;
 
@@ -782,6 +3266,65 @@ impl QShieldSign {
         })
     }
 
+    /// Sign a message digest computed by the caller, for large inputs.
+    ///
+    /// `digest_alg` is "SHA-512" (64-byte digest) or "SHA-256" (32 bytes).
+    /// Both algorithms sign in their pre-hash modes (HashML-DSA and
+    /// HashSLH-DSA) with that same hash function, under a context that ends
+    /// with its OID, so the signature verifies neither as a direct signature
+    /// nor under another digest algorithm. Hash functions without a FIPS
+    /// 204/205 pre-hash mode, such as SHA3-512, are rejected.
+    #[wasm_bindgen]
+    pub fn sign_prehashed(&self, digest: &[u8], digest_alg: &str) -> Result<DualSignature, JsValue> {
+        let (context, dsa_ph, slh_ph) = prehash_params(digest, digest_alg)?;
+
+        let mldsa_sig: MlDsaSignature =
+            DsaSigner::try_hash_sign(&self.mldsa_sk, digest, &context, &dsa_ph)
+                .map_err(|e| JsValue::from_str(&format!("ML-DSA signing failed: {}", e)))?;
+
+        let slhdsa_sig: SlhDsaSignature =
+            SlhSigner::try_hash_sign(&self.slhdsa_sk, digest, &context, &slh_ph, true)
+                .map_err(|e| JsValue::from_str(&format!("SLH-DSA signing failed: {}", e)))?;
+
+        Ok(DualSignature {
+            mldsa_signature: mldsa_sig.to_vec(),
+            slhdsa_signature: slhdsa_sig.to_vec(),
+        })
+    }
+
+    /// Sign `payload` as a tagged COSE_Sign structure (RFC 9052) with a
+    /// detached payload.
+    ///
+    /// Unlike `sign(..).to_cose()`, each algorithm signs the COSE
+    /// Sig_structure with an empty context, as COSE requires, so any COSE
+    /// library supporting both algorithms can verify the result. Verify it
+    /// here with `QShieldVerifier::verify_cose`.
+    #[wasm_bindgen]
+    pub fn sign_cose(&self, payload: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let mldsa_protected = cose_protected_header(COSE_ALG_MLDSA65);
+        let slhdsa_protected = cose_protected_header(COSE_ALG_SLHDSA_SHAKE_128F);
+
+        let mldsa_sig: MlDsaSignature = DsaSigner::try_sign(
+            &self.mldsa_sk,
+            &cose_sig_structure(&[], &mldsa_protected, payload),
+            &[],
+        )
+        .map_err(|e| JsValue::from_str(&format!("ML-DSA signing failed: {}", e)))?;
+
+        let slhdsa_sig: SlhDsaSignature = SlhSigner::try_sign(
+            &self.slhdsa_sk,
+            &cose_sig_structure(&[], &slhdsa_protected, payload),
+            &[],
+            true,
+        )
+        .map_err(|e| JsValue::from_str(&format!("SLH-DSA signing failed: {}", e)))?;
+
+        Ok(encode_cose_sign([
+            (mldsa_protected, &mldsa_sig[..]),
+            (slhdsa_protected, &slhdsa_sig[..]),
+        ]))
+    }
+
     /// Sign a UTF-8 string message.
     #[wasm_bindgen]
     pub fn sign_string(&self, message: &str) -> Result<DualSignature, JsValue> {
@@ -791,8 +3334,17 @@ impl QShieldSign {
     /// Verify a dual signature. Returns `true` only if BOTH signatures are valid.
     #[wasm_bindgen]
     pub fn verify(&self, message: &[u8], signature: &DualSignature) -> Result<bool, JsValue> {
-        let context = b"QShield-DualSign-v1";
+        self.verify_with_context(message, signature, DEFAULT_SIGNATURE_CONTEXT)
+    }
 
+    /// Verify a dual signature made under `context` (see `sign_with_context`).
+    #[wasm_bindgen]
+    pub fn verify_with_context(
+        &self,
+        message: &[u8],
+        signature: &DualSignature,
+        context: &[u8],
+    ) -> Result<bool, JsValue> {
         let mldsa_sig: MlDsaSignature = signature.mldsa_signature.clone()
             .try_into()
             .map_err(|_| JsValue::from_str("Invalid ML-DSA signature length (expected 3309 bytes)"))?;
@@ -808,11 +3360,57 @@ impl QShieldSign {
         Ok(mldsa_valid && slhdsa_valid)
     }
 
+    /// Verify a signature from `sign_prehashed` over `digest`.
+    ///
+    /// Fails if `digest_alg` differs from the one used to sign.
+    #[wasm_bindgen]
+    pub fn verify_prehashed(
+        &self,
+        digest: &[u8],
+        digest_alg: &str,
+        signature: &DualSignature,
+    ) -> Result<bool, JsValue> {
+        verify_dual_prehashed(&self.mldsa_pk, &self.slhdsa_pk, digest, digest_alg, signature)
+    }
+
     /// Verify a string message's dual signature.
     #[wasm_bindgen]
     pub fn verify_string(&self, message: &str, signature: &DualSignature) -> Result<bool, JsValue> {
         self.verify(message.as_bytes(), signature)
     }
+
+    /// Countersign an existing signature, attesting that it existed at
+    /// `timestamp` (e.g. as a timestamp authority).
+    ///
+    /// Signs the original's `bytes()` followed by `timestamp` (i64 big-endian)
+    /// under a dedicated context, so a countersignature never verifies as an
+    /// ordinary signature. Check it with `verify_countersignature`.
+    #[wasm_bindgen]
+    pub fn countersign(
+        &self,
+        original: &DualSignature,
+        timestamp: i64,
+    ) -> Result<DualSignature, JsValue> {
+        self.sign_with_context(
+            &countersignature_message(original, timestamp),
+            COUNTERSIGNATURE_CONTEXT,
+        )
+    }
+
+    /// Verify a countersignature from `countersign` over `original` and `timestamp`.
+    #[wasm_bindgen]
+    pub fn verify_countersignature(
+        &self,
+        original: &DualSignature,
+        timestamp: i64,
+        countersignature: &DualSignature,
+    ) -> Result<bool, JsValue> {
+        self.verify_with_context(
+            &countersignature_message(original, timestamp),
+            countersignature,
+            COUNTERSIGNATURE_CONTEXT,
+        )
+    }
 }
 
 impl Default for QShieldSign {
@@ -830,16 +3428,28 @@ pub struct DualSignature {
 
 #[wasm_bindgen]
 impl DualSignature {
-    /// Get the combined signature bytes (length-prefixed for parsing).
+    /// Get the combined signature bytes: a format tag followed by the
+    /// length-prefixed ML-DSA signature and the SLH-DSA signature.
     #[wasm_bindgen(getter)]
     pub fn bytes(&self) -> Vec<u8> {
-        let mut combined = Vec::with_capacity(self.mldsa_signature.len() + self.slhdsa_signature.len() + 4);
+        let mut combined = Vec::with_capacity(1 + 4 + self.mldsa_signature.len() + self.slhdsa_signature.len());
+        combined.push(SIGNATURE_FORMAT_DUAL);
         combined.extend_from_slice(&(self.mldsa_signature.len() as u32).to_le_bytes());
         combined.extend_from_slice(&self.mldsa_signature);
         combined.extend_from_slice(&self.slhdsa_signature);
         combined
     }
 
+    /// Get the signature in fixed layout: ML-DSA ∥ SLH-DSA with no format tag
+    /// or length prefix (exactly 20397 bytes), for fixed-offset parsers.
+    #[wasm_bindgen]
+    pub fn to_bytes_fixed(&self) -> Vec<u8> {
+        let mut combined = Vec::with_capacity(MLDSA_SIGNATURE_SIZE + SLHDSA_SIGNATURE_SIZE);
+        combined.extend_from_slice(&self.mldsa_signature);
+        combined.extend_from_slice(&self.slhdsa_signature);
+        combined
+    }
+
     /// Get the signature as base64.
     #[wasm_bindgen(getter)]
     pub fn base64(&self) -> String {
@@ -865,33 +3475,409 @@ impl DualSignature {
     }
 
     /// Parse a dual signature from combined bytes.
+    ///
+    /// Only the tagged format produced by `bytes` is accepted; use
+    /// `from_bytes_with_options` to read untagged legacy signatures.
     #[wasm_bindgen]
     pub fn from_bytes(data: &[u8]) -> Result<DualSignature, JsValue> {
+        Self::from_bytes_with_options(data, false)
+    }
+
+    /// Parse a dual signature, optionally accepting the legacy untagged format
+    /// (`[mldsa_len][mldsa][slhdsa]`) written before format tags were added.
+    #[wasm_bindgen]
+    pub fn from_bytes_with_options(data: &[u8], allow_legacy: bool) -> Result<DualSignature, JsValue> {
+        match data.first() {
+            Some(&SIGNATURE_FORMAT_DUAL) => Self::parse_prefixed(&data[1..]),
+            // Legacy blobs start with the LE length prefix 3309 (0xED), never a format tag
+            Some(_) if allow_legacy => Self::parse_prefixed(data),
+            Some(_) => Err(JsValue::from_str("Unsupported signature format")),
+            None => Err(JsValue::from_str("Signature too short")),
+        }
+    }
+
+    /// Parse a dual signature from the fixed layout produced by `to_bytes_fixed`.
+    #[wasm_bindgen]
+    pub fn from_bytes_fixed(data: &[u8]) -> Result<DualSignature, JsValue> {
+        if data.len() != MLDSA_SIGNATURE_SIZE + SLHDSA_SIGNATURE_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid fixed signature length: expected {}, got {}",
+                MLDSA_SIGNATURE_SIZE + SLHDSA_SIGNATURE_SIZE,
+                data.len()
+            )));
+        }
+
+        let (mldsa, slhdsa) = data.split_at(MLDSA_SIGNATURE_SIZE);
+        Self::from_components(mldsa, slhdsa)
+    }
+
+    /// Parse a dual signature from base64.
+    #[wasm_bindgen]
+    pub fn from_base64(b64: &str) -> Result<DualSignature, JsValue> {
+        let data = BASE64.decode(b64)
+            .map_err(|_| JsValue::from_str("Invalid base64"))?;
+        Self::from_bytes(&data)
+    }
+
+    /// Wrap in a tagged COSE_Sign structure (RFC 9052) with a detached
+    /// payload and one COSE_Signature per algorithm, for transport.
+    ///
+    /// Each signature's protected header names its algorithm: -49 for
+    /// ML-DSA-65 and -65537 (private use, until a code point is registered)
+    /// for SLH-DSA-SHAKE-128f. The signature bytes are unchanged: they sign
+    /// the message under the QShield context, not a COSE Sig_structure, so
+    /// generic COSE verifiers reject them. Check them with `QShieldVerifier`
+    /// after `from_cose`, or use `QShieldSign::sign_cose` for signatures that
+    /// COSE libraries can verify.
+    #[wasm_bindgen]
+    pub fn to_cose(&self) -> Vec<u8> {
+        encode_cose_sign([
+            (cose_protected_header(COSE_ALG_MLDSA65), &self.mldsa_signature),
+            (cose_protected_header(COSE_ALG_SLHDSA_SHAKE_128F), &self.slhdsa_signature),
+        ])
+    }
+
+    /// Parse a COSE_Sign structure from `to_cose` or another COSE encoder.
+    ///
+    /// The CBOR tag is optional and unprotected headers are ignored. Exactly
+    /// one signature of each algorithm is required.
+    #[wasm_bindgen]
+    pub fn from_cose(data: &[u8]) -> Result<DualSignature, JsValue> {
+        let parts = CoseSignParts::parse(data)?;
+        Self::from_components(parts.mldsa.1, parts.slhdsa.1)
+    }
+}
+
+impl DualSignature {
+    /// Parse `[mldsa_len: u32 LE][mldsa][slhdsa]`, requiring exact component sizes.
+    fn parse_prefixed(data: &[u8]) -> Result<DualSignature, JsValue> {
         if data.len() < 4 {
             return Err(JsValue::from_str("Signature too short"));
         }
 
         let mldsa_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-
-        if data.len() < 4 + mldsa_len {
+        if mldsa_len > data.len() - 4 {
             return Err(JsValue::from_str("Invalid signature format"));
         }
 
-        let mldsa_signature = data[4..4 + mldsa_len].to_vec();
-        let slhdsa_signature = data[4 + mldsa_len..].to_vec();
+        let (mldsa, slhdsa) = data[4..].split_at(mldsa_len);
+        Self::from_components(mldsa, slhdsa)
+    }
+
+    /// Check that each slot holds a plausible signature of its own algorithm.
+    ///
+    /// The sizes differ (3309 vs 17088 bytes), so a swapped or duplicated
+    /// component is recognisable by length alone and gets its own error.
+    fn from_components(mldsa: &[u8], slhdsa: &[u8]) -> Result<DualSignature, JsValue> {
+        match (mldsa.len(), slhdsa.len()) {
+            (MLDSA_SIGNATURE_SIZE, SLHDSA_SIGNATURE_SIZE) => {}
+            (SLHDSA_SIGNATURE_SIZE, MLDSA_SIGNATURE_SIZE) => {
+                return Err(JsValue::from_str("Signature components swapped"))
+            }
+            (MLDSA_SIGNATURE_SIZE, MLDSA_SIGNATURE_SIZE) => {
+                return Err(JsValue::from_str("ML-DSA signature in SLH-DSA slot"))
+            }
+            _ => return Err(JsValue::from_str("Invalid signature format")),
+        }
+
+        if mldsa.iter().all(|&b| b == 0) {
+            return Err(JsValue::from_str("ML-DSA signature is all zeros"));
+        }
+        if slhdsa.iter().all(|&b| b == 0) {
+            return Err(JsValue::from_str("SLH-DSA signature is all zeros"));
+        }
 
         Ok(DualSignature {
-            mldsa_signature,
-            slhdsa_signature,
+            mldsa_signature: mldsa.to_vec(),
+            slhdsa_signature: slhdsa.to_vec(),
         })
     }
+}
 
-    /// Parse a dual signature from base64.
-    #[wasm_bindgen]
-    pub fn from_base64(b64: &str) -> Result<DualSignature, JsValue> {
-        let data = BASE64.decode(b64)
-            .map_err(|_| JsValue::from_str("Invalid base64"))?;
-        Self::from_bytes(&data)
+/// Append a CBOR item head (major type and argument) in shortest form.
+fn cbor_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn cbor_int(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        cbor_head(out, 0, value as u64);
+    } else {
+        // Negative integers encode -1 - value
+        cbor_head(out, 1, !value as u64);
+    }
+}
+
+fn cbor_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    cbor_head(out, 2, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+/// Serialized COSE protected header `{1: alg}`.
+fn cose_protected_header(alg: i64) -> Vec<u8> {
+    let mut protected = Vec::new();
+    cbor_head(&mut protected, 5, 1);
+    cbor_int(&mut protected, COSE_HEADER_ALG);
+    cbor_int(&mut protected, alg);
+    protected
+}
+
+/// Tagged COSE_Sign with empty body headers, a detached payload and one
+/// COSE_Signature per `(protected header, signature)`.
+fn encode_cose_sign<S: AsRef<[u8]>>(signatures: [(Vec<u8>, S); 2]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(
+        32 + signatures.iter().map(|(_, sig)| sig.as_ref().len()).sum::<usize>(),
+    );
+    cbor_head(&mut out, 6, COSE_SIGN_TAG);
+    cbor_head(&mut out, 4, 4);
+    cbor_bytes(&mut out, &[]); // protected
+    cbor_head(&mut out, 5, 0); // unprotected
+    out.push(0xf6); // payload: nil (detached)
+
+    cbor_head(&mut out, 4, 2);
+    for (protected, signature) in &signatures {
+        cbor_head(&mut out, 4, 3);
+        cbor_bytes(&mut out, protected);
+        cbor_head(&mut out, 5, 0);
+        cbor_bytes(&mut out, signature.as_ref());
+    }
+    out
+}
+
+/// The COSE Sig_structure a COSE_Signature signs (RFC 9052, section 4.4),
+/// with no external AAD.
+fn cose_sig_structure(body_protected: &[u8], sign_protected: &[u8], payload: &[u8]) -> Vec<u8> {
+    const CONTEXT: &[u8] = b"Signature";
+    let mut out =
+        Vec::with_capacity(32 + body_protected.len() + sign_protected.len() + payload.len());
+    cbor_head(&mut out, 4, 5);
+    cbor_head(&mut out, 3, CONTEXT.len() as u64);
+    out.extend_from_slice(CONTEXT);
+    cbor_bytes(&mut out, body_protected);
+    cbor_bytes(&mut out, sign_protected);
+    cbor_bytes(&mut out, &[]); // external_aad
+    cbor_bytes(&mut out, payload);
+    out
+}
+
+/// The fields of a COSE_Sign structure that signatures cover.
+struct CoseSignParts<'a> {
+    body_protected: &'a [u8],
+    /// (protected header, signature) for each algorithm
+    mldsa: (&'a [u8], &'a [u8]),
+    slhdsa: (&'a [u8], &'a [u8]),
+}
+
+impl<'a> CoseSignParts<'a> {
+    /// Parse a COSE_Sign structure, with or without its CBOR tag.
+    fn parse(data: &'a [u8]) -> Result<Self, JsValue> {
+        let invalid = || JsValue::from_str("Invalid COSE_Sign structure");
+        let mut reader = CborReader::new(data);
+
+        let mut head = reader.head()?;
+        if head == (6, COSE_SIGN_TAG) {
+            head = reader.head()?;
+        }
+        if head != (4, 4) {
+            return Err(invalid());
+        }
+        let body_protected = reader.bytes()?;
+        reader.skip_map()?; // unprotected
+        match reader.head()? {
+            (2, len) => {
+                reader.take(len)?;
+            }
+            (7, 22) => {}
+            _ => return Err(invalid()),
+        }
+
+        let mut mldsa = None;
+        let mut slhdsa = None;
+        for _ in 0..reader.array()? {
+            if reader.array()? != 3 {
+                return Err(invalid());
+            }
+            let protected = reader.bytes()?;
+            let alg = cose_header_alg(protected)?;
+            reader.skip_map()?;
+            let signature = reader.bytes()?;
+
+            let slot = match alg {
+                COSE_ALG_MLDSA65 => &mut mldsa,
+                COSE_ALG_SLHDSA_SHAKE_128F => &mut slhdsa,
+                _ => {
+                    return Err(JsValue::from_str(&format!(
+                        "Unsupported COSE signature algorithm: {}",
+                        alg
+                    )))
+                }
+            };
+            if slot.replace((protected, signature)).is_some() {
+                return Err(JsValue::from_str("Duplicate COSE signature algorithm"));
+            }
+        }
+        if !reader.is_empty() {
+            return Err(invalid());
+        }
+
+        match (mldsa, slhdsa) {
+            (Some(mldsa), Some(slhdsa)) => Ok(Self { body_protected, mldsa, slhdsa }),
+            _ => Err(JsValue::from_str("COSE_Sign is missing a signature algorithm")),
+        }
+    }
+}
+
+/// The `alg` (label 1) value of a serialized COSE protected header.
+fn cose_header_alg(protected: &[u8]) -> Result<i64, JsValue> {
+    let mut reader = CborReader::new(protected);
+    let mut alg = None;
+
+    for _ in 0..reader.map()? {
+        let (major, value) = reader.head()?;
+        match major {
+            0 | 1 if cbor_int_value(major, value) == Some(COSE_HEADER_ALG) => {
+                let (major, value) = reader.head()?;
+                let value = cbor_int_value(major, value)
+                    .ok_or_else(|| JsValue::from_str("Invalid COSE algorithm identifier"))?;
+                if alg.replace(value).is_some() {
+                    return Err(JsValue::from_str("Invalid COSE_Sign structure"));
+                }
+            }
+            0 | 1 => reader.skip(0)?,
+            2 | 3 => {
+                reader.take(value)?;
+                reader.skip(0)?;
+            }
+            _ => return Err(JsValue::from_str("Invalid COSE_Sign structure")),
+        }
+    }
+    if !reader.is_empty() {
+        return Err(JsValue::from_str("Invalid COSE_Sign structure"));
+    }
+
+    alg.ok_or_else(|| JsValue::from_str("COSE signature has no algorithm identifier"))
+}
+
+/// A CBOR integer from its major type (0 or 1) and argument.
+fn cbor_int_value(major: u8, value: u64) -> Option<i64> {
+    let value = i64::try_from(value).ok()?;
+    match major {
+        0 => Some(value),
+        1 => Some(-1 - value),
+        _ => None,
+    }
+}
+
+/// Minimal reader for the definite-length CBOR used by COSE_Sign.
+struct CborReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset == self.data.len()
+    }
+
+    fn take(&mut self, len: u64) -> Result<&'a [u8], JsValue> {
+        let truncated = || JsValue::from_str("Truncated COSE_Sign structure");
+        let len = usize::try_from(len).map_err(|_| truncated())?;
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(truncated)?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Read an item head: major type and argument. Indefinite lengths are rejected.
+    fn head(&mut self) -> Result<(u8, u64), JsValue> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let value = match initial & 0x1f {
+            info @ 0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => self.take(2)?.iter().fold(0, |acc, &b| (acc << 8) | b as u64),
+            26 => self.take(4)?.iter().fold(0, |acc, &b| (acc << 8) | b as u64),
+            27 => self.take(8)?.iter().fold(0, |acc, &b| (acc << 8) | b as u64),
+            _ => return Err(JsValue::from_str("Unsupported CBOR encoding")),
+        };
+        Ok((major, value))
+    }
+
+    fn expect(&mut self, major: u8) -> Result<u64, JsValue> {
+        match self.head()? {
+            (found, value) if found == major => Ok(value),
+            _ => Err(JsValue::from_str("Invalid COSE_Sign structure")),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], JsValue> {
+        let len = self.expect(2)?;
+        self.take(len)
+    }
+
+    fn array(&mut self) -> Result<u64, JsValue> {
+        self.expect(4)
+    }
+
+    fn map(&mut self) -> Result<u64, JsValue> {
+        self.expect(5)
+    }
+
+    fn skip_map(&mut self) -> Result<(), JsValue> {
+        for _ in 0..self.map()?.saturating_mul(2) {
+            self.skip(0)?;
+        }
+        Ok(())
+    }
+
+    /// Skip one complete item of any type.
+    fn skip(&mut self, depth: usize) -> Result<(), JsValue> {
+        if depth > CBOR_MAX_DEPTH {
+            return Err(JsValue::from_str("COSE_Sign structure nested too deeply"));
+        }
+        let (major, value) = self.head()?;
+        let items = match major {
+            2 | 3 => {
+                self.take(value)?;
+                0
+            }
+            4 => value,
+            5 => value.saturating_mul(2),
+            6 => 1,
+            _ => 0,
+        };
+        // Every item is at least one byte, so longer counts are truncated input
+        if items > (self.data.len() - self.offset) as u64 {
+            return Err(JsValue::from_str("Truncated COSE_Sign structure"));
+        }
+        for _ in 0..items {
+            self.skip(depth + 1)?;
+        }
+        Ok(())
     }
 }
 
@@ -899,6 +3885,35 @@ impl DualSignature {
 // VERIFIER — Verify signatures with public key only
 // ============================================================================
 
+/// Per-algorithm result of `QShieldVerifier::verify_detailed`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationReport {
+    mldsa_valid: bool,
+    slhdsa_valid: bool,
+}
+
+#[wasm_bindgen]
+impl VerificationReport {
+    /// Whether the ML-DSA-65 signature verified.
+    #[wasm_bindgen(getter)]
+    pub fn mldsa_valid(&self) -> bool {
+        self.mldsa_valid
+    }
+
+    /// Whether the SLH-DSA-SHAKE-128f signature verified.
+    #[wasm_bindgen(getter)]
+    pub fn slhdsa_valid(&self) -> bool {
+        self.slhdsa_valid
+    }
+
+    /// Whether both verified: the same result `verify` gives.
+    #[wasm_bindgen(getter)]
+    pub fn valid(&self) -> bool {
+        self.mldsa_valid && self.slhdsa_valid
+    }
+}
+
 /// Signature verifier that requires only a public key (no private key).
 ///
 /// Use this when you need to verify signatures without access to the signing key,
@@ -948,8 +3963,17 @@ impl QShieldVerifier {
     /// Verify a dual signature. Returns `true` only if BOTH signatures are valid.
     #[wasm_bindgen]
     pub fn verify(&self, message: &[u8], signature: &DualSignature) -> Result<bool, JsValue> {
-        let context = b"QShield-DualSign-v1";
+        self.verify_with_context(message, signature, DEFAULT_SIGNATURE_CONTEXT)
+    }
 
+    /// Verify a dual signature made under `context` (see `sign_with_context`).
+    #[wasm_bindgen]
+    pub fn verify_with_context(
+        &self,
+        message: &[u8],
+        signature: &DualSignature,
+        context: &[u8],
+    ) -> Result<bool, JsValue> {
         let mldsa_sig: MlDsaSignature = signature.mldsa_signature.clone()
             .try_into()
             .map_err(|_| JsValue::from_str("Invalid ML-DSA signature length (expected 3309 bytes)"))?;
@@ -965,18 +3989,192 @@ impl QShieldVerifier {
         Ok(mldsa_valid && slhdsa_valid)
     }
 
+    /// Verify a signature from `sign_prehashed` over `digest`.
+    ///
+    /// Fails if `digest_alg` differs from the one used to sign.
+    #[wasm_bindgen]
+    pub fn verify_prehashed(
+        &self,
+        digest: &[u8],
+        digest_alg: &str,
+        signature: &DualSignature,
+    ) -> Result<bool, JsValue> {
+        verify_dual_prehashed(&self.mldsa_pk, &self.slhdsa_pk, digest, digest_alg, signature)
+    }
+
+    /// Verify a COSE_Sign structure from `QShieldSign::sign_cose` (or any
+    /// COSE signer using the same algorithms) over the detached `payload`.
+    ///
+    /// Returns `true` only if both signatures verify over their COSE
+    /// Sig_structures. An attached payload in the structure is ignored.
+    #[wasm_bindgen]
+    pub fn verify_cose(&self, cose: &[u8], payload: &[u8]) -> Result<bool, JsValue> {
+        let parts = CoseSignParts::parse(cose)?;
+        let (mldsa_protected, mldsa_sig) = parts.mldsa;
+        let (slhdsa_protected, slhdsa_sig) = parts.slhdsa;
+
+        let mldsa_sig: MlDsaSignature = mldsa_sig
+            .try_into()
+            .map_err(|_| JsValue::from_str("Invalid ML-DSA signature length (expected 3309 bytes)"))?;
+        let slhdsa_sig: SlhDsaSignature = slhdsa_sig
+            .try_into()
+            .map_err(|_| JsValue::from_str("Invalid SLH-DSA signature length (expected 17088 bytes)"))?;
+
+        let mldsa_message = cose_sig_structure(parts.body_protected, mldsa_protected, payload);
+        let slhdsa_message = cose_sig_structure(parts.body_protected, slhdsa_protected, payload);
+        let mldsa_valid = DsaVerifier::verify(&self.mldsa_pk, &mldsa_message, &mldsa_sig, &[]);
+        let slhdsa_valid = SlhVerifier::verify(&self.slhdsa_pk, &slhdsa_message, &slhdsa_sig, &[]);
+
+        Ok(mldsa_valid && slhdsa_valid)
+    }
+
     /// Verify a string message's dual signature.
     #[wasm_bindgen]
     pub fn verify_string(&self, message: &str, signature: &DualSignature) -> Result<bool, JsValue> {
         self.verify(message.as_bytes(), signature)
     }
 
+    /// Verify each half of a dual signature separately, for diagnostics.
+    ///
+    /// A component of the wrong length counts as invalid. Use `verify` to
+    /// accept signatures; this only reports which algorithm failed.
+    #[wasm_bindgen]
+    pub fn verify_detailed(&self, message: &[u8], signature: &DualSignature) -> VerificationReport {
+        let context = DEFAULT_SIGNATURE_CONTEXT;
+
+        let mldsa_valid = MlDsaSignature::try_from(signature.mldsa_signature.clone())
+            .is_ok_and(|sig| DsaVerifier::verify(&self.mldsa_pk, message, &sig, context));
+        let slhdsa_valid = SlhDsaSignature::try_from(signature.slhdsa_signature.clone())
+            .is_ok_and(|sig| SlhVerifier::verify(&self.slhdsa_pk, message, &sig, context));
+
+        VerificationReport {
+            mldsa_valid,
+            slhdsa_valid,
+        }
+    }
+
+    /// Verify a string message's dual signature made under `context`.
+    #[wasm_bindgen]
+    pub fn verify_string_with_context(
+        &self,
+        message: &str,
+        signature: &DualSignature,
+        context: &[u8],
+    ) -> Result<bool, JsValue> {
+        self.verify_with_context(message.as_bytes(), signature, context)
+    }
+
     /// Verify using a base64-encoded signature.
     #[wasm_bindgen]
     pub fn verify_base64(&self, message: &[u8], signature_b64: &str) -> Result<bool, JsValue> {
+        self.verify_base64_with_context(message, signature_b64, DEFAULT_SIGNATURE_CONTEXT)
+    }
+
+    /// Verify using a base64-encoded signature made under `context`.
+    #[wasm_bindgen]
+    pub fn verify_base64_with_context(
+        &self,
+        message: &[u8],
+        signature_b64: &str,
+        context: &[u8],
+    ) -> Result<bool, JsValue> {
         let signature = DualSignature::from_base64(signature_b64)?;
-        self.verify(message, &signature)
+        self.verify_with_context(message, &signature, context)
+    }
+
+    /// Verify a countersignature (see `QShieldSign::countersign`) made by the
+    /// holder of this public key over `original` and `timestamp`.
+    ///
+    /// This only checks the countersignature; verify `original` against its
+    /// own signer's key separately.
+    #[wasm_bindgen]
+    pub fn verify_countersignature(
+        &self,
+        original: &DualSignature,
+        timestamp: i64,
+        countersignature: &DualSignature,
+    ) -> Result<bool, JsValue> {
+        self.verify_with_context(
+            &countersignature_message(original, timestamp),
+            countersignature,
+            COUNTERSIGNATURE_CONTEXT,
+        )
+    }
+}
+
+/// Message covered by a countersignature: `original.bytes()` ∥ `timestamp` (i64 BE).
+fn countersignature_message(original: &DualSignature, timestamp: i64) -> Vec<u8> {
+    let mut message = original.bytes();
+    message.extend_from_slice(&timestamp.to_be_bytes());
+    message
+}
+
+/// Parameters for pre-hashed signing with `digest_alg`, after checking the
+/// digest length matches it: the context (`PREHASH_SIGNATURE_CONTEXT` ∥ DER
+/// OID of `digest_alg`) and the FIPS 204/205 pre-hash modes of that same
+/// hash function.
+///
+/// Only hash functions with a pre-hash mode in both standards are accepted,
+/// so the HashML-DSA and HashSLH-DSA signatures always name `digest_alg`.
+fn prehash_params(digest: &[u8], digest_alg: &str) -> Result<(Vec<u8>, DsaPh, SlhPh), JsValue> {
+    let (oid, digest_len, dsa_ph, slh_ph): (&[u8], usize, DsaPh, SlhPh) = match digest_alg {
+        // id-sha512, 2.16.840.1.101.3.4.2.3
+        "SHA-512" => (
+            &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03],
+            64,
+            DsaPh::SHA512,
+            SlhPh::SHA512,
+        ),
+        // id-sha256, 2.16.840.1.101.3.4.2.1
+        "SHA-256" => (
+            &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
+            32,
+            DsaPh::SHA256,
+            SlhPh::SHA256,
+        ),
+        _ => {
+            return Err(JsValue::from_str(&format!(
+                "Unsupported digest algorithm: {}",
+                digest_alg
+            )))
+        }
+    };
+    if digest.len() != digest_len {
+        return Err(JsValue::from_str(&format!(
+            "Invalid {} digest length: expected {}, got {}",
+            digest_alg,
+            digest_len,
+            digest.len()
+        )));
     }
+
+    Ok(([PREHASH_SIGNATURE_CONTEXT, oid].concat(), dsa_ph, slh_ph))
+}
+
+fn verify_dual_prehashed(
+    mldsa_pk: &ml_dsa_65::PublicKey,
+    slhdsa_pk: &slh_dsa_shake_128f::PublicKey,
+    digest: &[u8],
+    digest_alg: &str,
+    signature: &DualSignature,
+) -> Result<bool, JsValue> {
+    let (context, dsa_ph, slh_ph) = prehash_params(digest, digest_alg)?;
+
+    let mldsa_sig: MlDsaSignature = signature.mldsa_signature.clone()
+        .try_into()
+        .map_err(|_| JsValue::from_str("Invalid ML-DSA signature length (expected 3309 bytes)"))?;
+
+    let mldsa_valid =
+        DsaVerifier::hash_verify(mldsa_pk, digest, &mldsa_sig, &context, &dsa_ph);
+
+    let slhdsa_sig: SlhDsaSignature = signature.slhdsa_signature.clone()
+        .try_into()
+        .map_err(|_| JsValue::from_str("Invalid SLH-DSA signature length (expected 17088 bytes)"))?;
+
+    let slhdsa_valid =
+        SlhVerifier::hash_verify(slhdsa_pk, digest, &slhdsa_sig, &context, &slh_ph);
+
+    Ok(mldsa_valid && slhdsa_valid)
 }
 
 // ============================================================================
@@ -1065,15 +4263,15 @@ pub fn benchmark_hybrid_kem(iterations: u32) -> Result<JsValue, JsValue> {
         keygen_time / iterations as f64,
         encaps_time / iterations as f64,
         decaps_time / iterations as f64,
-        32 + 1184,
-        32 + 1088
+        peer_pk.len(),
+        ct.len()
     )))
 }
 
 /// Benchmark symmetric encryption/decryption throughput.
 #[wasm_bindgen]
 pub fn benchmark(iterations: u32, data_size: usize) -> Result<JsValue, JsValue> {
-    let cipher = QShieldCipher::from_bytes(b"benchmark-key-32-bytes-exactly!!")?;
+    let cipher = QShieldCipher::insecure_test_key("benchmark")?;
     let data: Vec<u8> = (0..data_size).map(|i| i as u8).collect();
 
     let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
@@ -1114,7 +4312,93 @@ pub fn secure_compare(a: &[u8], b: &[u8]) -> bool {
     if a.len() != b.len() {
         return false;
     }
-    a.ct_eq(b).into()
+    a.ct_eq(b).into()
+}
+
+/// Recipient secret key of the core-sealed envelope vector (test-vectors/envelope):
+/// X25519 secret key (32) || ML-KEM-768 decapsulation key (2400)
+const SELF_TEST_RECIPIENT_KEY: &[u8] =
+    include_bytes!("../../test-vectors/envelope/recipient_secret_key.bin");
+const SELF_TEST_ENVELOPE: &[u8] =
+    include_bytes!("../../test-vectors/envelope/hybrid_envelope.bin");
+const SELF_TEST_ENVELOPE_PLAINTEXT: &[u8] = b"sealed by the core crate";
+
+/// Power-on self-test: open a known envelope, then exercise the hybrid KEM,
+/// dual signatures, and the cascade cipher once each, failing with the name
+/// of the first primitive that misbehaves.
+///
+/// The known answer covers X25519, ML-KEM-768 decapsulation, and the AEAD
+/// layers against output of the core crate. Signatures and `QShieldCipher`
+/// are randomized and have no shared vector, so they get pairwise-consistency
+/// checks only.
+///
+/// Call at startup and refuse to encrypt anything if it throws.
+#[wasm_bindgen]
+pub fn self_test() -> Result<(), JsValue> {
+    let fail = |primitive: &str| JsValue::from_str(&format!("Self-test failed: {}", primitive));
+
+    // Known answer: open an envelope sealed by the core crate
+    let mut exported = vec![KEM_SUITE_DEFAULT];
+    exported.extend_from_slice(&32u32.to_le_bytes());
+    exported.extend_from_slice(&SELF_TEST_RECIPIENT_KEY[..32]);
+    exported.extend_from_slice(&2400u32.to_le_bytes());
+    exported.extend_from_slice(&SELF_TEST_RECIPIENT_KEY[32..]);
+    let recipient = QShieldHybridKEM::from_secret_bytes(&exported)
+        .map_err(|_| fail("QShieldHybridKEM known answer"))?;
+    exported.zeroize();
+    let opened = QShieldEnvelope::from_bytes(SELF_TEST_ENVELOPE)
+        .and_then(|envelope| envelope.open(&recipient))
+        .map_err(|_| fail("QShieldHybridKEM known answer"))?;
+    if opened != SELF_TEST_ENVELOPE_PLAINTEXT {
+        return Err(fail("QShieldHybridKEM known answer"));
+    }
+
+    // Hybrid KEM: encapsulate to our own key and recover the same secret
+    let kem = QShieldHybridKEM::new().map_err(|_| fail("QShieldHybridKEM keygen"))?;
+    let encapsulation = kem
+        .encapsulate(&kem.public_key())
+        .map_err(|_| fail("QShieldHybridKEM encapsulate"))?;
+    let shared_secret = kem
+        .decapsulate(&encapsulation.ciphertext)
+        .map_err(|_| fail("QShieldHybridKEM decapsulate"))?;
+    if shared_secret != encapsulation.shared_secret {
+        return Err(fail("QShieldHybridKEM"));
+    }
+
+    // Dual signatures: accept the signed message, reject any other
+    let signer = QShieldSign::new().map_err(|_| fail("QShieldSign keygen"))?;
+    let signature = signer
+        .sign(b"QuantumShield self-test: sign")
+        .map_err(|_| fail("QShieldSign sign"))?;
+    let valid = signer
+        .verify(b"QuantumShield self-test: sign", &signature)
+        .map_err(|_| fail("QShieldSign verify"))?;
+    let forged = signer
+        .verify(b"QuantumShield self-test: forged", &signature)
+        .map_err(|_| fail("QShieldSign verify"))?;
+    if !valid || forged {
+        return Err(fail("QShieldSign"));
+    }
+
+    // Cascade cipher: round trip, then reject a flipped bit
+    let cipher = QShieldCipher::from_bytes(&shared_secret).map_err(|_| fail("QShieldCipher"))?;
+    let mut ciphertext = cipher
+        .encrypt_with_aad(b"QuantumShield self-test: cascade", b"self-test")
+        .map_err(|_| fail("QShieldCipher encrypt"))?;
+    let plaintext = cipher
+        .decrypt_with_aad(&ciphertext, b"self-test")
+        .map_err(|_| fail("QShieldCipher decrypt"))?;
+    if plaintext != b"QuantumShield self-test: cascade" {
+        return Err(fail("QShieldCipher"));
+    }
+    if let Some(last) = ciphertext.last_mut() {
+        *last ^= 0x01;
+    }
+    if cipher.decrypt_with_aad(&ciphertext, b"self-test").is_ok() {
+        return Err(fail("QShieldCipher authentication"));
+    }
+
+    Ok(())
 }
 
 /// Get library information as JSON.
@@ -1146,6 +4430,43 @@ pub fn demo(message: &str, password: &str) -> Result<String, JsValue> {
 mod tests {
     use super::*;
 
+    const KEM_CIPHERTEXT_SIZE: usize = 1 + LEGACY_KEM_CIPHERTEXT_SIZE;
+
+    #[test]
+    fn test_encrypt_with_nonce_roundtrip() {
+        let sender = QShieldCipher::from_bytes(b"record-layer-key").unwrap();
+        let receiver = QShieldCipher::from_bytes(b"record-layer-key").unwrap();
+
+        let first = sender.encrypt_with_nonce(b"record 0", b"hdr", 0, 0).unwrap();
+        let second = sender.encrypt_with_nonce(b"record 1", b"hdr", 0, 1).unwrap();
+        assert_eq!(receiver.decrypt_with_nonce(&first, b"hdr", 0, 0).unwrap(), b"record 0");
+        assert_eq!(receiver.decrypt_with_nonce(&second, b"hdr", 0, 1).unwrap(), b"record 1");
+        assert_eq!(sender.last_sequence(), Some(1));
+
+        // No nonces on the wire
+        let random_nonce = sender.encrypt_with_aad(b"record 2", b"hdr").unwrap();
+        let sequenced = sender.encrypt_with_nonce(b"record 2", b"hdr", 0, 2).unwrap();
+        assert_eq!(random_nonce.len() - sequenced.len(), HEADER_SIZE - SEQUENCED_HEADER_SIZE);
+
+        // The peer's sequence 0 uses different nonces
+        let reply = receiver.encrypt_with_nonce(b"reply 0", b"hdr", 1, 0).unwrap();
+        let (aes_0, chacha_0) = sender.sequence_nonces(0, 0);
+        let (aes_1, chacha_1) = sender.sequence_nonces(1, 0);
+        assert_ne!(aes_0, aes_1);
+        assert_ne!(chacha_0, chacha_1);
+        assert_eq!(sender.decrypt_with_nonce(&reply, b"hdr", 1, 0).unwrap(), b"reply 0");
+    }
+
+    #[test]
+    fn test_insecure_test_key() {
+        let cipher = QShieldCipher::insecure_test_key("unit-test").unwrap();
+        let same = QShieldCipher::insecure_test_key("unit-test").unwrap();
+
+        let encrypted = cipher.encrypt(b"fixture data").unwrap();
+        assert_eq!(same.decrypt(&encrypted).unwrap(), b"fixture data");
+        assert!(cipher.has_length_hiding());
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let cipher = QShieldCipher::from_bytes(b"test-key-32-bytes-exactly-here!").unwrap();
@@ -1155,6 +4476,114 @@ mod tests {
         assert_eq!(data.as_slice(), decrypted.as_slice());
     }
 
+    #[test]
+    fn test_from_bytes_with_hash() {
+        let secret = b"kdf-hash-selection-secret";
+        let default = QShieldCipher::from_bytes(secret).unwrap();
+        let sha3_512 = QShieldCipher::from_bytes_with_hash(secret, KdfHash::Sha3_512).unwrap();
+        let sha3_256 = QShieldCipher::from_bytes_with_hash(secret, KdfHash::Sha3_256).unwrap();
+        assert_eq!(default.kdf_hash(), KdfHash::Sha3_512);
+        assert_eq!(sha3_256.kdf_hash(), KdfHash::Sha3_256);
+
+        // SHA3-512 is what from_bytes already used
+        let encrypted = default.encrypt(b"same keys").unwrap();
+        assert_eq!(sha3_512.decrypt(&encrypted).unwrap(), b"same keys");
+        assert_eq!(encrypted[1] & FLAG_KDF_SHA3_256, 0);
+
+        // SHA3-256 round-trips and records the hash in the flags
+        let encrypted = sha3_256.encrypt(b"smaller hash").unwrap();
+        assert_eq!(encrypted[1], FLAG_PADDED | FLAG_KDF_SHA3_256);
+        assert_eq!(sha3_256.decrypt(&encrypted).unwrap(), b"smaller hash");
+        let sequenced = sha3_256.encrypt_with_nonce(b"sequenced", b"", 0, 1).unwrap();
+        assert_eq!(sha3_256.decrypt_with_nonce(&sequenced, b"", 0, 1).unwrap(), b"sequenced");
+    }
+
+    #[test]
+    fn test_chunked_stream_roundtrip() {
+        let sender = QShieldCipher::from_bytes(b"chunked-stream-secret").unwrap();
+        let receiver = QShieldCipher::from_bytes(b"chunked-stream-secret").unwrap();
+        let data: Vec<u8> = (0..10 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let chunks: Vec<&[u8]> = data.chunks(64 * 1024).collect();
+
+        let header = sender.begin_stream().unwrap();
+        assert_eq!(header.len(), STREAM_HEADER_SIZE);
+        let encrypted: Vec<Vec<u8>> = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                sender.encrypt_chunk(chunk, i as u64, i == chunks.len() - 1).unwrap()
+            })
+            .collect();
+
+        receiver.begin_decrypt_stream(&header).unwrap();
+        let mut decrypted = Vec::new();
+        for (i, chunk) in encrypted.iter().enumerate() {
+            let last = i == encrypted.len() - 1;
+            decrypted.extend(receiver.decrypt_chunk(chunk, i as u64, last).unwrap());
+        }
+        receiver.finish_decrypt_stream().unwrap();
+        assert_eq!(decrypted, data);
+
+        // Same plaintext as the one-shot path, with 32 bytes of tags per chunk
+        let one_shot = sender.encrypt(&data).unwrap();
+        assert_eq!(receiver.decrypt(&one_shot).unwrap(), decrypted);
+        let streamed_len: usize = encrypted.iter().map(Vec::len).sum();
+        assert_eq!(streamed_len, data.len() + 32 * chunks.len());
+
+        // A second stream gets fresh nonce material
+        assert_ne!(sender.begin_stream().unwrap(), header);
+    }
+
+    #[test]
+    fn test_streamed_base64_matches_encrypt_string() {
+        let cipher = QShieldCipher::from_bytes(b"base64-streaming-key").unwrap();
+        let message = "streamed to a download without one big base64 string";
+        let ciphertext = cipher.encrypt(message.as_bytes()).unwrap();
+        let one_shot = BASE64.encode(&ciphertext);
+
+        for chunk_size in [1, 2, 3, 4, 7, 64, ciphertext.len()] {
+            let mut encoder = QShieldBase64Encoder::new();
+            let mut streamed = String::new();
+            for chunk in ciphertext.chunks(chunk_size) {
+                streamed.push_str(&encoder.update(chunk));
+            }
+            streamed.push_str(&encoder.finish());
+            assert_eq!(streamed, one_shot);
+            assert_eq!(cipher.decrypt_string(&streamed).unwrap(), message);
+
+            let mut decoder = QShieldBase64Decoder::new();
+            let mut decoded = Vec::new();
+            for chunk in streamed.as_bytes().chunks(chunk_size) {
+                decoded.extend(decoder.update(std::str::from_utf8(chunk).unwrap()).unwrap());
+            }
+            decoder.finish().unwrap();
+            assert_eq!(decoded, ciphertext);
+        }
+
+        // Same result as the one-shot string API
+        let encrypted = cipher.encrypt_string(message).unwrap();
+        let mut decoder = QShieldBase64Decoder::new();
+        let mut decoded = decoder.update(&encrypted[..5]).unwrap();
+        decoded.extend(decoder.update(&encrypted[5..]).unwrap());
+        decoder.finish().unwrap();
+        assert_eq!(cipher.decrypt(&decoded).unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_from_bytes_with_context() {
+        let secret = b"shared-secret-from-kem-exchange!";
+        let data = b"Context-bound message";
+
+        // Empty context is the plain from_bytes cipher
+        let plain = QShieldCipher::from_bytes(secret).unwrap();
+        let empty = QShieldCipher::from_bytes_with_context(secret, b"").unwrap();
+        assert_eq!(empty.decrypt(&plain.encrypt(data).unwrap()).unwrap(), data);
+
+        let chat = QShieldCipher::from_bytes_with_context(secret, b"chat").unwrap();
+        let chat2 = QShieldCipher::from_bytes_with_context(secret, b"chat").unwrap();
+        assert_eq!(chat2.decrypt(&chat.encrypt(data).unwrap()).unwrap(), data);
+    }
+
     #[test]
     #[cfg(target_arch = "wasm32")]
     fn test_encrypt_decrypt_with_aad() {
@@ -1169,6 +4598,195 @@ mod tests {
         assert!(cipher.decrypt_with_aad(&encrypted, b"wrong-aad").is_err());
     }
 
+    #[test]
+    fn test_short_secret_allowed_with_override() {
+        let short = QShieldCipher::from_bytes_with_options(b"short-key", true).unwrap();
+        let encrypted = short.encrypt(b"data").unwrap();
+        assert_eq!(short.decrypt(&encrypted).unwrap(), b"data");
+
+        // A full-length secret derives the same keys either way
+        let secret = b"test-key-32-bytes-exactly-here!";
+        let checked = QShieldCipher::from_bytes(secret).unwrap();
+        let unchecked = QShieldCipher::from_bytes_with_options(secret, true).unwrap();
+        let strict = QShieldCipher::from_bytes_with_options(secret, false).unwrap();
+        let encrypted = checked.encrypt(b"data").unwrap();
+        assert_eq!(unchecked.decrypt(&encrypted).unwrap(), b"data");
+        assert_eq!(strict.decrypt(&encrypted).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_padding_flag_overrides_local_setting() {
+        let secret = b"test-key-32-bytes-exactly-here!";
+        let padded = QShieldCipher::from_bytes(secret).unwrap();
+        let unpadded = QShieldCipher { enable_padding: false, ..QShieldCipher::from_bytes(secret).unwrap() };
+        let data = b"Padding interop";
+
+        let encrypted = padded.encrypt(data).unwrap();
+        assert_eq!(encrypted[0], VERSION_BYTE);
+        assert_eq!(encrypted[1], FLAG_PADDED);
+        assert_eq!(unpadded.decrypt(&encrypted).unwrap(), data);
+
+        let encrypted = unpadded.encrypt(data).unwrap();
+        assert_eq!(encrypted[1], 0);
+        assert_eq!(padded.decrypt(&encrypted).unwrap(), data);
+    }
+
+    /// A ciphertext in an older `encrypt_with_aad` format, built from the
+    /// cipher's own layers.
+    #[cfg(target_arch = "wasm32")]
+    fn legacy_ciphertext(
+        cipher: &QShieldCipher,
+        version: u8,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Vec<u8> {
+        let aes_nonce = [7u8; NONCE_SIZE];
+        let chacha_nonce = [9u8; NONCE_SIZE];
+        let mut result = vec![version];
+        let layer_aad = match version {
+            V7_VERSION_BYTE => {
+                result.push(0);
+                length_bound_aad(version, 0, aad)
+            }
+            V6_VERSION_BYTE => {
+                result.push(0);
+                versioned_aad(version, 0, aad)
+            }
+            _ => aad.to_vec(),
+        };
+        result.extend_from_slice(&aes_nonce);
+        result.extend_from_slice(&chacha_nonce);
+        let sealed = cipher.seal_layers(plaintext, &aes_nonce, &chacha_nonce, &layer_aad).unwrap();
+        result.extend_from_slice(&sealed);
+        result
+    }
+
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_aad_length_bound() {
+        let secret = b"test-key-32-bytes-exactly-here!";
+        let cipher = QShieldCipher { enable_padding: false, ..QShieldCipher::from_bytes(secret).unwrap() };
+
+        let encrypted = cipher.encrypt_with_aad(b"payload", b"ctx").unwrap();
+        assert_eq!(encrypted[0], VERSION_BYTE);
+        assert_eq!(cipher.decrypt_with_aad(&encrypted, b"ctx").unwrap(), b"payload");
+
+        // The layers authenticate the AAD length, not just its bytes
+        let aes_nonce = &encrypted[HEADER_SIZE - 2 * NONCE_SIZE..HEADER_SIZE - NONCE_SIZE];
+        let chacha_nonce = &encrypted[HEADER_SIZE - NONCE_SIZE..HEADER_SIZE];
+        let body = &encrypted[HEADER_SIZE..];
+        let unframed = versioned_aad(VERSION_BYTE, 0, b"ctx");
+        assert!(cipher.open_layers(body, aes_nonce, chacha_nonce, &unframed).is_err());
+        let framed = length_bound_aad(VERSION_BYTE, 0, b"ctx");
+        assert!(cipher.open_layers(body, aes_nonce, chacha_nonce, &framed).is_ok());
+    }
+
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_older_versions_still_decrypt() {
+        let secret = b"test-key-32-bytes-exactly-here!";
+        let cipher = QShieldCipher { enable_padding: false, ..QShieldCipher::from_bytes(secret).unwrap() };
+
+        let v7 = legacy_ciphertext(&cipher, V7_VERSION_BYTE, b"from v7", b"ctx");
+        assert_eq!(cipher.decrypt_with_aad(&v7, b"ctx").unwrap(), b"from v7");
+        let (header, body) = v7.split_at(V6_HEADER_SIZE);
+        assert_eq!(cipher.decrypt_detached(header, body, b"ctx").unwrap(), b"from v7");
+
+        let v6 = legacy_ciphertext(&cipher, V6_VERSION_BYTE, b"from v6", b"ctx");
+        assert_eq!(cipher.decrypt_with_aad(&v6, b"ctx").unwrap(), b"from v6");
+        let (header, body) = v6.split_at(V6_HEADER_SIZE);
+        assert_eq!(cipher.decrypt_detached(header, body, b"ctx").unwrap(), b"from v6");
+
+        let v5 = legacy_ciphertext(&cipher, 0x05, b"from v5", b"ctx");
+        assert_eq!(cipher.decrypt_with_aad(&v5, b"ctx").unwrap(), b"from v5");
+        assert!(cipher.decrypt_with_aad(&v5, b"other").is_err());
+    }
+
+    #[test]
+    fn test_key_commitment_in_header() {
+        let secret = b"test-key-32-bytes-exactly-here!";
+        let cipher = QShieldCipher::from_bytes(secret).unwrap();
+        let encrypted = cipher.encrypt_with_aad(b"payload", b"ctx").unwrap();
+        let (flags, nonces) = (encrypted[1], &encrypted[2 + KEY_COMMITMENT_SIZE..HEADER_SIZE]);
+        let commitment = cipher.key_commitment(flags, &nonces[..NONCE_SIZE], &nonces[NONCE_SIZE..]);
+        assert_eq!(&encrypted[2..2 + KEY_COMMITMENT_SIZE], &commitment);
+
+        // The commitment is per message, so ciphertexts under one key are not linkable
+        let again = cipher.encrypt_with_aad(b"payload", b"ctx").unwrap();
+        assert_ne!(&again[2..2 + KEY_COMMITMENT_SIZE], &encrypted[2..2 + KEY_COMMITMENT_SIZE]);
+        assert!(!encrypted.windows(KEY_COMMITMENT_SIZE).any(|w| w == cipher.commitment_key));
+
+        // Same keys, same commitment key; other keys of the same length, another one
+        let same = QShieldCipher::from_bytes(secret).unwrap();
+        assert_eq!(same.commitment_key, cipher.commitment_key);
+        let other = QShieldCipher::from_bytes(b"other-key-32-bytes-exactly-here").unwrap();
+        assert_ne!(other.commitment_key, cipher.commitment_key);
+        assert_eq!(same.decrypt_with_aad(&encrypted, b"ctx").unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_encrypt_with_header_roundtrip() {
+        let cipher = QShieldCipher::from_bytes(b"test-key-32-bytes-exactly-here!").unwrap();
+        let fields: [(&str, &[u8]); 2] = [("timestamp", b"1700000000"), ("content-type", b"text/plain")];
+
+        let encrypted = cipher.encrypt_with_header(b"payload", &fields, b"ctx").unwrap();
+        let (header, plaintext) = cipher.decrypt_with_header(&encrypted, b"ctx").unwrap();
+
+        assert_eq!(plaintext, b"payload");
+        assert_eq!(
+            header,
+            vec![
+                ("content-type".to_string(), b"text/plain".to_vec()),
+                ("timestamp".to_string(), b"1700000000".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named_aad_order_independent() {
+        let cipher = QShieldCipher::from_bytes(b"test-key-32-bytes-exactly-here!").unwrap();
+        let fields: [(&str, &[u8]); 2] = [("user", b"alice"), ("purpose", b"backup")];
+        let reordered: [(&str, &[u8]); 2] = [("purpose", b"backup"), ("user", b"alice")];
+
+        let encrypted = cipher.encrypt_with_named_aad(b"payload", &fields).unwrap();
+        let decrypted = cipher.decrypt_with_named_aad(&encrypted, &reordered).unwrap();
+        assert_eq!(decrypted, b"payload");
+    }
+
+    #[test]
+    fn test_detached_matches_inline() {
+        let cipher = QShieldCipher::from_bytes(b"test-key-32-bytes-exactly-here!").unwrap();
+
+        let (header, ciphertext) = cipher.encrypt_detached(b"payload", b"row-42").unwrap();
+        assert_eq!(header.len(), HEADER_SIZE);
+        assert_eq!(cipher.decrypt_detached(&header, &ciphertext, b"row-42").unwrap(), b"payload");
+
+        // Concatenated parts are the inline encoding
+        let inline = [header.as_slice(), ciphertext.as_slice()].concat();
+        assert_eq!(cipher.decrypt_with_aad(&inline, b"row-42").unwrap(), b"payload");
+
+        // And an inline ciphertext splits into detached parts
+        let inline = cipher.encrypt_with_aad(b"payload", b"row-42").unwrap();
+        let (header, ciphertext) = inline.split_at(HEADER_SIZE);
+        assert_eq!(cipher.decrypt_detached(header, ciphertext, b"row-42").unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_header_encoding_is_canonical() {
+        let a: [(&str, &[u8]); 2] = [("a", b"1"), ("b", b"2")];
+        let b: [(&str, &[u8]); 2] = [("b", b"2"), ("a", b"1")];
+        assert_eq!(encode_header_fields(&a).unwrap(), encode_header_fields(&b).unwrap());
+
+        // Moving bytes between name and value changes the encoding
+        let shifted: [(&str, &[u8]); 1] = [("ab", b"c")];
+        let original: [(&str, &[u8]); 1] = [("a", b"bc")];
+        assert_ne!(encode_header_fields(&shifted).unwrap(), encode_header_fields(&original).unwrap());
+
+        // Header and AAD boundaries are framed
+        let header = encode_header_fields(&original).unwrap();
+        assert_ne!(header_aad(&header, b"x"), header_aad(&[header.as_slice(), &b"x"[..]].concat(), b""));
+    }
+
     #[test]
     #[cfg(target_arch = "wasm32")]
     fn test_string_encrypt_decrypt() {
@@ -1190,6 +4808,64 @@ mod tests {
         assert_eq!(data.as_slice(), decrypted.as_slice());
     }
 
+    #[test]
+    fn test_remove_padding() {
+        let cipher = QShieldCipher::insecure_test_key("padding").unwrap();
+
+        let padded = cipher.apply_padding(b"length hidden");
+        assert_eq!(padded.len() % PADDING_BLOCK_SIZE, 0);
+        assert_eq!(cipher.remove_padding(&padded).unwrap(), b"length hidden");
+
+        // Content filling the whole buffer is still valid
+        let mut exact = 3u32.to_le_bytes().to_vec();
+        exact.extend_from_slice(b"abc");
+        assert_eq!(cipher.remove_padding(&exact).unwrap(), b"abc");
+    }
+
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_remove_padding_uniform_error() {
+        let cipher = QShieldCipher::insecure_test_key("padding").unwrap();
+        let padded = cipher.apply_padding(b"length hidden");
+        let body_len = (padded.len() - 4) as u32;
+
+        let mut corruptions = vec![Vec::new(), vec![0u8; 3]];
+        for bad_len in [body_len + 1, body_len + 64, u32::MAX] {
+            let mut corrupted = padded.clone();
+            corrupted[..4].copy_from_slice(&bad_len.to_le_bytes());
+            corruptions.push(corrupted);
+        }
+
+        // Every malformed buffer fails the same way
+        for corrupted in corruptions {
+            let err = cipher.remove_padding(&corrupted).unwrap_err();
+            assert_eq!(err.as_string().unwrap(), "Invalid padding");
+        }
+    }
+
+    #[test]
+    fn test_session_directional_chains() {
+        let secret = b"directional-session-secret";
+        let mut alice = QShieldSession::new_with_directions(secret, SessionRole::Initiator).unwrap();
+        let mut bob = QShieldSession::new_with_directions(secret, SessionRole::Responder).unwrap();
+
+        // Both peers send before reading anything
+        let from_alice = alice.encrypt(b"hello bob").unwrap();
+        let from_bob = bob.encrypt(b"hello alice").unwrap();
+
+        // Same message number, but different directional keys
+        assert_eq!(from_alice[..8], from_bob[..8]);
+        assert_ne!(alice.send.chain_key, bob.send.chain_key);
+
+        assert_eq!(bob.decrypt(&from_alice).unwrap(), b"hello bob");
+        assert_eq!(alice.decrypt(&from_bob).unwrap(), b"hello alice");
+
+        let second = alice.encrypt(b"second").unwrap();
+        assert_eq!(bob.decrypt(&second).unwrap(), b"second");
+        assert_eq!(alice.message_count(), 3);
+        assert_eq!(bob.message_count(), 3);
+    }
+
     #[test]
     fn test_hybrid_kem() {
         let alice = QShieldHybridKEM::new().unwrap();
@@ -1204,8 +4880,70 @@ mod tests {
     #[test]
     fn test_hybrid_kem_public_key_size() {
         let kem = QShieldHybridKEM::new().unwrap();
-        assert_eq!(kem.public_key().len(), 1216);
-        assert_eq!(QShieldHybridKEM::public_key_size(), 1216);
+        assert_eq!(kem.public_key().len(), 1217);
+        assert_eq!(QShieldHybridKEM::public_key_size(), 1217);
+    }
+
+    #[test]
+    fn test_hybrid_kem_suites() {
+        for (suite, pk_size, ct_size) in [
+            (KEM_SUITE_DEFAULT, 1217, 1121),
+            (KEM_SUITE_HIGH_SECURITY, 1601, 1601),
+            (KEM_SUITE_COMPACT, 833, 801),
+        ] {
+            let alice = QShieldHybridKEM::with_suite(suite).unwrap();
+            let bob = QShieldHybridKEM::with_suite(suite).unwrap();
+            assert_eq!(bob.suite(), suite);
+            assert_eq!(bob.public_key().len(), pk_size);
+            assert_eq!(bob.public_key()[0], suite);
+            assert_eq!(QShieldHybridKEM::public_key_size_for_suite(suite).unwrap(), pk_size);
+
+            let encap = alice.encapsulate(&bob.public_key()).unwrap();
+            assert_eq!(encap.ciphertext().len(), ct_size);
+            assert_eq!(bob.decapsulate(&encap.ciphertext()).unwrap(), encap.shared_secret());
+
+            let sealed = alice.seal(&bob.public_key(), b"suite payload").unwrap();
+            assert_eq!(bob.open(&sealed).unwrap(), b"suite payload");
+        }
+    }
+
+    #[test]
+    fn test_hybrid_kem_legacy_public_key() {
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        // An unprefixed key from before suites were encoded
+        let legacy_pk = &bob.public_key()[1..];
+        assert_eq!(legacy_pk.len(), LEGACY_HYBRID_PUBLIC_KEY_SIZE);
+        assert_eq!(
+            hybrid_public_key_fingerprint(legacy_pk),
+            hybrid_public_key_fingerprint(&bob.public_key())
+        );
+
+        let encap = alice.encapsulate(legacy_pk).unwrap();
+        assert_eq!(bob.decapsulate(&encap.ciphertext()).unwrap(), encap.shared_secret());
+    }
+
+    #[test]
+    fn test_hybrid_kem_secret_export_roundtrip() {
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        // Encapsulated against Bob's original public key
+        let encap = alice.encapsulate(&bob.public_key()).unwrap();
+
+        let exported = bob.export_secret();
+        assert_eq!(exported.len(), 1 + 4 + 32 + 4 + ml_kem_768::DK_LEN);
+        let restored = QShieldHybridKEM::from_secret_bytes(&exported).unwrap();
+
+        assert_eq!(restored.public_key(), bob.public_key());
+        assert_eq!(restored.decapsulate(&encap.ciphertext()).unwrap(), encap.shared_secret());
+
+        // Other suites round-trip too
+        let compact = QShieldHybridKEM::with_suite(KEM_SUITE_COMPACT).unwrap();
+        let restored = QShieldHybridKEM::from_secret_bytes(&compact.export_secret()).unwrap();
+        assert_eq!(restored.suite(), KEM_SUITE_COMPACT);
+        assert_eq!(restored.public_key(), compact.public_key());
     }
 
     #[test]
@@ -1222,6 +4960,176 @@ mod tests {
         assert_eq!(plaintext.as_slice(), decrypted.as_slice());
     }
 
+    #[test]
+    fn test_hybrid_kem_transcript_binding() {
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        let encap = alice.encapsulate(&bob.public_key()).unwrap();
+        let ct = encap.ciphertext();
+        assert_eq!(ct.len(), KEM_CIPHERTEXT_SIZE);
+        assert_eq!(ct[0], HYBRID_KEM_VERSION);
+
+        // Same component secrets, different X25519 pk in the transcript
+        let fingerprint = bob.public_key_fingerprint();
+        let mut altered = ct.clone();
+        altered[1] ^= 0x01;
+        let a = combine_hybrid_secrets(&[1u8; 32], &[2u8; 32], Some((&fingerprint, &ct))).unwrap();
+        let b = combine_hybrid_secrets(&[1u8; 32], &[2u8; 32], Some((&fingerprint, &altered))).unwrap();
+        let legacy = combine_hybrid_secrets(&[1u8; 32], &[2u8; 32], None).unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a, legacy);
+
+        // Tampering with the ciphertext's X25519 pk changes the decapsulated secret
+        assert_ne!(bob.decapsulate(&altered).unwrap(), encap.shared_secret());
+    }
+
+    #[test]
+    fn test_hybrid_kem_encapsulate_auto() {
+        let alice = QShieldHybridKEM::new().unwrap();
+
+        // Hybrid key: same as encapsulate
+        let bob = QShieldHybridKEM::new().unwrap();
+        let encap = alice.encapsulate_auto(&bob.public_key()).unwrap();
+        assert_eq!(encap.ciphertext().len(), KEM_CIPHERTEXT_SIZE);
+        assert_eq!(
+            bob.decapsulate_auto(&encap.ciphertext(), false).unwrap(),
+            encap.shared_secret()
+        );
+        assert_eq!(
+            bob.decapsulate_auto(&encap.ciphertext(), true).unwrap(),
+            encap.shared_secret()
+        );
+
+        // Classical key: tagged X25519-only ciphertext
+        let carol = QShieldKeyExchange::new();
+        let encap = alice.encapsulate_auto(&carol.public_key()).unwrap();
+        assert_eq!(encap.ciphertext().len(), CLASSICAL_KEM_CIPHERTEXT_SIZE);
+        assert_eq!(encap.ciphertext()[0], CLASSICAL_KEM_VERSION);
+        assert_eq!(encap.shared_secret().len(), 64);
+        assert_eq!(carol.decapsulate(&encap.ciphertext()).unwrap(), encap.shared_secret());
+
+        // A hybrid holder that handed out only its X25519 half
+        let encap = alice.encapsulate_auto(&bob.public_key()[1..33]).unwrap();
+        assert_eq!(
+            bob.decapsulate_auto(&encap.ciphertext(), false).unwrap(),
+            encap.shared_secret()
+        );
+    }
+
+    #[test]
+    fn test_hybrid_kem_legacy_ciphertext() {
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        let encap = alice.encapsulate_versioned(&bob.public_key(), 0x01).unwrap();
+        assert_eq!(encap.ciphertext().len(), LEGACY_KEM_CIPHERTEXT_SIZE);
+        assert_eq!(bob.decapsulate(&encap.ciphertext()).unwrap(), encap.shared_secret());
+    }
+
+    #[test]
+    fn test_hybrid_kem_seal_open() {
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        let sealed = alice.seal(&bob.public_key(), b"sealed payload").unwrap();
+        assert_eq!(bob.open(&sealed).unwrap(), b"sealed payload");
+    }
+
+    #[test]
+    fn test_group_message_three_recipients() {
+        let sender = QShieldHybridKEM::new().unwrap();
+        let recipients: Vec<_> = (0..3).map(|_| QShieldHybridKEM::new().unwrap()).collect();
+        let outsider = QShieldHybridKEM::new().unwrap();
+
+        let mut keys = Vec::new();
+        for r in &recipients {
+            keys.extend_from_slice(&r.public_key());
+        }
+
+        let plaintext = b"Group announcement";
+        let message = QShieldGroupMessage::encrypt(&sender, &keys, plaintext).unwrap();
+        assert_eq!(message.recipient_count(), 3);
+
+        let parsed = QShieldGroupMessage::from_bytes(&message.bytes()).unwrap();
+        for r in &recipients {
+            assert_eq!(parsed.decrypt(r).unwrap(), plaintext);
+        }
+
+        // An outsider has no wrapped key addressed to them
+        let outsider_id = recipient_id(&outsider.public_key());
+        assert!(parsed.wrapped_keys.iter().all(|(id, _)| *id != outsider_id));
+    }
+
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_encapsulate_multi_three_recipients() {
+        let sender = QShieldHybridKEM::new().unwrap();
+        let recipients: Vec<_> = (0..3).map(|_| QShieldHybridKEM::new().unwrap()).collect();
+        let keys: Vec<Vec<u8>> = recipients.iter().map(|r| r.public_key()).collect();
+
+        let multi = sender.encapsulate_multi(&keys).unwrap();
+        assert_eq!(multi.recipient_count(), 3);
+        assert_eq!(multi.content_key().len(), CONTENT_KEY_SIZE);
+
+        for (r, wrapped) in recipients.iter().zip(multi.wrapped_keys()) {
+            assert_eq!(r.unwrap_content_key(wrapped).unwrap(), multi.content_key());
+        }
+
+        // Each entry is addressed to its own recipient only
+        assert!(recipients[0].unwrap_content_key(&multi.wrapped_keys()[1]).is_err());
+    }
+
+    /// Two-recipient envelope, byte for byte the vector in the core crate's envelope tests
+    fn envelope_test_vector() -> Vec<u8> {
+        [
+            &[0x01, 0x02, 0x02, 0x00][..],
+            &[0x11; 32],
+            &[0x02, 0x00, 0x00, 0x00, 0xaa, 0xbb],
+            &[0x22; 32],
+            &[0x01, 0x00, 0x00, 0x00, 0xcc],
+            &[0x03, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_envelope_matches_core_format() {
+        let envelope = QShieldEnvelope::from_bytes(&envelope_test_vector()).unwrap();
+        assert_eq!(envelope.suite(), ENVELOPE_SUITE_WASM);
+        assert_eq!(envelope.recipient_count(), 2);
+        assert_eq!(envelope.recipients[0], ([0x11; 32], vec![0xaa, 0xbb]));
+        assert_eq!(envelope.recipients[1], ([0x22; 32], vec![0xcc]));
+        assert_eq!(envelope.payload, vec![0x01, 0x02, 0x03]);
+        assert_eq!(envelope.bytes(), envelope_test_vector());
+    }
+
+    #[test]
+    fn test_envelope_limits() {
+        let vector = envelope_test_vector();
+        let envelope = QShieldEnvelope::from_bytes_with_limits(&vector, 2, vector.len()).unwrap();
+        assert_eq!(envelope.recipient_count(), 2);
+    }
+
+    #[test]
+    fn test_envelope_two_recipients() {
+        let sender = QShieldHybridKEM::new().unwrap();
+        let alice = QShieldHybridKEM::new().unwrap();
+        let bob = QShieldHybridKEM::new().unwrap();
+
+        let mut keys = alice.public_key();
+        keys.extend_from_slice(&bob.public_key());
+
+        let envelope = QShieldEnvelope::seal(&sender, &keys, b"envelope payload").unwrap();
+        let parsed = QShieldEnvelope::from_bytes(&envelope.bytes()).unwrap();
+        assert_eq!(parsed.suite(), ENVELOPE_SUITE_HYBRID);
+        assert_eq!(parsed.recipient_count(), 2);
+        assert!(parsed.has_recipient(&alice.public_key()));
+        assert!(!parsed.has_recipient(&sender.public_key()));
+        assert_eq!(parsed.open(&alice).unwrap(), b"envelope payload");
+        assert_eq!(parsed.open(&bob).unwrap(), b"envelope payload");
+    }
+
     #[test]
     fn test_secure_compare() {
         assert!(secure_compare(b"hello", b"hello"));
@@ -1239,6 +5147,50 @@ mod tests {
         assert!(!signer.verify(b"Wrong message", &signature).unwrap());
     }
 
+    #[test]
+    fn test_sign_secret_key_roundtrip() {
+        let signer = QShieldSign::new().unwrap();
+        let secret = signer.secret_key_bytes();
+        assert_eq!(secret.len(), SIGNING_SECRET_KEY_SIZE);
+
+        let restored = QShieldSign::from_secret_bytes(&secret).unwrap();
+        assert_eq!(restored.public_key(), signer.public_key());
+
+        // Signatures from the restored signer verify under the original public key
+        let message = b"Persistent signing identity";
+        let signature = restored.sign(message).unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        assert!(verifier.verify(message, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_countersignature() {
+        let signer = QShieldSign::new().unwrap();
+        let authority = QShieldSign::new().unwrap();
+        let message = b"Document to notarize";
+        let timestamp: i64 = 1_767_225_600;
+
+        let original = signer.sign(message).unwrap();
+        let countersignature = authority.countersign(&original, timestamp).unwrap();
+
+        // Verifiable from the authority's public key alone
+        let verifier = QShieldVerifier::new(&authority.public_key()).unwrap();
+        let cs = &countersignature;
+        assert!(verifier.verify_countersignature(&original, timestamp, cs).unwrap());
+        assert!(authority.verify_countersignature(&original, timestamp, cs).unwrap());
+
+        // Tampered timestamp, another original, or the wrong key all fail
+        assert!(!verifier.verify_countersignature(&original, timestamp + 1, cs).unwrap());
+        let other = signer.sign(b"Another document").unwrap();
+        assert!(!verifier.verify_countersignature(&other, timestamp, cs).unwrap());
+        assert!(!signer.verify_countersignature(&original, timestamp, cs).unwrap());
+
+        // A countersignature is not an ordinary signature over the same bytes
+        let mut chained = original.bytes();
+        chained.extend_from_slice(&timestamp.to_be_bytes());
+        assert!(!verifier.verify(&chained, &countersignature).unwrap());
+    }
+
     #[test]
     fn test_dual_signature_string() {
         let signer = QShieldSign::new().unwrap();
@@ -1260,6 +5212,36 @@ mod tests {
         assert!(signer.verify(message, &parsed).unwrap());
     }
 
+    #[test]
+    fn test_dual_signature_format_tag() {
+        let signer = QShieldSign::new().unwrap();
+        let message = b"Format tag test";
+
+        let signature = signer.sign(message).unwrap();
+        let sig_bytes = signature.bytes();
+        assert_eq!(sig_bytes[0], SIGNATURE_FORMAT_DUAL);
+        assert_eq!(sig_bytes.len(), 1 + 4 + MLDSA_SIGNATURE_SIZE + SLHDSA_SIGNATURE_SIZE);
+
+        // Legacy untagged blobs are still readable when explicitly allowed
+        let legacy = &sig_bytes[1..];
+        let parsed = DualSignature::from_bytes_with_options(legacy, true).unwrap();
+        assert!(signer.verify(message, &parsed).unwrap());
+    }
+
+    #[test]
+    fn test_dual_signature_fixed_layout() {
+        let signer = QShieldSign::new().unwrap();
+        let message = b"Fixed layout test";
+
+        let signature = signer.sign(message).unwrap();
+        let fixed = signature.to_bytes_fixed();
+        assert_eq!(fixed.len(), 20397);
+        assert_eq!(&fixed[..MLDSA_SIGNATURE_SIZE], signature.mldsa_signature().as_slice());
+
+        let parsed = DualSignature::from_bytes_fixed(&fixed).unwrap();
+        assert!(signer.verify(message, &parsed).unwrap());
+    }
+
     #[test]
     fn test_dual_signature_base64_roundtrip() {
         let signer = QShieldSign::new().unwrap();
@@ -1308,6 +5290,151 @@ mod tests {
         assert!(verifier.verify_base64(message, &sig_b64).unwrap());
     }
 
+    #[test]
+    fn test_verifier_signature_context() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let message = b"Context-bound signature";
+
+        let signature = signer.sign_with_context(message, b"invoices").unwrap();
+        assert!(verifier.verify_with_context(message, &signature, b"invoices").unwrap());
+        assert!(verifier
+            .verify_base64_with_context(message, &signature.base64(), b"invoices")
+            .unwrap());
+
+        // Neither the default context nor another application accepts it
+        assert!(!verifier.verify(message, &signature).unwrap());
+        assert!(!verifier.verify_with_context(message, &signature, b"receipts").unwrap());
+
+        // Default-context signatures don't verify under a custom context either
+        let default = signer.sign(message).unwrap();
+        assert!(verifier.verify(message, &default).unwrap());
+        assert!(!verifier.verify_with_context(message, &default, b"invoices").unwrap());
+    }
+
+    #[test]
+    fn test_verify_detailed_flags_tampered_component() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let message = b"Detailed verification";
+
+        let signature = signer.sign(message).unwrap();
+        let report = verifier.verify_detailed(message, &signature);
+        assert!(report.mldsa_valid() && report.slhdsa_valid() && report.valid());
+
+        // Only the ML-DSA half is damaged
+        let mut mldsa = signature.mldsa_signature.clone();
+        mldsa[0] ^= 0x01;
+        let tampered = DualSignature {
+            mldsa_signature: mldsa,
+            slhdsa_signature: signature.slhdsa_signature.clone(),
+        };
+        let report = verifier.verify_detailed(message, &tampered);
+        assert!(!report.mldsa_valid());
+        assert!(report.slhdsa_valid());
+        assert!(!report.valid());
+        assert!(!verifier.verify(message, &tampered).unwrap());
+
+        // A truncated SLH-DSA half is reported as invalid, not an error
+        let truncated = DualSignature {
+            mldsa_signature: signature.mldsa_signature.clone(),
+            slhdsa_signature: signature.slhdsa_signature[1..].to_vec(),
+        };
+        let report = verifier.verify_detailed(message, &truncated);
+        assert!(report.mldsa_valid() && !report.slhdsa_valid());
+    }
+
+    #[test]
+    fn test_dual_signature_cose_roundtrip() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let message = b"COSE interop";
+
+        let signature = signer.sign(message).unwrap();
+        let cose = signature.to_cose();
+
+        // Tag 98, then a four-element COSE_Sign array
+        assert_eq!(&cose[..3], &[0xd8, 0x62, 0x84]);
+        let parsed = DualSignature::from_cose(&cose).unwrap();
+        assert_eq!(parsed.bytes(), signature.bytes());
+        assert!(verifier.verify(message, &parsed).unwrap());
+
+        // Untagged COSE_Sign is accepted too
+        let untagged = DualSignature::from_cose(&cose[2..]).unwrap();
+        assert!(verifier.verify(message, &untagged).unwrap());
+    }
+
+    #[test]
+    fn test_sign_cose_signs_sig_structure() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let payload = b"COSE interop";
+
+        let cose = signer.sign_cose(payload).unwrap();
+        assert!(verifier.verify_cose(&cose, payload).unwrap());
+        assert!(!verifier.verify_cose(&cose, b"another payload").unwrap());
+
+        // The ML-DSA signature is a plain FIPS 204 signature over the Sig_structure
+        let parts = CoseSignParts::parse(&cose).unwrap();
+        let expected = [
+            &[0x85, 0x69][..],
+            b"Signature",
+            &[0x40, 0x44, 0xa1, 0x01, 0x38, 0x30, 0x40, 0x4c],
+            payload,
+        ]
+        .concat();
+        assert_eq!(cose_sig_structure(&[], parts.mldsa.0, payload), expected);
+        let mldsa_sig: MlDsaSignature = parts.mldsa.1.try_into().unwrap();
+        assert!(DsaVerifier::verify(&verifier.mldsa_pk, &expected, &mldsa_sig, &[]));
+
+        // QShield-context signatures wrapped by to_cose do not pass as COSE signatures
+        let wrapped = signer.sign(payload).unwrap().to_cose();
+        assert!(!verifier.verify_cose(&wrapped, payload).unwrap());
+    }
+
+    #[test]
+    fn test_prehashed_signature() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let digest = [0x5a; 64]; // stands in for a SHA-512 digest
+
+        let signature = signer.sign_prehashed(&digest, "SHA-512").unwrap();
+        assert!(verifier.verify_prehashed(&digest, "SHA-512", &signature).unwrap());
+        assert!(signer.verify_prehashed(&digest, "SHA-512", &signature).unwrap());
+
+        // Not a direct signature over the digest bytes
+        assert!(!verifier.verify(&digest, &signature).unwrap());
+        let direct = signer.sign(&digest).unwrap();
+        assert!(!verifier.verify_prehashed(&digest, "SHA-512", &direct).unwrap());
+
+        // The ML-DSA half is HashML-DSA with SHA-512, matching the label
+        let (context, _, _) = prehash_params(&digest, "SHA-512").unwrap();
+        let mldsa_sig: MlDsaSignature = signature.mldsa_signature.clone().try_into().unwrap();
+        let hash_verify = |ph: DsaPh| {
+            DsaVerifier::hash_verify(&verifier.mldsa_pk, &digest, &mldsa_sig, &context, &ph)
+        };
+        assert!(hash_verify(DsaPh::SHA512));
+        assert!(!hash_verify(DsaPh::SHA256));
+
+        let short = &digest[..32];
+        let signature = signer.sign_prehashed(short, "SHA-256").unwrap();
+        assert!(verifier.verify_prehashed(short, "SHA-256", &signature).unwrap());
+    }
+
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_prehashed_signature_label_mismatch() {
+        let signer = QShieldSign::new().unwrap();
+        let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+        let digest = [0x5a; 64];
+
+        // Swapping the digest algorithm label fails verification
+        let short = &digest[..32];
+        let signature = signer.sign_prehashed(short, "SHA-256").unwrap();
+        assert!(!matches!(verifier.verify_prehashed(short, "SHA-512", &signature), Ok(true)));
+        assert!(!matches!(verifier.verify_prehashed(&digest, "SHA-512", &signature), Ok(true)));
+    }
+
     #[test]
     fn test_session_encrypt_decrypt() {
         let shared_secret = b"session-shared-secret-for-testing";
@@ -1328,6 +5455,25 @@ mod tests {
         assert_eq!(receiver.message_count(), 2);
     }
 
+    #[test]
+    fn test_session_versioned_format() {
+        let shared_secret = b"session-versioned-format-test";
+        let mut sender = QShieldSession::new(shared_secret).unwrap();
+        let mut receiver = QShieldSession::new(shared_secret).unwrap();
+
+        let encrypted = sender.encrypt(b"versioned").unwrap();
+        assert_eq!(encrypted[0], SESSION_FORMAT_SYMMETRIC);
+        assert_eq!(&encrypted[1..9], &0u64.to_le_bytes());
+        assert_eq!(receiver.decrypt(&encrypted).unwrap(), b"versioned");
+
+        // With legacy support on, both formats decrypt
+        receiver.set_accept_legacy_format(true);
+        let legacy = sender.encrypt(b"legacy").unwrap()[1..].to_vec();
+        assert_eq!(receiver.decrypt(&legacy).unwrap(), b"legacy");
+        let current = sender.encrypt(b"current").unwrap();
+        assert_eq!(receiver.decrypt(&current).unwrap(), b"current");
+    }
+
     #[test]
     #[cfg(target_arch = "wasm32")]
     fn test_session_out_of_order() {
@@ -1344,6 +5490,29 @@ mod tests {
         receiver.decrypt(&encrypted1).unwrap();
     }
 
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_session_decrypt_with_skip() {
+        let shared_secret = b"session-skip-ahead-test";
+        let mut sender = QShieldSession::new(shared_secret).unwrap();
+        let mut receiver = QShieldSession::new(shared_secret).unwrap();
+
+        let messages: Vec<Vec<u8>> = (0..5u8).map(|i| sender.encrypt(&[i]).unwrap()).collect();
+
+        // Messages 0-2 are delayed; message 3 arrives first
+        assert_eq!(receiver.decrypt_with_skip(&messages[3], 10).unwrap(), vec![3]);
+        assert_eq!(receiver.skipped_message_count(), 3);
+
+        // The skipped messages still decrypt when they turn up, once each
+        assert_eq!(receiver.decrypt_with_skip(&messages[1], 10).unwrap(), vec![1]);
+        assert!(receiver.decrypt_with_skip(&messages[1], 10).is_err());
+        assert_eq!(receiver.decrypt_with_skip(&messages[0], 10).unwrap(), vec![0]);
+        assert_eq!(receiver.decrypt_with_skip(&messages[4], 10).unwrap(), vec![4]);
+        assert_eq!(receiver.decrypt_with_skip(&messages[2], 10).unwrap(), vec![2]);
+        assert_eq!(receiver.skipped_message_count(), 0);
+        assert_eq!(receiver.message_count(), 5);
+    }
+
     #[test]
     fn test_empty_data() {
         let cipher = QShieldCipher::from_bytes(b"test-key-for-empty-data").unwrap();
@@ -1365,7 +5534,7 @@ mod tests {
     #[test]
     #[cfg(target_arch = "wasm32")]
     fn test_tampered_ciphertext() {
-        let cipher = QShieldCipher::from_bytes(b"tamper-test-key").unwrap();
+        let cipher = QShieldCipher::from_bytes(b"tamper-test-key-native").unwrap();
         let mut encrypted = cipher.encrypt(b"original data").unwrap();
 
         // Tamper with the ciphertext
@@ -0,0 +1,147 @@
+//! Timing harness for `QShieldCipher` decryption failures.
+//!
+//! Decryption can fail for different reasons: a bad authentication tag, an
+//! invalid padding length inside an authentic legacy ciphertext, or a
+//! version byte rewritten to one of the accepted legacy versions. Callers
+//! only ever see an error, and the time taken to produce it should not tell
+//! them which check failed. This harness times many iterations of each
+//! failure mode, interleaved to cancel out drift, and fails if the mean
+//! times diverge by more than `TOLERANCE`.
+//!
+//! The bounds are deliberately loose: the goal is to catch a regression
+//! such as an early exit before authentication, not to prove constant time.
+//!
+//! Run with: `wasm-pack test --node -- --test timing`
+
+use quantum_shield::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+const ROUNDS: usize = 10;
+const ITERATIONS_PER_ROUND: usize = 200;
+
+/// Maximum ratio between the slowest and fastest mean failure time
+const TOLERANCE: f64 = 3.0;
+
+const PASSWORD: &str = "timing-harness-password";
+const AAD: &[u8] = b"timing-harness";
+const KEY_COMMITMENT_SIZE: usize = 32; // follows [version][flags] in v8 ciphertexts
+
+/// Current time in milliseconds, preferring the high-resolution clock
+fn now_ms() -> f64 {
+    let performance = js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+        .ok()
+        .filter(|p| !p.is_undefined())
+        .map(|p| p.unchecked_into::<web_sys::Performance>());
+    match performance {
+        Some(performance) => performance.now(),
+        None => js_sys::Date::now(),
+    }
+}
+
+struct FailureMode<'a> {
+    name: &'static str,
+    cipher: &'a QShieldCipher,
+    ciphertext: Vec<u8>,
+    aad: Vec<u8>,
+    total_ms: f64,
+}
+
+impl FailureMode<'_> {
+    fn run(&mut self) {
+        let start = now_ms();
+        for _ in 0..ITERATIONS_PER_ROUND {
+            assert!(self
+                .cipher
+                .decrypt_with_aad(&self.ciphertext, &self.aad)
+                .is_err());
+        }
+        self.total_ms += now_ms() - start;
+    }
+
+    fn mean_us(&self) -> f64 {
+        self.total_ms * 1000.0 / (ROUNDS * ITERATIONS_PER_ROUND) as f64
+    }
+}
+
+#[wasm_bindgen_test]
+fn decryption_failures_take_similar_time() {
+    let padded = QShieldCipher::from_password_with_options(PASSWORD, true).unwrap();
+    let unpadded = QShieldCipher::from_password_with_options(PASSWORD, false).unwrap();
+    let message = [0x42u8; 64];
+
+    // Bad tag: flip the last byte of a current-version ciphertext
+    let mut bad_tag = padded.encrypt_with_aad(&message, AAD).unwrap();
+    *bad_tag.last_mut().unwrap() ^= 0x01;
+
+    // Wrong version: relabel a current-version ciphertext as legacy v5
+    let mut wrong_version = padded.encrypt_with_aad(&message, AAD).unwrap();
+    wrong_version[0] = 0x05;
+
+    // Bad padding: an authentic legacy v5 ciphertext whose padded plaintext
+    // claims a length larger than itself. Legacy versions authenticate only
+    // the caller's AAD, so an unpadded v8 ciphertext re-framed without its
+    // flags byte and key commitment decrypts as v5 when given its layer AAD,
+    // [version][flags][aad_len: u64 BE] || aad, as AAD.
+    let mut bogus_padding = vec![0xffu8; 4];
+    bogus_padding.extend_from_slice(&message);
+    let v8 = unpadded.encrypt_with_aad(&bogus_padding, AAD).unwrap();
+    assert_eq!(v8[1], 0x00, "expected an unpadded v8 ciphertext");
+    let mut bad_padding = vec![0x05];
+    bad_padding.extend_from_slice(&v8[2 + KEY_COMMITMENT_SIZE..]);
+    let legacy_aad = [&v8[..2], &(AAD.len() as u64).to_be_bytes()[..], AAD].concat();
+    let err = padded
+        .decrypt_with_aad(&bad_padding, &legacy_aad)
+        .unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Invalid padding");
+
+    let mut modes = [
+        FailureMode {
+            name: "bad tag",
+            cipher: &padded,
+            ciphertext: bad_tag,
+            aad: AAD.to_vec(),
+            total_ms: 0.0,
+        },
+        FailureMode {
+            name: "wrong legacy version",
+            cipher: &padded,
+            ciphertext: wrong_version,
+            aad: AAD.to_vec(),
+            total_ms: 0.0,
+        },
+        FailureMode {
+            name: "bad padding length",
+            cipher: &padded,
+            ciphertext: bad_padding,
+            aad: legacy_aad,
+            total_ms: 0.0,
+        },
+    ];
+
+    // Warm up, then interleave modes so drift affects each one equally
+    for mode in modes.iter_mut() {
+        mode.run();
+        mode.total_ms = 0.0;
+    }
+    for _ in 0..ROUNDS {
+        for mode in modes.iter_mut() {
+            mode.run();
+        }
+    }
+
+    let means: Vec<f64> = modes.iter().map(FailureMode::mean_us).collect();
+    let fastest = means.iter().cloned().fold(f64::INFINITY, f64::min);
+    let slowest = means.iter().cloned().fold(0.0, f64::max);
+    let report: Vec<String> = modes
+        .iter()
+        .zip(&means)
+        .map(|(mode, mean)| format!("{}: {:.2}us", mode.name, mean))
+        .collect();
+
+    assert!(
+        slowest <= fastest.max(f64::EPSILON) * TOLERANCE,
+        "decryption failure times diverge: {}",
+        report.join(", ")
+    );
+}
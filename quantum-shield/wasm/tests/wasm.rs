@@ -34,6 +34,93 @@ fn cipher_with_aad() {
     assert!(cipher.decrypt_with_aad(&encrypted, b"wrong").is_err());
 }
 
+#[wasm_bindgen_test]
+fn cipher_with_header_reordered_fields() {
+    let cipher = QShieldCipher::from_bytes(b"header-test-key-wasm").unwrap();
+    let fields: [(&str, &[u8]); 2] = [("content-type", b"application/json"), ("timestamp", b"1700000000")];
+    let reordered: [(&str, &[u8]); 2] = [("timestamp", b"1700000000"), ("content-type", b"application/json")];
+
+    let encrypted = cipher.encrypt_with_header(b"body", &fields, b"ctx").unwrap();
+    let (header, plaintext) = cipher.decrypt_with_header(&encrypted, b"ctx").unwrap();
+    assert_eq!(plaintext, b"body");
+
+    // Fields that sort identically produce the same header and both authenticate
+    let encrypted_reordered = cipher.encrypt_with_header(b"body", &reordered, b"ctx").unwrap();
+    let (header_reordered, _) = cipher.decrypt_with_header(&encrypted_reordered, b"ctx").unwrap();
+    assert_eq!(header, header_reordered);
+    let header_end = 4 + u32::from_le_bytes(encrypted[..4].try_into().unwrap()) as usize;
+    assert_eq!(encrypted[..header_end], encrypted_reordered[..header_end]);
+
+    // Wrong AAD fails
+    assert!(cipher.decrypt_with_header(&encrypted, b"other").is_err());
+
+    // Duplicate names are rejected
+    let duplicate: [(&str, &[u8]); 2] = [("a", b"1"), ("a", b"2")];
+    assert!(cipher.encrypt_with_header(b"body", &duplicate, b"").is_err());
+}
+
+#[wasm_bindgen_test]
+fn cipher_with_header_tampered_value_fails() {
+    let cipher = QShieldCipher::from_bytes(b"header-tamper-key-wasm").unwrap();
+    let fields: [(&str, &[u8]); 1] = [("content-type", b"text/plain")];
+    let mut encrypted = cipher.encrypt_with_header(b"body", &fields, b"").unwrap();
+
+    // Layout: [header_len (4)][count (2)][name_len (2)][name][value_len (4)][value]
+    let value_offset = 4 + 2 + 2 + "content-type".len() + 4;
+    assert_eq!(&encrypted[value_offset..value_offset + 10], b"text/plain");
+    encrypted[value_offset] ^= 0x01;
+
+    assert!(cipher.decrypt_with_header(&encrypted, b"").is_err());
+}
+
+#[wasm_bindgen_test]
+fn cipher_with_named_aad() {
+    let cipher = QShieldCipher::from_bytes(b"named-aad-key-wasm").unwrap();
+    let fields: [(&str, &[u8]); 2] = [("user", b"alice"), ("purpose", b"backup")];
+    let reordered: [(&str, &[u8]); 2] = [("purpose", b"backup"), ("user", b"alice")];
+
+    let encrypted = cipher.encrypt_with_named_aad(b"body", &fields).unwrap();
+    assert_eq!(cipher.decrypt_with_named_aad(&encrypted, &reordered).unwrap(), b"body");
+
+    // A changed value does not authenticate
+    let changed: [(&str, &[u8]); 2] = [("purpose", b"restore"), ("user", b"alice")];
+    let err = cipher.decrypt_with_named_aad(&encrypted, &changed).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Named AAD fields do not authenticate");
+
+    // A missing field does not authenticate either
+    let missing: [(&str, &[u8]); 1] = [("user", b"alice")];
+    assert!(cipher.decrypt_with_named_aad(&encrypted, &missing).is_err());
+
+    // Errors unrelated to the fields keep their own message
+    let err = cipher.decrypt_with_named_aad(&encrypted[..4], &fields).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Ciphertext too short");
+    let other = QShieldCipher::from_bytes(b"other-named-aad-key").unwrap();
+    let err = other.decrypt_with_named_aad(&encrypted, &fields).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Key commitment mismatch");
+}
+
+#[wasm_bindgen_test]
+fn cipher_detached_header_is_authenticated() {
+    let cipher = QShieldCipher::from_bytes(b"detached-key-wasm").unwrap();
+    let (header, ciphertext) = cipher.encrypt_detached(b"body", b"aad").unwrap();
+
+    // Tampered nonce
+    let mut tampered = header.clone();
+    *tampered.last_mut().unwrap() ^= 0x01;
+    assert!(cipher.decrypt_detached(&tampered, &ciphertext, b"aad").is_err());
+
+    // Flipped padding flag
+    let mut tampered = header.clone();
+    tampered[1] ^= 0x01;
+    assert!(cipher.decrypt_detached(&tampered, &ciphertext, b"aad").is_err());
+
+    // Bytes shifted from the ciphertext into the header
+    let mut shifted = header.clone();
+    shifted.push(ciphertext[0]);
+    let err = cipher.decrypt_detached(&shifted, &ciphertext[1..], b"aad").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Invalid header length");
+}
+
 #[wasm_bindgen_test]
 fn cipher_string_encrypt_decrypt() {
     let cipher = QShieldCipher::from_bytes(b"string-test-key-wasm").unwrap();
@@ -66,6 +153,61 @@ fn cipher_password_no_padding() {
     assert_eq!(data.as_slice(), decrypted.as_slice());
 }
 
+#[wasm_bindgen_test]
+fn cipher_password_with_params() {
+    // Default costs derive the same keys as from_password_with_options
+    let default = QShieldCipher::from_password_with_options("params-pass", true).unwrap();
+    let explicit =
+        QShieldCipher::from_password_with_params("params-pass", 19456, 3, 1, true).unwrap();
+    assert_eq!(explicit.argon2_params(), Some(vec![19456, 3, 1]));
+    assert_eq!(explicit.decrypt(&default.encrypt(b"same keys").unwrap()).unwrap(), b"same keys");
+
+    // Identical custom costs agree; different costs do not
+    let light = QShieldCipher::from_password_with_params("params-pass", 4096, 2, 1, true).unwrap();
+    let params = light.argon2_params().unwrap();
+    let again =
+        QShieldCipher::from_password_with_params("params-pass", params[0], params[1], params[2], true)
+            .unwrap();
+    let encrypted = light.encrypt(b"custom costs").unwrap();
+    assert_eq!(again.decrypt(&encrypted).unwrap(), b"custom costs");
+    assert!(default.decrypt(&encrypted).is_err());
+
+    assert_eq!(QShieldCipher::from_bytes(b"not-a-password-cipher").unwrap().argon2_params(), None);
+}
+
+#[wasm_bindgen_test]
+fn cipher_password_rejects_zero_memory() {
+    let err = QShieldCipher::from_password_with_params("params-pass", 0, 3, 1, true).err().unwrap();
+    assert!(err.as_string().unwrap().starts_with("Invalid Argon2 parameters: "));
+}
+
+#[wasm_bindgen_test]
+fn cipher_padding_flag_interop() {
+    let padded = QShieldCipher::from_password_with_options("interop-pass", true).unwrap();
+    let unpadded = QShieldCipher::from_password_with_options("interop-pass", false).unwrap();
+
+    // The padding bit in the header decides, not the decrypting cipher's setting
+    let encrypted = padded.encrypt(b"padded payload").unwrap();
+    assert_eq!(unpadded.decrypt(&encrypted).unwrap(), b"padded payload");
+
+    let encrypted = unpadded.encrypt(b"unpadded payload").unwrap();
+    assert_eq!(padded.decrypt(&encrypted).unwrap(), b"unpadded payload");
+}
+
+#[wasm_bindgen_test]
+fn cipher_padding_flag_is_authenticated() {
+    let cipher = QShieldCipher::from_bytes(b"flag-auth-key-wasm").unwrap();
+    let mut encrypted = cipher.encrypt(b"flag test").unwrap();
+
+    // Clearing the padding bit must not yield the padded bytes as plaintext
+    encrypted[1] ^= 0x01;
+    assert!(cipher.decrypt(&encrypted).is_err());
+
+    // Unknown flag bits are rejected
+    encrypted[1] = 0x80;
+    assert!(cipher.decrypt(&encrypted).is_err());
+}
+
 #[wasm_bindgen_test]
 fn cipher_empty_data() {
     let cipher = QShieldCipher::from_bytes(b"empty-data-test-key").unwrap();
@@ -76,16 +218,202 @@ fn cipher_empty_data() {
 
 #[wasm_bindgen_test]
 fn cipher_wrong_key_fails() {
-    let cipher1 = QShieldCipher::from_bytes(b"key-one").unwrap();
-    let cipher2 = QShieldCipher::from_bytes(b"key-two").unwrap();
+    let cipher1 = QShieldCipher::from_bytes(b"key-one-for-wasm-tests").unwrap();
+    let cipher2 = QShieldCipher::from_bytes(b"key-two-for-wasm-tests").unwrap();
 
     let encrypted = cipher1.encrypt(b"secret").unwrap();
     assert!(cipher2.decrypt(&encrypted).is_err());
 }
 
+#[wasm_bindgen_test]
+fn cipher_key_commitment_mismatch_rejected() {
+    // Same-length secrets, so both derive the same amount of key material
+    let cipher1 = QShieldCipher::from_bytes(b"commitment-key-one-wasm").unwrap();
+    let cipher2 = QShieldCipher::from_bytes(b"commitment-key-two-wasm").unwrap();
+
+    let encrypted = cipher1.encrypt_with_aad(b"committed", b"aad").unwrap();
+    let err = cipher2.decrypt_with_aad(&encrypted, b"aad").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Key commitment mismatch");
+
+    // The commitment covers the nonces, so another message's commitment fails too
+    let mut relabelled = encrypted.clone();
+    let other = cipher2.encrypt_with_aad(b"x", b"aad").unwrap();
+    relabelled[2..34].copy_from_slice(&other[2..34]);
+    let err = cipher2.decrypt_with_aad(&relabelled, b"aad").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Key commitment mismatch");
+
+    // Swapping in its commitment and nonces passes the check, but the AEAD layers still fail
+    relabelled[2..58].copy_from_slice(&other[2..58]);
+    let err = cipher2.decrypt_with_aad(&relabelled, b"aad").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Decryption failed");
+
+    assert_eq!(cipher1.decrypt_with_aad(&encrypted, b"aad").unwrap(), b"committed");
+}
+
+#[wasm_bindgen_test]
+fn cipher_context_separation() {
+    let secret = b"same-shared-secret-two-channels";
+    let files = QShieldCipher::from_bytes_with_context(secret, b"files").unwrap();
+    let chat = QShieldCipher::from_bytes_with_context(secret, b"chat").unwrap();
+    let plain = QShieldCipher::from_bytes(secret).unwrap();
+
+    let encrypted = files.encrypt(b"file contents").unwrap();
+    assert!(chat.decrypt(&encrypted).is_err());
+    assert!(plain.decrypt(&encrypted).is_err());
+    assert_eq!(files.decrypt(&encrypted).unwrap(), b"file contents");
+}
+
+#[wasm_bindgen_test]
+fn cipher_sequenced_nonce_reuse_rejected() {
+    let cipher = QShieldCipher::from_bytes(b"sequenced-key-wasm").unwrap();
+
+    let encrypted = cipher.encrypt_with_nonce(b"record", b"", 0, 5).unwrap();
+    assert_eq!(cipher.decrypt_with_nonce(&encrypted, b"", 0, 5).unwrap(), b"record");
+
+    // Reusing or rewinding the sequence is refused
+    assert!(cipher.encrypt_with_nonce(b"again", b"", 0, 5).is_err());
+    assert!(cipher.encrypt_with_nonce(b"older", b"", 0, 4).is_err());
+    assert!(cipher.encrypt_with_nonce(b"next", b"", 0, 6).is_ok());
+
+    // The wrong sequence or sender derives the wrong nonces
+    assert!(cipher.decrypt_with_nonce(&encrypted, b"", 0, 6).is_err());
+    assert!(cipher.decrypt_with_nonce(&encrypted, b"", 1, 5).is_err());
+    // Sequenced ciphertexts are not accepted by the random-nonce decoder
+    assert!(cipher.decrypt(&encrypted).is_err());
+}
+
+#[wasm_bindgen_test]
+fn cipher_sequence_resumes_after_restart() {
+    let before = QShieldCipher::from_bytes(b"sequenced-key-wasm").unwrap();
+    before.encrypt_with_nonce(b"record", b"", 0, 9).unwrap();
+    let persisted = before.last_sequence().unwrap();
+
+    // A fresh instance under the same key refuses the persisted range
+    let after = QShieldCipher::from_bytes(b"sequenced-key-wasm").unwrap();
+    after.resume_sequence(persisted);
+    assert!(after.encrypt_with_nonce(b"reused", b"", 0, 9).is_err());
+    after.resume_sequence(3);
+    assert_eq!(after.last_sequence(), Some(9));
+    assert!(after.encrypt_with_nonce(b"fresh", b"", 0, 10).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn cipher_kdf_hash_mismatch_rejected() {
+    let secret = b"kdf-hash-mismatch-secret";
+    let sha3_512 = QShieldCipher::from_bytes_with_hash(secret, KdfHash::Sha3_512).unwrap();
+    let sha3_256 = QShieldCipher::from_bytes_with_hash(secret, KdfHash::Sha3_256).unwrap();
+
+    let encrypted = sha3_256.encrypt(b"hash-bound").unwrap();
+    let err = sha3_512.decrypt(&encrypted).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "KDF hash mismatch");
+
+    let encrypted = sha3_512.encrypt(b"hash-bound").unwrap();
+    let err = sha3_256.decrypt(&encrypted).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "KDF hash mismatch");
+
+    // Clearing the flag doesn't help: it is authenticated, and the keys differ anyway
+    let mut encrypted = sha3_256.encrypt(b"hash-bound").unwrap();
+    encrypted[1] &= !0x02;
+    assert!(sha3_512.decrypt(&encrypted).is_err());
+}
+
+#[wasm_bindgen_test]
+fn base64_decoder_rejects_malformed_streams() {
+    let mut decoder = QShieldBase64Decoder::new();
+    let err = decoder.update("QUJD*A==").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Invalid base64");
+
+    // Nothing may follow a padded group, however the input is split
+    let mut decoder = QShieldBase64Decoder::new();
+    assert_eq!(decoder.update("QQ==").unwrap(), b"A");
+    assert!(decoder.update("").unwrap().is_empty());
+    let err = decoder.update("QkJC").unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Invalid base64");
+
+    let mut decoder = QShieldBase64Decoder::new();
+    assert_eq!(decoder.update("QUJDRE").unwrap(), b"ABC");
+    let err = decoder.finish().unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Truncated base64");
+}
+
+#[wasm_bindgen_test]
+fn chunked_stream_rejects_reordering_and_truncation() {
+    let sender = QShieldCipher::from_bytes(b"chunked-stream-secret").unwrap();
+    let receiver = QShieldCipher::from_bytes(b"chunked-stream-secret").unwrap();
+
+    let err = sender.encrypt_chunk(b"early", 0, false).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "No stream in progress");
+
+    let header = sender.begin_stream().unwrap();
+    let c0 = sender.encrypt_chunk(b"chunk 0", 0, false).unwrap();
+    let err = sender.encrypt_chunk(b"chunk 2", 2, false).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Chunk out of order");
+    let c1 = sender.encrypt_chunk(b"chunk 1", 1, false).unwrap();
+    let c2 = sender.encrypt_chunk(b"chunk 2", 2, true).unwrap();
+    let err = sender.encrypt_chunk(b"chunk 3", 3, false).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Stream already finished");
+
+    // Out of order
+    receiver.begin_decrypt_stream(&header).unwrap();
+    assert_eq!(receiver.decrypt_chunk(&c0, 0, false).unwrap(), b"chunk 0");
+    let err = receiver.decrypt_chunk(&c2, 2, true).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Chunk out of order");
+
+    // A chunk swapped into another's position
+    let err = receiver.decrypt_chunk(&c2, 1, false).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Decryption failed");
+
+    // Truncated after chunk 1: it was not sealed as final
+    let err = receiver.decrypt_chunk(&c1, 1, true).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Decryption failed");
+
+    // Failed chunks do not advance the stream
+    assert_eq!(receiver.decrypt_chunk(&c1, 1, false).unwrap(), b"chunk 1");
+    assert_eq!(receiver.decrypt_chunk(&c2, 2, true).unwrap(), b"chunk 2");
+    let err = receiver.decrypt_chunk(&c2, 3, true).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Stream already finished");
+    receiver.finish_decrypt_stream().unwrap();
+    let err = receiver.finish_decrypt_stream().unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "No stream in progress");
+
+    // Truncated after chunk 1 by a receiver that never passes is_final
+    receiver.begin_decrypt_stream(&header).unwrap();
+    assert_eq!(receiver.decrypt_chunk(&c0, 0, false).unwrap(), b"chunk 0");
+    assert_eq!(receiver.decrypt_chunk(&c1, 1, false).unwrap(), b"chunk 1");
+    let err = receiver.finish_decrypt_stream().unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Stream ended without a final chunk");
+
+    let err = receiver.begin_decrypt_stream(&header[..10]).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Invalid stream header");
+}
+
+#[wasm_bindgen_test]
+fn cipher_rejects_short_secret() {
+    let err = QShieldCipher::from_bytes(b"too-short").err().unwrap();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "Secret too short: expected at least 16 bytes, got 9"
+    );
+    assert!(QShieldCipher::from_bytes_with_context(b"too-short", b"chat").is_err());
+    assert!(QShieldCipher::from_bytes_with_options(b"too-short", false).is_err());
+
+    // Explicit override
+    assert!(QShieldCipher::from_bytes_with_options(b"too-short", true).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn cipher_insecure_test_key_separated_from_production() {
+    let test_cipher = QShieldCipher::insecure_test_key("fixture").unwrap();
+    let production = QShieldCipher::from_bytes_with_options(b"fixture", true).unwrap();
+
+    let encrypted = test_cipher.encrypt(b"fixture data").unwrap();
+    assert_eq!(test_cipher.decrypt(&encrypted).unwrap(), b"fixture data");
+    assert!(production.decrypt(&encrypted).is_err());
+}
+
 #[wasm_bindgen_test]
 fn cipher_tampered_ciphertext_fails() {
-    let cipher = QShieldCipher::from_bytes(b"tamper-test").unwrap();
+    let cipher = QShieldCipher::from_bytes(b"tamper-test-key-wasm").unwrap();
     let mut encrypted = cipher.encrypt(b"original").unwrap();
 
     if let Some(last) = encrypted.last_mut() {
@@ -97,7 +425,7 @@ fn cipher_tampered_ciphertext_fails() {
 
 #[wasm_bindgen_test]
 fn cipher_overhead() {
-    let cipher_padded = QShieldCipher::from_bytes(b"overhead-test").unwrap();
+    let cipher_padded = QShieldCipher::from_bytes(b"overhead-test-key-wasm").unwrap();
     assert!(cipher_padded.has_length_hiding());
     assert!(cipher_padded.overhead() > 0);
 
@@ -125,8 +453,8 @@ fn hybrid_kem_keygen_encapsulate_decapsulate() {
 #[wasm_bindgen_test]
 fn hybrid_kem_public_key_size() {
     let kem = QShieldHybridKEM::new().unwrap();
-    assert_eq!(kem.public_key().len(), 1216);
-    assert_eq!(QShieldHybridKEM::public_key_size(), 1216);
+    assert_eq!(kem.public_key().len(), 1217);
+    assert_eq!(QShieldHybridKEM::public_key_size(), 1217);
 }
 
 #[wasm_bindgen_test]
@@ -135,7 +463,7 @@ fn hybrid_kem_ciphertext_size() {
     let bob = QShieldHybridKEM::new().unwrap();
 
     let encap = alice.encapsulate(&bob.public_key()).unwrap();
-    assert_eq!(encap.ciphertext().len(), 1120); // 32 + 1088
+    assert_eq!(encap.ciphertext().len(), 1121); // version + 32 + 1088
 }
 
 #[wasm_bindgen_test]
@@ -189,6 +517,170 @@ fn hybrid_kem_invalid_ciphertext() {
     assert!(bob.decapsulate(&[0u8; 32]).is_err());
 }
 
+#[wasm_bindgen_test]
+fn hybrid_kem_require_hybrid_rejects_classical() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+
+    let classical = alice.encapsulate_auto(&bob.public_key()[1..33]).unwrap();
+    assert!(bob.decapsulate_auto(&classical.ciphertext(), true).is_err());
+    assert_eq!(
+        bob.decapsulate_auto(&classical.ciphertext(), false).unwrap(),
+        classical.shared_secret()
+    );
+}
+
+#[wasm_bindgen_test]
+fn group_message_recipients_and_outsider() {
+    let sender = QShieldHybridKEM::new().unwrap();
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+    let carol = QShieldHybridKEM::new().unwrap();
+    let mallory = QShieldHybridKEM::new().unwrap();
+
+    let mut keys = alice.public_key();
+    keys.extend_from_slice(&bob.public_key());
+    keys.extend_from_slice(&carol.public_key());
+
+    let message = QShieldGroupMessage::encrypt(&sender, &keys, b"team update").unwrap();
+    let parsed = QShieldGroupMessage::from_bytes(&message.bytes()).unwrap();
+
+    assert_eq!(parsed.decrypt(&alice).unwrap(), b"team update");
+    assert_eq!(parsed.decrypt(&bob).unwrap(), b"team update");
+    assert_eq!(parsed.decrypt(&carol).unwrap(), b"team update");
+    assert!(parsed.decrypt(&mallory).is_err());
+}
+
+#[wasm_bindgen_test]
+fn group_message_header_is_authenticated() {
+    let sender = QShieldHybridKEM::new().unwrap();
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+
+    let mut keys = alice.public_key();
+    keys.extend_from_slice(&bob.public_key());
+    let bytes = QShieldGroupMessage::encrypt(&sender, &keys, b"team update").unwrap().bytes();
+
+    // Drop alice's entry: bob still finds his key, but the header no longer matches
+    let wrapped_len = u32::from_le_bytes(bytes[35..39].try_into().unwrap()) as usize;
+    let mut stripped = vec![bytes[0]];
+    stripped.extend_from_slice(&1u16.to_le_bytes());
+    stripped.extend_from_slice(&bytes[39 + wrapped_len..]);
+    let stripped = QShieldGroupMessage::from_bytes(&stripped).unwrap();
+    assert_eq!(stripped.recipient_count(), 1);
+    assert!(stripped.decrypt(&bob).is_err());
+
+    // Relabelling it as an unauthenticated version 1 message fails too
+    let mut legacy = bytes;
+    legacy[0] = 0x01;
+    assert!(QShieldGroupMessage::from_bytes(&legacy).unwrap().decrypt(&bob).is_err());
+}
+
+#[wasm_bindgen_test]
+fn envelope_rejects_foreign_suite_and_bad_input() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let mallory = QShieldHybridKEM::new().unwrap();
+    let envelope = QShieldEnvelope::seal(&alice, &alice.public_key(), b"sealed").unwrap();
+    assert_eq!(envelope.open(&alice).unwrap(), b"sealed");
+    assert!(envelope.open(&mallory).is_err());
+
+    // Sealed by the core crate: parses, but cannot be opened here
+    let mut bytes = envelope.bytes();
+    bytes[1] = 0x01;
+    let core = QShieldEnvelope::from_bytes(&bytes).unwrap();
+    assert_eq!(core.suite(), 0x01);
+    let err = core.open(&alice).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Unsupported envelope suite");
+
+    let mut bytes = envelope.bytes();
+    bytes[0] = 0x02;
+    let err = QShieldEnvelope::from_bytes(&bytes).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Unsupported envelope version");
+
+    let bytes = envelope.bytes();
+    let err = QShieldEnvelope::from_bytes(&bytes[..bytes.len() - 1]).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid envelope format");
+}
+
+#[wasm_bindgen_test]
+fn envelope_header_is_authenticated() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+
+    let mut keys = alice.public_key();
+    keys.extend_from_slice(&bob.public_key());
+    let bytes = QShieldEnvelope::seal(&alice, &keys, b"sealed").unwrap().bytes();
+
+    // Drop alice's entry: bob still finds his key, but the header no longer matches
+    let wrapped_len = u32::from_le_bytes(bytes[36..40].try_into().unwrap()) as usize;
+    let mut stripped = bytes[..2].to_vec();
+    stripped.extend_from_slice(&1u16.to_le_bytes());
+    stripped.extend_from_slice(&bytes[40 + wrapped_len..]);
+    let stripped = QShieldEnvelope::from_bytes(&stripped).unwrap();
+    assert_eq!(stripped.recipient_count(), 1);
+    assert!(stripped.open(&bob).is_err());
+    assert_eq!(QShieldEnvelope::from_bytes(&bytes).unwrap().open(&bob).unwrap(), b"sealed");
+}
+
+#[wasm_bindgen_test]
+fn envelope_opens_core_sealed_vector() {
+    // Sealed by the core crate's QShieldEnvelope; see test-vectors/envelope
+    let secret_key = include_bytes!("../../test-vectors/envelope/recipient_secret_key.bin");
+    let public_key = include_bytes!("../../test-vectors/envelope/recipient_public_key.bin");
+    let envelope = include_bytes!("../../test-vectors/envelope/hybrid_envelope.bin");
+
+    // Same keys in export_secret layout: [suite][len][X25519 sk][len][ML-KEM dk]
+    let mut exported = vec![0x01];
+    exported.extend_from_slice(&32u32.to_le_bytes());
+    exported.extend_from_slice(&secret_key[..32]);
+    exported.extend_from_slice(&2400u32.to_le_bytes());
+    exported.extend_from_slice(&secret_key[32..]);
+    let recipient = QShieldHybridKEM::from_secret_bytes(&exported).unwrap();
+    assert_eq!(&recipient.public_key()[1..], &public_key[..]);
+
+    let parsed = QShieldEnvelope::from_bytes(envelope).unwrap();
+    assert_eq!(parsed.suite(), 0x03);
+    assert!(parsed.has_recipient(public_key));
+    assert_eq!(parsed.open(&recipient).unwrap(), b"sealed by the core crate");
+}
+
+#[wasm_bindgen_test]
+fn envelope_decode_limits() {
+    let sender = QShieldHybridKEM::new().unwrap();
+    let mut keys = sender.public_key();
+    keys.extend_from_slice(&QShieldHybridKEM::new().unwrap().public_key());
+    let bytes = QShieldEnvelope::seal(&sender, &keys, b"limited").unwrap().bytes();
+
+    let err = QShieldEnvelope::from_bytes_with_limits(&bytes, 1, bytes.len()).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Too many recipients");
+    let err = QShieldEnvelope::from_bytes_with_limits(&bytes, 2, bytes.len() - 1).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Envelope too large");
+
+    // A header claiming 65535 recipients is rejected before allocating for them
+    let mut huge = vec![0x01, 0x02, 0xff, 0xff];
+    huge.extend_from_slice(&[0u8; 40]);
+    let err = QShieldEnvelope::from_bytes(&huge).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Too many recipients");
+    let err = QShieldEnvelope::from_bytes_with_limits(&huge, usize::MAX, usize::MAX).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid envelope format");
+
+    // A wrapped key length running past the buffer
+    let mut overflow = bytes;
+    overflow[4 + 32..4 + 36].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = QShieldEnvelope::from_bytes(&overflow).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid envelope format");
+}
+
+#[wasm_bindgen_test]
+fn hybrid_kem_open_wrong_recipient_fails() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+    let eve = QShieldHybridKEM::new().unwrap();
+
+    let sealed = alice.seal(&bob.public_key(), b"for bob only").unwrap();
+    assert!(eve.open(&sealed).is_err());
+}
+
 // ============================================================================
 // DUAL SIGNATURE TESTS
 // ============================================================================
@@ -274,6 +766,49 @@ fn dual_sign_public_key_size() {
     assert!(info.contains("1984"));
 }
 
+#[wasm_bindgen_test]
+fn dual_sign_secret_key_roundtrip() {
+    let signer = QShieldSign::new().unwrap();
+    let restored = QShieldSign::from_secret_bytes(&signer.secret_key_bytes()).unwrap();
+
+    let signature = restored.sign(b"restored signer").unwrap();
+    let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+    assert!(verifier.verify(b"restored signer", &signature).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn dual_sign_secret_key_invalid_length() {
+    let secret = QShieldSign::new().unwrap().secret_key_bytes();
+
+    assert!(QShieldSign::from_secret_bytes(&secret[..secret.len() - 1]).is_err());
+    let mut longer = secret.clone();
+    longer.push(0);
+    assert!(QShieldSign::from_secret_bytes(&longer).is_err());
+
+    // Length prefix must match the ML-DSA-65 secret key size
+    let mut bad_prefix = secret;
+    bad_prefix[0] ^= 0x01;
+    assert!(QShieldSign::from_secret_bytes(&bad_prefix).is_err());
+}
+
+#[wasm_bindgen_test]
+fn dual_sign_prehashed_rejects_bad_digests() {
+    let signer = QShieldSign::new().unwrap();
+
+    let err = signer.sign_prehashed(&[0u8; 32], "SHA-512").err().unwrap();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "Invalid SHA-512 digest length: expected 64, got 32"
+    );
+
+    // No FIPS 204/205 pre-hash mode for SHA3-512
+    let err = signer.sign_prehashed(&[0u8; 64], "SHA3-512").err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Unsupported digest algorithm: SHA3-512");
+
+    let signature = signer.sign_prehashed(&[7u8; 32], "SHA-256").unwrap();
+    assert!(signer.verify_prehashed(&[7u8; 32], "sha-256", &signature).is_err());
+}
+
 // ============================================================================
 // VERIFIER TESTS
 // ============================================================================
@@ -331,6 +866,17 @@ fn verifier_wrong_message_fails() {
     assert!(!verifier.verify(b"tampered", &signature).unwrap());
 }
 
+#[wasm_bindgen_test]
+fn verifier_requires_matching_context() {
+    let signer = QShieldSign::new().unwrap();
+    let verifier = QShieldVerifier::new(&signer.public_key()).unwrap();
+
+    let signature = signer.sign_with_context(b"payload", b"app-a").unwrap();
+    assert!(verifier.verify_string_with_context("payload", &signature, b"app-a").unwrap());
+    assert!(!verifier.verify_string("payload", &signature).unwrap());
+    assert!(!verifier.verify_with_context(b"payload", &signature, b"app-b").unwrap());
+}
+
 #[wasm_bindgen_test]
 fn verifier_invalid_public_key_length() {
     assert!(QShieldVerifier::new(&[0u8; 100]).is_err());
@@ -383,6 +929,29 @@ fn session_out_of_order_fails() {
     assert!(receiver.decrypt(&encrypted2).is_err());
 }
 
+#[wasm_bindgen_test]
+fn session_decrypt_with_skip_limit() {
+    let secret = b"skip-limit-test";
+    let mut sender = QShieldSession::new(secret).unwrap();
+    let mut receiver = QShieldSession::new(secret).unwrap();
+
+    let messages: Vec<Vec<u8>> = (0..5).map(|_| sender.encrypt(b"msg").unwrap()).collect();
+
+    // Message 4 is four ahead of the chain
+    let err = receiver.decrypt_with_skip(&messages[4], 3).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Too many skipped messages");
+    assert_eq!(receiver.message_count(), 0);
+    assert_eq!(receiver.skipped_message_count(), 0);
+
+    // A tampered message within the limit leaves the chain where it was
+    let mut tampered = messages[2].clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(receiver.decrypt_with_skip(&tampered, 3).is_err());
+    assert_eq!(receiver.message_count(), 0);
+
+    assert!(receiver.decrypt_with_skip(&messages[3], 3).is_ok());
+}
+
 #[wasm_bindgen_test]
 fn session_forward_secrecy() {
     let secret = b"forward-secrecy-test";
@@ -405,6 +974,50 @@ fn session_forward_secrecy() {
     assert_eq!(b"msg1".as_slice(), re_decrypted1.as_slice());
 }
 
+#[wasm_bindgen_test]
+fn session_rejects_unknown_version() {
+    let secret = b"session-version-test";
+    let mut sender = QShieldSession::new(secret).unwrap();
+    let mut receiver = QShieldSession::new(secret).unwrap();
+
+    let mut encrypted = sender.encrypt(b"versioned").unwrap();
+    encrypted[0] = 0x7f;
+    let err = receiver.decrypt(&encrypted).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "Unsupported session message version");
+
+    // The rejected message didn't advance the chain
+    encrypted[0] = 0x01;
+    assert_eq!(receiver.decrypt(&encrypted).unwrap(), b"versioned");
+}
+
+#[wasm_bindgen_test]
+fn session_legacy_format_requires_flag() {
+    let secret = b"session-legacy-test";
+    let mut sender = QShieldSession::new(secret).unwrap();
+    let mut receiver = QShieldSession::new(secret).unwrap();
+
+    // Legacy messages are the versioned ones without the leading byte
+    let legacy = sender.encrypt(b"legacy").unwrap()[1..].to_vec();
+    assert!(receiver.decrypt(&legacy).is_err());
+
+    receiver.set_accept_legacy_format(true);
+    assert_eq!(receiver.decrypt(&legacy).unwrap(), b"legacy");
+}
+
+#[wasm_bindgen_test]
+fn session_directions_reject_own_messages() {
+    let secret = b"directional-echo-test";
+    let mut alice = QShieldSession::new_with_directions(secret, SessionRole::Initiator).unwrap();
+    let mut echo = QShieldSession::new_with_directions(secret, SessionRole::Initiator).unwrap();
+    let mut bob = QShieldSession::new_with_directions(secret, SessionRole::Responder).unwrap();
+
+    let encrypted = alice.encrypt(b"not for echo").unwrap();
+
+    // A message reflected back to a peer in the same role does not decrypt
+    assert!(echo.decrypt(&encrypted).is_err());
+    assert_eq!(bob.decrypt(&encrypted).unwrap(), b"not for echo");
+}
+
 // ============================================================================
 // CLASSICAL KEY EXCHANGE TESTS
 // ============================================================================
@@ -449,6 +1062,11 @@ fn test_secure_compare() {
     assert!(secure_compare(b"", b""));
 }
 
+#[wasm_bindgen_test]
+fn test_self_test_passes() {
+    assert!(self_test().is_ok());
+}
+
 #[wasm_bindgen_test]
 fn test_info() {
     let info_str = info();
@@ -482,7 +1100,7 @@ fn large_data_encrypt_decrypt() {
 
 #[wasm_bindgen_test]
 fn cipher_ciphertext_too_short() {
-    let cipher = QShieldCipher::from_bytes(b"short-ct-test").unwrap();
+    let cipher = QShieldCipher::from_bytes(b"short-ct-test-key-wasm").unwrap();
     assert!(cipher.decrypt(&[0u8; 10]).is_err());
 }
 
@@ -491,7 +1109,275 @@ fn dual_signature_from_bytes_too_short() {
     assert!(DualSignature::from_bytes(&[0u8; 2]).is_err());
 }
 
+#[wasm_bindgen_test]
+fn dual_signature_legacy_format_requires_flag() {
+    let signer = QShieldSign::new().unwrap();
+    let message = b"Legacy format";
+    let sig_bytes = signer.sign(message).unwrap().bytes();
+    let legacy = &sig_bytes[1..];
+
+    assert!(DualSignature::from_bytes(legacy).is_err());
+    let parsed = DualSignature::from_bytes_with_options(legacy, true).unwrap();
+    assert!(signer.verify(message, &parsed).unwrap());
+}
+
+/// Tagged dual signature bytes with arbitrary components, for malformed inputs
+fn dual_signature_bytes(mldsa: &[u8], slhdsa: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x01];
+    bytes.extend_from_slice(&(mldsa.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(mldsa);
+    bytes.extend_from_slice(slhdsa);
+    bytes
+}
+
+#[wasm_bindgen_test]
+fn dual_signature_misplaced_components_rejected() {
+    let signer = QShieldSign::new().unwrap();
+    let signature = signer.sign(b"Component check").unwrap();
+    let mldsa = signature.mldsa_signature();
+    let slhdsa = signature.slhdsa_signature();
+    let parse_error = |mldsa: &[u8], slhdsa: &[u8]| {
+        DualSignature::from_bytes(&dual_signature_bytes(mldsa, slhdsa))
+            .err()
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+
+    assert!(DualSignature::from_bytes(&dual_signature_bytes(&mldsa, &slhdsa)).is_ok());
+    assert_eq!(parse_error(&slhdsa, &mldsa), "Signature components swapped");
+    assert_eq!(parse_error(&mldsa, &mldsa), "ML-DSA signature in SLH-DSA slot");
+    assert_eq!(
+        parse_error(&vec![0u8; mldsa.len()], &slhdsa),
+        "ML-DSA signature is all zeros"
+    );
+    assert_eq!(
+        parse_error(&mldsa, &vec![0u8; slhdsa.len()]),
+        "SLH-DSA signature is all zeros"
+    );
+
+    // The fixed layout has no length prefix, but zeroed components are still caught
+    let mut fixed = signature.to_bytes_fixed();
+    fixed[mldsa.len()..].fill(0);
+    let err = DualSignature::from_bytes_fixed(&fixed).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "SLH-DSA signature is all zeros");
+}
+
+#[wasm_bindgen_test]
+fn dual_signature_unknown_format_rejected() {
+    let signer = QShieldSign::new().unwrap();
+    let mut sig_bytes = signer.sign(b"Format mismatch").unwrap().bytes();
+
+    sig_bytes[0] = 0x02;
+    assert!(DualSignature::from_bytes(&sig_bytes).is_err());
+
+    // Truncated component under the correct tag is also rejected
+    sig_bytes[0] = 0x01;
+    sig_bytes.pop();
+    assert!(DualSignature::from_bytes(&sig_bytes).is_err());
+}
+
+#[wasm_bindgen_test]
+fn dual_signature_fixed_layout_length_checked() {
+    let signer = QShieldSign::new().unwrap();
+    let fixed = signer.sign(b"fixed").unwrap().to_bytes_fixed();
+    assert_eq!(fixed.len(), 20397);
+    assert!(DualSignature::from_bytes_fixed(&fixed).is_ok());
+
+    assert!(DualSignature::from_bytes_fixed(&fixed[..20396]).is_err());
+    let mut longer = fixed.clone();
+    longer.push(0);
+    assert!(DualSignature::from_bytes_fixed(&longer).is_err());
+    // The prefixed form is not accepted as fixed layout
+    assert!(DualSignature::from_bytes_fixed(&signer.sign(b"fixed").unwrap().bytes()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn dual_signature_from_cose_malformed() {
+    let signer = QShieldSign::new().unwrap();
+    let cose = signer.sign(b"COSE").unwrap().to_cose();
+    assert!(DualSignature::from_cose(&cose).is_ok());
+
+    // Every truncation is an error, never a panic
+    for len in [0, 1, 2, 3, 10, 20, cose.len() / 2, cose.len() - 1] {
+        assert!(DualSignature::from_cose(&cose[..len]).is_err());
+    }
+
+    let mut trailing = cose.clone();
+    trailing.push(0);
+    assert!(DualSignature::from_cose(&trailing).is_err());
+
+    // Signatures array declaring far more entries than the input holds
+    let mut huge = cose[..6].to_vec();
+    huge.extend_from_slice(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    assert!(DualSignature::from_cose(&huge).is_err());
+
+    // Indefinite-length items and an unknown algorithm
+    let err = DualSignature::from_cose(&[0x9f, 0xff]).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Unsupported CBOR encoding");
+
+    let mut relabelled = cose.clone();
+    let alg = relabelled.windows(4).position(|w| w == [0x43, 0xa1, 0x01, 0x38]).unwrap();
+    relabelled[alg + 4] = 0x31; // -49 becomes -50 (ML-DSA-87)
+    let err = DualSignature::from_cose(&relabelled).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Unsupported COSE signature algorithm: -50");
+}
+
 #[wasm_bindgen_test]
 fn dual_signature_from_base64_invalid() {
     assert!(DualSignature::from_base64("not-valid-base64!!!").is_err());
 }
+
+/// Low-order Curve25519 points (u-coordinates, little-endian)
+const LOW_ORDER_POINTS: [[u8; 32]; 3] = [
+    [0; 32],
+    [
+        1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ],
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+];
+
+#[wasm_bindgen_test]
+fn hybrid_kem_encapsulate_auto_key_sizes() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+    let carol = QShieldKeyExchange::new();
+
+    let hybrid = alice.encapsulate_auto(&bob.public_key()).unwrap();
+    assert_eq!(hybrid.ciphertext().len(), 1121);
+    assert_eq!(bob.decapsulate(&hybrid.ciphertext()).unwrap(), hybrid.shared_secret());
+
+    let classical = alice.encapsulate_auto(&carol.public_key()).unwrap();
+    assert_eq!(classical.ciphertext().len(), 33);
+    assert_eq!(carol.decapsulate(&classical.ciphertext()).unwrap(), classical.shared_secret());
+
+    // The strict decapsulate never accepts the X25519-only mode
+    assert!(bob.decapsulate(&classical.ciphertext()).is_err());
+
+    for len in [0, 31, 33, 64, 1184, 1215, 1218] {
+        let err = alice.encapsulate_auto(&vec![9u8; len]).err().unwrap();
+        assert!(err.as_string().unwrap().starts_with("Ambiguous public key length"));
+    }
+}
+
+#[wasm_bindgen_test]
+fn hybrid_kem_secret_import_rejects_corruption() {
+    let kem = QShieldHybridKEM::new().unwrap();
+    let exported = kem.export_secret();
+
+    let err = QShieldHybridKEM::from_secret_bytes(&exported[..exported.len() - 1]).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid ML-KEM secret key length");
+
+    let mut bad_x25519 = exported.clone();
+    bad_x25519[1] = 31;
+    let err = QShieldHybridKEM::from_secret_bytes(&bad_x25519).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid X25519 secret key length");
+
+    let mut trailing = exported.clone();
+    trailing.push(0);
+    let err = QShieldHybridKEM::from_secret_bytes(&trailing).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid KEM secret key: trailing bytes");
+
+    // The embedded encapsulation key no longer matches its hash
+    let mut tampered = exported.clone();
+    let hash_offset = tampered.len() - 64;
+    tampered[hash_offset - 1] ^= 1;
+    let err = QShieldHybridKEM::from_secret_bytes(&tampered).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid ML-KEM secret key");
+
+    let err = QShieldHybridKEM::from_secret_bytes(&[]).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Invalid KEM secret key: empty");
+}
+
+#[wasm_bindgen_test]
+fn hybrid_kem_rejects_mismatched_suites() {
+    let compact = QShieldHybridKEM::with_suite(0x03).unwrap();
+    let high = QShieldHybridKEM::with_suite(0x02).unwrap();
+
+    let err = compact.encapsulate(&high.public_key()).err().unwrap();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "KEM suite mismatch: keypair uses 0x03, peer key uses 0x02"
+    );
+
+    // A ciphertext made for one suite is refused by a keypair of another
+    let peer = QShieldHybridKEM::with_suite(0x03).unwrap();
+    let ct = peer.encapsulate(&compact.public_key()).unwrap().ciphertext();
+    let err = high.decapsulate(&ct).unwrap_err();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "KEM suite mismatch: ciphertext is not for suite 0x02"
+    );
+
+    let err = QShieldHybridKEM::with_suite(0x04).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "Unknown KEM suite: 0x04");
+
+    // A key whose suite byte disagrees with its length
+    let mut relabelled = compact.public_key();
+    relabelled[0] = 0x01;
+    let default = QShieldHybridKEM::new().unwrap();
+    let err = default.encapsulate(&relabelled).err().unwrap();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "Invalid hybrid public key length: expected 1217, got 833"
+    );
+}
+
+#[wasm_bindgen_test]
+fn hybrid_kem_rejects_low_order_x25519_keys() {
+    let alice = QShieldHybridKEM::new().unwrap();
+    let bob = QShieldHybridKEM::new().unwrap();
+    let bob_pk = bob.public_key();
+    let valid_ct = alice.encapsulate(&bob_pk).unwrap().ciphertext();
+
+    for point in LOW_ORDER_POINTS {
+        // Encapsulating to a public key with a low-order X25519 half
+        let mut peer_pk = bob_pk.clone();
+        peer_pk[1..33].copy_from_slice(&point);
+        let err = alice.encapsulate(&peer_pk).err().unwrap();
+        assert_eq!(err.as_string().unwrap(), "Degenerate X25519 public key");
+
+        // Decapsulating a ciphertext whose X25519 key is low-order
+        let mut ct = valid_ct.clone();
+        ct[1..33].copy_from_slice(&point);
+        let err = bob.decapsulate(&ct).unwrap_err();
+        assert_eq!(err.as_string().unwrap(), "Degenerate X25519 public key");
+
+        // Unprefixed legacy keys and the classical exchange are checked too
+        let mut legacy_pk = bob_pk[1..].to_vec();
+        legacy_pk[..32].copy_from_slice(&point);
+        let err = alice.encapsulate(&legacy_pk).err().unwrap();
+        assert_eq!(err.as_string().unwrap(), "Degenerate X25519 public key");
+
+        let err = QShieldKeyExchange::new().derive_cipher(&point).err().unwrap();
+        assert_eq!(err.as_string().unwrap(), "Degenerate X25519 public key");
+    }
+
+    // Legitimate keys still agree
+    let encap = alice.encapsulate(&bob_pk).unwrap();
+    assert_eq!(
+        bob.decapsulate(&encap.ciphertext()).unwrap(),
+        encap.shared_secret()
+    );
+}
+
+#[wasm_bindgen_test]
+fn hybrid_kem_encapsulate_multi_reports_bad_key_index() {
+    let sender = QShieldHybridKEM::new().unwrap();
+    let good = QShieldHybridKEM::new().unwrap().public_key();
+    let keys = vec![good.clone(), good.clone(), good[..100].to_vec()];
+
+    let err = sender.encapsulate_multi(&keys).err().unwrap();
+    assert_eq!(
+        err.as_string().unwrap(),
+        "Invalid public key length for recipient 2: expected 1217, got 100"
+    );
+
+    let err = sender.encapsulate_multi(&[]).err().unwrap();
+    assert_eq!(err.as_string().unwrap(), "No recipient public keys");
+}